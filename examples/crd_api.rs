@@ -12,6 +12,7 @@ use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomRe
 use kube::{
     api::{Api, DeleteParams, ListParams, Patch, PatchParams, PostParams, ResourceExt},
     core::crd::CustomResourceExt,
+    runtime::wait::{await_condition, conditions, Condition},
     Client, CustomResource,
 };
 
@@ -78,7 +79,12 @@ async fn main() -> Result<()> {
         Err(e) => return Err(e.into()),                        // any other case is probably bad
     }
     // Wait for the api to catch up
-    sleep(Duration::from_secs(1)).await;
+    let establish = await_condition(
+        crds.clone(),
+        "foos.clux.dev",
+        conditions::is_crd_established().and(conditions::are_crd_names_accepted()),
+    );
+    tokio::time::timeout(Duration::from_secs(10), establish).await??;
 
     // Manage the Foo CR
     let foos: Api<Foo> = Api::default_namespaced(client.clone());
@@ -1,25 +1,28 @@
-use std::sync::Arc;
-use anyhow::{Result};
+use anyhow::Result;
 use garde::Validate;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 use tokio::time::sleep;
 use tracing::*;
 
-use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
-use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
-use kube::runtime::watcher::Config;
+use futures::{FutureExt, StreamExt};
+use k8s_openapi::{
+    apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition,
+    apimachinery::pkg::apis::meta::v1::ObjectMeta,
+};
 use kube::{
     api::{Api, Patch, PatchParams, ResourceExt},
     core::crd::CustomResourceExt,
+    runtime::{
+        controller::{await_crd_established, Action},
+        reflector::ObjectRef,
+        watcher::Config,
+        Controller,
+    },
     Client, CustomResource,
 };
-use kube::runtime::Controller;
-use kube::runtime::controller::Action;
-use kube::runtime::reflector::ObjectRef;
-use futures::{FutureExt, StreamExt};
 use tokio::time;
 
 
@@ -61,15 +64,20 @@ async fn run_iteration(large_iteration_index: i32) -> Result<()> {
     let main_thing_api: Api<MainThing> = Api::all(client.clone());
     let referer_thing_api: Api<RefererThing> = Api::all(client.clone());
 
-    let mut stream = Box::pin(Controller::new(main_thing_api.clone(), Config::default())
-        .watches(referer_thing_api, Config::default(), |o| {
-            let current_namespace = o.namespace().expect("referer thing should always be namespaced");
-            let object_ref = ObjectRef::new(&o.spec.main_thing_name).within(o.spec.main_thing_namespace.as_deref().unwrap_or(&current_namespace));
-            Some(object_ref)
-        })
-        .run(
-            reconcile_main_thing,
-            error_policy, Arc::new(())));
+    let mut stream = Box::pin(
+        Controller::new(main_thing_api.clone(), Config::default())
+            .watches(referer_thing_api, Config::default(), |o| {
+                let current_namespace = o.namespace().expect("referer thing should always be namespaced");
+                let object_ref = ObjectRef::new(&o.spec.main_thing_name).within(
+                    o.spec
+                        .main_thing_namespace
+                        .as_deref()
+                        .unwrap_or(&current_namespace),
+                );
+                Some(object_ref)
+            })
+            .run(reconcile_main_thing, error_policy, Arc::new(())),
+    );
 
     let now = time::Instant::now();
 
@@ -88,7 +96,13 @@ async fn run_iteration(large_iteration_index: i32) -> Result<()> {
             }
         });
 
-        referer_thing_api.patch("my-referer", &PatchParams::default(), &Patch::Merge(updated_referer)).await?;
+        referer_thing_api
+            .patch(
+                "my-referer",
+                &PatchParams::default(),
+                &Patch::Merge(updated_referer),
+            )
+            .await?;
         info!("updated referer resource {i} in {large_iteration_index}");
 
         let _ = stream.next().await.unwrap();
@@ -127,14 +141,26 @@ async fn ensure_crds() -> Result<()> {
     // Create the CRD so we can create MainThings in kube
     let main_thing_crd = MainThing::crd();
     let patch_params = PatchParams::apply("example-crd-watcher").force();
-    crds.patch(&main_thing_crd.name_any(), &patch_params, &Patch::Apply(main_thing_crd)).await?;
+    crds.patch(
+        &main_thing_crd.name_any(),
+        &patch_params,
+        &Patch::Apply(main_thing_crd),
+    )
+    .await?;
 
     // Create the CRD so we can create RefererThings in kube
     let referer_thing_crd = RefererThing::crd();
-    crds.patch(&referer_thing_crd.name_any(), &patch_params, &Patch::Apply(referer_thing_crd)).await?;
+    crds.patch(
+        &referer_thing_crd.name_any(),
+        &patch_params,
+        &Patch::Apply(referer_thing_crd),
+    )
+    .await?;
 
     // Wait for the api to catch up
-    sleep(Duration::from_secs(1)).await;
+    let establish = Duration::from_secs(10);
+    await_crd_established::<MainThing>(crds.clone(), &(), establish).await?;
+    await_crd_established::<RefererThing>(crds.clone(), &(), establish).await?;
 
     let main_thing_api: Api<MainThing> = Api::default_namespaced(client.clone());
     let referer_thing_api: Api<RefererThing> = Api::default_namespaced(client.clone());
@@ -147,7 +173,13 @@ async fn ensure_crds() -> Result<()> {
         spec: MainThingSpec {},
     };
 
-    let main_thing_instance = main_thing_api.patch(&main_thing_instance.name_any(), &patch_params, &Patch::Apply(main_thing_instance)).await?;
+    let main_thing_instance = main_thing_api
+        .patch(
+            &main_thing_instance.name_any(),
+            &patch_params,
+            &Patch::Apply(main_thing_instance),
+        )
+        .await?;
 
     let referer_instance = RefererThing {
         metadata: ObjectMeta {
@@ -161,9 +193,15 @@ async fn ensure_crds() -> Result<()> {
         },
     };
 
-    let _referer_instance = referer_thing_api.patch(&referer_instance.name_any(), &patch_params, &Patch::Apply(referer_instance)).await?;
+    let _referer_instance = referer_thing_api
+        .patch(
+            &referer_instance.name_any(),
+            &patch_params,
+            &Patch::Apply(referer_instance),
+        )
+        .await?;
 
     info!("All test resources created");
 
     Ok(())
-}
\ No newline at end of file
+}
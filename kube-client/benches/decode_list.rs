@@ -0,0 +1,68 @@
+//! Throughput of decoding a large list response
+//!
+//! ```sh
+//! cargo bench -p kube-client --bench decode_list
+//! ```
+use std::{collections::BTreeMap, convert::Infallible, time::Instant};
+
+use http::{Request, Response};
+use hyper::Body;
+use k8s_openapi::api::core::v1::ConfigMap;
+use kube_client::{core::ObjectList, Client};
+
+/// The size of the list body to decode
+const LIST_BYTES: usize = 50 * 1024 * 1024;
+const ITERATIONS: u32 = 5;
+
+/// A list of config maps of around `LIST_BYTES` in total
+fn list_body() -> Vec<u8> {
+    let mut items = Vec::new();
+    let mut size = 0;
+    while size < LIST_BYTES {
+        let cm = serde_json::json!({
+            "metadata": {
+                "name": format!("config-{}", items.len()),
+                "namespace": "default",
+                "resourceVersion": items.len().to_string(),
+                "labels": { "app": "bench", "tier": "backend" },
+            },
+            "data": (0..10)
+                .map(|i| (format!("key-{i}"), "x".repeat(100)))
+                .collect::<BTreeMap<_, _>>(),
+        });
+        size += cm.to_string().len();
+        items.push(cm);
+    }
+    serde_json::to_vec(&serde_json::json!({
+        "apiVersion": "v1",
+        "kind": "ConfigMapList",
+        "metadata": { "resourceVersion": "1" },
+        "items": items,
+    }))
+    .unwrap()
+}
+
+#[tokio::main]
+async fn main() {
+    let body = bytes::Bytes::from(list_body());
+    let len = body.len();
+    let service = tower::service_fn(move |_: Request<Body>| {
+        let body = body.clone();
+        async move { Ok::<_, Infallible>(Response::new(Body::from(body))) }
+    });
+    let client = Client::new(service, "default");
+
+    let mut items = 0;
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        let request = Request::get("/api/v1/configmaps").body(vec![]).unwrap();
+        let list: ObjectList<ConfigMap> = client.request(request).await.unwrap();
+        items = list.items.len();
+    }
+    let elapsed = start.elapsed() / ITERATIONS;
+    println!(
+        "decoded {items} objects ({:.1} MiB) in {elapsed:?}, {:.1} MiB/s",
+        len as f64 / 1024.0 / 1024.0,
+        len as f64 / 1024.0 / 1024.0 / elapsed.as_secs_f64()
+    );
+}
@@ -0,0 +1,139 @@
+//! Allocations of decoding a high-churn watch stream
+//!
+//! Compares [`Client::request_events`] with the previous way of splitting watch streams into a `String` per line,
+//! counting allocations with a global allocator:
+//!
+//! ```sh
+//! cargo bench -p kube-client --bench watch_events
+//! ```
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    convert::Infallible,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use futures::{stream, StreamExt, TryStreamExt};
+use http::{Request, Response};
+use hyper::Body;
+use k8s_openapi::api::core::v1::ConfigMap;
+use kube_client::{core::WatchEvent, Client};
+use tokio_util::{
+    codec::{FramedRead, LinesCodec},
+    io::StreamReader,
+};
+
+const EVENTS: usize = 100_000;
+/// The size of the chunks that the watch stream arrives in, so that events are split across chunks
+const CHUNK_BYTES: usize = 8 * 1024;
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// A watch stream of modified config maps, one JSON event per line
+fn watch_body() -> Vec<u8> {
+    let mut body = Vec::new();
+    for i in 0..EVENTS {
+        let event = serde_json::json!({
+            "type": "MODIFIED",
+            "object": {
+                "apiVersion": "v1",
+                "kind": "ConfigMap",
+                "metadata": {
+                    "name": format!("config-{}", i % 100),
+                    "namespace": "default",
+                    "resourceVersion": i.to_string(),
+                },
+                "data": { "key": "x".repeat(200) },
+            },
+        });
+        serde_json::to_writer(&mut body, &event).unwrap();
+        body.push(b'\n');
+    }
+    body
+}
+
+fn chunked(body: &bytes::Bytes) -> Body {
+    let chunks = (0..body.len())
+        .step_by(CHUNK_BYTES)
+        .map(|start| Ok::<_, Infallible>(body.slice(start..body.len().min(start + CHUNK_BYTES))))
+        .collect::<Vec<_>>();
+    Body::wrap_stream(stream::iter(chunks))
+}
+
+/// Decodes the events like `request_events` did before, with a `String` per line
+async fn decode_lines(body: &bytes::Bytes) -> usize {
+    let reader =
+        StreamReader::new(chunked(body).map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err)));
+    FramedRead::new(reader, LinesCodec::new())
+        .map(|line| serde_json::from_str::<WatchEvent<ConfigMap>>(&line.unwrap()).unwrap())
+        .count()
+        .await
+}
+
+async fn decode_events(client: &Client) -> usize {
+    let request = Request::get("/api/v1/configmaps?watch=true")
+        .body(vec![])
+        .unwrap();
+    let events = client.request_events::<ConfigMap>(request).await.unwrap();
+    events.map(Result::unwrap).count().await
+}
+
+fn report(name: &str, events: usize, allocations: usize, elapsed: Duration) {
+    println!(
+        "{name}: {events} events in {elapsed:?}, {:.1} allocations/event, {:.0} allocations/s",
+        allocations as f64 / events as f64,
+        allocations as f64 / elapsed.as_secs_f64()
+    );
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let body = bytes::Bytes::from(watch_body());
+    let served = body.clone();
+    let service = tower::service_fn(move |_: Request<Body>| {
+        let body = chunked(&served);
+        async move { Ok::<_, Infallible>(Response::new(body)) }
+    });
+    let client = Client::new(service, "default");
+
+    let (allocations, start) = (ALLOCATIONS.load(Ordering::Relaxed), Instant::now());
+    let events = decode_lines(&body).await;
+    let elapsed = start.elapsed();
+    report(
+        "lines",
+        events,
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        elapsed,
+    );
+
+    let (allocations, start) = (ALLOCATIONS.load(Ordering::Relaxed), Instant::now());
+    let events = decode_events(&client).await;
+    let elapsed = start.elapsed();
+    report(
+        "request_events",
+        events,
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        elapsed,
+    );
+}
@@ -0,0 +1,292 @@
+//! API helpers for applying a set of objects and pruning the ones that are no longer part of it
+//!
+//! [`Api::apply_set`] is the primary entry point for this API.
+use std::{collections::HashSet, fmt::Debug};
+
+use crate::{Api, Error};
+use kube_core::{
+    params::{DeleteParams, ListParams, Patch, PatchParams, Preconditions},
+    Resource, ResourceExt,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Label identifying the apply set that an object was applied as part of
+pub const APPLY_SET_LABEL: &str = "applyset.kube.rs/part-of";
+
+impl<K: Resource + Clone + DeserializeOwned + Serialize + Debug> Api<K> {
+    /// Apply a set of objects with server-side apply, and prune the objects that are no longer part of it
+    ///
+    /// This is the equivalent of `kubectl apply --prune` for a single kind of object, for
+    /// declaratively reconciling the children of an object: every [`ApplySet::apply`] applies the given objects,
+    /// and deletes the objects that were applied as part of the same set before, but are no longer given.
+    ///
+    /// Members of the set are tracked with the [`APPLY_SET_LABEL`] label, whose value is the `id` of the set.
+    /// The `id` must be a valid label value that is unique to the set, such as the uid of the parent object.
+    ///
+    /// # Usage
+    ///
+    /// ```rust,no_run
+    /// # use k8s_openapi::api::core::v1::ConfigMap;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// # let desired: Vec<ConfigMap> = todo!();
+    /// let cms = kube::Api::<ConfigMap>::namespaced(client, "apps");
+    /// let outcome = cms.apply_set("my-app-config", "my-operator").apply(desired).await?;
+    /// println!("pruned {:?}", outcome.pruned);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Safety guards
+    ///
+    /// To avoid deleting objects that are not part of the set, an object is only pruned if:
+    ///
+    /// - it has the [`APPLY_SET_LABEL`] label of this set, within the scope of this [`Api`],
+    /// - `field_manager` still has fields applied in it, so objects taken over by someone else are left alone,
+    /// - it is not already being deleted, and
+    /// - its uid did not change since it was listed, so a concurrently recreated object is left alone.
+    ///
+    /// Objects are only pruned once all the given objects were applied successfully. Applying an empty set
+    /// is refused unless [`ApplySet::allow_empty`] is set, since it would prune every member of the set.
+    pub fn apply_set<'a>(&'a self, id: &'a str, field_manager: &'a str) -> ApplySet<'a, K> {
+        ApplySet {
+            api: self,
+            id,
+            field_manager,
+            force: false,
+            dry_run: false,
+            allow_empty: false,
+        }
+    }
+}
+
+/// A set of objects that are applied together, see [`Api::apply_set`]
+#[derive(Debug)]
+#[must_use]
+pub struct ApplySet<'a, K> {
+    api: &'a Api<K>,
+    id: &'a str,
+    field_manager: &'a str,
+    force: bool,
+    dry_run: bool,
+    allow_empty: bool,
+}
+
+/// The result of a successful [`ApplySet::apply`]
+#[derive(Debug)]
+pub struct ApplySetOutcome<K> {
+    /// The applied objects, as returned by the apiserver
+    pub applied: Vec<K>,
+    /// The names of the objects that were pruned
+    pub pruned: Vec<String>,
+}
+
+impl<K: Resource + Clone + DeserializeOwned + Serialize + Debug> ApplySet<'_, K> {
+    /// Force the apply of the objects, taking ownership of conflicting fields
+    ///
+    /// See [`PatchParams::force`].
+    pub fn force(mut self) -> Self {
+        self.force = true;
+        self
+    }
+
+    /// Only validate the applies and prunes, without persisting them
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// Allow applying an empty set of objects, pruning all previous members of the set
+    pub fn allow_empty(mut self) -> Self {
+        self.allow_empty = true;
+        self
+    }
+
+    /// Apply `objects` as the members of the set, and prune the previous members that are not among them
+    pub async fn apply(&self, objects: impl IntoIterator<Item = K>) -> Result<ApplySetOutcome<K>, ApplySetError> {
+        if !is_label_value(self.id) {
+            return Err(ApplySetError::InvalidId(self.id.to_string()));
+        }
+        let mut objects = objects.into_iter().collect::<Vec<_>>();
+        if objects.is_empty() && !self.allow_empty {
+            return Err(ApplySetError::EmptySet(self.id.to_string()));
+        }
+        if objects.iter().any(|obj| obj.meta().name.is_none()) {
+            return Err(ApplySetError::MissingName);
+        }
+
+        let mut pp = PatchParams::apply(self.field_manager);
+        pp.force = self.force;
+        pp.dry_run = self.dry_run;
+        let mut applied = Vec::with_capacity(objects.len());
+        for obj in &mut objects {
+            obj.labels_mut().insert(APPLY_SET_LABEL.to_string(), self.id.to_string());
+            let name = obj.name_any();
+            let obj = self
+                .api
+                .patch(&name, &pp, &Patch::Apply(&*obj))
+                .await
+                .map_err(|err| ApplySetError::Apply(name, err))?;
+            applied.push(obj);
+        }
+
+        let desired = objects.iter().map(ResourceExt::name_any).collect::<HashSet<_>>();
+        let lp = ListParams::default().labels(&format!("{APPLY_SET_LABEL}={}", self.id));
+        let members = self.api.list(&lp).await.map_err(ApplySetError::List)?;
+        let mut pruned = Vec::new();
+        for member in members {
+            let name = member.name_any();
+            if desired.contains(&name) || !self.is_prunable(&member) {
+                continue;
+            }
+            let dp = DeleteParams {
+                dry_run: self.dry_run,
+                preconditions: Some(Preconditions {
+                    uid: member.uid(),
+                    resource_version: None,
+                }),
+                ..DeleteParams::default()
+            };
+            match self.api.delete(&name, &dp).await {
+                // already gone
+                Ok(_) | Err(Error::Api(kube_core::ErrorResponse { code: 404, .. })) => pruned.push(name),
+                Err(err) => return Err(ApplySetError::Prune(name, err)),
+            }
+        }
+        Ok(ApplySetOutcome { applied, pruned })
+    }
+
+    /// Whether a listed member of the set was last applied by this set, and can be pruned
+    fn is_prunable(&self, member: &K) -> bool {
+        let applied_by_manager = member.managed_fields().iter().any(|entry| {
+            entry.manager.as_deref() == Some(self.field_manager)
+                && entry.operation.as_deref() == Some("Apply")
+                && entry.subresource.as_deref().map_or(true, str::is_empty)
+        });
+        applied_by_manager && member.meta().deletion_timestamp.is_none()
+    }
+}
+
+/// Whether `value` is a valid label value, as required for apply set ids
+fn is_label_value(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    !value.is_empty()
+        && value.len() <= 63
+        && bytes.iter().all(|b| b.is_ascii_alphanumeric() || b"-_.".contains(b))
+        && bytes[0].is_ascii_alphanumeric()
+        && bytes[bytes.len() - 1].is_ascii_alphanumeric()
+}
+
+/// Errors from [`ApplySet::apply`]
+#[derive(Debug, thiserror::Error)]
+pub enum ApplySetError {
+    /// The id of the set is not a valid label value
+    #[error("apply set id {0:?} is not a valid label value")]
+    InvalidId(String),
+    /// No objects were given, which would prune all members of the set
+    #[error("refusing to apply an empty apply set {0:?}, which would prune all of its members")]
+    EmptySet(String),
+    /// An object to apply has no `.metadata.name`
+    #[error("objects in an apply set must have a .metadata.name")]
+    MissingName,
+    /// Applying an object failed, no objects were pruned
+    #[error("failed to apply object {0}")]
+    Apply(String, #[source] Error),
+    /// Listing the members of the set failed, no objects were pruned
+    #[error("failed to list members of the apply set")]
+    List(#[source] Error),
+    /// Pruning an object failed
+    #[error("failed to prune object {0}")]
+    Prune(String, #[source] Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_label_value, ApplySetError, APPLY_SET_LABEL};
+    use crate::{Api, Client};
+    use futures::pin_mut;
+    use http::{Method, Request, Response};
+    use hyper::Body;
+    use k8s_openapi::api::core::v1::ConfigMap;
+    use serde_json::json;
+    use tower_test::mock;
+
+    fn member(name: &str, manager: &str) -> serde_json::Value {
+        json!({
+            "metadata": {
+                "name": name,
+                "namespace": "apps",
+                "uid": format!("uid-{name}"),
+                "labels": { APPLY_SET_LABEL: "set" },
+                "managedFields": [{ "manager": manager, "operation": "Apply", "apiVersion": "v1" }]
+            }
+        })
+    }
+
+    #[test]
+    fn apply_set_ids_must_be_label_values() {
+        assert!(is_label_value("0c7b3e12-7d3f-4b2f-9f57-5d2d3d1c2e1a"));
+        assert!(is_label_value("my.app_config"));
+        assert!(!is_label_value(""));
+        assert!(!is_label_value("-leading"));
+        assert!(!is_label_value("has/slash"));
+        assert!(!is_label_value(&"a".repeat(64)));
+    }
+
+    #[tokio::test]
+    async fn apply_set_refuses_empty_sets() {
+        let (mock_service, _handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let api: Api<ConfigMap> = Api::namespaced(Client::new(mock_service, "default"), "apps");
+        let res = api.apply_set("set", "my-operator").apply(vec![]).await;
+        assert!(matches!(res, Err(ApplySetError::EmptySet(_))));
+    }
+
+    #[tokio::test]
+    async fn apply_set_prunes_only_members_applied_by_the_manager() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::PATCH);
+            assert_eq!(
+                request.uri().to_string(),
+                "/api/v1/namespaces/apps/configmaps/keep?&fieldManager=my-operator"
+            );
+            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+            let applied: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(applied["metadata"]["labels"][APPLY_SET_LABEL], "set");
+            send.send_response(Response::builder().body(Body::from(member("keep", "my-operator").to_string())).unwrap());
+
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::GET);
+            assert_eq!(
+                request.uri().to_string(),
+                "/api/v1/namespaces/apps/configmaps?&labelSelector=applyset.kube.rs%2Fpart-of%3Dset"
+            );
+            let list = json!({
+                "metadata": { "resourceVersion": "1" },
+                "items": [
+                    member("keep", "my-operator"),
+                    member("stale", "my-operator"),
+                    member("adopted", "someone-else"),
+                ]
+            });
+            send.send_response(Response::builder().body(Body::from(list.to_string())).unwrap());
+
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::DELETE);
+            assert_eq!(request.uri().to_string(), "/api/v1/namespaces/apps/configmaps/stale?");
+            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+            let dp: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(dp["preconditions"]["uid"], "uid-stale");
+            send.send_response(Response::builder().body(Body::from(member("stale", "my-operator").to_string())).unwrap());
+        });
+
+        let api: Api<ConfigMap> = Api::namespaced(Client::new(mock_service, "default"), "apps");
+        let keep: ConfigMap = serde_json::from_value(json!({ "metadata": { "name": "keep" } })).unwrap();
+        let outcome = api.apply_set("set", "my-operator").apply(vec![keep]).await.unwrap();
+        assert_eq!(outcome.applied.len(), 1);
+        assert_eq!(outcome.pruned, ["stale"]);
+        spawned.await.unwrap();
+    }
+}
@@ -1,13 +1,52 @@
 use either::Either;
-use futures::Stream;
+use futures::{Stream, StreamExt, TryStreamExt};
 use serde::{de::DeserializeOwned, Serialize};
-use std::fmt::Debug;
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex, PoisonError},
+};
 
 use crate::{api::Api, Error, Result};
 use kube_core::{
-    metadata::PartialObjectMeta, object::ObjectList, params::*, response::Status, ErrorResponse, WatchEvent,
+    metadata::{ListMeta, PartialObjectMeta},
+    object::ObjectList,
+    params::*,
+    response::Status,
+    ErrorResponse, Resource, WatchEvent,
 };
 
+/// A list of objects that were deserialized one by one, returned by [`Api::list_lenient`]
+#[derive(Debug)]
+pub struct LenientList<K> {
+    /// ListMeta of the list, for its `resourceVersion` and `continue` token
+    pub metadata: ListMeta,
+    /// The items of the list, or the error from deserializing them
+    pub items: Vec<Result<K>>,
+}
+
+/// The `resourceVersion` of the last event seen by a [`watch_resumable`](Api::watch_resumable) stream
+///
+/// Clones share the same version, so it can be read while the stream is consumed elsewhere,
+/// and passed to [`watch_resumable`](Api::watch_resumable) again to resume after the stream ended or was dropped.
+#[derive(Clone, Debug, Default)]
+pub struct ResumableVersion(Arc<Mutex<String>>);
+
+impl ResumableVersion {
+    /// Start watching from `version`, see [`Api::watch`] for its semantics
+    pub fn new(version: impl Into<String>) -> Self {
+        Self(Arc::new(Mutex::new(version.into())))
+    }
+
+    /// The version to resume watching from
+    pub fn get(&self) -> String {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    fn set(&self, version: &str) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = version.to_string();
+    }
+}
+
 /// PUSH/PUT/POST/GET abstractions
 impl<K> Api<K>
 where
@@ -118,6 +157,34 @@ where
         self.client.request::<PartialObjectMeta<K>>(req).await
     }
 
+    /// Get a named resource, along with the headers of the response
+    ///
+    /// This is [`Api::get`] for when the response headers are needed as well,
+    /// for instance `Warning` headers, or custom headers set by aggregated apis.
+    ///
+    /// ```no_run
+    /// # use kube::Api;
+    /// use k8s_openapi::api::core::v1::Pod;
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::namespaced(client, "apps");
+    /// let (pod, headers) = pods.get_with_response("blog").await?;
+    /// for warning in headers.get_all("warning") {
+    ///     println!("warning: {:?}", warning);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_with_response(&self, name: &str) -> Result<(K, http::HeaderMap)> {
+        let mut req = self
+            .request
+            .get(name, &GetParams::default())
+            .map_err(Error::BuildRequest)?;
+        req.extensions_mut().insert("get_with_response");
+        self.client.request_with_headers::<K>(req).await
+    }
+
     /// [Get](`Api::get`) a named resource if it exists, returns [`None`] if it doesn't exist
     ///
     /// ```no_run
@@ -170,6 +237,36 @@ where
         }
     }
 
+    /// Get the current `resourceVersion` of a named resource, returns [`None`] if it doesn't exist
+    ///
+    /// Only the metadata of the object is requested (see [`Api::get_metadata`]), making this a cheap
+    /// way to check whether an object exists or has changed, or to fill in a precondition for a write.
+    ///
+    /// ```no_run
+    /// # use kube::Api;
+    /// use k8s_openapi::api::core::v1::Pod;
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// # let last_seen = String::new();
+    /// let pods: Api<Pod> = Api::namespaced(client, "apps");
+    /// match pods.get_resource_version("blog").await? {
+    ///     Some(rv) if rv == last_seen => { /* unchanged */ }
+    ///     Some(_) => { /* changed */ }
+    ///     None => { /* deleted */ }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// An object is assumed to always have a `resourceVersion`, an empty string is returned otherwise.
+    pub async fn get_resource_version(&self, name: &str) -> Result<Option<String>> {
+        Ok(self
+            .get_metadata_opt(name)
+            .await?
+            .map(|meta| meta.metadata.resource_version.unwrap_or_default()))
+    }
+
     /// Get a list of resources
     ///
     /// You use this to get everything, or a subset matching fields/labels, say:
@@ -194,6 +291,89 @@ where
         self.client.request::<ObjectList<K>>(req).await
     }
 
+    /// Get a named resource as the unparsed response body
+    ///
+    /// This is [`Api::get`] without deserializing the object, for when the typed `K` lags the apiserver
+    /// (e.g. a field missing from k8s-openapi), or to parse the object with a different parser.
+    /// The request still goes through the [`Client`](crate::Client) with its authentication and TLS.
+    ///
+    /// Error statuses are still mapped to [`Error::Api`] (or [`Error::TooManyRequests`]),
+    /// but the body of successful responses is returned as is, without any checks that it is a valid object.
+    ///
+    /// ```no_run
+    /// # use kube::Api;
+    /// use k8s_openapi::api::core::v1::Pod;
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::namespaced(client, "apps");
+    /// let raw: Vec<u8> = pods.get_raw("blog").await?;
+    /// let pod: serde_json::Value = serde_json::from_slice(&raw)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_raw(&self, name: &str) -> Result<Vec<u8>> {
+        let mut req = self
+            .request
+            .get(name, &GetParams::default())
+            .map_err(Error::BuildRequest)?;
+        req.extensions_mut().insert("get_raw");
+        self.client.request_bytes(req).await
+    }
+
+    /// Get a list of resources, deserializing each item separately
+    ///
+    /// Unlike [`Api::list`], an item that fails to deserialize as `K` does not fail the whole list,
+    /// but is returned as an [`Error::SerdeError`] in place of the item (and logged with its name).
+    /// This keeps controllers working on the remaining objects when some of them no longer match `K`,
+    /// for instance while the schema of a custom resource is changed by a rolling upgrade.
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, ListParams};
+    /// use k8s_openapi::api::core::v1::Pod;
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::default_namespaced(client);
+    /// let list = pods.list_lenient(&ListParams::default()).await?;
+    /// for pod in list.items.into_iter().flatten() {
+    ///     println!("Found Pod: {}", pod.metadata.name.unwrap());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Errors of the list request itself, and lists that are not valid JSON, still fail the whole call.
+    pub async fn list_lenient(&self, lp: &ListParams) -> Result<LenientList<K>> {
+        let mut req = self.request.list(lp).map_err(Error::BuildRequest)?;
+        req.extensions_mut().insert("list_lenient");
+        let list = self.client.request::<ObjectList<serde_json::Value>>(req).await?;
+        let items = list
+            .items
+            .into_iter()
+            .map(|item| {
+                let (name, namespace) = (item["metadata"]["name"].clone(), item["metadata"]["namespace"].clone());
+                serde_json::from_value(item).map_err(|err| {
+                    tracing::warn!(%name, %namespace, "Skipping list item that failed to deserialize: {}", err);
+                    Error::SerdeError(err)
+                })
+            })
+            .collect();
+        Ok(LenientList {
+            metadata: list.metadata,
+            items,
+        })
+    }
+
+    /// Get a list of resources as the unparsed response body
+    ///
+    /// This is [`Api::list`] without deserializing the list, see [`Api::get_raw`] for details.
+    pub async fn list_raw(&self, lp: &ListParams) -> Result<Vec<u8>> {
+        let mut req = self.request.list(lp).map_err(Error::BuildRequest)?;
+        req.extensions_mut().insert("list_raw");
+        self.client.request_bytes(req).await
+    }
+
     /// Get a list of resources that contains only their metadata as
     ///
     /// Similar to [list](`Api::list`), you use this to get everything, or a
@@ -240,23 +420,59 @@ where
     ///
     /// Note that this method cannot write to the status object (when it exists) of a resource.
     /// To set status objects please see [`Api::replace_status`] or [`Api::patch_status`].
+    ///
+    /// The object is posted to the collection, so the name is only taken from `metadata.name` of the object.
+    /// Objects with a `metadata.generateName` (and no name) get a unique name assigned by the apiserver,
+    /// which is set on the returned object. See [`Api::create_with_generated_name`].
     pub async fn create(&self, pp: &PostParams, data: &K) -> Result<K>
     where
         K: Serialize,
     {
         let bytes = serde_json::to_vec(&data).map_err(Error::SerdeError)?;
-        let mut req = self.request.create(pp, bytes).map_err(Error::BuildRequest)?;
+        let mut req = self.request.create(&self.post_params(pp), bytes).map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("create");
         self.client.request::<K>(req).await
     }
 
+    /// Create a resource with a name generated by the apiserver
+    ///
+    /// Sets `metadata.generateName` of (a copy of) `data` to `prefix` and clears its `metadata.name`,
+    /// since the apiserver ignores `generateName` for objects that have a name.
+    /// The apiserver appends a random suffix to the `prefix`, and the returned object carries the assigned name.
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, PostParams, ResourceExt};
+    /// use k8s_openapi::api::batch::v1::Job;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// # let job: Job = todo!();
+    /// let jobs: Api<Job> = Api::namespaced(client, "apps");
+    /// let created = jobs.create_with_generated_name(&PostParams::default(), "backup-", &job).await?;
+    /// println!("created job {}", created.name_any()); // e.g. backup-x7k2p
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Note that a create with a generated name is not idempotent: retrying it creates another object.
+    pub async fn create_with_generated_name(&self, pp: &PostParams, prefix: &str, data: &K) -> Result<K>
+    where
+        K: Resource + Serialize,
+    {
+        let mut data = data.clone();
+        let meta = data.meta_mut();
+        meta.name = None;
+        meta.generate_name = Some(prefix.to_string());
+        self.create(pp, &data).await
+    }
+
     /// Delete a named resource
     ///
     /// When you get a `K` via `Left`, your delete has started.
     /// When you get a `Status` via `Right`, this should be a a 2XX style
     /// confirmation that the object being gone.
     ///
-    /// 4XX and 5XX status types are returned as an [`Err(kube_client::Error::Api)`](crate::Error::Api).
+    /// 4XX and 5XX status types are returned as an [`Err(kube_client::Error::Api)`](crate::Error::Api),
+    /// except for `429 Too Many Requests`, see [`Error::TooManyRequests`].
     ///
     /// ```no_run
     /// use kube::api::{Api, DeleteParams};
@@ -285,7 +501,8 @@ where
     /// When you get a `Status` via `Right`, this should be a a 2XX style
     /// confirmation that the object being gone.
     ///
-    /// 4XX and 5XX status types are returned as an [`Err(kube_client::Error::Api)`](crate::Error::Api).
+    /// 4XX and 5XX status types are returned as an [`Err(kube_client::Error::Api)`](crate::Error::Api),
+    /// except for `429 Too Many Requests`, see [`Error::TooManyRequests`].
     ///
     /// ```no_run
     /// use kube::api::{Api, DeleteParams, ListParams, ResourceExt};
@@ -347,8 +564,11 @@ where
     /// # }
     /// ```
     /// [`Patch`]: super::Patch
+    /// [`Patch::Apply`]: super::Patch::Apply
     /// [`PatchParams`]: super::PatchParams
     ///
+    /// Apply patches do not need to be a complete object, see [`Patch::Apply`] for applying a partial object.
+    ///
     /// Note that this method cannot write to the status object (when it exists) of a resource.
     /// To set status objects please see [`Api::replace_status`] or [`Api::patch_status`].
     pub async fn patch<P: Serialize + Debug>(
@@ -357,7 +577,10 @@ where
         pp: &PatchParams,
         patch: &Patch<P>,
     ) -> Result<K> {
-        let mut req = self.request.patch(name, pp, patch).map_err(Error::BuildRequest)?;
+        let mut req = self
+            .request
+            .patch(name, &self.patch_params(pp), patch)
+            .map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("patch");
         self.client.request::<K>(req).await
     }
@@ -406,7 +629,7 @@ where
     ) -> Result<PartialObjectMeta<K>> {
         let mut req = self
             .request
-            .patch_metadata(name, pp, patch)
+            .patch_metadata(name, &self.patch_params(pp), patch)
             .map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("patch_metadata");
         self.client.request::<PartialObjectMeta<K>>(req).await
@@ -466,7 +689,7 @@ where
         let bytes = serde_json::to_vec(&data).map_err(Error::SerdeError)?;
         let mut req = self
             .request
-            .replace(name, pp, bytes)
+            .replace(name, &self.post_params(pp), bytes)
             .map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("replace");
         self.client.request::<K>(req).await
@@ -519,6 +742,96 @@ where
         self.client.request_events::<K>(req).await
     }
 
+    /// Watch a list of resources as a single stream of decoded events
+    ///
+    /// This is a [`watch`](Api::watch) that does not have to be awaited before streaming: the initial request
+    /// is sent when the stream is first polled, and its failure is returned as the first item of the stream.
+    /// The stream owns a clone of the [`Api`], so it can be stored or moved to another task freely.
+    ///
+    /// The response is decoded into [`WatchEvent`]s as it arrives. Events may be split across any number of
+    /// network chunks (and chunks may hold several events), incomplete events are buffered until the rest arrives.
+    /// An incomplete event at the end of the response is discarded.
+    ///
+    /// Like [`watch`](Api::watch), the stream ends when the apiserver closes the watch, and the watch has to be
+    /// re-issued with the last seen resource version to continue. Consider using a managed [`watcher`] to
+    /// deal with automatic re-watches and error cases.
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, WatchParams, ResourceExt, WatchEvent};
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// use futures::TryStreamExt;
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::namespaced(client, "apps");
+    /// let events = pods.watch_events(&WatchParams::default(), "0");
+    /// futures::pin_mut!(events);
+    /// while let Some(event) = events.try_next().await? {
+    ///     if let WatchEvent::Added(pod) = event {
+    ///         println!("Added {}", pod.name_any());
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    /// [`watcher`]: https://docs.rs/kube_runtime/*/kube_runtime/watcher/fn.watcher.html
+    pub fn watch_events(&self, wp: &WatchParams, version: &str) -> impl Stream<Item = Result<WatchEvent<K>>>
+    where
+        K: 'static,
+    {
+        let api = self.clone();
+        let wp = wp.clone();
+        let version = version.to_string();
+        futures::stream::once(async move { api.watch(&wp, &version).await }).try_flatten()
+    }
+
+    /// Watch a single object by name
+    ///
+    /// This is a [`watch`](Api::watch) with a `metadata.name` field selector, so only events
+    /// for the named object are sent, in the namespace (or cluster) scope of the [`Api`].
+    ///
+    /// The `version` follows the same semantics as for [`watch`](Api::watch).
+    /// If `version` is empty, the current state of the object is fetched first and emitted
+    /// as an `Added` event (if the object exists), and the watch continues from that state.
+    /// This avoids missing changes that happen between a separate `get` and `watch`.
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, WatchEvent};
+    /// use k8s_openapi::api::core::v1::ConfigMap;
+    /// use futures::TryStreamExt;
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let cms: Api<ConfigMap> = Api::namespaced(client, "apps");
+    /// let mut stream = Box::pin(cms.watch_one("settings", "").await?);
+    /// while let Some(event) = stream.try_next().await? {
+    ///     if let WatchEvent::Added(cm) | WatchEvent::Modified(cm) = event {
+    ///         println!("settings: {:?}", cm.data);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn watch_one(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Result<impl Stream<Item = Result<WatchEvent<K>>>> {
+        let field_selector = format!("metadata.name={name}");
+        let mut initial = Vec::new();
+        let mut version = version.to_string();
+        if version.is_empty() {
+            // a list rather than a get, to obtain a version to watch from even if the object does not exist
+            let list = self.list(&ListParams::default().fields(&field_selector)).await?;
+            version = list.metadata.resource_version.unwrap_or_default();
+            initial.extend(list.items.into_iter().map(|obj| Ok(WatchEvent::Added(obj))));
+        }
+        let events = self
+            .watch(&WatchParams::default().fields(&field_selector), &version)
+            .await?;
+        Ok(futures::stream::iter(initial).chain(events))
+    }
+
     /// Watch a list of metadata for a given resources
     ///
     /// This returns a future that awaits the initial response,
@@ -571,3 +884,62 @@ where
         self.client.request_events::<PartialObjectMeta<K>>(req).await
     }
 }
+
+impl<K> Api<K>
+where
+    K: Resource + Clone + DeserializeOwned + Debug + 'static,
+{
+    /// Watch a list of resources from a shared [`ResumableVersion`], which follows the events of the watch
+    ///
+    /// This is a [`watch_events`](Api::watch_events) starting from the current `version`, which is updated with
+    /// the `resourceVersion` of every object event and [`WatchEvent::Bookmark`] as they are streamed.
+    /// Once the stream ends (or is dropped), calling this again with the same `version` resumes the watch
+    /// without missing or repeating events. Bookmarks (requested by default, see [`WatchParams::bookmarks`])
+    /// are passed through as they are, and keep the version fresh when few objects change.
+    ///
+    /// This is the building block for custom restart logic; a [`watcher`] manages restarts on its own.
+    /// The version is left as is on errors. A [`WatchEvent::Error`] with code `410` means that the version
+    /// has expired, so the objects have to be listed again to find a new version to watch from.
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, ResumableVersion, WatchEvent, WatchParams};
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// use futures::TryStreamExt;
+    ///
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::namespaced(client, "apps");
+    /// let version = ResumableVersion::new("0");
+    /// loop {
+    ///     let events = pods.watch_resumable(&WatchParams::default(), &version);
+    ///     futures::pin_mut!(events);
+    ///     while let Some(event) = events.try_next().await? {
+    ///         if let WatchEvent::Error(err) = event {
+    ///             return Err(err.into());
+    ///         }
+    ///     }
+    ///     println!("watch closed, resuming from {}", version.get());
+    /// }
+    /// # }
+    /// ```
+    /// [`watcher`]: https://docs.rs/kube_runtime/*/kube_runtime/watcher/fn.watcher.html
+    pub fn watch_resumable(
+        &self,
+        wp: &WatchParams,
+        version: &ResumableVersion,
+    ) -> impl Stream<Item = Result<WatchEvent<K>>> {
+        let version = version.clone();
+        self.watch_events(wp, &version.get()).inspect_ok(move |event| {
+            let seen = match event {
+                WatchEvent::Added(obj) | WatchEvent::Modified(obj) | WatchEvent::Deleted(obj) => {
+                    obj.meta().resource_version.as_deref()
+                }
+                WatchEvent::Bookmark(bookmark) => Some(bookmark.metadata.resource_version.as_str()),
+                WatchEvent::Error(_) => None,
+            };
+            if let Some(seen) = seen {
+                version.set(seen);
+            }
+        })
+    }
+}
@@ -0,0 +1,247 @@
+//! API helpers for adding and removing individual finalizers
+//!
+//! [`Api::add_finalizer`] and [`Api::remove_finalizer`] are the entry points for this API.
+use std::fmt::Debug;
+
+use crate::{error::ErrorResponse, Api, Error, Result};
+use json_patch::{AddOperation, PatchOperation, RemoveOperation, TestOperation};
+use kube_core::{
+    params::{Patch, PatchParams},
+    Resource, ResourceExt,
+};
+use serde::de::DeserializeOwned;
+
+/// How many times a finalizer patch is attempted before giving up on conflicts
+const MAX_FINALIZER_PATCH_ATTEMPTS: usize = 5;
+
+impl<K: Resource + Clone + DeserializeOwned + Debug> Api<K> {
+    /// Add the finalizer `finalizer` to the object `name`, if it does not have it yet
+    ///
+    /// The finalizers are patched with a JSON patch that `test`s the current finalizers first, so concurrent
+    /// changes to the finalizers are never lost, and the finalizer is never added twice. The patch is retried
+    /// against the latest version of the object when the finalizers changed in the meantime.
+    ///
+    /// Returns the updated object.
+    ///
+    /// # Usage
+    ///
+    /// ```rust,no_run
+    /// # use k8s_openapi::api::core::v1::ConfigMap;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let cms: kube::Api<ConfigMap> = kube::Api::namespaced(client, "apps");
+    /// cms.add_finalizer("settings", "example.com/cleanup").await?;
+    /// // clean up the resources that the finalizer guards, then
+    /// cms.remove_finalizer("settings", "example.com/cleanup").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "jsonpatch")))]
+    pub async fn add_finalizer(&self, name: &str, finalizer: &str) -> Result<K> {
+        self.patch_finalizers(name, |finalizers| {
+            if finalizers.iter().any(|f| f == finalizer) {
+                return None;
+            }
+            Some(if finalizers.is_empty() {
+                vec![
+                    PatchOperation::Test(TestOperation {
+                        path: "/metadata/finalizers".to_string(),
+                        value: serde_json::Value::Null,
+                    }),
+                    PatchOperation::Add(AddOperation {
+                        path: "/metadata/finalizers".to_string(),
+                        value: vec![finalizer].into(),
+                    }),
+                ]
+            } else {
+                vec![
+                    PatchOperation::Test(TestOperation {
+                        path: "/metadata/finalizers".to_string(),
+                        value: finalizers.into(),
+                    }),
+                    PatchOperation::Add(AddOperation {
+                        path: "/metadata/finalizers/-".to_string(),
+                        value: finalizer.into(),
+                    }),
+                ]
+            })
+        })
+        .await
+    }
+
+    /// Remove the finalizer `finalizer` from the object `name`, if it has it
+    ///
+    /// The finalizer is removed by its index, after a `test` that it is still at that index, so other finalizers
+    /// are never removed by accident. The patch is retried against the latest version of the object when the
+    /// finalizers changed in the meantime.
+    ///
+    /// Returns the updated object. Note that the object may be deleted as soon as its last finalizer is removed.
+    #[cfg_attr(docsrs, doc(cfg(feature = "jsonpatch")))]
+    pub async fn remove_finalizer(&self, name: &str, finalizer: &str) -> Result<K> {
+        self.patch_finalizers(name, |finalizers| {
+            let index = finalizers.iter().position(|f| f == finalizer)?;
+            let path = format!("/metadata/finalizers/{index}");
+            Some(vec![
+                PatchOperation::Test(TestOperation {
+                    path: path.clone(),
+                    value: finalizer.into(),
+                }),
+                PatchOperation::Remove(RemoveOperation { path }),
+            ])
+        })
+        .await
+    }
+
+    /// Patch the finalizers of the object `name` with the operations built by `operations` from its current
+    /// finalizers, retrying on conflicts
+    ///
+    /// `operations` returns `None` when the finalizers do not need to be changed.
+    async fn patch_finalizers(
+        &self,
+        name: &str,
+        operations: impl Fn(&[String]) -> Option<Vec<PatchOperation>>,
+    ) -> Result<K> {
+        let mut attempt = 1;
+        loop {
+            let object = self.get(name).await?;
+            let patch = match operations(object.finalizers()) {
+                Some(patch) => json_patch::Patch(patch),
+                None => return Ok(object),
+            };
+            match self
+                .patch(name, &PatchParams::default(), &Patch::Json::<()>(patch))
+                .await
+            {
+                // A failed `test` is reported as 422 Unprocessable Entity
+                Err(Error::Api(ErrorResponse { code: 409 | 422, .. })) if attempt < MAX_FINALIZER_PATCH_ATTEMPTS => {
+                    tracing::debug!(name, attempt, "finalizers changed concurrently, retrying");
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Api, Client};
+    use futures::pin_mut;
+    use http::{Method, Request, Response, StatusCode};
+    use hyper::Body;
+    use k8s_openapi::api::core::v1::ConfigMap;
+    use serde_json::json;
+    use tower_test::mock;
+
+    fn config_map(finalizers: &[&str]) -> Body {
+        let object = json!({
+            "apiVersion": "v1",
+            "kind": "ConfigMap",
+            "metadata": { "name": "settings", "namespace": "apps", "finalizers": finalizers },
+        });
+        Body::from(object.to_string())
+    }
+
+    async fn patch_body(request: Request<Body>) -> serde_json::Value {
+        assert_eq!(request.method(), Method::PATCH);
+        let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn add_finalizer_retries_when_finalizers_changed_concurrently() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::GET);
+            send.send_response(Response::builder().body(config_map(&[])).unwrap());
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(
+                patch_body(request).await,
+                json!([
+                    { "op": "test", "path": "/metadata/finalizers", "value": null },
+                    { "op": "add", "path": "/metadata/finalizers", "value": ["example.com/cleanup"] },
+                ])
+            );
+            let status = json!({
+                "kind": "Status",
+                "apiVersion": "v1",
+                "status": "Failure",
+                "message": "the server rejected our request due to an error in our request",
+                "reason": "Invalid",
+                "code": 422
+            });
+            send.send_response(
+                Response::builder()
+                    .status(StatusCode::UNPROCESSABLE_ENTITY)
+                    .body(Body::from(status.to_string()))
+                    .unwrap(),
+            );
+
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::GET);
+            send.send_response(Response::builder().body(config_map(&["other"])).unwrap());
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(
+                patch_body(request).await,
+                json!([
+                    { "op": "test", "path": "/metadata/finalizers", "value": ["other"] },
+                    { "op": "add", "path": "/metadata/finalizers/-", "value": "example.com/cleanup" },
+                ])
+            );
+            send.send_response(
+                Response::builder()
+                    .body(config_map(&["other", "example.com/cleanup"]))
+                    .unwrap(),
+            );
+
+            // already added
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::GET);
+            send.send_response(
+                Response::builder()
+                    .body(config_map(&["other", "example.com/cleanup"]))
+                    .unwrap(),
+            );
+        });
+
+        let api: Api<ConfigMap> = Api::namespaced(Client::new(mock_service, "default"), "apps");
+        let cm = api.add_finalizer("settings", "example.com/cleanup").await.unwrap();
+        assert_eq!(cm.metadata.finalizers.unwrap(), ["other", "example.com/cleanup"]);
+        api.add_finalizer("settings", "example.com/cleanup").await.unwrap();
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn remove_finalizer_removes_it_by_index() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::GET);
+            send.send_response(
+                Response::builder()
+                    .body(config_map(&["other", "example.com/cleanup"]))
+                    .unwrap(),
+            );
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(
+                patch_body(request).await,
+                json!([
+                    { "op": "test", "path": "/metadata/finalizers/1", "value": "example.com/cleanup" },
+                    { "op": "remove", "path": "/metadata/finalizers/1" },
+                ])
+            );
+            send.send_response(Response::builder().body(config_map(&["other"])).unwrap());
+        });
+
+        let api: Api<ConfigMap> = Api::namespaced(Client::new(mock_service, "default"), "apps");
+        let cm = api
+            .remove_finalizer("settings", "example.com/cleanup")
+            .await
+            .unwrap();
+        assert_eq!(cm.metadata.finalizers.unwrap(), ["other"]);
+        spawned.await.unwrap();
+    }
+}
@@ -0,0 +1,177 @@
+//! API helpers for forced server-side applies that report the fields taken over from other managers
+//!
+//! [`Api::force_apply`] is the primary entry point for this API.
+use std::fmt::Debug;
+
+use crate::{Api, Error};
+use kube_core::{
+    managed_fields::{transferred_fields, FieldTransfer, FieldsV1Error},
+    params::{Patch, PatchParams},
+    Resource,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+impl<K: Resource + Clone + DeserializeOwned + Debug> Api<K> {
+    /// Force a server-side apply of `data`, and report the fields whose ownership was taken from other managers
+    ///
+    /// A forced apply (see [`PatchParams::force`]) resolves conflicts by taking ownership of the conflicting fields,
+    /// which silently hides that another controller (or a human) manages the same fields. This applies `data` like
+    /// a forced [`Api::patch`] with [`Patch::Apply`], and compares the `managedFields` of the object before and after
+    /// the apply to find the fields that were taken over, so that fights between controllers can be detected.
+    ///
+    /// The object is read before it is applied, so a field that changes owners in between may be misreported.
+    /// Use a plain forced [`Api::patch`] when the transferred fields are not needed, to save the extra request.
+    ///
+    /// # Usage
+    ///
+    /// ```rust,no_run
+    /// # use k8s_openapi::api::apps::v1::Deployment;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// use kube::api::{Api, PatchParams};
+    /// let deploys: Api<Deployment> = Api::namespaced(client, "apps");
+    /// let replicas = serde_json::json!({
+    ///     "apiVersion": "apps/v1",
+    ///     "kind": "Deployment",
+    ///     "metadata": { "name": "web" },
+    ///     "spec": { "replicas": 3 }
+    /// });
+    /// let outcome = deploys.force_apply("web", &PatchParams::apply("autoscaler"), &replicas).await?;
+    /// for transfer in &outcome.transferred {
+    ///     println!("took over {} from {}", transfer.field, transfer.from);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`PatchParams::force`]: crate::api::PatchParams::force
+    /// [`Patch::Apply`]: crate::api::Patch::Apply
+    pub async fn force_apply<P: Serialize + Debug>(
+        &self,
+        name: &str,
+        pp: &PatchParams,
+        data: &P,
+    ) -> Result<ForceApplyOutcome<K>, ForceApplyError> {
+        let mut pp = self.patch_params(pp).into_owned();
+        pp.force = true;
+        let manager = pp.field_manager.clone().ok_or(ForceApplyError::MissingFieldManager)?;
+        let before = self.get_opt(name).await.map_err(ForceApplyError::Get)?;
+        let object = self
+            .patch(name, &pp, &Patch::Apply(data))
+            .await
+            .map_err(ForceApplyError::Apply)?;
+        let transferred = match &before {
+            Some(before) => transferred_fields(before, &object, &manager).map_err(ForceApplyError::ManagedFields)?,
+            // created by the apply
+            None => Vec::new(),
+        };
+        Ok(ForceApplyOutcome { object, transferred })
+    }
+}
+
+/// The result of a successful [`Api::force_apply`]
+#[derive(Debug)]
+pub struct ForceApplyOutcome<K> {
+    /// The applied object, as returned by the apiserver
+    pub object: K,
+    /// The fields that were taken over from other field managers by the apply
+    pub transferred: Vec<FieldTransfer>,
+}
+
+/// Errors from [`Api::force_apply`]
+#[derive(Debug, thiserror::Error)]
+pub enum ForceApplyError {
+    /// Neither the [`PatchParams`] nor the client have a field manager, which is required for applies
+    #[error("a field manager is required for server-side apply")]
+    MissingFieldManager,
+    /// Reading the object before the apply failed, nothing was applied
+    #[error("failed to get object before applying it")]
+    Get(#[source] Error),
+    /// The apply failed
+    #[error("failed to apply object")]
+    Apply(#[source] Error),
+    /// The object was applied, but its `managedFields` could not be decoded to find the transferred fields
+    #[error("failed to decode managed fields of applied object")]
+    ManagedFields(#[source] FieldsV1Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{api::PatchParams, Api, Client};
+    use futures::pin_mut;
+    use http::{Method, Request, Response};
+    use hyper::Body;
+    use k8s_openapi::api::apps::v1::Deployment;
+    use serde_json::json;
+    use tower_test::mock;
+
+    fn deployment(managed_fields: serde_json::Value) -> serde_json::Value {
+        json!({
+            "apiVersion": "apps/v1",
+            "kind": "Deployment",
+            "metadata": { "name": "web", "namespace": "apps", "managedFields": managed_fields },
+        })
+    }
+
+    #[tokio::test]
+    async fn force_apply_reports_fields_taken_from_other_managers() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::GET);
+            assert_eq!(request.uri().to_string(), "/apis/apps/v1/namespaces/apps/deployments/web");
+            let before = deployment(json!([{
+                "manager": "kubectl-edit",
+                "operation": "Update",
+                "apiVersion": "apps/v1",
+                "fieldsType": "FieldsV1",
+                "fieldsV1": { "f:spec": { "f:replicas": {}, "f:paused": {} } }
+            }]));
+            send.send_response(Response::builder().body(Body::from(before.to_string())).unwrap());
+
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::PATCH);
+            assert_eq!(
+                request.uri().to_string(),
+                "/apis/apps/v1/namespaces/apps/deployments/web?&force=true&fieldManager=autoscaler"
+            );
+            let after = deployment(json!([
+                {
+                    "manager": "kubectl-edit",
+                    "operation": "Update",
+                    "apiVersion": "apps/v1",
+                    "fieldsType": "FieldsV1",
+                    "fieldsV1": { "f:spec": { "f:paused": {} } }
+                },
+                {
+                    "manager": "autoscaler",
+                    "operation": "Apply",
+                    "apiVersion": "apps/v1",
+                    "fieldsType": "FieldsV1",
+                    "fieldsV1": { "f:spec": { "f:replicas": {} } }
+                }
+            ]));
+            send.send_response(Response::builder().body(Body::from(after.to_string())).unwrap());
+        });
+
+        let api: Api<Deployment> = Api::namespaced(Client::new(mock_service, "default"), "apps");
+        let replicas = json!({
+            "apiVersion": "apps/v1",
+            "kind": "Deployment",
+            "metadata": { "name": "web" },
+            "spec": { "replicas": 3 }
+        });
+        let outcome = api
+            .force_apply("web", &PatchParams::apply("autoscaler"), &replicas)
+            .await
+            .unwrap();
+        let transferred = outcome
+            .transferred
+            .iter()
+            .map(|transfer| format!("{} from {}", transfer.field, transfer.from))
+            .collect::<Vec<_>>();
+        assert_eq!(transferred, [".spec.replicas from kubectl-edit"]);
+        spawned.await.unwrap();
+    }
+}
@@ -1,10 +1,12 @@
 //! API helpers for structured interaction with the Kubernetes API
 
 mod core_methods;
+pub use core_methods::{LenientList, ResumableVersion};
 #[cfg(feature = "ws")] mod remote_command;
-use std::fmt::Debug;
+use std::{borrow::Cow, fmt::Debug};
 
-#[cfg(feature = "ws")] pub use remote_command::{AttachedProcess, TerminalSize};
+#[cfg(feature = "ws")]
+pub use remote_command::{AttachedProcess, Error as RemoteCommandError, ExecOutput, TerminalSize};
 #[cfg(feature = "ws")] mod portforward;
 #[cfg(feature = "ws")] pub use portforward::Portforwarder;
 
@@ -12,7 +14,7 @@ mod subresource;
 #[cfg(feature = "ws")]
 #[cfg_attr(docsrs, doc(cfg(feature = "ws")))]
 pub use subresource::{Attach, AttachParams, Execute, Portforward};
-pub use subresource::{Evict, EvictParams, Log, LogParams, ScaleSpec, ScaleStatus};
+pub use subresource::{Evict, EvictParams, Log, LogParams, PodLogs, ScaleSpec, ScaleStatus};
 
 // Ephemeral containers were stabilized in Kubernetes 1.25.
 k8s_openapi::k8s_if_ge_1_25! {
@@ -20,8 +22,15 @@ k8s_openapi::k8s_if_ge_1_25! {
 }
 
 mod util;
+pub use util::{AllowedVerbs, ResourceAccess, LAST_APPLIED_CONFIG_ANNOTATION};
 
+pub mod apply_set;
 pub mod entry;
+#[cfg(feature = "jsonpatch")] mod finalizers;
+pub mod force_apply;
+
+mod scoped;
+pub use scoped::ScopedClient;
 
 // Re-exports from kube-core
 #[cfg(feature = "admission")]
@@ -31,7 +40,7 @@ pub(crate) use kube_core::params;
 pub use kube_core::{
     dynamic::{ApiResource, DynamicObject},
     gvk::{GroupVersionKind, GroupVersionResource},
-    metadata::{ListMeta, ObjectMeta, PartialObjectMeta, PartialObjectMetaExt, TypeMeta},
+    metadata::{ListMeta, ObjectMeta, ObjectMetaBuilder, PartialObjectMeta, PartialObjectMetaExt, TypeMeta},
     object::{NotUsed, Object, ObjectList},
     request::Request,
     watch::WatchEvent,
@@ -123,6 +132,30 @@ impl<K: Resource> Api<K> {
     }
 }
 
+impl<K> Api<K> {
+    /// Fill in the client's default field manager when the params do not specify one
+    pub(crate) fn patch_params<'a>(&self, pp: &'a PatchParams) -> Cow<'a, PatchParams> {
+        match (&pp.field_manager, self.client.field_manager()) {
+            (None, Some(manager)) => Cow::Owned(PatchParams {
+                field_manager: Some(manager.to_string()),
+                ..pp.clone()
+            }),
+            _ => Cow::Borrowed(pp),
+        }
+    }
+
+    /// Fill in the client's default field manager when the params do not specify one
+    pub(crate) fn post_params<'a>(&self, pp: &'a PostParams) -> Cow<'a, PostParams> {
+        match (&pp.field_manager, self.client.field_manager()) {
+            (None, Some(manager)) => Cow::Owned(PostParams {
+                field_manager: Some(manager.to_string()),
+                ..pp.clone()
+            }),
+            _ => Cow::Borrowed(pp),
+        }
+    }
+}
+
 /// Api constructors for Resource implementors with Default DynamicTypes
 ///
 /// This generally means structs implementing `k8s_openapi::Resource`.
@@ -242,11 +275,16 @@ impl<K> Debug for Api<K> {
 /// Sanity test on scope restrictions
 #[cfg(test)]
 mod test {
-    use crate::{Api, Client};
+    use crate::{
+        api::{ApiResource, DynamicObject, Patch, PatchParams, PostParams, ResumableVersion, WatchParams},
+        Api, Client, Error,
+    };
     use k8s_openapi::api::core::v1 as corev1;
 
+    use futures::{pin_mut, StreamExt, TryStreamExt};
     use http::{Request, Response};
     use hyper::Body;
+    use kube_core::{ErrorResponse, WatchEvent};
     use tower_test::mock;
 
     #[tokio::test]
@@ -259,4 +297,373 @@ mod test {
         let _: Api<corev1::PersistentVolume> = Api::all(client.clone());
         let _: Api<corev1::ConfigMap> = Api::namespaced(client, "default");
     }
+
+    #[tokio::test]
+    async fn scoped_client_creates_namespaced_apis() {
+        let (mock_service, _handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let scoped = Client::new(mock_service, "default").namespaced("apps");
+        assert_eq!(scoped.namespace(), "apps");
+
+        let pods: Api<corev1::Pod> = scoped.api();
+        assert_eq!(pods.resource_url(), "/api/v1/namespaces/apps/pods");
+        let ar = ApiResource::erase::<corev1::Pod>(&());
+        let dynpods: Api<DynamicObject> = scoped.api_with(&ar);
+        assert_eq!(dynpods.resource_url(), "/api/v1/namespaces/apps/pods");
+        let nodes: Api<corev1::Node> = scoped.all();
+        assert_eq!(nodes.resource_url(), "/api/v1/nodes");
+    }
+
+    #[tokio::test]
+    async fn write_params_inherit_client_field_manager() {
+        let (mock_service, _handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "default").with_field_manager("my-controller");
+        let api: Api<corev1::Pod> = Api::default_namespaced(client);
+
+        let unset = PatchParams::default();
+        assert_eq!(
+            api.patch_params(&unset).field_manager.as_deref(),
+            Some("my-controller")
+        );
+
+        let explicit = PatchParams::apply("explicit");
+        assert_eq!(
+            api.patch_params(&explicit).field_manager.as_deref(),
+            Some("explicit")
+        );
+
+        let unset = PostParams::default();
+        assert_eq!(
+            api.post_params(&unset).field_manager.as_deref(),
+            Some("my-controller")
+        );
+    }
+
+    #[tokio::test]
+    async fn patch_status_applies_to_status_subresource() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), http::Method::PATCH);
+            assert_eq!(
+                request.uri().to_string(),
+                "/api/v1/namespaces/apps/pods/blog/status?&fieldManager=status-manager"
+            );
+            assert_eq!(request.headers()["content-type"], "application/apply-patch+yaml");
+            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+            let applied: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(applied["status"]["phase"], "Running");
+            let pod = serde_json::json!({ "metadata": { "name": "blog" }, "status": { "phase": "Running" } });
+            send.send_response(Response::builder().body(Body::from(pod.to_string())).unwrap());
+        });
+
+        let pods: Api<corev1::Pod> = Api::namespaced(Client::new(mock_service, "default"), "apps");
+        let status = serde_json::json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "status": { "phase": "Running" },
+        });
+        let pp = PatchParams::apply("status-manager");
+        let pod = pods.patch_status("blog", &pp, &Patch::Apply(status)).await.unwrap();
+        assert_eq!(pod.status.unwrap().phase.as_deref(), Some("Running"));
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn create_with_generated_name_returns_assigned_names() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            for suffix in ["abcde", "fghij"] {
+                let (request, send) = handle.next_request().await.expect("service not called");
+                assert_eq!(request.method(), http::Method::POST);
+                assert_eq!(request.uri().to_string(), "/api/v1/namespaces/apps/configmaps?");
+                let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+                let mut cm: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                assert!(cm["metadata"].get("name").is_none());
+                let prefix = cm["metadata"]["generateName"].as_str().unwrap().to_string();
+                cm["metadata"]["name"] = format!("{prefix}{suffix}").into();
+                send.send_response(Response::builder().body(Body::from(cm.to_string())).unwrap());
+            }
+        });
+
+        let cms: Api<corev1::ConfigMap> = Api::namespaced(Client::new(mock_service, "default"), "apps");
+        let mut cm = corev1::ConfigMap::default();
+        cm.metadata.name = Some("ignored".into());
+        let pp = Default::default();
+        let first = cms.create_with_generated_name(&pp, "cm-", &cm).await.unwrap();
+        let second = cms.create_with_generated_name(&pp, "cm-", &cm).await.unwrap();
+        assert_eq!(first.metadata.name.as_deref(), Some("cm-abcde"));
+        assert_eq!(second.metadata.name.as_deref(), Some("cm-fghij"));
+        assert_eq!(first.metadata.generate_name.as_deref(), Some("cm-"));
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn watch_one_lists_before_watching() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(
+                request.uri().to_string(),
+                "/api/v1/nodes?&fieldSelector=metadata.name%3Dnode-1"
+            );
+            let list = serde_json::json!({
+                "apiVersion": "v1",
+                "kind": "NodeList",
+                "metadata": { "resourceVersion": "10" },
+                "items": [{ "metadata": { "name": "node-1", "resourceVersion": "9" } }]
+            });
+            send.send_response(Response::builder().body(Body::from(list.to_string())).unwrap());
+
+            let (request, send) = handle.next_request().await.expect("service not called");
+            let uri = request.uri().to_string();
+            assert!(uri.contains("watch=true"), "{uri}");
+            assert!(uri.contains("resourceVersion=10"), "{uri}");
+            assert!(uri.contains("fieldSelector=metadata.name%3Dnode-1"), "{uri}");
+            let event = serde_json::json!({
+                "type": "MODIFIED",
+                "object": { "apiVersion": "v1", "kind": "Node", "metadata": { "name": "node-1", "resourceVersion": "11" } }
+            });
+            send.send_response(Response::builder().body(Body::from(format!("{event}\n"))).unwrap());
+        });
+
+        let api: Api<corev1::Node> = Api::all(Client::new(mock_service, "default"));
+        let stream = api.watch_one("node-1", "").await.unwrap();
+        pin_mut!(stream);
+        let versions = |event: WatchEvent<corev1::Node>| match event {
+            WatchEvent::Added(node) => format!("added@{}", node.metadata.resource_version.unwrap()),
+            WatchEvent::Modified(node) => format!("modified@{}", node.metadata.resource_version.unwrap()),
+            _ => panic!("unexpected event"),
+        };
+        assert_eq!(versions(stream.next().await.unwrap().unwrap()), "added@9");
+        assert_eq!(versions(stream.next().await.unwrap().unwrap()), "modified@11");
+        assert!(stream.next().await.is_none());
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn watch_events_decodes_events_split_across_chunks() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            let uri = request.uri().to_string();
+            assert!(uri.contains("watch=true") && uri.contains("resourceVersion=10"), "{uri}");
+            let event = |type_: &str, name: &str| {
+                let event = serde_json::json!({
+                    "type": type_,
+                    "object": { "apiVersion": "v1", "kind": "ConfigMap", "metadata": { "name": name } }
+                });
+                format!("{event}\n")
+            };
+            // non-ascii names, so that a chunk boundary can fall within a character
+            let stream = [event("ADDED", "größe"), event("MODIFIED", "größe"), event("DELETED", "näme")].concat();
+            let bytes = stream.into_bytes();
+            let split = bytes.iter().position(|b| *b == 0xc3).unwrap() + 1;
+            let (mut tx, body) = Body::channel();
+            send.send_response(Response::builder().body(body).unwrap());
+            for chunk in [&bytes[..10], &bytes[10..split], &bytes[split..bytes.len() - 5], &bytes[bytes.len() - 5..]] {
+                tx.send_data(hyper::body::Bytes::copy_from_slice(chunk)).await.unwrap();
+            }
+            // an incomplete event when the watch is closed
+            tx.send_data(hyper::body::Bytes::from_static(b"{\"type\":\"ADD")).await.unwrap();
+        });
+
+        let api: Api<corev1::ConfigMap> = Api::namespaced(Client::new(mock_service, "default"), "apps");
+        let events = api
+            .watch_events(&WatchParams::default(), "10")
+            .map_ok(|event| match event {
+                WatchEvent::Added(cm) => format!("added {}", cm.metadata.name.unwrap()),
+                WatchEvent::Modified(cm) => format!("modified {}", cm.metadata.name.unwrap()),
+                WatchEvent::Deleted(cm) => format!("deleted {}", cm.metadata.name.unwrap()),
+                _ => panic!("unexpected event"),
+            })
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(events, ["added größe", "modified größe", "deleted näme"]);
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn watch_events_returns_request_errors_in_the_stream() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (_, send) = handle.next_request().await.expect("service not called");
+            let status = r#"{"kind":"Status","apiVersion":"v1","status":"Failure","message":"forbidden","reason":"Forbidden","code":403}"#;
+            send.send_response(Response::builder().status(403).body(Body::from(status)).unwrap());
+        });
+
+        let api: Api<corev1::ConfigMap> = Api::namespaced(Client::new(mock_service, "default"), "apps");
+        let events = api.watch_events(&WatchParams::default(), "0").collect::<Vec<_>>().await;
+        assert!(matches!(&events[..], [Err(Error::Api(ErrorResponse { code: 403, .. }))]));
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn watch_resumable_resumes_from_the_last_seen_version() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            let uri = request.uri().to_string();
+            assert!(uri.contains("resourceVersion=10"), "{uri}");
+            let events = [
+                serde_json::json!({
+                    "type": "ADDED",
+                    "object": { "apiVersion": "v1", "kind": "ConfigMap", "metadata": { "name": "cm", "resourceVersion": "11" } }
+                }),
+                serde_json::json!({
+                    "type": "BOOKMARK",
+                    "object": { "apiVersion": "v1", "kind": "ConfigMap", "metadata": { "resourceVersion": "12" } }
+                }),
+            ];
+            let body = events.iter().map(|event| format!("{event}\n")).collect::<String>();
+            send.send_response(Response::builder().body(Body::from(body)).unwrap());
+
+            let (request, send) = handle.next_request().await.expect("service not called");
+            let uri = request.uri().to_string();
+            assert!(uri.contains("resourceVersion=12"), "{uri}");
+            send.send_response(Response::builder().body(Body::empty()).unwrap());
+        });
+
+        let api: Api<corev1::ConfigMap> = Api::namespaced(Client::new(mock_service, "default"), "apps");
+        let version = ResumableVersion::new("10");
+        let events = api
+            .watch_resumable(&WatchParams::default(), &version)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert!(matches!(&events[..], [WatchEvent::Added(_), WatchEvent::Bookmark(_)]));
+        assert_eq!(version.get(), "12");
+        let events = api.watch_resumable(&WatchParams::default(), &version).collect::<Vec<_>>().await;
+        assert!(events.is_empty());
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn get_raw_returns_body_and_maps_errors() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.uri().to_string(), "/api/v1/namespaces/apps/pods/blog");
+            send.send_response(Response::builder().body(Body::from("{\"unknownField\": 1}")).unwrap());
+
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.uri().to_string(), "/api/v1/namespaces/apps/pods?");
+            let status = serde_json::json!({
+                "kind": "Status",
+                "apiVersion": "v1",
+                "status": "Failure",
+                "message": "pods is forbidden",
+                "reason": "Forbidden",
+                "code": 403,
+            });
+            send.send_response(
+                Response::builder()
+                    .status(403)
+                    .body(Body::from(status.to_string()))
+                    .unwrap(),
+            );
+        });
+
+        let pods: Api<corev1::Pod> = Api::namespaced(Client::new(mock_service, "default"), "apps");
+        let raw = pods.get_raw("blog").await.unwrap();
+        assert_eq!(raw, b"{\"unknownField\": 1}");
+        let err = pods.list_raw(&Default::default()).await.unwrap_err();
+        assert!(matches!(err, crate::Error::Api(ae) if ae.code == 403));
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn get_resource_version_requests_metadata_only() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.uri().to_string(), "/api/v1/namespaces/apps/pods/blog");
+            assert!(request.headers()["accept"]
+                .to_str()
+                .unwrap()
+                .contains("as=PartialObjectMetadata"));
+            let meta = serde_json::json!({
+                "apiVersion": "meta.k8s.io/v1",
+                "kind": "PartialObjectMetadata",
+                "metadata": { "name": "blog", "resourceVersion": "42" },
+            });
+            send.send_response(Response::builder().body(Body::from(meta.to_string())).unwrap());
+
+            let (_, send) = handle.next_request().await.expect("service not called");
+            let status = serde_json::json!({
+                "kind": "Status",
+                "apiVersion": "v1",
+                "status": "Failure",
+                "message": "pods \"gone\" not found",
+                "reason": "NotFound",
+                "code": 404,
+            });
+            send.send_response(
+                Response::builder()
+                    .status(404)
+                    .body(Body::from(status.to_string()))
+                    .unwrap(),
+            );
+        });
+
+        let pods: Api<corev1::Pod> = Api::namespaced(Client::new(mock_service, "default"), "apps");
+        assert_eq!(pods.get_resource_version("blog").await.unwrap().as_deref(), Some("42"));
+        assert_eq!(pods.get_resource_version("gone").await.unwrap(), None);
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn list_lenient_keeps_valid_items() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.uri().to_string(), "/api/v1/namespaces/apps/pods?");
+            let list = serde_json::json!({
+                "metadata": { "resourceVersion": "3" },
+                "items": [
+                    { "metadata": { "name": "good" } },
+                    { "metadata": { "name": "bad" }, "spec": { "containers": "not a list" } },
+                ],
+            });
+            send.send_response(Response::builder().body(Body::from(list.to_string())).unwrap());
+        });
+
+        let pods: Api<corev1::Pod> = Api::namespaced(Client::new(mock_service, "default"), "apps");
+        let list = pods.list_lenient(&Default::default()).await.unwrap();
+        assert_eq!(list.metadata.resource_version.as_deref(), Some("3"));
+        assert!(matches!(&list.items[..], [Ok(pod), Err(crate::Error::SerdeError(_))] if pod.metadata.name.as_deref() == Some("good")));
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn get_with_response_returns_headers() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.uri().to_string(), "/api/v1/namespaces/apps/pods/blog");
+            let pod = serde_json::json!({ "metadata": { "name": "blog" } });
+            send.send_response(
+                Response::builder()
+                    .header("warning", "299 - \"deprecated\"")
+                    .body(Body::from(pod.to_string()))
+                    .unwrap(),
+            );
+        });
+
+        let pods: Api<corev1::Pod> = Api::namespaced(Client::new(mock_service, "default"), "apps");
+        let (pod, headers) = pods.get_with_response("blog").await.unwrap();
+        assert_eq!(pod.metadata.name.as_deref(), Some("blog"));
+        assert_eq!(headers["warning"], "299 - \"deprecated\"");
+        spawned.await.unwrap();
+    }
 }
@@ -10,6 +10,8 @@ use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, DuplexStream};
 use tokio_tungstenite::{tungstenite as ws, WebSocketStream};
 use tokio_util::io::ReaderStream;
 
+use crate::client::StreamProtocol;
+
 /// Errors from Portforwarder.
 #[derive(Debug, Error)]
 pub enum Error {
@@ -66,6 +68,9 @@ pub enum Error {
     Shutdown(#[source] std::io::Error),
 }
 
+// close channel (v5) is used to signal that the channel in the message is closed
+const CLOSE_CHANNEL: u8 = 255;
+
 type ErrorReceiver = oneshot::Receiver<String>;
 type ErrorSender = oneshot::Sender<String>;
 
@@ -89,7 +94,7 @@ pub struct Portforwarder {
 }
 
 impl Portforwarder {
-    pub(crate) fn new<S>(stream: WebSocketStream<S>, port_nums: &[u16]) -> Self
+    pub(crate) fn new<S>(stream: WebSocketStream<S>, port_nums: &[u16], protocol: StreamProtocol) -> Self
     where
         S: AsyncRead + AsyncWrite + Unpin + Sized + Send + 'static,
     {
@@ -111,6 +116,7 @@ impl Portforwarder {
             port_nums.to_vec(),
             task_ios,
             error_txs,
+            protocol,
         ));
 
         Portforwarder {
@@ -163,6 +169,7 @@ async fn start_message_loop<S>(
     ports: Vec<u16>,
     duplexes: Vec<DuplexStream>,
     error_senders: Vec<Option<ErrorSender>>,
+    protocol: StreamProtocol,
 ) -> Result<(), Error>
 where
     S: AsyncRead + AsyncWrite + Unpin + Sized + Send + 'static,
@@ -183,7 +190,7 @@ where
 
     let (ws_sink, ws_stream) = stream.split();
     loops.push(from_pod_loop(ws_stream, sender).boxed());
-    loops.push(forwarder_loop(&ports, receiver, ws_sink, writers, error_senders).boxed());
+    loops.push(forwarder_loop(&ports, receiver, ws_sink, writers, error_senders, protocol).boxed());
 
     future::try_join_all(loops).await.map(|_| ())
 }
@@ -254,12 +261,14 @@ where
 // On `Message::ToPod(ch, bytes)`, a WebSocket message is sent with the channel prefix.
 // On `Message::FromPod(ch, bytes)` with an even `ch`, `bytes` are written to the port's sink.
 // On `Message::FromPod(ch, bytes)` with an odd `ch`, an error message is sent to the error channel of the port.
+// On `Message::ToPodClose(ch)`, the pod is told that the port's data channel is closed, if the protocol supports it.
 async fn forwarder_loop<S>(
     ports: &[u16],
     mut receiver: mpsc::Receiver<Message>,
     mut ws_sink: futures::stream::SplitSink<WebSocketStream<S>, ws::Message>,
     mut writers: Vec<tokio::io::WriteHalf<DuplexStream>>,
     mut error_senders: Vec<Option<ErrorSender>>,
+    protocol: StreamProtocol,
 ) -> Result<(), Error>
 where
     S: AsyncRead + AsyncWrite + Unpin + Sized + Send + 'static,
@@ -335,6 +344,12 @@ where
                     channel.shutdown = true;
 
                     closed_ports += 1;
+                    if protocol.supports_close() {
+                        ws_sink
+                            .send(ws::Message::binary(vec![CLOSE_CHANNEL, ch as u8]))
+                            .await
+                            .map_err(Error::SendWebSocketMessage)?;
+                    }
                 }
             }
             Message::FromPodClose => {
@@ -354,3 +369,50 @@ where
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Portforwarder;
+    use crate::client::StreamProtocol;
+    use futures::{SinkExt, StreamExt};
+    use tokio::io::AsyncWriteExt;
+    use tokio_tungstenite::{
+        tungstenite::{protocol::Role, Message},
+        WebSocketStream,
+    };
+
+    async fn forwarded_port(
+        protocol: StreamProtocol,
+    ) -> (Portforwarder, WebSocketStream<tokio::io::DuplexStream>) {
+        let (client, server) = tokio::io::duplex(1024);
+        let client = WebSocketStream::from_raw_socket(client, Role::Client, None).await;
+        let mut server = WebSocketStream::from_raw_socket(server, Role::Server, None).await;
+        // the initial frames of the data and error channels, with the port number
+        for ch in [0, 1] {
+            server.send(Message::binary(vec![ch, 80, 0])).await.unwrap();
+        }
+        (Portforwarder::new(client, &[80], protocol), server)
+    }
+
+    #[tokio::test]
+    async fn closing_a_port_with_v5_signals_the_pod() {
+        let (mut forwarder, mut server) = forwarded_port(StreamProtocol::V5).await;
+        let mut port = forwarder.take_stream(80).unwrap();
+        port.write_all(b"hi").await.unwrap();
+        port.shutdown().await.unwrap();
+
+        assert_eq!(server.next().await.unwrap().unwrap(), Message::binary(b"\x00hi".to_vec()));
+        // the close signal for the data channel of the port, before the connection is closed
+        assert_eq!(server.next().await.unwrap().unwrap(), Message::binary(vec![255, 0]));
+        assert!(server.next().await.unwrap().unwrap().is_close());
+    }
+
+    #[tokio::test]
+    async fn closing_a_port_with_v4_closes_the_connection() {
+        let (mut forwarder, mut server) = forwarded_port(StreamProtocol::V4).await;
+        let mut port = forwarder.take_stream(80).unwrap();
+        port.shutdown().await.unwrap();
+
+        assert!(server.next().await.unwrap().unwrap().is_close());
+    }
+}
@@ -9,7 +9,7 @@ use futures::{
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tokio::{
-    io::{AsyncRead, AsyncWrite, AsyncWriteExt, DuplexStream},
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, DuplexStream},
     select,
 };
 use tokio_tungstenite::{
@@ -18,6 +18,7 @@ use tokio_tungstenite::{
 };
 
 use super::AttachParams;
+use crate::client::StreamProtocol;
 
 type StatusReceiver = oneshot::Receiver<Status>;
 type StatusSender = oneshot::Sender<Status>;
@@ -58,7 +59,7 @@ pub enum Error {
     #[error("failed to receive a WebSocket message: {0}")]
     ReceiveWebSocketMessage(#[source] ws::Error),
 
-    // Failed to complete the background task
+    /// Failed to complete the background task
     #[error("failed to complete the background task: {0}")]
     Spawn(#[source] tokio::task::JoinError),
 
@@ -89,6 +90,49 @@ pub enum Error {
 
 const MAX_BUF_SIZE: usize = 1024;
 
+/// The collected output of a command run with [`exec_and_collect`](crate::Api::exec_and_collect).
+#[cfg_attr(docsrs, doc(cfg(feature = "ws")))]
+#[derive(Debug, Clone, Default)]
+pub struct ExecOutput {
+    /// Everything the command wrote to stdout
+    pub stdout: Vec<u8>,
+    /// Everything the command wrote to stderr (empty when run with a `tty`)
+    pub stderr: Vec<u8>,
+    /// The status object sent by the server when the command exited, if any
+    pub status: Option<Status>,
+}
+
+impl ExecOutput {
+    /// The exit code of the command, as reported through its [`Status`]
+    ///
+    /// Returns `None` if no status was received, or if it did not carry an exit code.
+    pub fn exit_code(&self) -> Option<i32> {
+        let status = self.status.as_ref()?;
+        if status.status.as_deref() == Some("Success") {
+            return Some(0);
+        }
+        if status.reason.as_deref() != Some("NonZeroExitCode") {
+            return None;
+        }
+        status
+            .details
+            .as_ref()?
+            .causes
+            .as_ref()?
+            .iter()
+            .find(|cause| cause.reason.as_deref() == Some("ExitCode"))?
+            .message
+            .as_ref()?
+            .parse()
+            .ok()
+    }
+
+    /// Whether the command exited successfully
+    pub fn success(&self) -> bool {
+        self.exit_code() == Some(0)
+    }
+}
+
 /// Represents an attached process in a container for [`attach`] and [`exec`].
 ///
 /// Provides access to `stdin`, `stdout`, and `stderr` if attached.
@@ -107,11 +151,12 @@ pub struct AttachedProcess {
     stderr_reader: Option<DuplexStream>,
     status_rx: Option<StatusReceiver>,
     terminal_resize_tx: Option<TerminalSizeSender>,
+    protocol: StreamProtocol,
     task: tokio::task::JoinHandle<Result<(), Error>>,
 }
 
 impl AttachedProcess {
-    pub(crate) fn new<S>(stream: WebSocketStream<S>, ap: &AttachParams) -> Self
+    pub(crate) fn new<S>(stream: WebSocketStream<S>, ap: &AttachParams, protocol: StreamProtocol) -> Self
     where
         S: AsyncRead + AsyncWrite + Unpin + Sized + Send + 'static,
     {
@@ -145,6 +190,7 @@ impl AttachedProcess {
             stderr_writer,
             status_tx,
             terminal_resize_rx,
+            protocol,
         ));
 
         AttachedProcess {
@@ -157,6 +203,7 @@ impl AttachedProcess {
             stderr_reader,
             terminal_resize_tx,
             status_rx: Some(status_rx),
+            protocol,
         }
     }
 
@@ -172,6 +219,10 @@ impl AttachedProcess {
     /// # }
     /// ```
     /// Only available if [`AttachParams`](super::AttachParams) had `stdin`.
+    ///
+    /// Dropping the writer closes the stdin of the process. This requires the `v5.channel.k8s.io`
+    /// protocol (Kubernetes 1.29+), which is negotiated when the server supports it.
+    /// With older servers, dropping the writer closes the whole connection instead.
     pub fn stdin(&mut self) -> Option<impl AsyncWrite + Unpin> {
         if !self.has_stdin {
             return None;
@@ -237,6 +288,47 @@ impl AttachedProcess {
         self.status_rx.take().map(|recv| recv.map(|res| res.ok()))
     }
 
+    /// Pipe `stdin` (if any) into the process, and collect its output until it exits.
+    ///
+    /// The stdin of the process is closed once `stdin` is exhausted if the server supports it.
+    /// Otherwise the stdin pipe stays open until the process exits, as closing it would close the whole connection.
+    pub(crate) async fn collect(mut self, stdin: Option<impl AsyncRead + Unpin>) -> Result<ExecOutput, Error> {
+        let status = self.take_status();
+        let mut stdin_writer = self.stdin();
+        let (stdout, stderr) = (self.stdout(), self.stderr());
+        let close_stdin = self.protocol.supports_close();
+
+        let write_stdin = async {
+            if let (Some(mut stdin), Some(writer)) = (stdin, stdin_writer.as_mut()) {
+                match tokio::io::copy(&mut stdin, writer).await {
+                    // The process exited without reading all of stdin
+                    Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => {}
+                    Err(err) => return Err(Error::ReadStdin(err)),
+                    Ok(_) => {}
+                }
+            }
+            if close_stdin {
+                stdin_writer.take();
+            }
+            Ok(())
+        };
+        let read_stdout = read_to_end(stdout, Error::WriteStdout);
+        let read_stderr = read_to_end(stderr, Error::WriteStderr);
+        let ((), stdout, stderr) = futures::try_join!(write_stdin, read_stdout, read_stderr)?;
+        drop(stdin_writer);
+
+        let status = match status {
+            Some(status) => status.await,
+            None => None,
+        };
+        self.join().await?;
+        Ok(ExecOutput {
+            stdout,
+            stderr,
+            status,
+        })
+    }
+
     /// Async writer to change the terminal size
     /// ```no_run
     /// # use kube_client::api::{AttachedProcess, TerminalSize};
@@ -258,6 +350,17 @@ impl AttachedProcess {
     }
 }
 
+async fn read_to_end(
+    reader: Option<impl AsyncRead + Unpin>,
+    map_err: fn(std::io::Error) -> Error,
+) -> Result<Vec<u8>, Error> {
+    let mut buf = Vec::new();
+    if let Some(mut reader) = reader {
+        reader.read_to_end(&mut buf).await.map_err(map_err)?;
+    }
+    Ok(buf)
+}
+
 // theses values come from here: https://github.com/kubernetes/kubernetes/blob/master/pkg/kubelet/cri/streaming/remotecommand/websocket.go#L34
 const STDIN_CHANNEL: u8 = 0;
 const STDOUT_CHANNEL: u8 = 1;
@@ -266,6 +369,8 @@ const STDERR_CHANNEL: u8 = 2;
 const STATUS_CHANNEL: u8 = 3;
 // resize channel is use to send TerminalSize object to change the size of the terminal
 const RESIZE_CHANNEL: u8 = 4;
+// close channel (v5) is used to signal that the channel in the message is closed
+const CLOSE_CHANNEL: u8 = 255;
 
 async fn start_message_loop<S>(
     stream: WebSocketStream<S>,
@@ -274,6 +379,7 @@ async fn start_message_loop<S>(
     mut stderr: Option<impl AsyncWrite + Unpin>,
     status_tx: StatusSender,
     mut terminal_size_rx: Option<TerminalSizeReceiver>,
+    protocol: StreamProtocol,
 ) -> Result<(), Error>
 where
     S: AsyncRead + AsyncWrite + Unpin + Sized + Send + 'static,
@@ -283,6 +389,7 @@ where
     // Work with filtered messages to reduce noise.
     let mut server_recv = raw_server_recv.filter_map(filter_message).boxed();
     let mut have_terminal_size_rx = terminal_size_rx.is_some();
+    let mut stdin_open = true;
 
     loop {
         let terminal_size_next = async {
@@ -318,7 +425,7 @@ where
                     },
                 }
             },
-            stdin_message = stdin_stream.next() => {
+            stdin_message = stdin_stream.next(), if stdin_open => {
                 match stdin_message {
                     Some(Ok(bytes)) => {
                         if !bytes.is_empty() {
@@ -334,9 +441,18 @@ where
                     Some(Err(err)) => {
                         return Err(Error::ReadStdin(err));
                     }
+                    None if protocol.supports_close() => {
+                        // Stdin closed (writer half dropped).
+                        // Let the server know, and keep receiving output until the process exits.
+                        server_send
+                            .send(ws::Message::binary(vec![CLOSE_CHANNEL, STDIN_CHANNEL]))
+                            .await
+                            .map_err(Error::SendStdin)?;
+                        stdin_open = false;
+                    }
                     None => {
                         // Stdin closed (writer half dropped).
-                        // Let the server know and disconnect.
+                        // The protocol cannot close a single channel, so let the server know and disconnect.
                         server_send.close().await.map_err(Error::SendClose)?;
                         break;
                     }
@@ -393,3 +509,118 @@ async fn filter_message(wsm: Result<ws::Message, ws::Error>) -> Option<Result<Me
         Err(err) => Some(Err(err)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{AttachedProcess, ExecOutput};
+    use crate::{api::AttachParams, client::StreamProtocol};
+    use futures::{SinkExt, StreamExt};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_tungstenite::{
+        tungstenite::{protocol::Role, Message},
+        WebSocketStream,
+    };
+
+    async fn connected_process(
+        protocol: StreamProtocol,
+    ) -> (AttachedProcess, WebSocketStream<tokio::io::DuplexStream>) {
+        connected_process_with(protocol, AttachParams::default().stdin(true).stderr(false)).await
+    }
+
+    async fn connected_process_with(
+        protocol: StreamProtocol,
+        ap: AttachParams,
+    ) -> (AttachedProcess, WebSocketStream<tokio::io::DuplexStream>) {
+        let (client, server) = tokio::io::duplex(1024);
+        let client = WebSocketStream::from_raw_socket(client, Role::Client, None).await;
+        let server = WebSocketStream::from_raw_socket(server, Role::Server, None).await;
+        (AttachedProcess::new(client, &ap, protocol), server)
+    }
+
+    /// Sends `stdout` and a successful status, then closes the connection like a finished process
+    async fn exit_with(server: &mut WebSocketStream<tokio::io::DuplexStream>, stdout: &[u8]) {
+        server.send(Message::binary([&[1], stdout].concat())).await.unwrap();
+        let status = [&[3], &br#"{"status":"Success"}"#[..]].concat();
+        server.send(Message::binary(status)).await.unwrap();
+        server.close(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn closing_stdin_with_v5_keeps_the_connection_open() {
+        let (mut process, mut server) = connected_process(StreamProtocol::V5).await;
+        let mut stdin = process.stdin().unwrap();
+        let mut stdout = process.stdout().unwrap();
+        let status = process.take_status().unwrap();
+        stdin.write_all(b"hi").await.unwrap();
+        drop(stdin);
+
+        assert_eq!(server.next().await.unwrap().unwrap(), Message::binary(b"\x00hi".to_vec()));
+        // the close signal for the stdin channel
+        assert_eq!(server.next().await.unwrap().unwrap(), Message::binary(vec![255, 0]));
+        // the process keeps running, and its output and status are still received
+        server.send(Message::binary(b"\x01bye".to_vec())).await.unwrap();
+        let mut status_msg = vec![3];
+        status_msg.extend_from_slice(br#"{"status":"Success"}"#);
+        server.send(Message::binary(status_msg)).await.unwrap();
+
+        let mut out = [0; 3];
+        stdout.read_exact(&mut out).await.unwrap();
+        assert_eq!(&out, b"bye");
+        assert_eq!(status.await.unwrap().status.as_deref(), Some("Success"));
+        process.join().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn closing_stdin_with_v4_closes_the_connection() {
+        let (mut process, mut server) = connected_process(StreamProtocol::V4).await;
+        drop(process.stdin().unwrap());
+        assert!(matches!(server.next().await.unwrap().unwrap(), Message::Close(_)));
+        drop(server);
+        process.join().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn collect_keeps_stdin_open_with_v4() {
+        let (process, mut server) = connected_process(StreamProtocol::V4).await;
+        let server = async move {
+            assert_eq!(server.next().await.unwrap().unwrap(), Message::binary(b"\x00hi".to_vec()));
+            // stdin is not closed, since that would close the connection, so the process exits on its own
+            exit_with(&mut server, b"bye").await;
+        };
+        let (output, ()) = futures::join!(process.collect(Some(&b"hi"[..])), server);
+        let output = output.unwrap();
+        assert_eq!(output.stdout, b"bye");
+        assert!(output.success());
+    }
+
+    #[tokio::test]
+    async fn collect_without_stdin_does_not_write_to_the_process() {
+        let ap = AttachParams::default().stdin(false).stderr(false);
+        let (process, mut server) = connected_process_with(StreamProtocol::V5, ap).await;
+        let server = async move {
+            exit_with(&mut server, b"done").await;
+            // nothing was sent on the stdin channel, not even a close signal
+            assert!(!matches!(server.next().await, Some(Ok(Message::Binary(_)))));
+        };
+        let (output, ()) = futures::join!(process.collect(None::<&[u8]>), server);
+        assert_eq!(output.unwrap().stdout, b"done");
+    }
+
+    #[test]
+    fn exec_output_exit_code() {
+        let output = |status: serde_json::Value| ExecOutput {
+            status: Some(serde_json::from_value(status).unwrap()),
+            ..ExecOutput::default()
+        };
+        assert_eq!(output(serde_json::json!({ "status": "Success" })).exit_code(), Some(0));
+        let failed = output(serde_json::json!({
+            "status": "Failure",
+            "reason": "NonZeroExitCode",
+            "message": "command terminated with non-zero exit code: error executing command [sh -c exit 3], exit code 3",
+            "details": { "causes": [{ "reason": "ExitCode", "message": "3" }] }
+        }));
+        assert_eq!(failed.exit_code(), Some(3));
+        assert!(!failed.success());
+        assert_eq!(ExecOutput::default().exit_code(), None);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{api::Api, Client};
+use kube_core::{DynamicResourceScope, NamespaceResourceScope, Resource};
+
+/// A [`Client`] bound to a single namespace
+///
+/// Creates [`Api`] instances for that namespace, for controllers and tools that only ever
+/// operate within one namespace and would otherwise have to pass it to every [`Api::namespaced`] call.
+/// Created with [`Client::namespaced`].
+///
+/// The underlying [`Client`] is shared by all the created [`Api`] instances,
+/// so they all use the same connection pool.
+///
+/// ```no_run
+/// use k8s_openapi::api::core::v1::{ConfigMap, Pod};
+/// use kube::{Api, Client};
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = Client::try_default().await?;
+/// let scoped = client.namespaced("apps");
+/// let pods: Api<Pod> = scoped.api();
+/// let cms = scoped.api::<ConfigMap>();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ScopedClient {
+    client: Client,
+    namespace: String,
+}
+
+impl ScopedClient {
+    /// Bind a [`Client`] to a namespace
+    pub fn new(client: Client, namespace: impl Into<String>) -> Self {
+        Self {
+            client,
+            namespace: namespace.into(),
+        }
+    }
+
+    /// The namespace this client is bound to
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// A reference to the underlying [`Client`]
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// An [`Api`] for a namespaced resource within the bound namespace
+    ///
+    /// This will ONLY work on namespaced resources, as with [`Api::namespaced`]:
+    ///
+    /// ```compile_fail
+    /// # use kube::{Api, Client};
+    /// # let client: Client = todo!();
+    /// use k8s_openapi::api::core::v1::Node;
+    /// let api: Api<Node> = client.namespaced("default").api(); // resource not namespaced!
+    /// ```
+    pub fn api<K>(&self) -> Api<K>
+    where
+        K: Resource<Scope = NamespaceResourceScope>,
+        K::DynamicType: Default,
+    {
+        Api::namespaced(self.client.clone(), &self.namespace)
+    }
+
+    /// An [`Api`] for a dynamic resource within the bound namespace
+    ///
+    /// This function accepts `K::DynamicType` so it can be used with dynamic resources.
+    pub fn api_with<K>(&self, dyntype: &K::DynamicType) -> Api<K>
+    where
+        K: Resource<Scope = DynamicResourceScope>,
+    {
+        Api::namespaced_with(self.client.clone(), &self.namespace, dyntype)
+    }
+
+    /// An [`Api`] for cluster level resources, or resources across all namespaces
+    ///
+    /// Convenience for [`Api::all`] on the underlying [`Client`], for the few cluster level
+    /// resources (like `Namespace` or `Node`) a namespaced controller may still need.
+    pub fn all<K>(&self) -> Api<K>
+    where
+        K: Resource,
+        K::DynamicType: Default,
+    {
+        Api::all(self.client.clone())
+    }
+}
+
+impl From<ScopedClient> for Client {
+    fn from(scoped: ScopedClient) -> Self {
+        scoped.client
+    }
+}
+
+impl Client {
+    /// Bind a clone of this client to a namespace
+    ///
+    /// See [`ScopedClient`] for details.
+    pub fn namespaced(&self, namespace: impl Into<String>) -> ScopedClient {
+        ScopedClient::new(self.clone(), namespace)
+    }
+}
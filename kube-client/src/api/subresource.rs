@@ -1,6 +1,7 @@
 use futures::AsyncBufRead;
+use k8s_openapi::api::core::v1::{ContainerState, ContainerStatus, Pod};
 use serde::{de::DeserializeOwned, Serialize};
-use std::fmt::Debug;
+use std::{collections::BTreeMap, fmt::Debug};
 
 use crate::{
     api::{Api, Patch, PatchParams, PostParams},
@@ -17,7 +18,9 @@ pub use kube_core::subresource::AttachParams;
 pub use k8s_openapi::api::autoscaling::v1::{Scale, ScaleSpec, ScaleStatus};
 
 #[cfg(feature = "ws")] use crate::api::portforward::Portforwarder;
-#[cfg(feature = "ws")] use crate::api::remote_command::AttachedProcess;
+#[cfg(feature = "ws")]
+use crate::api::remote_command::{AttachedProcess, ExecOutput};
+#[cfg(feature = "ws")] use crate::client::STREAM_PROTOCOLS;
 
 /// Methods for [scale subresource](https://kubernetes.io/docs/tasks/access-kubernetes-api/custom-resources/custom-resource-definitions/#scale-subresource).
 impl<K> Api<K>
@@ -43,7 +46,7 @@ where
     ) -> Result<Scale> {
         let mut req = self
             .request
-            .patch_subresource("scale", name, pp, patch)
+            .patch_subresource("scale", name, &self.patch_params(pp), patch)
             .map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("patch_scale");
         self.client.request::<Scale>(req).await
@@ -53,7 +56,7 @@ where
     pub async fn replace_scale(&self, name: &str, pp: &PostParams, data: Vec<u8>) -> Result<Scale> {
         let mut req = self
             .request
-            .replace_subresource("scale", name, pp, data)
+            .replace_subresource("scale", name, &self.post_params(pp), data)
             .map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("replace_scale");
         self.client.request::<Scale>(req).await
@@ -88,7 +91,7 @@ where
     {
         let mut req = self
             .request
-            .create_subresource(subresource_name, name, pp, data)
+            .create_subresource(subresource_name, name, &self.post_params(pp), data)
             .map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("create_subresource");
         self.client.request::<T>(req).await
@@ -104,7 +107,7 @@ where
     ) -> Result<K> {
         let mut req = self
             .request
-            .patch_subresource(subresource_name, name, pp, patch)
+            .patch_subresource(subresource_name, name, &self.patch_params(pp), patch)
             .map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("patch_subresource");
         self.client.request::<K>(req).await
@@ -120,7 +123,7 @@ where
     ) -> Result<K> {
         let mut req = self
             .request
-            .replace_subresource(subresource_name, name, pp, data)
+            .replace_subresource(subresource_name, name, &self.post_params(pp), data)
             .map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("replace_subresource");
         self.client.request::<K>(req).await
@@ -195,7 +198,7 @@ where
             .replace_subresource(
                 "ephemeralcontainers",
                 name,
-                pp,
+                &self.post_params(pp),
                 serde_json::to_vec(data).map_err(Error::SerdeError)?,
             )
             .map_err(Error::BuildRequest)?;
@@ -260,7 +263,7 @@ where
     ) -> Result<K> {
         let mut req = self
             .request
-            .patch_subresource("ephemeralcontainers", name, pp, patch)
+            .patch_subresource("ephemeralcontainers", name, &self.patch_params(pp), patch)
             .map_err(Error::BuildRequest)?;
 
         req.extensions_mut().insert("patch_ephemeralcontainers");
@@ -324,6 +327,34 @@ where
     /// # Ok(())
     /// # }
     /// ```
+    ///
+    /// # Server-side apply
+    ///
+    /// With [`Patch::Apply`], only the status is applied, to the `/status` endpoint with the field manager of
+    /// the [`PatchParams`]. The apiserver tracks the applied status fields in a separate `managedFields` entry
+    /// with `subresource: status`, so a controller can apply the status with its own field manager without
+    /// taking ownership of (or pruning) any fields of the spec, and vice versa.
+    ///
+    /// Like any apply, the patch must include the `apiVersion` and `kind` of the object:
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, PatchParams, Patch};
+    /// use k8s_openapi::api::batch::v1::Job;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = kube::Client::try_default().await?;
+    /// let jobs: Api<Job> = Api::namespaced(client, "apps");
+    /// let status = serde_json::json!({
+    ///     "apiVersion": "batch/v1",
+    ///     "kind": "Job",
+    ///     "status": {
+    ///         "succeeded": 2
+    ///     }
+    /// });
+    /// let pp = PatchParams::apply("my-status-manager");
+    /// jobs.patch_status("baz", &pp, &Patch::Apply(status)).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub async fn patch_status<P: serde::Serialize + Debug>(
         &self,
         name: &str,
@@ -332,7 +363,7 @@ where
     ) -> Result<K> {
         let mut req = self
             .request
-            .patch_subresource("status", name, pp, patch)
+            .patch_subresource("status", name, &self.patch_params(pp), patch)
             .map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("patch_status");
         self.client.request::<K>(req).await
@@ -359,7 +390,7 @@ where
     pub async fn replace_status(&self, name: &str, pp: &PostParams, data: Vec<u8>) -> Result<K> {
         let mut req = self
             .request
-            .replace_subresource("status", name, pp, data)
+            .replace_subresource("status", name, &self.post_params(pp), data)
             .map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("replace_status");
         self.client.request::<K>(req).await
@@ -431,6 +462,163 @@ where
     }
 }
 
+/// Logs for all containers of a pod, as returned by [`Api::logs_all_containers`]
+#[derive(Debug, Default)]
+pub struct PodLogs {
+    /// Logs keyed by container name
+    pub logs: BTreeMap<String, String>,
+    /// Containers that were skipped because they have not started, keyed by container name
+    ///
+    /// The value is a note with the reason the container is waiting, when known.
+    pub skipped: BTreeMap<String, String>,
+    /// Failures to fetch logs keyed by container name
+    pub errors: BTreeMap<String, Error>,
+}
+
+impl Api<Pod> {
+    /// Fetch logs for every container in a pod concurrently
+    ///
+    /// The container of the passed [`LogParams`] is ignored; every container of the pod is queried instead.
+    /// Init containers are included when `include_init` is set.
+    ///
+    /// Containers that have not started yet are skipped rather than failing the call,
+    /// and failures for individual containers are collected in [`PodLogs::errors`].
+    /// Only failing to fetch the pod itself returns an error.
+    ///
+    /// ```no_run
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # use k8s_openapi::api::core::v1::Pod;
+    /// # use kube::{api::{Api, LogParams}, Client};
+    /// # let client: Client = todo!();
+    /// let pods: Api<Pod> = Api::default_namespaced(client);
+    /// let pod_logs = pods.logs_all_containers("my-pod", &LogParams::default(), true).await?;
+    /// for (container, logs) in &pod_logs.logs {
+    ///     println!("{container}: {logs}");
+    /// }
+    /// for (container, note) in &pod_logs.skipped {
+    ///     println!("{container} skipped: {note}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn logs_all_containers(
+        &self,
+        name: &str,
+        lp: &LogParams,
+        include_init: bool,
+    ) -> Result<PodLogs> {
+        let pod = self.get(name).await?;
+        let (containers, skipped) = log_containers(&pod, include_init);
+        let fetches = containers.into_iter().map(|container| async move {
+            let lp = LogParams {
+                container: Some(container.clone()),
+                ..lp.clone()
+            };
+            let logs = self.logs(name, &lp).await;
+            (container, logs)
+        });
+        let mut pod_logs = PodLogs {
+            skipped,
+            ..PodLogs::default()
+        };
+        for (container, logs) in futures::future::join_all(fetches).await {
+            match logs {
+                Ok(logs) => {
+                    pod_logs.logs.insert(container, logs);
+                }
+                Err(err) => {
+                    pod_logs.errors.insert(container, err);
+                }
+            }
+        }
+        Ok(pod_logs)
+    }
+}
+
+/// Split the containers of a pod into those that can have logs and those that have not started
+fn log_containers(pod: &Pod, include_init: bool) -> (Vec<String>, BTreeMap<String, String>) {
+    let (spec, status) = (pod.spec.as_ref(), pod.status.as_ref());
+    let mut containers: Vec<(&str, Option<&Vec<ContainerStatus>>)> = vec![];
+    if include_init {
+        let init_statuses = status.and_then(|s| s.init_container_statuses.as_ref());
+        for c in spec
+            .and_then(|s| s.init_containers.as_ref())
+            .into_iter()
+            .flatten()
+        {
+            containers.push((&c.name, init_statuses));
+        }
+    }
+    let statuses = status.and_then(|s| s.container_statuses.as_ref());
+    for c in spec.map(|s| &s.containers).into_iter().flatten() {
+        containers.push((&c.name, statuses));
+    }
+
+    let mut started = vec![];
+    let mut skipped = BTreeMap::new();
+    for (container, statuses) in containers {
+        let status = statuses.and_then(|ss| ss.iter().find(|s| s.name == container));
+        match status.map(container_started) {
+            Some(Ok(())) => started.push(container.to_string()),
+            Some(Err(note)) => {
+                skipped.insert(container.to_string(), note);
+            }
+            None => {
+                skipped.insert(container.to_string(), "no container status".to_string());
+            }
+        }
+    }
+    (started, skipped)
+}
+
+/// Whether a container has run at some point, otherwise a note on why it is waiting
+fn container_started(status: &ContainerStatus) -> std::result::Result<(), String> {
+    let has_run = |state: Option<&ContainerState>| {
+        state.map_or(false, |s| s.running.is_some() || s.terminated.is_some())
+    };
+    if has_run(status.state.as_ref()) || has_run(status.last_state.as_ref()) {
+        return Ok(());
+    }
+    let waiting = status.state.as_ref().and_then(|s| s.waiting.as_ref());
+    Err(match waiting.and_then(|w| w.reason.as_ref()) {
+        Some(reason) => format!("container not started: {reason}"),
+        None => "container not started".to_string(),
+    })
+}
+
+#[test]
+fn log_containers_skips_unstarted() {
+    let pod: Pod = serde_json::from_value(serde_json::json!({
+        "metadata": { "name": "foo" },
+        "spec": {
+            "initContainers": [{ "name": "init" }],
+            "containers": [{ "name": "app" }, { "name": "sidecar" }, { "name": "new" }]
+        },
+        "status": {
+            "initContainerStatuses": [{
+                "name": "init", "image": "i", "imageID": "", "ready": false, "restartCount": 0,
+                "state": { "terminated": { "exitCode": 0 } }
+            }],
+            "containerStatuses": [{
+                "name": "app", "image": "i", "imageID": "", "ready": true, "restartCount": 0,
+                "state": { "running": {} }
+            }, {
+                "name": "sidecar", "image": "i", "imageID": "", "ready": false, "restartCount": 0,
+                "state": { "waiting": { "reason": "ContainerCreating" } }
+            }]
+        }
+    }))
+    .unwrap();
+
+    let (started, skipped) = log_containers(&pod, false);
+    assert_eq!(started, vec!["app"]);
+    assert_eq!(skipped["sidecar"], "container not started: ContainerCreating");
+    assert_eq!(skipped["new"], "no container status");
+
+    let (started, _) = log_containers(&pod, true);
+    assert_eq!(started, vec!["init", "app"]);
+}
+
 // ----------------------------------------------------------------------------
 // Eviction subresource
 // ----------------------------------------------------------------------------
@@ -502,8 +690,11 @@ where
     pub async fn attach(&self, name: &str, ap: &AttachParams) -> Result<AttachedProcess> {
         let mut req = self.request.attach(name, ap).map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("attach");
-        let stream = self.client.connect(req).await?;
-        Ok(AttachedProcess::new(stream, ap))
+        let (stream, protocol) = self
+            .client
+            .connect_with_protocols(req, STREAM_PROTOCOLS)
+            .await?;
+        Ok(AttachedProcess::new(stream, ap, protocol))
     }
 }
 
@@ -560,8 +751,59 @@ where
             .exec(name, command, ap)
             .map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("exec");
-        let stream = self.client.connect(req).await?;
-        Ok(AttachedProcess::new(stream, ap))
+        let (stream, protocol) = self
+            .client
+            .connect_with_protocols(req, STREAM_PROTOCOLS)
+            .await?;
+        Ok(AttachedProcess::new(stream, ap, protocol))
+    }
+
+    /// Execute a command in a pod, piping `stdin` into it and collecting its output
+    ///
+    /// This is the equivalent of `kubectl exec`, and always attaches stdout (stderr is collected if enabled in `ap`).
+    /// Stdin is only attached when `stdin` is given, which is the equivalent of `kubectl exec -i`.
+    /// Returns once the command has exited.
+    ///
+    /// ```no_run
+    /// # use k8s_openapi::api::core::v1::Pod;
+    /// # use kube::{api::{Api, AttachParams}, Client};
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: Client = todo!();
+    /// let pods: Api<Pod> = Api::default_namespaced(client);
+    /// let input = &b"hello\n"[..];
+    /// let output = pods
+    ///     .exec_and_collect("busybox", vec!["head", "-n1"], &AttachParams::default(), Some(input))
+    ///     .await?;
+    /// assert_eq!(output.stdout, b"hello\n");
+    /// assert!(output.success());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Commands that do not read any input should be run without stdin (`None::<&[u8]>`).
+    ///
+    /// The stdin of the command is closed once `stdin` is exhausted, which requires the `v5.channel.k8s.io`
+    /// protocol (Kubernetes 1.29+). With older servers, stdin can only be closed by closing the whole
+    /// connection, so it stays open until the command exits, and commands that read stdin until EOF
+    /// (like `cat`) do not terminate on their own.
+    pub async fn exec_and_collect<I, T, R>(
+        &self,
+        name: &str,
+        command: I,
+        ap: &AttachParams,
+        stdin: Option<R>,
+    ) -> Result<ExecOutput>
+    where
+        I: IntoIterator<Item = T> + Debug,
+        T: Into<String>,
+        R: tokio::io::AsyncRead + Unpin,
+    {
+        let ap = ap.clone().stdin(stdin.is_some()).stdout(true);
+        let process = self.exec(name, command, &ap).await?;
+        process
+            .collect(stdin)
+            .await
+            .map_err(|err| Error::RemoteCommand(Box::new(err)))
     }
 }
 
@@ -599,7 +841,7 @@ where
             .request
             .portforward(name, ports)
             .map_err(Error::BuildRequest)?;
-        let stream = self.client.connect(req).await?;
-        Ok(Portforwarder::new(stream, ports))
+        let (stream, protocol) = self.client.connect_with_protocols(req, STREAM_PROTOCOLS).await?;
+        Ok(Portforwarder::new(stream, ports, protocol))
     }
 }
@@ -0,0 +1,201 @@
+use crate::{
+    api::{Api, Patch, PatchParams, PostParams},
+    Error, Result,
+};
+use kube_core::{csaupgrade::upgrade_managed_fields, Resource, ResourceExt};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Map, Value};
+use std::fmt::Debug;
+
+/// Annotation used by `kubectl apply` to store the last applied configuration
+pub const LAST_APPLIED_CONFIG_ANNOTATION: &str = "kubectl.kubernetes.io/last-applied-configuration";
+
+impl<K> Api<K>
+where
+    K: Resource + Clone + DeserializeOwned + Serialize + Debug,
+{
+    /// Apply an object using server-side apply when available, and client-side apply otherwise
+    ///
+    /// This lets the same code target clusters with and without
+    /// [server-side apply](https://kubernetes.io/docs/reference/using-api/server-side-apply/).
+    /// The decision is made from the [`Capabilities`](crate::client::Capabilities) of the apiserver:
+    ///
+    /// - On 1.16+ (where server-side apply is enabled by default) the object is sent as a
+    ///   [`Patch::Apply`] with the given [`PatchParams`].
+    /// - On older clusters, a client-side three-way merge is performed like `kubectl apply` does.
+    ///   The object is created if it does not exist. Otherwise a JSON merge patch is computed from the
+    ///   configuration stored in the `kubectl.kubernetes.io/last-applied-configuration` annotation,
+    ///   the given object, and the current object. Fields that were previously applied but are no longer
+    ///   set are removed, and fields that differ from the current object are set.
+    ///   The annotation is updated with the given object.
+    ///
+    /// Unlike `kubectl apply`, the client-side fallback does not use strategic merge patches,
+    /// so lists are always replaced as a whole. `PatchParams::force` is ignored by the fallback.
+    ///
+    /// ```no_run
+    /// use kube::api::{Api, PatchParams};
+    /// use k8s_openapi::api::core::v1::ConfigMap;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let cms: Api<ConfigMap> = Api::namespaced(client, "apps");
+    /// let cm: ConfigMap = serde_json::from_value(serde_json::json!({
+    ///     "metadata": { "name": "settings" },
+    ///     "data": { "mode": "fast" }
+    /// }))?;
+    /// cms.smart_apply("settings", &PatchParams::apply("myapp"), &cm).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn smart_apply(&self, name: &str, pp: &PatchParams, data: &K) -> Result<K> {
+        if self.client.capabilities().await?.server_side_apply() {
+            return self.patch(name, pp, &Patch::Apply(data)).await;
+        }
+
+        let mut desired = serde_json::to_value(data).map_err(Error::SerdeError)?;
+        if let Some(annotations) = desired.pointer_mut("/metadata/annotations").and_then(Value::as_object_mut) {
+            annotations.remove(LAST_APPLIED_CONFIG_ANNOTATION);
+        }
+        let last_applied_config = serde_json::to_string(&desired).map_err(Error::SerdeError)?;
+        set_annotation(&mut desired, LAST_APPLIED_CONFIG_ANNOTATION, last_applied_config);
+
+        match self.get_opt(name).await? {
+            None => {
+                let pp = PostParams {
+                    dry_run: pp.dry_run,
+                    field_manager: pp.field_manager.clone(),
+                };
+                let data = serde_json::from_value(desired).map_err(Error::SerdeError)?;
+                self.create(&pp, &data).await
+            }
+            Some(current) => {
+                let current = serde_json::to_value(&current).map_err(Error::SerdeError)?;
+                let last_applied = current
+                    .pointer(&format!(
+                        "/metadata/annotations/{}",
+                        LAST_APPLIED_CONFIG_ANNOTATION.replace('/', "~1")
+                    ))
+                    .and_then(Value::as_str)
+                    .and_then(|config| serde_json::from_str::<Value>(config).ok());
+                let patch = three_way_merge(last_applied.as_ref(), &desired, Some(&current))
+                    .unwrap_or_else(|| Value::Object(Map::new()));
+                let pp = PatchParams {
+                    dry_run: pp.dry_run,
+                    field_manager: pp.field_manager.clone(),
+                    ..PatchParams::default()
+                };
+                self.patch(name, &pp, &Patch::Merge(patch)).await
+            }
+        }
+    }
+
+    /// Transfer ownership of fields from client-side apply managers to a server-side apply manager
+    ///
+    /// Objects previously managed with client-side apply (like `kubectl apply` without `--server-side`)
+    /// have their fields owned by the client-side managers. Server-side applying them under `ssa_manager`
+    /// would then conflict with, or fail to remove, fields owned by those managers.
+    /// This rewrites the `managedFields` of the object so that `ssa_manager` owns them instead,
+    /// mirroring what `kubectl apply --server-side` does when taking over an object.
+    /// See [`upgrade_managed_fields`] for the details of the migration.
+    ///
+    /// The rewrite is guarded by the `resourceVersion` of the object, so a concurrent change fails it
+    /// with a `409 Conflict`, after which it can be retried. Objects that are already migrated are returned
+    /// without being written.
+    ///
+    /// ```no_run
+    /// use kube::{api::{Api, Patch, PatchParams}, core::csaupgrade::KUBECTL_CSA_MANAGERS};
+    /// use k8s_openapi::api::apps::v1::Deployment;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// # let deployment: Deployment = todo!();
+    /// let deploys: Api<Deployment> = Api::namespaced(client, "apps");
+    /// deploys.upgrade_to_server_side_apply("web", KUBECTL_CSA_MANAGERS, "my-operator").await?;
+    /// deploys.patch("web", &PatchParams::apply("my-operator"), &Patch::Apply(&deployment)).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn upgrade_to_server_side_apply(
+        &self,
+        name: &str,
+        csa_managers: &[&str],
+        ssa_manager: &str,
+    ) -> Result<K> {
+        let mut obj = self.get(name).await?;
+        if !upgrade_managed_fields(obj.managed_fields_mut(), csa_managers, ssa_manager) {
+            return Ok(obj);
+        }
+        let patch = json!({
+            "metadata": {
+                "managedFields": obj.managed_fields(),
+                "resourceVersion": obj.resource_version(),
+            }
+        });
+        self.patch(name, &PatchParams::default(), &Patch::Merge(patch)).await
+    }
+}
+
+fn set_annotation(obj: &mut Value, key: &str, value: String) {
+    let metadata = obj
+        .as_object_mut()
+        .map(|obj| obj.entry("metadata").or_insert_with(|| Value::Object(Map::new())));
+    let annotations = metadata
+        .and_then(Value::as_object_mut)
+        .map(|meta| meta.entry("annotations").or_insert_with(|| Value::Object(Map::new())));
+    if let Some(annotations) = annotations.and_then(Value::as_object_mut) {
+        annotations.insert(key.to_string(), Value::String(value));
+    }
+}
+
+/// Compute the JSON merge patch that turns `current` into `desired`, removing fields only set in `last_applied`
+///
+/// Returns `None` if no changes are needed.
+fn three_way_merge(last_applied: Option<&Value>, desired: &Value, current: Option<&Value>) -> Option<Value> {
+    match (desired, current) {
+        (Value::Object(desired), Some(Value::Object(current))) => {
+            let last_applied = last_applied.and_then(Value::as_object);
+            let mut patch = Map::new();
+            for (key, value) in desired {
+                let last = last_applied.and_then(|last| last.get(key));
+                if let Some(change) = three_way_merge(last, value, current.get(key)) {
+                    patch.insert(key.clone(), change);
+                }
+            }
+            for key in last_applied.into_iter().flat_map(Map::keys) {
+                if !desired.contains_key(key) && current.contains_key(key) {
+                    patch.insert(key.clone(), Value::Null);
+                }
+            }
+            (!patch.is_empty()).then(|| Value::Object(patch))
+        }
+        (desired, current) if current == Some(desired) => None,
+        (desired, _) => Some(desired.clone()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::three_way_merge;
+    use serde_json::json;
+
+    #[test]
+    fn three_way_merge_removes_fields_dropped_since_last_apply() {
+        let last_applied = json!({ "data": { "a": "1", "b": "2" } });
+        let desired = json!({ "data": { "a": "1", "c": "3" } });
+        // `d` was set by another actor, and `a` was changed behind our back
+        let current = json!({ "data": { "a": "changed", "b": "2", "d": "4" }, "status": {} });
+        assert_eq!(
+            three_way_merge(Some(&last_applied), &desired, Some(&current)),
+            Some(json!({ "data": { "a": "1", "b": null, "c": "3" } }))
+        );
+    }
+
+    #[test]
+    fn three_way_merge_replaces_lists_and_skips_unchanged() {
+        let desired = json!({ "spec": { "ports": [80], "replicas": 2 } });
+        let current = json!({ "spec": { "ports": [80, 443], "replicas": 2 } });
+        assert_eq!(
+            three_way_merge(None, &desired, Some(&current)),
+            Some(json!({ "spec": { "ports": [80] } }))
+        );
+        assert_eq!(three_way_merge(Some(&desired), &desired, Some(&desired)), None);
+    }
+}
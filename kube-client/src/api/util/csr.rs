@@ -13,7 +13,7 @@ impl Api<CertificateSigningRequest> {
     ) -> Result<CertificateSigningRequest> {
         let mut req = self
             .request
-            .patch_subresource("approval", name, pp, patch)
+            .patch_subresource("approval", name, &self.patch_params(pp), patch)
             .map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("approval");
         self.client.request::<CertificateSigningRequest>(req).await
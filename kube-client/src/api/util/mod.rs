@@ -1,14 +1,21 @@
 use crate::{
-    api::{Api, Resource},
-    Error, Result,
+    api::{Api, ObjectMeta, Patch, PatchParams, Resource, ResourceExt},
+    Client, Error, Result,
 };
 use k8s_openapi::api::{
     authentication::v1::TokenRequest,
-    core::v1::{Node, ServiceAccount},
+    authorization::v1::{
+        ResourceAttributes, ResourceRule, SelfSubjectAccessReview, SelfSubjectAccessReviewSpec,
+        SelfSubjectRulesReview, SelfSubjectRulesReviewSpec, SubjectRulesReviewStatus,
+    },
+    core::v1::{Namespace, Node, ServiceAccount, Taint},
 };
-use kube_core::{params::PostParams, util::Restart};
+use kube_core::{params::PostParams, util::Restart, ErrorResponse};
 use serde::de::DeserializeOwned;
+use std::collections::BTreeSet;
 
+mod apply;
+pub use apply::LAST_APPLIED_CONFIG_ANNOTATION;
 mod csr;
 
 impl<K> Api<K>
@@ -25,6 +32,8 @@ where
 
 impl Api<Node> {
     /// Cordon a Node.
+    ///
+    /// Cordoning a Node that is already cordoned is a no-op.
     pub async fn cordon(&self, name: &str) -> Result<Node> {
         let mut req = self.request.cordon(name).map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("cordon");
@@ -37,6 +46,317 @@ impl Api<Node> {
         req.extensions_mut().insert("cordon");
         self.client.request::<Node>(req).await
     }
+
+    /// Add taints to a Node, like `kubectl taint`
+    ///
+    /// Existing taints with the same key and effect as one of `taints` are replaced by it,
+    /// all other taints of the Node are kept. The Node is not patched when this changes nothing.
+    ///
+    /// The taints are replaced as a whole, guarded by the `resourceVersion` of the Node,
+    /// so this fails with a `409 Conflict` when the Node changed concurrently.
+    pub async fn patch_taints(&self, name: &str, taints: &[Taint]) -> Result<Node> {
+        self.update_taints(name, |current| merge_taints(current, taints))
+            .await
+    }
+
+    /// Remove taints from a Node, like `kubectl taint <taint>-`
+    ///
+    /// Taints are removed when their key and effect match one of `taints`, regardless of their value.
+    /// See [`Api::patch_taints`] for how the Node is patched.
+    pub async fn remove_taints(&self, name: &str, taints: &[Taint]) -> Result<Node> {
+        self.update_taints(name, |current| {
+            current
+                .iter()
+                .filter(|taint| !taints.iter().any(|removed| same_taint(taint, removed)))
+                .cloned()
+                .collect()
+        })
+        .await
+    }
+
+    async fn update_taints(&self, name: &str, update: impl FnOnce(&[Taint]) -> Vec<Taint>) -> Result<Node> {
+        let node = self.get(name).await?;
+        let current = node
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.taints.as_deref())
+            .unwrap_or_default();
+        let taints = update(current);
+        if taints == current {
+            return Ok(node);
+        }
+        // lists are replaced by merge patches, the resourceVersion guards against lost updates
+        let patch = serde_json::json!({
+            "metadata": { "resourceVersion": node.resource_version() },
+            "spec": { "taints": taints },
+        });
+        self.patch(name, &PatchParams::default(), &Patch::Merge(patch)).await
+    }
+}
+
+/// Taints are identified by their key and effect, like `kubectl taint` does
+fn same_taint(a: &Taint, b: &Taint) -> bool {
+    a.key == b.key && a.effect == b.effect
+}
+
+fn merge_taints(current: &[Taint], taints: &[Taint]) -> Vec<Taint> {
+    let mut merged = current.to_vec();
+    for taint in taints {
+        match merged.iter_mut().find(|existing| same_taint(existing, taint)) {
+            Some(existing) => *existing = taint.clone(),
+            None => merged.push(taint.clone()),
+        }
+    }
+    merged
+}
+
+impl Api<Namespace> {
+    /// Create a Namespace if it does not already exist
+    ///
+    /// A namespace that already exists is treated as success, so this can be used to
+    /// idempotently prepare the target namespace before applying resources into it.
+    ///
+    /// A namespace that is being deleted cannot hold new resources, so it is rejected with an
+    /// [`Error::Api`] with reason `NamespaceTerminating`.
+    pub async fn ensure_namespace(&self, name: &str) -> Result<()> {
+        if let Some(ns) = self.get_opt(name).await? {
+            return ensure_active(ns);
+        }
+        let ns = Namespace {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                ..ObjectMeta::default()
+            },
+            ..Namespace::default()
+        };
+        match self.create(&PostParams::default(), &ns).await {
+            Ok(_) => Ok(()),
+            // created concurrently
+            Err(Error::Api(ErrorResponse { code: 409, .. })) => ensure_active(self.get(name).await?),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+fn ensure_active(ns: Namespace) -> Result<()> {
+    let phase = ns.status.and_then(|status| status.phase);
+    if phase.as_deref() != Some("Terminating") {
+        return Ok(());
+    }
+    Err(Error::Api(ErrorResponse {
+        status: "Failure".into(),
+        message: format!("namespace {} is being terminated", ns.metadata.name.unwrap_or_default()),
+        reason: "NamespaceTerminating".into(),
+        code: 409,
+    }))
+}
+
+/// The attributes of an action on a resource, to check with [`Client::can_i_with`]
+///
+/// Unset attributes act as wildcards, so a check without a namespace asks whether the action is allowed
+/// in all namespaces.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ResourceAccess {
+    attributes: ResourceAttributes,
+}
+
+impl ResourceAccess {
+    /// Check `verb` (like `get`, `list` or `create`) on the plural `resource` of the core group
+    pub fn new(verb: impl Into<String>, resource: impl Into<String>) -> Self {
+        Self {
+            attributes: ResourceAttributes {
+                verb: Some(verb.into()),
+                resource: Some(resource.into()),
+                ..ResourceAttributes::default()
+            },
+        }
+    }
+
+    /// Check `verb` on the resource `K`, taking its group and plural from [`Resource`]
+    pub fn of<K: Resource>(verb: impl Into<String>, dt: &K::DynamicType) -> Self {
+        Self::new(verb, K::plural(dt)).group(K::group(dt))
+    }
+
+    /// Set the api group of the resource, the core group is `""`
+    #[must_use]
+    pub fn group(mut self, group: impl Into<String>) -> Self {
+        self.attributes.group = Some(group.into());
+        self
+    }
+
+    /// Set the api version of the resource
+    #[must_use]
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.attributes.version = Some(version.into());
+        self
+    }
+
+    /// Limit the check to a namespace
+    #[must_use]
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.attributes.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Limit the check to a single object
+    #[must_use]
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.attributes.name = Some(name.into());
+        self
+    }
+
+    /// Check a subresource, like `log` or `scale`
+    #[must_use]
+    pub fn subresource(mut self, subresource: impl Into<String>) -> Self {
+        self.attributes.subresource = Some(subresource.into());
+        self
+    }
+}
+
+impl From<ResourceAccess> for ResourceAttributes {
+    fn from(access: ResourceAccess) -> Self {
+        access.attributes
+    }
+}
+
+impl Client {
+    /// Create a Namespace if it does not already exist
+    ///
+    /// See [`Api::ensure_namespace`].
+    pub async fn ensure_namespace(&self, name: &str) -> Result<()> {
+        Api::<Namespace>::all(self.clone()).ensure_namespace(name).await
+    }
+
+    /// Check whether the client is allowed to perform an action, like `kubectl auth can-i`
+    ///
+    /// The `resource` uses the format of `kubectl auth can-i`: a plural resource name, qualified with its
+    /// api group for resources outside the core group, and optionally followed by a subresource:
+    /// `pods`, `deployments.apps`, or `pods/log`. Pass `None` as `namespace` to check all namespaces,
+    /// or for cluster scoped resources.
+    ///
+    /// This performs a `SelfSubjectAccessReview`, see [`Client::can_i_with`] to check other attributes,
+    /// such as a specific object name.
+    ///
+    /// ```no_run
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// if !client.can_i("patch", "deployments.apps", Some("apps")).await? {
+    ///     return Err("missing rbac permissions to patch deployments".into());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn can_i(&self, verb: &str, resource: &str, namespace: Option<&str>) -> Result<bool> {
+        let (resource, subresource) = match resource.split_once('/') {
+            Some((resource, subresource)) => (resource, Some(subresource.to_string())),
+            None => (resource, None),
+        };
+        let (resource, group) = match resource.split_once('.') {
+            Some((resource, group)) => (resource, Some(group.to_string())),
+            None => (resource, None),
+        };
+        self.can_i_with(ResourceAttributes {
+            verb: Some(verb.to_string()),
+            resource: Some(resource.to_string()),
+            group,
+            subresource,
+            namespace: namespace.map(String::from),
+            ..ResourceAttributes::default()
+        })
+        .await
+    }
+
+    /// Check whether the client is allowed to perform an action on resources with the given attributes
+    ///
+    /// This performs a `SelfSubjectAccessReview` and returns whether it was allowed.
+    /// The attributes can be built with [`ResourceAccess`]:
+    ///
+    /// ```no_run
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// use k8s_openapi::api::apps::v1::Deployment;
+    /// use kube::api::ResourceAccess;
+    ///
+    /// let access = ResourceAccess::of::<Deployment>("patch", &())
+    ///     .namespace("apps")
+    ///     .name("web")
+    ///     .subresource("scale");
+    /// if !client.can_i_with(access).await? {
+    ///     return Err("missing rbac permissions to scale apps/web".into());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn can_i_with(&self, attributes: impl Into<ResourceAttributes>) -> Result<bool> {
+        let review = SelfSubjectAccessReview {
+            spec: SelfSubjectAccessReviewSpec {
+                resource_attributes: Some(attributes.into()),
+                non_resource_attributes: None,
+            },
+            ..SelfSubjectAccessReview::default()
+        };
+        let review = Api::<SelfSubjectAccessReview>::all(self.clone())
+            .create(&PostParams::default(), &review)
+            .await?;
+        Ok(review.status.map_or(false, |status| status.allowed))
+    }
+
+    /// List the rules that the client is allowed to act on in a namespace
+    ///
+    /// This performs a `SelfSubjectRulesReview`. The rules are `incomplete` when an authorizer of the apiserver
+    /// can not enumerate its rules (such as a webhook authorizer), so a missing rule does not imply that
+    /// an action is denied. Prefer [`Client::can_i`] to check a single action.
+    pub async fn rules_review(&self, namespace: &str) -> Result<SubjectRulesReviewStatus> {
+        let review = SelfSubjectRulesReview {
+            spec: SelfSubjectRulesReviewSpec {
+                namespace: Some(namespace.to_string()),
+            },
+            ..SelfSubjectRulesReview::default()
+        };
+        let review = Api::<SelfSubjectRulesReview>::all(self.clone())
+            .create(&PostParams::default(), &review)
+            .await?;
+        Ok(review.status.unwrap_or_default())
+    }
+
+    /// List the verbs that the client is allowed to use on all objects of a resource in a namespace
+    ///
+    /// The `group` of core resources is `""`. Rules that are limited to specific objects (via `resourceNames`)
+    /// are left out, and a `*` verb is returned as is. See [`Client::rules_review`] for the caveats.
+    pub async fn allowed_verbs(&self, namespace: &str, group: &str, resource: &str) -> Result<AllowedVerbs> {
+        let status = self.rules_review(namespace).await?;
+        Ok(AllowedVerbs {
+            verbs: allowed_verbs(&status.resource_rules, group, resource),
+            incomplete: status.incomplete,
+        })
+    }
+}
+
+/// The verbs that the client is allowed to use on a resource, see [`Client::allowed_verbs`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AllowedVerbs {
+    /// The allowed verbs, sorted and deduplicated
+    pub verbs: Vec<String>,
+    /// Whether an authorizer could not enumerate its rules, so that other verbs may be allowed as well
+    pub incomplete: bool,
+}
+
+/// The verbs that `rules` allow on all objects of `resource` in `group`, sorted and deduplicated
+fn allowed_verbs(rules: &[ResourceRule], group: &str, resource: &str) -> Vec<String> {
+    let matches = |values: &Option<Vec<String>>, wanted: &str| {
+        values
+            .iter()
+            .flatten()
+            .any(|value| value == "*" || value == wanted)
+    };
+    rules
+        .iter()
+        .filter(|rule| matches(&rule.api_groups, group) && matches(&rule.resources, resource))
+        .filter(|rule| rule.resource_names.as_ref().map_or(true, Vec::is_empty))
+        .flat_map(|rule| rule.verbs.iter().cloned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
 }
 
 impl Api<ServiceAccount> {
@@ -50,13 +370,219 @@ impl Api<ServiceAccount> {
         let bytes = serde_json::to_vec(token_request).map_err(Error::SerdeError)?;
         let mut req = self
             .request
-            .create_subresource("token", name, pp, bytes)
+            .create_subresource("token", name, &self.post_params(pp), bytes)
             .map_err(Error::BuildRequest)?;
         req.extensions_mut().insert("create_token_request");
         self.client.request::<TokenRequest>(req).await
     }
 }
 
+#[cfg(test)]
+mod taint_test {
+    use super::merge_taints;
+    use k8s_openapi::api::core::v1::Taint;
+
+    fn taint(key: &str, value: &str, effect: &str) -> Taint {
+        Taint {
+            key: key.into(),
+            value: Some(value.into()),
+            effect: effect.into(),
+            time_added: None,
+        }
+    }
+
+    #[test]
+    fn taints_with_the_same_key_and_effect_are_replaced() {
+        let current = [
+            taint("dedicated", "gpu", "NoSchedule"),
+            taint("dedicated", "gpu", "NoExecute"),
+            taint("maintenance", "true", "NoSchedule"),
+        ];
+        let merged = merge_taints(&current, &[
+            taint("dedicated", "batch", "NoSchedule"),
+            taint("spot", "true", "PreferNoSchedule"),
+        ]);
+        assert_eq!(merged, [
+            taint("dedicated", "batch", "NoSchedule"),
+            taint("dedicated", "gpu", "NoExecute"),
+            taint("maintenance", "true", "NoSchedule"),
+            taint("spot", "true", "PreferNoSchedule"),
+        ]);
+    }
+
+    #[test]
+    fn merging_present_taints_changes_nothing() {
+        let current = [taint("maintenance", "true", "NoSchedule")];
+        assert_eq!(merge_taints(&current, &current), current);
+        assert_eq!(merge_taints(&current, &[]), current);
+    }
+}
+
+#[cfg(test)]
+mod namespace_test {
+    use crate::{Client, Error};
+    use futures::pin_mut;
+    use http::{Request, Response};
+    use hyper::Body;
+    use kube_core::ErrorResponse;
+    use tower_test::mock;
+
+    #[tokio::test]
+    async fn ensure_namespace_accepts_existing_namespaces() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let not_found = r#"{"kind":"Status","apiVersion":"v1","status":"Failure","message":"namespaces \"apps\" not found","reason":"NotFound","code":404}"#;
+            let exists = r#"{"kind":"Status","apiVersion":"v1","status":"Failure","message":"namespaces \"apps\" already exists","reason":"AlreadyExists","code":409}"#;
+            let active = r#"{"apiVersion":"v1","kind":"Namespace","metadata":{"name":"apps"},"status":{"phase":"Active"}}"#;
+            let terminating = r#"{"apiVersion":"v1","kind":"Namespace","metadata":{"name":"apps"},"status":{"phase":"Terminating"}}"#;
+            let forbidden = r#"{"kind":"Status","apiVersion":"v1","status":"Failure","message":"forbidden","reason":"Forbidden","code":403}"#;
+            for (method, uri, code, body) in [
+                // created
+                ("GET", "/api/v1/namespaces/apps", 404, not_found),
+                ("POST", "/api/v1/namespaces?", 201, active),
+                // exists
+                ("GET", "/api/v1/namespaces/apps", 200, active),
+                // created concurrently
+                ("GET", "/api/v1/namespaces/apps", 404, not_found),
+                ("POST", "/api/v1/namespaces?", 409, exists),
+                ("GET", "/api/v1/namespaces/apps", 200, active),
+                // terminating
+                ("GET", "/api/v1/namespaces/apps", 200, terminating),
+                // forbidden
+                ("GET", "/api/v1/namespaces/apps", 403, forbidden),
+            ] {
+                let (request, send) = handle.next_request().await.expect("service not called");
+                assert_eq!(request.method(), method);
+                assert_eq!(request.uri().to_string(), uri);
+                send.send_response(Response::builder().status(code).body(Body::from(body)).unwrap());
+            }
+        });
+
+        let client = Client::new(mock_service, "default");
+        client.ensure_namespace("apps").await.unwrap();
+        client.ensure_namespace("apps").await.unwrap();
+        client.ensure_namespace("apps").await.unwrap();
+        let err = client.ensure_namespace("apps").await.unwrap_err();
+        assert!(matches!(err, Error::Api(ErrorResponse { reason, .. }) if reason == "NamespaceTerminating"));
+        assert!(client.ensure_namespace("apps").await.is_err());
+        spawned.await.unwrap();
+    }
+}
+
+#[cfg(test)]
+mod access_review_test {
+    use super::ResourceAccess;
+    use crate::Client;
+    use futures::pin_mut;
+    use http::{Request, Response};
+    use hyper::Body;
+    use k8s_openapi::api::core::v1::Pod;
+    use tower_test::mock;
+
+    #[tokio::test]
+    async fn can_i_with_checks_resource_access() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+            let mut review: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(
+                review["spec"]["resourceAttributes"],
+                serde_json::json!({
+                    "verb": "delete",
+                    "group": "",
+                    "resource": "pods",
+                    "namespace": "apps",
+                    "name": "web",
+                })
+            );
+            review["status"] = serde_json::json!({ "allowed": true });
+            send.send_response(Response::builder().body(Body::from(review.to_string())).unwrap());
+        });
+
+        let client = Client::new(mock_service, "default");
+        let access = ResourceAccess::of::<Pod>("delete", &())
+            .namespace("apps")
+            .name("web");
+        assert!(client.can_i_with(access).await.unwrap());
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn allowed_verbs_merges_matching_rules() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), http::Method::POST);
+            assert_eq!(
+                request.uri().to_string(),
+                "/apis/authorization.k8s.io/v1/selfsubjectrulesreviews?"
+            );
+            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+            let mut review: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(review["spec"]["namespace"], "apps");
+            review["status"] = serde_json::json!({
+                "incomplete": false,
+                "nonResourceRules": [],
+                "resourceRules": [
+                    { "verbs": ["get", "list"], "apiGroups": [""], "resources": ["pods"] },
+                    { "verbs": ["watch", "get"], "apiGroups": ["*"], "resources": ["*"] },
+                    { "verbs": ["delete"], "apiGroups": [""], "resources": ["pods"], "resourceNames": ["web"] },
+                    { "verbs": ["create"], "apiGroups": ["apps"], "resources": ["pods"] },
+                ],
+            });
+            send.send_response(Response::builder().body(Body::from(review.to_string())).unwrap());
+        });
+
+        let client = Client::new(mock_service, "default");
+        let allowed = client.allowed_verbs("apps", "", "pods").await.unwrap();
+        assert_eq!(allowed.verbs, ["get", "list", "watch"]);
+        assert!(!allowed.incomplete);
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn can_i_posts_self_subject_access_review() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), http::Method::POST);
+            assert_eq!(
+                request.uri().to_string(),
+                "/apis/authorization.k8s.io/v1/selfsubjectaccessreviews?"
+            );
+            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+            let review: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(
+                review["spec"]["resourceAttributes"],
+                serde_json::json!({
+                    "verb": "get",
+                    "group": "apps",
+                    "resource": "deployments",
+                    "subresource": "scale",
+                    "namespace": "apps",
+                })
+            );
+            let response = serde_json::json!({
+                "apiVersion": "authorization.k8s.io/v1",
+                "kind": "SelfSubjectAccessReview",
+                "spec": review["spec"],
+                "status": { "allowed": false, "reason": "no rbac policy matched" },
+            });
+            send.send_response(Response::builder().body(Body::from(response.to_string())).unwrap());
+        });
+
+        let client = Client::new(mock_service, "default");
+        let allowed = client.can_i("get", "deployments.apps/scale", Some("apps")).await.unwrap();
+        assert!(!allowed);
+        spawned.await.unwrap();
+    }
+}
+
 // Tests that require a cluster and the complete feature set
 // Can be run with `cargo test -p kube-client --lib -- --ignored`
 #[cfg(test)]
@@ -64,7 +590,8 @@ impl Api<ServiceAccount> {
 mod test {
     use crate::{
         api::{Api, DeleteParams, ListParams, PostParams},
-        Client,
+        config::AuthInfo,
+        Client, Config,
     };
     use k8s_openapi::api::{
         authentication::v1::{TokenRequest, TokenRequestSpec, TokenReview, TokenReviewSpec},
@@ -170,4 +697,47 @@ mod test {
 
         Ok(())
     }
+
+    #[tokio::test]
+    #[ignore = "requires a cluster"]
+    async fn can_i_is_denied_for_restricted_token() -> Result<(), Box<dyn std::error::Error>> {
+        let client = Client::try_default().await?;
+        assert!(client.can_i("list", "pods", Some("default")).await?);
+
+        // A fresh ServiceAccount has no permissions beyond discovery and self reviews
+        let serviceaccount_name = "can-i-restricted";
+        let serviceaccounts: Api<ServiceAccount> = Api::namespaced(client.clone(), "default");
+        let fake_sa = serde_json::from_value(json!({
+            "apiVersion": "v1",
+            "kind": "ServiceAccount",
+            "metadata": { "name": serviceaccount_name },
+        }))?;
+        serviceaccounts.create(&PostParams::default(), &fake_sa).await?;
+        let tokenrequest = serviceaccounts
+            .create_token_request(serviceaccount_name, &PostParams::default(), &TokenRequest {
+                metadata: Default::default(),
+                spec: TokenRequestSpec {
+                    audiences: vec![],
+                    bound_object_ref: None,
+                    expiration_seconds: None,
+                },
+                status: None,
+            })
+            .await?;
+
+        let mut config = Config::infer().await?;
+        config.auth_info = AuthInfo {
+            token: Some(tokenrequest.status.unwrap().token.into()),
+            ..AuthInfo::default()
+        };
+        let restricted = Client::try_from(config)?;
+        assert!(!restricted.can_i("list", "pods", Some("default")).await?);
+        assert!(!restricted.can_i("delete", "deployments.apps", None).await?);
+        assert!(!restricted.can_i("get", "pods/log", Some("default")).await?);
+
+        serviceaccounts
+            .delete(serviceaccount_name, &DeleteParams::default())
+            .await?;
+        Ok(())
+    }
 }
@@ -3,12 +3,23 @@ use http::{Request, Response};
 use hyper::{self, client::HttpConnector};
 use hyper_timeout::TimeoutConnector;
 pub use kube_core::response::Status;
-use tower::{util::BoxService, BoxError, Layer, Service, ServiceBuilder};
+use tower::{
+    util::{BoxCloneService, BoxService},
+    BoxError, Layer, Service, ServiceBuilder,
+};
 use tower_http::{
     classify::ServerErrorsFailureClass, map_response_body::MapResponseBodyLayer, trace::TraceLayer,
 };
 
-use crate::{client::ConfigExt, Client, Config, Error, Result};
+use crate::{
+    client::{
+        http1_fallback::{Http1Fallback, Http1Switch},
+        middleware::RateLimitLayer,
+        ConfigExt,
+    },
+    config::HttpVersion,
+    Client, Config, Error, Result,
+};
 
 /// HTTP body of a dynamic backing type.
 ///
@@ -16,9 +27,33 @@ use crate::{client::ConfigExt, Client, Config, Error, Result};
 pub type DynBody = dyn http_body::Body<Data = Bytes, Error = BoxError> + Send + Unpin;
 
 /// Builder for [`Client`] instances with customized [tower](`Service`) middleware.
+///
+/// The [`Client`] builds the request paths and bodies for the kubernetes api, and hands the requests to
+/// the service stack. Any stack of [`Service`]s that implements
+/// `Service<Request<hyper::Body>, Response = Response<B>>` (where `B` is a [`http_body::Body`] of [`Bytes`],
+/// and the errors convert into a [`BoxError`]) can be used, so custom middleware (authentication,
+/// header injection, rate limiting, circuit breaking..) can be added with [`ClientBuilder::with_layer`].
+///
+/// # Layer ordering
+///
+/// Requests pass through the stack built by [`ClientBuilder::try_from`] in this order:
+///
+/// 1. layers added with [`ClientBuilder::with_layer`], the last added layer first
+/// 2. client-side rate limiting, when [`Config::qps`] is set
+/// 3. the cluster url from the [`Config`], which makes the request uri absolute
+/// 4. response decompression, with the `gzip` feature
+/// 5. authentication, and impersonation headers
+/// 6. request tracing
+/// 7. layers added with [`ClientBuilder::try_from_with_connection_layer`]
+/// 8. the HTTP connection, which switches to HTTP/1.1 when HTTP/2 fails (see [`HttpVersion::PreferHttp2`])
+///
+/// The default namespace is not part of the stack: [`Api`](crate::Api) includes it in the request path
+/// before the request reaches any layer.
 pub struct ClientBuilder<Svc> {
     service: Svc,
     default_ns: String,
+    max_response_bytes: Option<usize>,
+    rate_limit: Option<RateLimitLayer>,
 }
 
 impl<Svc> ClientBuilder<Svc> {
@@ -33,18 +68,43 @@ impl<Svc> ClientBuilder<Svc> {
         Self {
             service,
             default_ns: default_namespace.into(),
+            max_response_bytes: None,
+            rate_limit: None,
         }
     }
 
     /// Add a [`Layer`] to the current [`Service`] stack.
+    ///
+    /// The layer wraps the whole existing stack, so it sees every request after the [`Client`] has
+    /// constructed it, and before it is authenticated and sent by the default stack.
+    ///
+    /// ```no_run
+    /// # async fn doc() -> Result<(), Box<dyn std::error::Error>> {
+    /// use kube::{client::ClientBuilder, Client, Config};
+    /// use tower::util::MapRequestLayer;
+    ///
+    /// let config = Config::infer().await?;
+    /// let client: Client = ClientBuilder::try_from(config)?
+    ///     .with_layer(&MapRequestLayer::new(|mut req: http::Request<hyper::Body>| {
+    ///         req.headers_mut().insert("x-tenant", http::HeaderValue::from_static("blue"));
+    ///         req
+    ///     }))
+    ///     .build();
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn with_layer<L: Layer<Svc>>(self, layer: &L) -> ClientBuilder<L::Service> {
         let Self {
             service: stack,
             default_ns,
+            max_response_bytes,
+            rate_limit,
         } = self;
         ClientBuilder {
             service: layer.layer(stack),
             default_ns,
+            max_response_bytes,
+            rate_limit,
         }
     }
 
@@ -57,23 +117,71 @@ impl<Svc> ClientBuilder<Svc> {
         B: http_body::Body<Data = bytes::Bytes> + Send + 'static,
         B::Error: Into<BoxError>,
     {
-        Client::new(self.service, self.default_ns)
+        let mut client = Client::new(self.service, self.default_ns);
+        client.rate_limits.extend(self.rate_limit);
+        match self.max_response_bytes {
+            Some(limit) => client.with_max_response_bytes(limit),
+            None => client,
+        }
     }
 }
 
+/// The HTTP connection at the bottom of the default [`ClientBuilder`] stack
+///
+/// This is the service that [`ClientBuilder::try_from_with_connection_layer`] layers are applied to.
+pub type ConnectionService = BoxCloneService<Request<hyper::Body>, Response<hyper::Body>, BoxError>;
+
 impl TryFrom<Config> for ClientBuilder<BoxService<Request<hyper::Body>, Response<Box<DynBody>>, BoxError>> {
     type Error = Error;
 
     /// Builds a default [`ClientBuilder`] stack from a given configuration
     fn try_from(config: Config) -> Result<Self> {
+        Self::try_from_with_connection_layer(config, tower::layer::util::Identity::new())
+    }
+}
+
+impl ClientBuilder<BoxService<Request<hyper::Body>, Response<Box<DynBody>>, BoxError>> {
+    /// Builds a default [`ClientBuilder`] stack from a given configuration, with `layer` wrapping the HTTP connection
+    ///
+    /// Unlike [`ClientBuilder::with_layer`], which wraps the default stack, `layer` is added at the bottom of
+    /// the default stack, so it sees every request exactly as it is sent to the apiserver: after the cluster url,
+    /// authentication and impersonation headers were applied. Responses reach it before they are
+    /// decompressed and traced. This is the place for middleware that needs the final request, such as audit
+    /// logging of the authenticated identity, or routing to a service mesh sidecar.
+    ///
+    /// ```no_run
+    /// # async fn doc() -> Result<(), Box<dyn std::error::Error>> {
+    /// use kube::{client::ClientBuilder, Client, Config};
+    /// use tower::util::MapRequestLayer;
+    ///
+    /// let config = Config::infer().await?;
+    /// let audit = MapRequestLayer::new(|req: http::Request<hyper::Body>| {
+    ///     tracing::info!(method = %req.method(), uri = %req.uri(), "sending request");
+    ///     req
+    /// });
+    /// let client: Client = ClientBuilder::try_from_with_connection_layer(config, audit)?.build();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn try_from_with_connection_layer<L, S>(config: Config, layer: L) -> Result<Self>
+    where
+        L: Layer<ConnectionService, Service = S>,
+        S: Service<Request<hyper::Body>, Response = Response<hyper::Body>> + Clone + Send + 'static,
+        S::Future: Send + 'static,
+        S::Error: Into<BoxError>,
+    {
         use std::time::Duration;
 
         use http::header::HeaderMap;
         use tracing::Span;
 
         let default_ns = config.default_namespace.clone();
+        let max_response_bytes = config.max_response_bytes;
         let auth_layer = config.auth_layer()?;
 
+        // Shared by the connector and the connection service, which turns it on when HTTP/2 fails
+        let switch = Http1Switch::new(config.http_version == HttpVersion::Http1Only);
+
         let client: hyper::Client<_, hyper::Body> = {
             let mut connector = HttpConnector::new();
             connector.enforce_http(false);
@@ -84,9 +192,9 @@ impl TryFrom<Config> for ClientBuilder<BoxService<Request<hyper::Body>, Response
             // Create a custom client to use something else.
             // If TLS features are not enabled, http connector will be used.
             #[cfg(feature = "rustls-tls")]
-            let connector = config.rustls_https_connector_with_connector(connector)?;
+            let connector = config.rustls_https_connector_with_switch(connector, switch.clone())?;
             #[cfg(all(not(feature = "rustls-tls"), feature = "openssl-tls"))]
-            let connector = config.openssl_https_connector_with_connector(connector)?;
+            let connector = config.openssl_https_connector_with_switch(connector, Some(switch.clone()))?;
             #[cfg(all(not(feature = "rustls-tls"), not(feature = "openssl-tls")))]
             if auth_layer.is_none() || config.cluster_url.scheme() == Some(&http::uri::Scheme::HTTPS) {
                 // no tls stack situation only works on anonymous auth with http scheme
@@ -100,8 +208,24 @@ impl TryFrom<Config> for ClientBuilder<BoxService<Request<hyper::Body>, Response
             connector.set_read_timeout(config.read_timeout);
             connector.set_write_timeout(config.write_timeout);
 
-            hyper::Client::builder().build(connector)
+            let mut builder = hyper::Client::builder();
+            builder.pool_idle_timeout(config.pool_idle_timeout);
+            if let Some(max_idle) = config.pool_max_idle_per_host {
+                builder.pool_max_idle_per_host(max_idle);
+            }
+            builder.http2_keep_alive_interval(config.http2_keep_alive_interval);
+            if let Some(timeout) = config.http2_keep_alive_timeout {
+                builder.http2_keep_alive_timeout(timeout);
+            }
+            builder.build(connector)
         };
+        let client = Http1Fallback::new(client, switch);
+        let connection = ServiceBuilder::new()
+            .map_err(Into::<BoxError>::into)
+            .layer(layer)
+            .layer_fn(BoxCloneService::new)
+            .map_err(BoxError::from)
+            .service(client);
 
         let stack = ServiceBuilder::new().layer(config.base_uri_layer()).into_inner();
         #[cfg(feature = "gzip")]
@@ -110,7 +234,9 @@ impl TryFrom<Config> for ClientBuilder<BoxService<Request<hyper::Body>, Response
             .layer(tower_http::decompression::DecompressionLayer::new())
             .into_inner();
 
+        let rate_limit = config.rate_limit_layer();
         let service = ServiceBuilder::new()
+            .option_layer(rate_limit.clone())
             .layer(stack)
             .option_layer(auth_layer)
             .layer(config.extra_headers_layer()?)
@@ -162,16 +288,64 @@ impl TryFrom<Config> for ClientBuilder<BoxService<Request<hyper::Body>, Response
                         }
                     }),
             )
-            .service(client);
+            .service(connection);
+
+        Ok(Self {
+            max_response_bytes,
+            rate_limit,
+            ..Self::new(
+                BoxService::new(
+                    MapResponseBodyLayer::new(|body| {
+                        Box::new(http_body::Body::map_err(body, BoxError::from)) as Box<DynBody>
+                    })
+                    .layer(service),
+                ),
+                default_ns,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use http::{Request, Response};
+    use hyper::Body;
+    use k8s_openapi::api::core::v1::Pod;
+    use tower::layer::layer_fn;
 
-        Ok(Self::new(
-            BoxService::new(
-                MapResponseBodyLayer::new(|body| {
-                    Box::new(http_body::Body::map_err(body, BoxError::from)) as Box<DynBody>
+    use super::ClientBuilder;
+    use crate::{Api, Client, Config};
+
+    #[tokio::test]
+    async fn connection_layer_sees_final_requests() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = {
+            let seen = seen.clone();
+            layer_fn(move |_connection| {
+                let seen = seen.clone();
+                tower::service_fn(move |req: Request<Body>| {
+                    let impersonated = req.headers().get("impersonate-user").cloned();
+                    seen.lock().unwrap().push((req.uri().to_string(), impersonated));
+                    async {
+                        let list = serde_json::json!({ "metadata": {}, "items": [] });
+                        Ok::<_, hyper::Error>(Response::new(Body::from(list.to_string())))
+                    }
                 })
-                .layer(service),
-            ),
-            default_ns,
-        ))
+            })
+        };
+        let mut config = Config::new("http://k8s.example".parse().unwrap());
+        config.auth_info.impersonate = Some("alice".into());
+        let client: Client = ClientBuilder::try_from_with_connection_layer(config, recorder)
+            .unwrap()
+            .build();
+
+        let pods: Api<Pod> = Api::default_namespaced(client);
+        assert!(pods.list(&Default::default()).await.unwrap().items.is_empty());
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].0, "http://k8s.example/api/v1/namespaces/default/pods?");
+        assert_eq!(seen[0].1.as_ref().unwrap(), "alice");
     }
 }
@@ -0,0 +1,95 @@
+//! Feature detection based on the apiserver version
+use k8s_openapi::apimachinery::pkg::version::Info;
+
+/// Capabilities of an apiserver, derived from its version
+///
+/// Obtained from [`Client::capabilities`](crate::Client::capabilities).
+///
+/// Only features that are enabled by default in the given version are reported, except for
+/// [`Capabilities::streaming_lists`]. Alpha features (like CBOR encoding) are behind feature gates that
+/// cannot be detected from the version alone, so use [`Capabilities::at_least`] together with
+/// knowledge of your clusters for those.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    major: u32,
+    minor: u32,
+}
+
+impl Capabilities {
+    /// Derive the capabilities from a version response
+    ///
+    /// Returns `None` if the major or minor version could not be parsed.
+    /// Provider suffixes like the `+` in `"27+"` are ignored.
+    pub fn from_version(info: &Info) -> Option<Self> {
+        Some(Self {
+            major: parse_version_number(&info.major)?,
+            minor: parse_version_number(&info.minor)?,
+        })
+    }
+
+    /// The `(major, minor)` version of the apiserver
+    pub fn version(&self) -> (u32, u32) {
+        (self.major, self.minor)
+    }
+
+    /// Whether the apiserver is at least version `major.minor`
+    pub fn at_least(&self, major: u32, minor: u32) -> bool {
+        (self.major, self.minor) >= (major, minor)
+    }
+
+    /// Whether [server-side apply](https://kubernetes.io/docs/reference/using-api/server-side-apply/) is available (1.16+)
+    pub fn server_side_apply(&self) -> bool {
+        self.at_least(1, 16)
+    }
+
+    /// Whether the apiserver can serve [streaming lists](https://kubernetes.io/docs/reference/using-api/api-concepts/#streaming-lists)
+    /// via `sendInitialEvents` (1.27+)
+    ///
+    /// This only rules out apiservers that are too old, the `WatchList` feature gate also has to be enabled.
+    /// Used by the watchers of `kube-runtime` to fall back to a regular list.
+    pub fn streaming_lists(&self) -> bool {
+        self.at_least(1, 27)
+    }
+
+    /// Whether CEL validation rules (`x-kubernetes-validations`) are enforced for custom resources (1.25+)
+    pub fn crd_validation_rules(&self) -> bool {
+        self.at_least(1, 25)
+    }
+
+    /// Whether `ValidatingAdmissionPolicy` is served as a stable api (1.30+)
+    pub fn validating_admission_policy(&self) -> bool {
+        self.at_least(1, 30)
+    }
+}
+
+fn parse_version_number(s: &str) -> Option<u32> {
+    let digits = s.trim_end_matches(|c: char| !c.is_ascii_digit());
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Capabilities;
+    use k8s_openapi::apimachinery::pkg::version::Info;
+
+    fn info(major: &str, minor: &str) -> Info {
+        Info {
+            major: major.into(),
+            minor: minor.into(),
+            ..Info::default()
+        }
+    }
+
+    #[test]
+    fn capabilities_from_version() {
+        let caps = Capabilities::from_version(&info("1", "27+")).unwrap();
+        assert_eq!(caps.version(), (1, 27));
+        assert!(caps.at_least(1, 27));
+        assert!(!caps.at_least(1, 28));
+        assert!(caps.server_side_apply());
+        assert!(caps.streaming_lists());
+        assert!(caps.crd_validation_rules());
+        assert!(!caps.validating_admission_policy());
+        assert_eq!(Capabilities::from_version(&info("", "")), None);
+    }
+}
@@ -0,0 +1,83 @@
+//! Codecs for response bodies, negotiated with the apiserver via the `Accept` header
+use serde::de::DeserializeOwned;
+
+use super::decode;
+use crate::Result;
+
+/// Media type of JSON bodies
+const JSON: &str = "application/json";
+
+/// Decodes response bodies into `T`
+///
+/// A codec tells the apiserver which media types it can decode through the `Accept` header,
+/// and decodes the body according to the `Content-Type` that the apiserver picked from those.
+/// See [`Client::request_with_codec`](crate::Client::request_with_codec).
+///
+/// Request bodies are always sent as JSON. Implement this to negotiate other media types, such as
+/// `application/vnd.kubernetes.protobuf` for types generated from the Kubernetes `.proto` files.
+pub trait Codec<T> {
+    /// The media types to negotiate, as a value for the `Accept` header
+    fn accept(&self) -> &'static str;
+
+    /// Decode a response body, given its `Content-Type`
+    fn decode(&self, content_type: Option<&str>, body: &[u8]) -> Result<T>;
+}
+
+/// The default codec, decoding JSON bodies with `serde`
+///
+/// Every resource (including custom resources) can be served as JSON.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonCodec;
+
+impl<T: DeserializeOwned> Codec<T> for JsonCodec {
+    fn accept(&self) -> &'static str {
+        JSON
+    }
+
+    fn decode(&self, _content_type: Option<&str>, body: &[u8]) -> Result<T> {
+        decode::from_json(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Codec, JsonCodec};
+    use crate::Client;
+    use futures::pin_mut;
+    use http::{header, Request, Response};
+    use hyper::Body;
+    use serde::Deserialize;
+    use tower_test::mock;
+
+    #[derive(Debug, Deserialize)]
+    struct Namespace {
+        name: String,
+    }
+
+    async fn respond_with<C: Codec<Namespace>>(codec: C, content_type: &'static str, body: Vec<u8>) -> Namespace {
+        let accept = codec.accept();
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.headers()[header::ACCEPT], accept);
+            send.send_response(
+                Response::builder()
+                    .header(header::CONTENT_TYPE, content_type)
+                    .body(Body::from(body))
+                    .unwrap(),
+            );
+        });
+        let client = Client::new(mock_service, "default");
+        let request = Request::get("/api/v1/namespaces/default").body(vec![]).unwrap();
+        let namespace = client.request_with_codec(&codec, request).await.unwrap();
+        spawned.await.unwrap();
+        namespace
+    }
+
+    #[tokio::test]
+    async fn json_codec_negotiates_json() {
+        let namespace = respond_with(JsonCodec, "application/json", br#"{"name":"default"}"#.to_vec()).await;
+        assert_eq!(namespace.name, "default");
+    }
+}
@@ -5,12 +5,21 @@ use secrecy::ExposeSecret;
 use tower::{filter::AsyncFilterLayer, util::Either};
 
 #[cfg(any(feature = "rustls-tls", feature = "openssl-tls"))] use super::tls;
+#[cfg(feature = "rustls-tls")]
+use super::http1_fallback::Http1FallbackConnector;
+#[cfg(any(feature = "rustls-tls", feature = "openssl-tls"))]
+use super::http1_fallback::Http1Switch;
 use super::{
     auth::Auth,
-    middleware::{AddAuthorizationLayer, AuthLayer, BaseUriLayer, ExtraHeadersLayer},
+    middleware::{AddAuthorizationLayer, AuthLayer, BaseUriLayer, ExtraHeadersLayer, RateLimitLayer},
 };
+#[cfg(any(feature = "rustls-tls", feature = "openssl-tls"))]
+use crate::config::HttpVersion;
 use crate::{Config, Error, Result};
 
+// Matches the default burst of client-go
+const DEFAULT_BURST: u32 = 10;
+
 /// Extensions to [`Config`](crate::Config) for custom [`Client`](crate::Client).
 ///
 /// See [`Client::new`](crate::Client::new) for an example.
@@ -26,6 +35,11 @@ pub trait ConfigExt: private::Sealed {
     /// Layer to add non-authn HTTP headers depending on the config.
     fn extra_headers_layer(&self) -> Result<ExtraHeadersLayer>;
 
+    /// Optional layer to limit the rate of requests depending on the config.
+    ///
+    /// See [`Config::qps`].
+    fn rate_limit_layer(&self) -> Option<RateLimitLayer>;
+
     /// Create [`hyper_rustls::HttpsConnector`] based on config.
     ///
     /// # Example
@@ -171,6 +185,16 @@ impl ConfigExt for Config {
         })
     }
 
+    fn rate_limit_layer(&self) -> Option<RateLimitLayer> {
+        let qps = self.qps.filter(|qps| qps.is_finite() && *qps > 0.0)?;
+        let burst = self.burst.unwrap_or(DEFAULT_BURST).max(1);
+        let layer = RateLimitLayer::new(qps, burst);
+        Some(match self.rate_limit_max_wait {
+            Some(max_wait) => layer.max_wait(max_wait),
+            None => layer,
+        })
+    }
+
     fn extra_headers_layer(&self) -> Result<ExtraHeadersLayer> {
         let mut headers = Vec::new();
         if let Some(impersonate_user) = &self.auth_info.impersonate {
@@ -202,6 +226,7 @@ impl ConfigExt for Config {
         tls::rustls_tls::rustls_client_config(
             identity.as_deref(),
             self.root_cert.as_deref(),
+            self.ca_bundle_path.as_deref(),
             self.accept_invalid_certs,
         )
         .map_err(Error::RustlsTls)
@@ -220,20 +245,17 @@ impl ConfigExt for Config {
         connector: hyper::client::HttpConnector,
     ) -> Result<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>> {
         let rustls_config = self.rustls_client_config()?;
-        let mut builder = hyper_rustls::HttpsConnectorBuilder::new()
-            .with_tls_config(rustls_config)
-            .https_or_http();
-        if let Some(tsn) = self.tls_server_name.as_ref() {
-            builder = builder.with_server_name(tsn.clone());
-        }
-        Ok(builder.enable_http1().wrap_connector(connector))
+        Ok(self.rustls_https_connector_with_tls_config(rustls_config, self.http_version, connector))
     }
 
     #[cfg(feature = "openssl-tls")]
     fn openssl_ssl_connector_builder(&self) -> Result<openssl::ssl::SslConnectorBuilder> {
         let identity = self.exec_identity_pem().or_else(|| self.identity_pem());
-        // TODO: pass self.tls_server_name for openssl
-        tls::openssl_tls::ssl_connector_builder(identity.as_ref(), self.root_cert.as_ref())
+        tls::openssl_tls::ssl_connector_builder(
+            identity.as_ref(),
+            self.root_cert.as_ref(),
+            self.ca_bundle_path.as_deref(),
+        )
             .map_err(|e| Error::OpensslTls(tls::openssl_tls::Error::CreateSslConnector(e)))
     }
 
@@ -249,20 +271,93 @@ impl ConfigExt for Config {
         &self,
         connector: hyper::client::HttpConnector,
     ) -> Result<hyper_openssl::HttpsConnector<hyper::client::HttpConnector>> {
-        let mut https =
-            hyper_openssl::HttpsConnector::with_connector(connector, self.openssl_ssl_connector_builder()?)
+        self.openssl_https_connector_with_switch(connector, None)
+    }
+}
+
+impl Config {
+    #[cfg(feature = "rustls-tls")]
+    fn rustls_https_connector_with_tls_config(
+        &self,
+        rustls_config: rustls::ClientConfig,
+        http_version: HttpVersion,
+        connector: hyper::client::HttpConnector,
+    ) -> hyper_rustls::HttpsConnector<hyper::client::HttpConnector> {
+        let mut builder = hyper_rustls::HttpsConnectorBuilder::new()
+            .with_tls_config(rustls_config)
+            .https_or_http();
+        if let Some(tsn) = self.tls_server_name.as_ref() {
+            builder = builder.with_server_name(tsn.clone());
+        }
+        match http_version {
+            HttpVersion::PreferHttp2 => builder.enable_all_versions().wrap_connector(connector),
+            HttpVersion::Http1Only => builder.enable_http1().wrap_connector(connector),
+        }
+    }
+
+    /// Create [`hyper_rustls::HttpsConnector`]s offering HTTP/2 until `switch` is on, and only HTTP/1.1 after
+    ///
+    /// Both use the same TLS configuration, which is only loaded once.
+    #[cfg(feature = "rustls-tls")]
+    pub(crate) fn rustls_https_connector_with_switch(
+        &self,
+        connector: hyper::client::HttpConnector,
+        switch: Http1Switch,
+    ) -> Result<Http1FallbackConnector<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>>> {
+        let rustls_config = self.rustls_client_config()?;
+        let http2 = self.rustls_https_connector_with_tls_config(
+            rustls_config.clone(),
+            HttpVersion::PreferHttp2,
+            connector.clone(),
+        );
+        let http1 = self.rustls_https_connector_with_tls_config(rustls_config, HttpVersion::Http1Only, connector);
+        Ok(Http1FallbackConnector::new(http2, http1, switch))
+    }
+
+    /// Create [`hyper_openssl::HttpsConnector`] that only offers HTTP/1.1 once `switch` is on
+    #[cfg(feature = "openssl-tls")]
+    pub(crate) fn openssl_https_connector_with_switch(
+        &self,
+        connector: hyper::client::HttpConnector,
+        switch: Option<Http1Switch>,
+    ) -> Result<hyper_openssl::HttpsConnector<hyper::client::HttpConnector>> {
+        let mut ssl = self.openssl_ssl_connector_builder()?;
+        if self.http_version == HttpVersion::PreferHttp2 {
+            ssl.set_alpn_protos(b"\x02h2\x08http/1.1")
                 .map_err(|e| Error::OpensslTls(tls::openssl_tls::Error::CreateHttpsConnector(e)))?;
-        if self.accept_invalid_certs {
-            https.set_callback(|ssl, _uri| {
-                ssl.set_verify(openssl::ssl::SslVerifyMode::NONE);
+        }
+        let mut https = hyper_openssl::HttpsConnector::with_connector(connector, ssl)
+            .map_err(|e| Error::OpensslTls(tls::openssl_tls::Error::CreateHttpsConnector(e)))?;
+        let accept_invalid_certs = self.accept_invalid_certs;
+        let tls_server_name = self.tls_server_name.clone();
+        if accept_invalid_certs || tls_server_name.is_some() || switch.is_some() {
+            https.set_callback(move |ssl, _uri| {
+                if switch.as_ref().map_or(false, Http1Switch::is_on) {
+                    ssl.set_alpn_protos(b"\x08http/1.1")?;
+                }
+                if accept_invalid_certs {
+                    ssl.set_verify(openssl::ssl::SslVerifyMode::NONE);
+                }
+                if let Some(tsn) = &tls_server_name {
+                    // Replace the host of the uri, which `hyper_openssl` would use otherwise
+                    ssl.set_use_server_name_indication(false);
+                    ssl.set_verify_hostname(false);
+                    match tsn.parse::<std::net::IpAddr>() {
+                        Ok(ip) => ssl.param_mut().set_ip(ip)?,
+                        Err(_) => {
+                            ssl.set_hostname(tsn)?;
+                            let param = ssl.param_mut();
+                            param.set_hostflags(openssl::x509::verify::X509CheckFlags::NO_PARTIAL_WILDCARDS);
+                            param.set_host(tsn)?;
+                        }
+                    }
+                }
                 Ok(())
             });
         }
         Ok(https)
     }
-}
 
-impl Config {
     // This is necessary to retrieve an identity when an exec plugin
     // returns a client certificate and key instead of a token.
     // This has be to be checked on TLS configuration vs tokens
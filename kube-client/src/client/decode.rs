@@ -0,0 +1,203 @@
+//! Decoding of JSON response bodies
+use std::borrow::Cow;
+
+use bytes::{Buf, Bytes, BytesMut};
+use serde::{de::DeserializeOwned, Deserialize};
+use tokio_util::codec::{Decoder, LinesCodecError};
+
+use crate::{Error, Result};
+
+/// How many bytes around a decoding error to include when logging a body that failed to decode
+const EXCERPT_CONTEXT: usize = 256;
+
+/// Deserialize a JSON response body
+///
+/// The body is parsed straight from its bytes: serde_json validates UTF-8 while parsing, so the separate validation
+/// pass and copy of converting the body to a `String` first is avoided, which matters for large lists.
+///
+/// When the body fails to decode, only an excerpt around the error is logged, since bodies can be megabytes large.
+pub(crate) fn from_json<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    parse(bytes).map_err(|err| {
+        tracing::warn!(body = %excerpt(bytes, &err), "failed to decode response body: {err}");
+        Error::SerdeError(err)
+    })
+}
+
+/// Deserialize a JSON document, like a response body or a watch event
+pub(crate) fn parse<T: DeserializeOwned>(bytes: &[u8]) -> serde_json::Result<T> {
+    serde_json::from_slice(bytes)
+}
+
+/// Whether a JSON response body is a `Status` object
+///
+/// Only the `kind` is deserialized, all other fields are skipped without being decoded.
+pub(crate) fn is_status(bytes: &[u8]) -> Result<bool> {
+    #[derive(Deserialize)]
+    struct Kind<'a> {
+        #[serde(borrow)]
+        kind: Option<Cow<'a, str>>,
+    }
+    let probe: Kind = serde_json::from_slice(bytes).map_err(Error::SerdeError)?;
+    Ok(probe.kind.as_deref() == Some("Status"))
+}
+
+/// The part of `bytes` around the position of a decoding error
+fn excerpt<'a>(bytes: &'a [u8], err: &serde_json::Error) -> Cow<'a, str> {
+    // serde_json reports 1-based lines and columns, with columns counted in bytes
+    let line_start = bytes
+        .split_inclusive(|b| *b == b'\n')
+        .take(err.line().saturating_sub(1))
+        .map(<[u8]>::len)
+        .sum::<usize>();
+    let offset = (line_start + err.column()).min(bytes.len());
+    let start = offset.saturating_sub(EXCERPT_CONTEXT);
+    let end = (offset + EXCERPT_CONTEXT).min(bytes.len());
+    String::from_utf8_lossy(&bytes[start..end])
+}
+
+/// Splits a stream of newline delimited JSON documents, like watch events, into one frame per document
+///
+/// This behaves like [`LinesCodec`](tokio_util::codec::LinesCodec), but the frames share the read buffer of the
+/// [`FramedRead`](tokio_util::codec::FramedRead) instead of being copied into a new `String` each, since they are
+/// deserialized straight from their bytes anyway.
+#[derive(Debug)]
+pub(crate) struct JsonLinesCodec {
+    /// Where to continue searching for a newline, so that partial frames are not searched again for every chunk
+    next_index: usize,
+    max_length: usize,
+    /// Whether the rest of a frame that exceeded `max_length` is being skipped
+    is_discarding: bool,
+}
+
+impl JsonLinesCodec {
+    /// A codec for frames of at most `max_length` bytes, or of any length for `None`
+    pub(crate) fn new(max_length: Option<usize>) -> Self {
+        Self {
+            next_index: 0,
+            max_length: max_length.unwrap_or(usize::MAX),
+            is_discarding: false,
+        }
+    }
+}
+
+impl Decoder for JsonLinesCodec {
+    type Error = LinesCodecError;
+    type Item = Bytes;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Bytes>, LinesCodecError> {
+        loop {
+            let read_to = self.max_length.saturating_add(1).min(buf.len());
+            let newline = buf[self.next_index..read_to]
+                .iter()
+                .position(|b| *b == b'\n')
+                .map(|offset| self.next_index + offset);
+            match (self.is_discarding, newline) {
+                (true, Some(newline)) => {
+                    buf.advance(newline + 1);
+                    self.is_discarding = false;
+                    self.next_index = 0;
+                }
+                (true, None) => {
+                    buf.advance(read_to);
+                    self.next_index = 0;
+                    if buf.is_empty() {
+                        return Ok(None);
+                    }
+                }
+                (false, Some(newline)) => {
+                    self.next_index = 0;
+                    let mut frame = buf.split_to(newline + 1);
+                    frame.truncate(newline);
+                    return Ok(Some(frame.freeze()));
+                }
+                (false, None) if buf.len() > self.max_length => {
+                    self.is_discarding = true;
+                    return Err(LinesCodecError::MaxLineLengthExceeded);
+                }
+                (false, None) => {
+                    self.next_index = read_to;
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<Bytes>, LinesCodecError> {
+        if let Some(frame) = self.decode(buf)? {
+            return Ok(Some(frame));
+        }
+        self.next_index = 0;
+        // the last document may not be terminated by a newline
+        if buf.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(buf.split().freeze()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{excerpt, from_json, is_status, parse, JsonLinesCodec, EXCERPT_CONTEXT};
+    use bytes::BytesMut;
+    use tokio_util::codec::{Decoder, LinesCodecError};
+    use k8s_openapi::api::core::v1::ConfigMap;
+
+    #[test]
+    fn decode_errors_log_an_excerpt_around_the_error() {
+        let padding = "x".repeat(4 * EXCERPT_CONTEXT);
+        let body = format!(r#"{{"metadata":{{"name":"{padding}"}},"data":{{"key":1}},"immutable":"{padding}"}}"#);
+        let err = serde_json::from_slice::<ConfigMap>(body.as_bytes()).unwrap_err();
+        let logged = excerpt(body.as_bytes(), &err);
+        assert!(logged.contains(r#""data":{"key":1}"#), "{logged}");
+        assert!(logged.len() <= 2 * EXCERPT_CONTEXT);
+        assert!(from_json::<ConfigMap>(body.as_bytes()).is_err());
+
+        // errors on later lines are located too
+        let body = format!("{{\n\"a\": \"{padding}\",\n\"b\": tru\n}}");
+        let err = serde_json::from_slice::<serde_json::Value>(body.as_bytes()).unwrap_err();
+        assert!(excerpt(body.as_bytes(), &err).contains("\"b\": tru"));
+    }
+
+    #[test]
+    fn parse_decodes_documents_and_locates_errors() {
+        let cm: ConfigMap = parse(br#"{"metadata":{"name":"a"},"data":{"key":"value"}}"#).unwrap();
+        assert_eq!(cm.data.unwrap()["key"], "value");
+        let err = parse::<ConfigMap>(b"{\n\"data\": {\"key\": 1}\n}").unwrap_err();
+        assert_eq!((err.line(), err.column()), (2, 17));
+    }
+
+    #[test]
+    fn status_bodies_are_detected_by_kind() {
+        assert!(is_status(br#"{"kind":"Status","apiVersion":"v1","status":"Success"}"#).unwrap());
+        assert!(!is_status(br#"{"kind":"Pod","metadata":{"name":"a","kind":"Status"}}"#).unwrap());
+        assert!(!is_status(br#"{"metadata":{}}"#).unwrap());
+        assert!(is_status(b"not json").is_err());
+    }
+
+    #[test]
+    fn json_lines_are_split_across_chunks() {
+        let mut codec = JsonLinesCodec::new(None);
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"{\"a\":1}\n{\"b\":");
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), &b"{\"a\":1}"[..]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        buf.extend_from_slice(b"2}\r\n{\"c\"");
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), &b"{\"b\":2}\r"[..]);
+        buf.extend_from_slice(b":3}");
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        assert_eq!(codec.decode_eof(&mut buf).unwrap().unwrap(), &b"{\"c\":3}"[..]);
+        assert_eq!(codec.decode_eof(&mut buf).unwrap(), None);
+    }
+
+    #[test]
+    fn json_lines_longer_than_the_limit_are_skipped() {
+        let mut codec = JsonLinesCodec::new(Some(8));
+        let mut buf = BytesMut::from(&b"{\"a\":\"too long\"}\n{\"b\":1}\n"[..]);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(LinesCodecError::MaxLineLengthExceeded)
+        ));
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), &b"{\"b\":1}"[..]);
+    }
+}
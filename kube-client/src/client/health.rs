@@ -0,0 +1,188 @@
+//! Health probes of the apiserver, via its `/livez` and `/readyz` endpoints
+use http::{Request, StatusCode};
+
+use super::Client;
+use crate::{Error, Result};
+
+/// The health of the apiserver, as reported by [`Client::livez`] or [`Client::readyz`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Health {
+    /// The overall outcome of the probe
+    pub status: HealthStatus,
+    /// The individual checks of the apiserver
+    ///
+    /// Only reported when probing with `verbose`, and for unhealthy apiservers.
+    pub checks: Vec<HealthCheck>,
+}
+
+impl Health {
+    /// Whether the apiserver passed all of its checks
+    pub fn is_healthy(&self) -> bool {
+        self.status == HealthStatus::Healthy
+    }
+
+    /// The checks that the apiserver failed
+    pub fn failing_checks(&self) -> impl Iterator<Item = &HealthCheck> {
+        self.checks.iter().filter(|check| !check.ok)
+    }
+}
+
+/// The overall outcome of a health probe
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HealthStatus {
+    /// The apiserver passed all of its checks
+    Healthy,
+    /// The apiserver failed at least one of its checks
+    Unhealthy,
+    /// The apiserver was reached, but refused to report its health
+    ///
+    /// This happens when the endpoint is not served (`404`, e.g. before Kubernetes 1.16), or when the
+    /// credentials of the client are not allowed to access it (`401` or `403`), which is the case when
+    /// anonymous access to the health endpoints was removed from the `system:public-info-viewer` role.
+    /// The apiserver is reachable, so this is usually good enough for probes gated on apiserver connectivity.
+    Unavailable(StatusCode),
+}
+
+/// An individual check of a [`Health`] probe, like `etcd` or `poststarthook/crd-informer-synced`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HealthCheck {
+    /// The name of the check
+    pub name: String,
+    /// Whether the check passed
+    pub ok: bool,
+    /// Why the check failed, the apiserver withholds the actual reason from clients
+    pub reason: Option<String>,
+}
+
+impl Client {
+    /// Probe whether the apiserver is alive, via its `/livez` endpoint
+    ///
+    /// With `verbose`, the individual checks are reported as well.
+    /// Failing to reach the apiserver at all is an error, see [`HealthStatus`] for the other outcomes.
+    ///
+    /// ```no_run
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let health = client.livez(true).await?;
+    /// for check in health.failing_checks() {
+    ///     println!("{} failed", check.name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn livez(&self, verbose: bool) -> Result<Health> {
+        self.probe_health("/livez", verbose).await
+    }
+
+    /// Probe whether the apiserver is ready to serve requests, via its `/readyz` endpoint
+    ///
+    /// With `verbose`, the individual checks are reported as well.
+    /// Failing to reach the apiserver at all is an error, see [`HealthStatus`] for the other outcomes.
+    pub async fn readyz(&self, verbose: bool) -> Result<Health> {
+        self.probe_health("/readyz", verbose).await
+    }
+
+    async fn probe_health(&self, path: &str, verbose: bool) -> Result<Health> {
+        let uri = if verbose {
+            format!("{path}?verbose")
+        } else {
+            path.to_string()
+        };
+        let req = Request::builder()
+            .uri(uri)
+            .body(hyper::Body::empty())
+            .map_err(Error::HttpError)?;
+        // the endpoints answer with plain text rather than a `Status` for failures, so the response is not
+        // passed through the error handling of `Client::request`
+        let res = self.send(req).await?;
+        let status = res.status();
+        let body = hyper::body::to_bytes(res.into_body())
+            .await
+            .map_err(super::hyper_error)?;
+        let status = match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN | StatusCode::NOT_FOUND => {
+                return Ok(Health {
+                    status: HealthStatus::Unavailable(status),
+                    checks: Vec::new(),
+                })
+            }
+            status if status.is_success() => HealthStatus::Healthy,
+            _ => HealthStatus::Unhealthy,
+        };
+        Ok(Health {
+            status,
+            checks: parse_checks(&String::from_utf8_lossy(&body)),
+        })
+    }
+}
+
+/// Parse the checks from a health response, formatted as `[+]name ok` or `[-]name failed: reason` per line
+fn parse_checks(body: &str) -> Vec<HealthCheck> {
+    body.lines()
+        .filter_map(|line| {
+            let (ok, check) = if let Some(check) = line.strip_prefix("[+]") {
+                (true, check)
+            } else {
+                (false, line.strip_prefix("[-]")?)
+            };
+            let (name, result) = check.split_once(' ').unwrap_or((check, ""));
+            let reason = (!ok).then(|| result.strip_prefix("failed: ").unwrap_or(result).to_string());
+            Some(HealthCheck {
+                name: name.to_string(),
+                ok,
+                reason,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HealthCheck, HealthStatus};
+    use crate::Client;
+    use futures::pin_mut;
+    use http::{Request, Response, StatusCode};
+    use hyper::Body;
+    use tower_test::mock;
+
+    async fn probe(status: StatusCode, body: &'static str) -> super::Health {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.uri().to_string(), "/readyz?verbose");
+            send.send_response(Response::builder().status(status).body(Body::from(body)).unwrap());
+        });
+        let health = Client::new(mock_service, "default").readyz(true).await.unwrap();
+        spawned.await.unwrap();
+        health
+    }
+
+    #[tokio::test]
+    async fn readyz_reports_failing_checks() {
+        let health = probe(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "[+]ping ok\n[+]log ok\n[-]etcd failed: reason withheld\n[+]poststarthook/crd-informer-synced ok\nreadyz check failed\n",
+        )
+        .await;
+        assert_eq!(health.status, HealthStatus::Unhealthy);
+        assert!(!health.is_healthy());
+        assert_eq!(health.checks.len(), 4);
+        assert_eq!(health.failing_checks().collect::<Vec<_>>(), [&HealthCheck {
+            name: "etcd".into(),
+            ok: false,
+            reason: Some("reason withheld".into()),
+        }]);
+
+        let health = probe(StatusCode::OK, "[+]ping ok\nreadyz check passed\n").await;
+        assert!(health.is_healthy());
+        assert_eq!(health.failing_checks().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn readyz_is_unavailable_when_access_is_denied() {
+        let health = probe(StatusCode::FORBIDDEN, r#"{"kind":"Status","code":403}"#).await;
+        assert_eq!(health.status, HealthStatus::Unavailable(StatusCode::FORBIDDEN));
+        assert!(health.checks.is_empty());
+    }
+}
@@ -0,0 +1,210 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+use futures::{future::BoxFuture, TryStreamExt};
+use http::{Request, Response, Uri, Version};
+use hyper::{body::HttpBody, Body};
+use tower::{BoxError, Service};
+
+use super::is_http2_error;
+
+/// Whether a client only offers HTTP/1.1, shared between the connector and all clones of the client
+///
+/// It is on from the start with [`HttpVersion::Http1Only`](crate::config::HttpVersion::Http1Only),
+/// and [`Http1Fallback`] switches it on once HTTP/2 turns out to be broken.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Http1Switch(Arc<AtomicBool>);
+
+impl Http1Switch {
+    pub(crate) fn new(http1_only: bool) -> Self {
+        Self(Arc::new(AtomicBool::new(http1_only)))
+    }
+
+    pub(crate) fn is_on(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn switch_on_after(&self, err: &hyper::Error) {
+        if is_http2_error(err) && !self.0.swap(true, Ordering::Relaxed) {
+            tracing::warn!("falling back to HTTP/1.1 for new connections after HTTP/2 failed: {err}");
+        }
+    }
+}
+
+/// Connector that connects with `http1` (offering only HTTP/1.1) once the [`Http1Switch`] is on,
+/// and with `http2` (offering HTTP/2 as well) before
+///
+/// Both connectors are meant to share their TLS configuration, so that it is only loaded once.
+#[derive(Clone)]
+pub(crate) struct Http1FallbackConnector<C> {
+    http2: C,
+    http1: C,
+    switch: Http1Switch,
+}
+
+impl<C> Http1FallbackConnector<C> {
+    pub(crate) fn new(http2: C, http1: C, switch: Http1Switch) -> Self {
+        Self { http2, http1, switch }
+    }
+}
+
+impl<C: Service<Uri>> Service<Uri> for Http1FallbackConnector<C> {
+    type Error = C::Error;
+    type Future = C::Future;
+    type Response = C::Response;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // either of them can be called, since the switch can happen between `poll_ready` and `call`
+        match self.http2.poll_ready(cx)? {
+            Poll::Ready(()) => self.http1.poll_ready(cx),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        if self.switch.is_on() {
+            self.http1.call(uri)
+        } else {
+            self.http2.call(uri)
+        }
+    }
+}
+
+/// Service that turns the [`Http1Switch`] on once a request fails with an HTTP/2 specific error,
+/// either while connecting or while streaming the response body
+#[derive(Clone)]
+pub(crate) struct Http1Fallback<S> {
+    inner: S,
+    switch: Http1Switch,
+}
+
+impl<S> Http1Fallback<S> {
+    pub(crate) fn new(inner: S, switch: Http1Switch) -> Self {
+        Self { inner, switch }
+    }
+}
+
+impl<S> Service<Request<Body>> for Http1Fallback<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>>,
+    S::Error: Into<BoxError>,
+    S::Future: Send + 'static,
+{
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Response<Body>, BoxError>>;
+    type Response = Response<Body>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let res = self.inner.call(req);
+        if self.switch.is_on() {
+            return Box::pin(async move { res.await.map_err(Into::into) });
+        }
+        let switch = self.switch.clone();
+        Box::pin(async move {
+            match res.await.map_err(Into::into) {
+                // Only streamed bodies (such as watches) are wrapped to catch their errors, since they have
+                // no size hint to lose. Bodies with a known length are passed on as they are.
+                Ok(res) if res.version() == Version::HTTP_2 && res.body().size_hint().exact().is_none() => {
+                    Ok(res.map(|body| {
+                        Body::wrap_stream(body.inspect_err(move |err| switch.switch_on_after(err)))
+                    }))
+                }
+                Ok(res) => Ok(res),
+                Err(err) => {
+                    if let Some(err) = err.downcast_ref::<hyper::Error>() {
+                        switch.switch_on_after(err);
+                    }
+                    Err(err)
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Http1Fallback, Http1FallbackConnector, Http1Switch};
+    use futures::pin_mut;
+    use http::{Request, Response, Uri};
+    use hyper::{body::HttpBody, Body};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tower::{BoxError, Service, ServiceExt};
+    use tower_test::mock;
+
+    /// Fails a request with an HTTP/2 protocol error, like a proxy that mishandles HTTP/2
+    async fn http2_error() -> hyper::Error {
+        let (client, mut server) = tokio::io::duplex(4096);
+        tokio::spawn(async move {
+            let mut preface = [0; 24];
+            server.read_exact(&mut preface).await.unwrap();
+            // empty SETTINGS
+            server.write_all(&[0, 0, 0, 0x4, 0, 0, 0, 0, 0]).await.unwrap();
+            // skip frames until the request headers arrived
+            loop {
+                let mut header = [0; 9];
+                server.read_exact(&mut header).await.unwrap();
+                let len = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+                server.read_exact(&mut vec![0; len]).await.unwrap();
+                if header[3] == 0x1 {
+                    break;
+                }
+            }
+            // GOAWAY with PROTOCOL_ERROR, before processing the request
+            let goaway = [0, 0, 8, 0x7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+            server.write_all(&goaway).await.unwrap();
+            futures::future::pending::<()>().await;
+        });
+        let (mut sender, connection) = hyper::client::conn::Builder::new()
+            .http2_only(true)
+            .handshake::<_, Body>(client)
+            .await
+            .unwrap();
+        tokio::spawn(connection);
+        sender.send_request(Request::new(Body::empty())).await.unwrap_err()
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_http1_after_http2_errors() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let switch = Http1Switch::new(false);
+        let mut service = Http1Fallback::new(mock_service, switch.clone());
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (_, send) = handle.next_request().await.expect("service not called");
+            send.send_response(Response::new(Body::from("{}")));
+            let (_, send) = handle.next_request().await.expect("service not called");
+            send.send_error(http2_error().await);
+        });
+
+        let request = || Request::new(Body::empty());
+        let res = service.ready().await.unwrap().call(request()).await.unwrap();
+        assert_eq!(res.body().size_hint().exact(), Some(2), "size hint was lost");
+        assert!(!switch.is_on());
+        // clones share the switch
+        let err = service.clone().ready().await.unwrap().call(request()).await.unwrap_err();
+        let err = err.downcast_ref::<hyper::Error>().unwrap();
+        assert!(super::is_http2_error(err), "not an HTTP/2 error: {err:?}");
+        assert!(switch.is_on());
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn connects_with_http1_once_switched_on() {
+        let connected = |version: &'static str| tower::service_fn(move |_: Uri| async move { Ok::<_, BoxError>(version) });
+        let switch = Http1Switch::new(false);
+        let mut connector = Http1FallbackConnector::new(connected("h2"), connected("http/1.1"), switch.clone());
+        let uri = || Uri::from_static("https://kubernetes.default.svc");
+        assert_eq!(connector.ready().await.unwrap().call(uri()).await.unwrap(), "h2");
+        switch.0.store(true, std::sync::atomic::Ordering::Relaxed);
+        assert_eq!(connector.ready().await.unwrap().call(uri()).await.unwrap(), "http/1.1");
+    }
+}
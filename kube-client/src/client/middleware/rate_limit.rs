@@ -0,0 +1,294 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::Duration,
+};
+
+use futures::future::BoxFuture;
+use http::Request;
+use tokio::time::Instant;
+use tower::{BoxError, Layer, Service};
+
+use crate::Error;
+
+// Matches client-go, which logs requests that were delayed for longer than this
+const LOG_WAIT_THRESHOLD: Duration = Duration::from_secs(1);
+
+/// Layer that limits the rate of requests with a token bucket
+///
+/// The bucket holds up to `burst` tokens and is refilled with `qps` tokens per second.
+/// Every request takes a token, and waits for the bucket to be refilled when it is empty,
+/// like the `QPS` and `Burst` settings of client-go.
+///
+/// The bucket is shared between all services created from the layer (and clones of it),
+/// so a single limit applies to all clones of a [`Client`](crate::Client).
+///
+/// Delayed requests are logged, and counted in the [`stats`](Self::stats) of the layer,
+/// to help with sizing the limit.
+#[derive(Clone, Debug)]
+pub struct RateLimitLayer {
+    bucket: Arc<TokenBucket>,
+    mutating_only: bool,
+    max_wait: Option<Duration>,
+}
+
+impl RateLimitLayer {
+    /// Limit requests to `qps` per second on average, with bursts of up to `burst` requests
+    ///
+    /// # Panics
+    ///
+    /// Panics if `qps` is not a positive number, or if `burst` is zero.
+    pub fn new(qps: f64, burst: u32) -> Self {
+        assert!(qps.is_finite() && qps > 0.0, "qps must be a positive number");
+        assert!(burst > 0, "burst must be greater than zero");
+        Self {
+            bucket: Arc::new(TokenBucket {
+                qps,
+                burst: f64::from(burst),
+                state: Mutex::new(BucketState {
+                    tokens: f64::from(burst),
+                    updated: Instant::now(),
+                }),
+                stats: Stats::default(),
+            }),
+            mutating_only: false,
+            max_wait: None,
+        }
+    }
+
+    /// Only limit mutating requests (everything but `GET`, `HEAD` and `OPTIONS`)
+    ///
+    /// Reads, including watches, pass through without taking a token.
+    #[must_use]
+    pub fn mutating_only(mut self) -> Self {
+        self.mutating_only = true;
+        self
+    }
+
+    /// Fail requests that would have to wait for longer than `max_wait` with [`Error::RateLimited`]
+    ///
+    /// Requests wait for as long as needed by default.
+    #[must_use]
+    pub fn max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = Some(max_wait);
+        self
+    }
+
+    /// Statistics about the requests delayed by this limit so far
+    pub fn stats(&self) -> RateLimitStats {
+        let stats = &self.bucket.stats;
+        RateLimitStats {
+            delayed: stats.delayed.load(Ordering::Relaxed),
+            rejected: stats.rejected.load(Ordering::Relaxed),
+            total_delay: Duration::from_nanos(stats.delay_nanos.load(Ordering::Relaxed)),
+        }
+    }
+
+    /// Take a token for `req`, returning how long the request has to wait before it can be sent
+    pub(crate) fn reserve<B>(&self, req: &Request<B>) -> Result<Duration, Error> {
+        if self.mutating_only && req.method().is_safe() {
+            return Ok(Duration::ZERO);
+        }
+        let delay = self.bucket.reserve(self.max_wait).map_err(|delay| {
+            tracing::warn!(method = %req.method(), uri = %req.uri(), ?delay, "Rejected request due to client-side rate limiting");
+            Error::RateLimited(delay)
+        })?;
+        if delay >= LOG_WAIT_THRESHOLD {
+            tracing::info!(method = %req.method(), uri = %req.uri(), ?delay, "Delaying request due to client-side rate limiting");
+        } else if !delay.is_zero() {
+            tracing::debug!(method = %req.method(), uri = %req.uri(), ?delay, "Delaying request due to client-side rate limiting");
+        }
+        Ok(delay)
+    }
+}
+
+/// Statistics of a [`RateLimitLayer`], or of all the limits of a [`Client`](crate::Client)
+///
+/// See [`RateLimitLayer::stats`] and [`Client::rate_limit_stats`](crate::Client::rate_limit_stats).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RateLimitStats {
+    /// The number of requests that had to wait for the rate limit
+    pub delayed: u64,
+    /// The number of requests that failed because they would have had to wait for longer than the maximum wait
+    pub rejected: u64,
+    /// The total time that requests waited for the rate limit
+    pub total_delay: Duration,
+}
+
+impl std::ops::Add for RateLimitStats {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            delayed: self.delayed + other.delayed,
+            rejected: self.rejected + other.rejected,
+            total_delay: self.total_delay + other.total_delay,
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimit {
+            inner,
+            limit: self.clone(),
+        }
+    }
+}
+
+/// Service that limits the rate of requests, see [`RateLimitLayer`]
+#[derive(Clone, Debug)]
+pub struct RateLimit<S> {
+    inner: S,
+    limit: RateLimitLayer,
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for RateLimit<S>
+where
+    S: Service<Request<ReqBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+    ReqBody: Send + 'static,
+{
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<S::Response, BoxError>>;
+    type Response = S::Response;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let delay = match self.limit.reserve(&req) {
+            Ok(delay) => delay,
+            Err(err) => return Box::pin(async move { Err(err.into()) }),
+        };
+        if delay.is_zero() {
+            let fut = self.inner.call(req);
+            return Box::pin(async move { fut.await.map_err(Into::into) });
+        }
+        // The service that was polled ready must be the one that is called
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            tokio::time::sleep(delay).await;
+            inner.call(req).await.map_err(Into::into)
+        })
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    qps: f64,
+    burst: f64,
+    state: Mutex<BucketState>,
+    stats: Stats,
+}
+
+#[derive(Debug, Default)]
+struct Stats {
+    delayed: AtomicU64,
+    rejected: AtomicU64,
+    delay_nanos: AtomicU64,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// Take a token, returning how long to wait until it is available
+    ///
+    /// Tokens are reserved in advance, so concurrent callers are queued behind each other.
+    /// If the wait would exceed `max_wait`, no token is taken and the wait is returned as an error.
+    fn reserve(&self, max_wait: Option<Duration>) -> Result<Duration, Duration> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        let refill = now.saturating_duration_since(state.updated).as_secs_f64() * self.qps;
+        state.tokens = (state.tokens + refill).min(self.burst);
+        state.updated = now;
+        let delay = if state.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - state.tokens) / self.qps)
+        };
+        if max_wait.map_or(false, |max_wait| delay > max_wait) {
+            self.stats.rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(delay);
+        }
+        state.tokens -= 1.0;
+        if !delay.is_zero() {
+            self.stats.delayed.fetch_add(1, Ordering::Relaxed);
+            let nanos = u64::try_from(delay.as_nanos()).unwrap_or(u64::MAX);
+            self.stats.delay_nanos.fetch_add(nanos, Ordering::Relaxed);
+        }
+        Ok(delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use http::{Method, Request, Response};
+    use hyper::Body;
+    use tower::ServiceExt;
+
+    fn request(method: Method) -> Request<Body> {
+        Request::builder().method(method).body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rate_limit_delays_requests_over_burst() {
+        let service = tower::service_fn(|_req: Request<Body>| async {
+            Ok::<_, std::convert::Infallible>(Response::new(Body::empty()))
+        });
+        let service = RateLimitLayer::new(2.0, 2).mutating_only().layer(service);
+
+        let start = Instant::now();
+        for _ in 0..2 {
+            service.clone().oneshot(request(Method::POST)).await.unwrap();
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        // reads are not limited
+        service.clone().oneshot(request(Method::GET)).await.unwrap();
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        service.clone().oneshot(request(Method::PATCH)).await.unwrap();
+        assert_eq!(start.elapsed(), Duration::from_millis(500));
+        service.clone().oneshot(request(Method::DELETE)).await.unwrap();
+        assert_eq!(start.elapsed(), Duration::from_millis(1000));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rate_limit_rejects_requests_over_max_wait() {
+        let service = tower::service_fn(|_req: Request<Body>| async {
+            Ok::<_, std::convert::Infallible>(Response::new(Body::empty()))
+        });
+        let limit = RateLimitLayer::new(1.0, 1).max_wait(Duration::from_millis(1500));
+        let mut service = limit.layer(service);
+
+        // reserve tokens for concurrent requests: the third one would have to wait for 2s
+        let first = service.ready().await.unwrap().call(request(Method::GET));
+        let second = service.ready().await.unwrap().call(request(Method::GET));
+        let third = service.ready().await.unwrap().call(request(Method::GET));
+        let err = third.await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<Error>(), Some(Error::RateLimited(_))));
+        first.await.unwrap();
+        second.await.unwrap();
+        // the rejected request did not take a token
+        service.oneshot(request(Method::GET)).await.unwrap();
+
+        assert_eq!(limit.stats(), RateLimitStats {
+            delayed: 2,
+            rejected: 1,
+            total_delay: Duration::from_secs(2),
+        });
+    }
+}
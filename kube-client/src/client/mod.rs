@@ -9,26 +9,39 @@
 //! retrieve the resources served by the kubernetes API.
 use either::{Either, Left, Right};
 use futures::{self, AsyncBufRead, StreamExt, TryStream, TryStreamExt};
-use http::{self, Request, Response, StatusCode};
+use http::{self, HeaderMap, Request, Response, StatusCode};
 use hyper::Body;
 use k8s_openapi::apimachinery::pkg::apis::meta::v1 as k8s_meta_v1;
 pub use kube_core::response::Status;
 use serde::de::DeserializeOwned;
-use serde_json::{self, Value};
+use std::{
+    sync::{Arc, PoisonError, RwLock},
+    time::Duration,
+};
+use tokio::sync::Mutex;
 #[cfg(feature = "ws")]
 use tokio_tungstenite::{tungstenite as ws, WebSocketStream};
 use tokio_util::{
-    codec::{FramedRead, LinesCodec, LinesCodecError},
+    codec::{FramedRead, LinesCodecError},
     io::StreamReader,
 };
 use tower::{buffer::Buffer, util::BoxService, BoxError, Layer, Service, ServiceExt};
 use tower_http::map_response_body::MapResponseBodyLayer;
 
-use crate::{api::WatchEvent, error::ErrorResponse, Config, Error, Result};
+use crate::{
+    api::WatchEvent,
+    error::{DiscoveryError, ErrorResponse},
+    Config, Error, Result,
+};
 
 mod auth;
 mod body;
 mod builder;
+mod capabilities;
+mod codec;
+mod decode;
+mod health;
+mod http1_fallback;
 // Add `into_stream()` to `http::Body`
 use body::BodyStreamExt;
 mod config_ext;
@@ -51,8 +64,13 @@ pub use auth::OAuthError;
 pub use auth::oidc_errors;
 
 #[cfg(feature = "ws")] pub use upgrade::UpgradeConnectionError;
+#[cfg(feature = "ws")]
+pub(crate) use upgrade::{StreamProtocol, STREAM_PROTOCOLS};
 
-pub use builder::{ClientBuilder, DynBody};
+pub use builder::{ClientBuilder, ConnectionService, DynBody};
+pub use capabilities::Capabilities;
+pub use codec::{Codec, JsonCodec};
+pub use health::{Health, HealthCheck, HealthStatus};
 
 /// Client for connecting with a Kubernetes cluster.
 ///
@@ -67,6 +85,15 @@ pub struct Client {
     // - `BoxService` for dynamic response future type
     inner: Buffer<BoxService<Request<Body>, Response<Body>, BoxError>, Request<Body>>,
     default_ns: String,
+    field_manager: Option<String>,
+    max_response_bytes: Option<usize>,
+    throttle_retries: usize,
+    // shared between clones, so that the version is only requested once until it is refreshed
+    version: Arc<Mutex<Option<k8s_openapi::apimachinery::pkg::version::Info>>>,
+    // the rate limits in the service stack, kept for their statistics
+    rate_limits: Vec<middleware::RateLimitLayer>,
+    // shared between clones, so that it can be set after the client was handed out
+    write_limit: Arc<RwLock<Option<middleware::RateLimitLayer>>>,
 }
 
 impl Client {
@@ -113,6 +140,12 @@ impl Client {
         Self {
             inner: Buffer::new(BoxService::new(service), 1024),
             default_ns: default_namespace.into(),
+            field_manager: None,
+            max_response_bytes: None,
+            throttle_retries: 0,
+            version: Arc::default(),
+            rate_limits: Vec::new(),
+            write_limit: Arc::default(),
         }
     }
 
@@ -138,10 +171,115 @@ impl Client {
         &self.default_ns
     }
 
+    /// Set a default field manager for write requests made through this client
+    ///
+    /// [`Api`](crate::Api) create, replace and patch calls (including those on subresources) whose
+    /// [`PostParams`](crate::api::PostParams) or [`PatchParams`](crate::api::PatchParams) do not specify a
+    /// `field_manager` will inherit this value. This keeps `managedFields` coherent when
+    /// the same actor writes from many places, and avoids having to repeat the name in every
+    /// [`PatchParams::apply`](crate::api::PatchParams::apply) call.
+    /// Requests sent directly through [`Client::request`] are not affected.
+    ///
+    /// An explicitly set `field_manager` on the params always takes precedence.
+    #[must_use]
+    pub fn with_field_manager(mut self, manager: impl Into<String>) -> Self {
+        self.field_manager = Some(manager.into());
+        self
+    }
+
+    /// Get the default field manager for the client, if one has been set
+    ///
+    /// See [`Client::with_field_manager`].
+    pub fn field_manager(&self) -> Option<&str> {
+        self.field_manager.as_deref()
+    }
+
+    /// Set the maximum size in bytes of buffered response bodies
+    ///
+    /// See [`Config::max_response_bytes`] for which requests are affected.
+    #[must_use]
+    pub fn with_max_response_bytes(mut self, max_response_bytes: usize) -> Self {
+        self.max_response_bytes = Some(max_response_bytes);
+        self
+    }
+
+    /// Retry safe requests rejected with `429 Too Many Requests` up to `max_retries` times
+    ///
+    /// The apiserver rejects requests with a `Retry-After` header when
+    /// [API Priority and Fairness](https://kubernetes.io/docs/concepts/cluster-administration/flow-control/)
+    /// is shedding load. With retries enabled, safe requests (e.g. `GET`) made through
+    /// [`Client::request_text`] and the methods built on it wait for the requested delay
+    /// (or one second if none was given) and are sent again.
+    ///
+    /// Other requests, and requests that run out of retries, fail with [`Error::TooManyRequests`].
+    /// Retries are disabled by default.
+    #[must_use]
+    pub fn with_throttle_retries(mut self, max_retries: usize) -> Self {
+        self.throttle_retries = max_retries;
+        self
+    }
+
+    /// Limit the rate of requests made through this client and its clones
+    ///
+    /// Returns a client whose requests go through the given [`RateLimitLayer`](middleware::RateLimitLayer),
+    /// on top of any limit of the original client. The original client is not affected, so a client
+    /// limited with [`RateLimitLayer::mutating_only`](middleware::RateLimitLayer::mutating_only) can be
+    /// handed to writers, while reads keep using the unlimited client.
+    ///
+    /// ```no_run
+    /// # async fn doc() -> Result<(), Box<dyn std::error::Error>> {
+    /// use kube::{client::middleware::RateLimitLayer, Client};
+    ///
+    /// let client = Client::try_default().await?;
+    /// let writer = client.clone().with_rate_limit(RateLimitLayer::new(5.0, 10).mutating_only());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_rate_limit(mut self, limit: middleware::RateLimitLayer) -> Self {
+        self.inner = Buffer::new(BoxService::new(limit.layer(self.inner)), 1024);
+        self.rate_limits.push(limit);
+        self
+    }
+
+    /// Limit the rate of writes made through this client and all of its clones
+    ///
+    /// Unlike [`Client::with_rate_limit`], this changes the client in place: the limit applies to the mutating
+    /// requests (everything but `GET`, `HEAD` and `OPTIONS`) of every clone of the client, including
+    /// the clones that were made before. Setting a limit replaces the previous write limit, and `None` removes it.
+    ///
+    /// This lets a write limit be set up after the client was handed out, like the limit of
+    /// `Controller::with_write_qps`, which applies to the client that the controller was created with.
+    pub fn set_write_limit(&self, limit: Option<middleware::RateLimitLayer>) {
+        *self.write_limit.write().unwrap_or_else(PoisonError::into_inner) =
+            limit.map(middleware::RateLimitLayer::mutating_only);
+    }
+
+    /// Statistics about the requests delayed by the client-side rate limits of this client
+    ///
+    /// Adds up the statistics of the limit set by [`Config::qps`], the limits added with
+    /// [`Client::with_rate_limit`], and the limit set with [`Client::set_write_limit`].
+    /// The limits are shared between clones of the client, and so are their statistics.
+    pub fn rate_limit_stats(&self) -> middleware::RateLimitStats {
+        let write_limit = self.write_limit.read().unwrap_or_else(PoisonError::into_inner);
+        self.rate_limits
+            .iter()
+            .chain(write_limit.as_ref())
+            .map(middleware::RateLimitLayer::stats)
+            .fold(middleware::RateLimitStats::default(), |total, stats| total + stats)
+    }
+
     /// Perform a raw HTTP request against the API and return the raw response back.
     /// This method can be used to get raw access to the API which may be used to, for example,
     /// create a proxy server or application-level gateway between localhost and the API server.
     pub async fn send(&self, request: Request<Body>) -> Result<Response<Body>> {
+        let write_limit = self.write_limit.read().unwrap_or_else(PoisonError::into_inner).clone();
+        if let Some(limit) = write_limit {
+            let delay = limit.reserve(&request)?;
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+        }
         let mut svc = self.inner.clone();
         let res = svc
             .ready()
@@ -154,7 +292,7 @@ impl Client {
                 err.downcast::<Error>()
                     .map(|e| *e)
                     // Error requesting
-                    .or_else(|err| err.downcast::<hyper::Error>().map(|err| Error::HyperError(*err)))
+                    .or_else(|err| err.downcast::<hyper::Error>().map(|err| hyper_error(*err)))
                     // Error from another middleware
                     .unwrap_or_else(Error::Service)
             })?;
@@ -162,12 +300,27 @@ impl Client {
     }
 
     /// Make WebSocket connection.
+    ///
+    /// Uses the `v4.channel.k8s.io` subprotocol of the apiserver for streaming subresources.
     #[cfg(feature = "ws")]
     #[cfg_attr(docsrs, doc(cfg(feature = "ws")))]
     pub async fn connect(
         &self,
         request: Request<Vec<u8>>,
     ) -> Result<WebSocketStream<hyper::upgrade::Upgraded>> {
+        let (stream, _) = self.connect_with_protocols(request, &[upgrade::WS_PROTOCOL]).await?;
+        Ok(stream)
+    }
+
+    /// Make WebSocket connection, offering the given subprotocols in order of preference.
+    ///
+    /// Returns the stream along with the subprotocol that was chosen by the server.
+    #[cfg(feature = "ws")]
+    pub(crate) async fn connect_with_protocols(
+        &self,
+        request: Request<Vec<u8>>,
+        protocols: &[&str],
+    ) -> Result<(WebSocketStream<hyper::upgrade::Upgraded>, upgrade::StreamProtocol)> {
         use http::header::HeaderValue;
         let (mut parts, body) = request.into_parts();
         parts
@@ -185,22 +338,24 @@ impl Client {
             http::header::SEC_WEBSOCKET_KEY,
             key.parse().expect("valid header value"),
         );
-        // Use the binary subprotocol v4, to get JSON `Status` object in `error` channel (3).
+        // Use the binary subprotocol v4 (or later), to get JSON `Status` object in `error` channel (3).
         // There's no official documentation about this protocol, but it's described in
         // [`k8s.io/apiserver/pkg/util/wsstream/conn.go`](https://git.io/JLQED).
         // There's a comment about v4 and `Status` object in
         // [`kublet/cri/streaming/remotecommand/httpstream.go`](https://git.io/JLQEh).
+        // v5 adds a message to close a single channel, like the stdin of a remote command.
         parts.headers.insert(
             http::header::SEC_WEBSOCKET_PROTOCOL,
-            HeaderValue::from_static(upgrade::WS_PROTOCOL),
+            protocols.join(", ").parse().expect("valid header value"),
         );
 
         let res = self.send(Request::from_parts(parts, Body::from(body))).await?;
-        upgrade::verify_response(&res, &key).map_err(Error::UpgradeConnection)?;
+        let protocol = upgrade::verify_response(&res, &key, protocols).map_err(Error::UpgradeConnection)?;
         match hyper::upgrade::on(res).await {
-            Ok(upgraded) => {
-                Ok(WebSocketStream::from_raw_socket(upgraded, ws::protocol::Role::Client, None).await)
-            }
+            Ok(upgraded) => Ok((
+                WebSocketStream::from_raw_socket(upgraded, ws::protocol::Role::Client, None).await,
+                protocol,
+            )),
 
             Err(e) => Err(Error::UpgradeConnection(
                 UpgradeConnectionError::GetPendingUpgrade(e),
@@ -210,31 +365,105 @@ impl Client {
 
     /// Perform a raw HTTP request against the API and deserialize the response
     /// as JSON to some known type.
+    ///
+    /// Error statuses are mapped to [`Error::Api`], except for `429 Too Many Requests`,
+    /// which is mapped to [`Error::TooManyRequests`].
     pub async fn request<T>(&self, request: Request<Vec<u8>>) -> Result<T>
     where
         T: DeserializeOwned,
     {
-        let text = self.request_text(request).await?;
+        let bytes = self.request_bytes(request).await?;
+        decode::from_json(&bytes)
+    }
 
-        serde_json::from_str(&text).map_err(|e| {
-            tracing::warn!("{}, {:?}", text, e);
-            Error::SerdeError(e)
-        })
+    /// Perform a raw HTTP request against the API and deserialize the response
+    /// as JSON to some known type, along with the headers of the response
+    ///
+    /// This is [`Client::request`] for when the response headers are needed as well,
+    /// e.g. for `Warning` headers, or custom headers from aggregated apis.
+    pub async fn request_with_headers<T>(&self, request: Request<Vec<u8>>) -> Result<(T, HeaderMap)>
+    where
+        T: DeserializeOwned,
+    {
+        let (bytes, headers) = self.request_bytes_with_headers(request).await?;
+        Ok((decode::from_json(&bytes)?, headers))
+    }
+
+    /// Perform a raw HTTP request against the API and decode the response with a [`Codec`]
+    ///
+    /// The `Accept` header of the request is set to the media types of the codec (unless it is already set),
+    /// and the response is decoded according to its `Content-Type`.
+    /// [`Client::request`] is equivalent to using the [`JsonCodec`].
+    pub async fn request_with_codec<T, C>(&self, codec: &C, mut request: Request<Vec<u8>>) -> Result<T>
+    where
+        C: Codec<T>,
+    {
+        request
+            .headers_mut()
+            .entry(http::header::ACCEPT)
+            .or_insert(http::HeaderValue::from_static(codec.accept()));
+        let (bytes, headers) = self.request_bytes_with_headers(request).await?;
+        let content_type = headers
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|ct| ct.to_str().ok());
+        codec.decode(content_type, &bytes)
     }
 
     /// Perform a raw HTTP request against the API and get back the response
     /// as a string
     pub async fn request_text(&self, request: Request<Vec<u8>>) -> Result<String> {
+        let bytes = self.request_bytes(request).await?;
+        String::from_utf8(bytes).map_err(Error::FromUtf8)
+    }
+
+    /// Perform a raw HTTP request against the API and get back the unparsed response body
+    ///
+    /// Error statuses are still mapped to [`Error::Api`] (or [`Error::TooManyRequests`]), like for all other requests.
+    pub async fn request_bytes(&self, request: Request<Vec<u8>>) -> Result<Vec<u8>> {
+        let (bytes, _headers) = self.request_bytes_with_headers(request).await?;
+        Ok(bytes)
+    }
+
+    async fn request_bytes_with_headers(&self, mut request: Request<Vec<u8>>) -> Result<(Vec<u8>, HeaderMap)> {
+        let mut retries = 0;
+        loop {
+            let retry = if retries < self.throttle_retries && request.method().is_safe() {
+                Some(clone_request(&request))
+            } else {
+                None
+            };
+            match (self.request_bytes_once(request).await, retry) {
+                (Err(Error::TooManyRequests { retry_after, .. }), Some(next)) => {
+                    let delay = retry_after.unwrap_or(DEFAULT_THROTTLE_DELAY);
+                    tracing::debug!("Throttled by the apiserver, retrying in {:?}", delay);
+                    tokio::time::sleep(delay).await;
+                    request = next;
+                    retries += 1;
+                }
+                (res, _) => return res,
+            }
+        }
+    }
+
+    async fn request_bytes_once(&self, request: Request<Vec<u8>>) -> Result<(Vec<u8>, HeaderMap)> {
         let res = self.send(request.map(Body::from)).await?;
         let status = res.status();
+        // the headers are moved out of the response rather than cloned, as most callers drop them
+        let (http::response::Parts { headers, .. }, body) = res.into_parts();
+        let retry_after = retry_after(&headers);
         // trace!("Status = {:?} for {}", status, res.url());
-        let body_bytes = hyper::body::to_bytes(res.into_body())
-            .await
-            .map_err(Error::HyperError)?;
-        let text = String::from_utf8(body_bytes.to_vec()).map_err(Error::FromUtf8)?;
-        handle_api_errors(&text, status)?;
-
-        Ok(text)
+        let body_bytes = match self.max_response_bytes {
+            Some(limit) => read_body_limited(&headers, body, limit).await?,
+            None => hyper::body::to_bytes(body)
+                .await
+                .map_err(hyper_error)?
+                .to_vec(),
+        };
+        if status.is_client_error() || status.is_server_error() {
+            let text = String::from_utf8(body_bytes).map_err(Error::FromUtf8)?;
+            return Err(handle_api_errors(&text, status, retry_after));
+        }
+        Ok((body_bytes, headers))
     }
 
     /// Perform a raw HTTP request against the API and stream the response body.
@@ -257,20 +486,13 @@ impl Client {
     where
         T: DeserializeOwned,
     {
-        let text = self.request_text(request).await?;
+        let bytes = self.request_bytes(request).await?;
         // It needs to be JSON:
-        let v: Value = serde_json::from_str(&text).map_err(Error::SerdeError)?;
-        if v["kind"] == "Status" {
-            tracing::trace!("Status from {}", text);
-            Ok(Right(serde_json::from_str::<Status>(&text).map_err(|e| {
-                tracing::warn!("{}, {:?}", text, e);
-                Error::SerdeError(e)
-            })?))
+        if decode::is_status(&bytes)? {
+            tracing::trace!("Status from {}", String::from_utf8_lossy(&bytes));
+            Ok(Right(decode::from_json::<Status>(&bytes)?))
         } else {
-            Ok(Left(serde_json::from_str::<T>(&text).map_err(|e| {
-                tracing::warn!("{}, {:?}", text, e);
-                Error::SerdeError(e)
-            })?))
+            Ok(Left(decode::from_json::<T>(&bytes)?))
         }
     }
 
@@ -299,12 +521,12 @@ impl Client {
                 }
                 std::io::Error::new(std::io::ErrorKind::Other, e)
             })),
-            LinesCodec::new(),
+            decode::JsonLinesCodec::new(self.max_response_bytes),
         );
 
         Ok(frames.filter_map(|res| async {
             match res {
-                Ok(line) => match serde_json::from_str::<WatchEvent<T>>(&line) {
+                Ok(line) => match decode::parse::<WatchEvent<T>>(&line) {
                     Ok(event) => Some(Ok(event)),
                     Err(e) => {
                         // Ignore EOF error that can happen for incomplete line from `decode_eof`.
@@ -313,7 +535,7 @@ impl Client {
                         }
 
                         // Got general error response
-                        if let Ok(e_resp) = serde_json::from_str::<ErrorResponse>(&line) {
+                        if let Ok(e_resp) = serde_json::from_slice::<ErrorResponse>(&line) {
                             return Some(Err(Error::Api(e_resp)));
                         }
                         // Parsing error
@@ -337,7 +559,7 @@ impl Client {
                 },
 
                 // Reached the maximum line length without finding a newline.
-                // This should never happen because we're using the default `usize::MAX`.
+                // This can only happen when `max_response_bytes` is configured.
                 Err(LinesCodecError::MaxLineLengthExceeded) => {
                     Some(Err(Error::LinesCodecMaxLineLengthExceeded))
                 }
@@ -346,6 +568,91 @@ impl Client {
     }
 }
 
+/// Classify a [`hyper::Error`] by its cause, so that transport failures can be told apart
+fn hyper_error(err: hyper::Error) -> Error {
+    let timed_out = error_chain(&err).any(|cause| {
+        cause
+            .downcast_ref::<std::io::Error>()
+            .map_or(false, |io| io.kind() == std::io::ErrorKind::TimedOut)
+    });
+    if timed_out {
+        Error::Timeout(err)
+    } else if error_chain(&err).any(is_tls_error) {
+        Error::Tls(err)
+    } else if err.is_connect() {
+        Error::Connect(err)
+    } else {
+        Error::HyperError(err)
+    }
+}
+
+/// The causes of an error, starting with the error itself
+fn error_chain<'a>(
+    err: &'a (dyn std::error::Error + 'static),
+) -> impl Iterator<Item = &'a (dyn std::error::Error + 'static)> {
+    std::iter::successors(Some(err), |current| match current.downcast_ref::<std::io::Error>() {
+        // the source of an `io::Error` skips the error it wraps
+        Some(io) => io.get_ref().map(|inner| inner as &(dyn std::error::Error + 'static)),
+        None => current.source(),
+    })
+}
+
+#[allow(unused_variables)] // without a tls stack
+fn is_tls_error(err: &(dyn std::error::Error + 'static)) -> bool {
+    #[cfg(feature = "rustls-tls")]
+    if err.is::<rustls::Error>() {
+        return true;
+    }
+    #[cfg(feature = "openssl-tls")]
+    if err.is::<openssl::ssl::Error>() || err.is::<openssl::error::ErrorStack>() {
+        return true;
+    }
+    false
+}
+
+/// Whether a request failed because of HTTP/2, so that it could succeed with HTTP/1.1
+///
+/// This is the case for HTTP/2 protocol errors (but not for a graceful shutdown of the connection),
+/// and for a TLS handshake where the apiserver (or a proxy) rejected the offered protocols.
+fn is_http2_error(err: &hyper::Error) -> bool {
+    error_chain(err).any(|cause| {
+        if let Some(h2) = cause.downcast_ref::<h2::Error>() {
+            return !h2.is_io() && h2.reason() != Some(h2::Reason::NO_ERROR);
+        }
+        #[cfg(feature = "rustls-tls")]
+        if let Some(tls) = cause.downcast_ref::<rustls::Error>() {
+            return matches!(
+                tls,
+                rustls::Error::NoApplicationProtocol
+                    | rustls::Error::AlertReceived(rustls::AlertDescription::NoApplicationProtocol)
+            );
+        }
+        false
+    })
+}
+
+/// Read a response body into memory, failing once it exceeds `limit` bytes
+async fn read_body_limited(headers: &HeaderMap, mut body: Body, limit: usize) -> Result<Vec<u8>> {
+    use hyper::body::HttpBody;
+
+    let content_length = headers
+        .get(http::header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+    if content_length.map_or(false, |len| len > limit) {
+        return Err(Error::ResponseTooLarge(limit));
+    }
+    let mut buf = Vec::with_capacity(content_length.unwrap_or_default());
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(hyper_error)?;
+        if buf.len() + chunk.len() > limit {
+            return Err(Error::ResponseTooLarge(limit));
+        }
+        buf.extend_from_slice(&chunk);
+    }
+    Ok(buf)
+}
+
 /// Low level discovery methods using `k8s_openapi` types.
 ///
 /// Consider using the [`discovery`](crate::discovery) module for
@@ -353,14 +660,41 @@ impl Client {
 /// The following methods might be deprecated to avoid confusion between similarly named types within `discovery`.
 impl Client {
     /// Returns apiserver version.
+    ///
+    /// The version is requested once and then cached, the cache is shared between clones of the [`Client`].
+    /// Use [`Client::refresh_apiserver_version`] to pick up an upgrade of the apiserver.
     pub async fn apiserver_version(&self) -> Result<k8s_openapi::apimachinery::pkg::version::Info> {
-        self.request(
-            Request::builder()
-                .uri("/version")
-                .body(vec![])
-                .map_err(Error::HttpError)?,
-        )
-        .await
+        let mut version = self.version.lock().await;
+        if let Some(version) = &*version {
+            return Ok(version.clone());
+        }
+        let info: k8s_openapi::apimachinery::pkg::version::Info = self
+            .request(
+                Request::builder()
+                    .uri("/version")
+                    .body(vec![])
+                    .map_err(Error::HttpError)?,
+            )
+            .await?;
+        *version = Some(info.clone());
+        Ok(info)
+    }
+
+    /// Requests the apiserver version again, replacing the cached version of [`Client::apiserver_version`].
+    pub async fn refresh_apiserver_version(&self) -> Result<k8s_openapi::apimachinery::pkg::version::Info> {
+        self.version.lock().await.take();
+        self.apiserver_version().await
+    }
+
+    /// Returns the [`Capabilities`] of the apiserver, based on its (cached) [version](Client::apiserver_version).
+    pub async fn capabilities(&self) -> Result<Capabilities> {
+        let version = self.apiserver_version().await?;
+        Capabilities::from_version(&version).ok_or_else(|| {
+            Error::Discovery(DiscoveryError::InvalidVersion(format!(
+                "{}.{}",
+                version.major, version.minor
+            )))
+        })
     }
 
     /// Lists api groups that apiserver serves.
@@ -427,6 +761,30 @@ impl Client {
     }
 }
 
+/// Delay used between throttle retries when the apiserver does not send a `Retry-After`
+const DEFAULT_THROTTLE_DELAY: Duration = Duration::from_secs(1);
+
+/// Parse a `Retry-After` header given in seconds, as sent by the apiserver
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let secs = headers.get(http::header::RETRY_AFTER)?.to_str().ok()?;
+    secs.trim().parse().ok().map(Duration::from_secs)
+}
+
+/// Copy a request so that it can be sent again
+///
+/// Only `&'static str` extensions (used to name the verb for tracing) are preserved.
+fn clone_request(request: &Request<Vec<u8>>) -> Request<Vec<u8>> {
+    let mut clone = Request::new(request.body().clone());
+    *clone.method_mut() = request.method().clone();
+    *clone.uri_mut() = request.uri().clone();
+    *clone.version_mut() = request.version();
+    *clone.headers_mut() = request.headers().clone();
+    if let Some(verb) = request.extensions().get::<&'static str>() {
+        clone.extensions_mut().insert(*verb);
+    }
+    clone
+}
+
 /// Kubernetes returned error handling
 ///
 /// Either kube returned an explicit ApiError struct,
@@ -434,26 +792,31 @@ impl Client {
 ///
 /// In either case, present an ApiError upstream.
 /// The latter is probably a bug if encountered.
-fn handle_api_errors(text: &str, s: StatusCode) -> Result<()> {
-    if s.is_client_error() || s.is_server_error() {
-        // Print better debug when things do fail
-        // trace!("Parsing error: {}", text);
-        if let Ok(errdata) = serde_json::from_str::<ErrorResponse>(text) {
-            tracing::debug!("Unsuccessful: {:?}", errdata);
-            Err(Error::Api(errdata))
-        } else {
-            tracing::warn!("Unsuccessful data error parse: {}", text);
-            let ae = ErrorResponse {
-                status: s.to_string(),
-                code: s.as_u16(),
-                message: format!("{text:?}"),
-                reason: "Failed to parse error data".into(),
-            };
-            tracing::debug!("Unsuccessful: {:?} (reconstruct)", ae);
-            Err(Error::Api(ae))
+/// Only called for responses with a client or server error status.
+fn handle_api_errors(text: &str, s: StatusCode, retry_after: Option<Duration>) -> Error {
+    // Print better debug when things do fail
+    // trace!("Parsing error: {}", text);
+    let errdata = if let Ok(errdata) = serde_json::from_str::<ErrorResponse>(text) {
+        tracing::debug!("Unsuccessful: {:?}", errdata);
+        errdata
+    } else {
+        tracing::warn!("Unsuccessful data error parse: {}", text);
+        let ae = ErrorResponse {
+            status: s.to_string(),
+            code: s.as_u16(),
+            message: format!("{text:?}"),
+            reason: "Failed to parse error data".into(),
+        };
+        tracing::debug!("Unsuccessful: {:?} (reconstruct)", ae);
+        ae
+    };
+    if s == StatusCode::TOO_MANY_REQUESTS {
+        Error::TooManyRequests {
+            response: errdata,
+            retry_after,
         }
     } else {
-        Ok(())
+        Error::Api(errdata)
     }
 }
 
@@ -468,14 +831,65 @@ impl TryFrom<Config> for Client {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Api, Client};
+    use super::middleware::{RateLimitLayer, RateLimitStats};
+    use crate::{Api, Client, Config, Error};
 
     use futures::pin_mut;
     use http::{Request, Response};
     use hyper::Body;
     use k8s_openapi::api::core::v1::Pod;
+    use std::time::Duration;
+    use tokio::{io::AsyncWriteExt, net::TcpListener};
     use tower_test::mock;
 
+    async fn get_version(config: Config) -> Result<String, Error> {
+        let client = Client::try_from(config).unwrap();
+        client
+            .request_text(Request::get("/version").body(vec![]).unwrap())
+            .await
+    }
+
+    #[tokio::test]
+    async fn refused_connections_are_connect_errors() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        let config = Config::new(format!("http://{addr}").parse().unwrap());
+        let err = get_version(config).await.unwrap_err();
+        assert!(matches!(err, Error::Connect(_)), "{err:?}");
+        // the cause is kept for logging
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    #[tokio::test]
+    async fn unanswered_requests_are_timeouts() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (_socket, _) = listener.accept().await.unwrap();
+            std::future::pending::<()>().await;
+        });
+        let mut config = Config::new(format!("http://{addr}").parse().unwrap());
+        config.read_timeout = Some(Duration::from_millis(100));
+        let err = get_version(config).await.unwrap_err();
+        assert!(matches!(err, Error::Timeout(_)), "{err:?}");
+    }
+
+    #[cfg(feature = "rustls-tls")]
+    #[tokio::test]
+    async fn failed_handshakes_are_tls_errors() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            // answer the client hello with plain http
+            let _ = socket.write_all(b"HTTP/1.1 400 Bad Request\r\n\r\n").await;
+        });
+        let config = Config::new(format!("https://{addr}").parse().unwrap());
+        let err = get_version(config).await.unwrap_err();
+        assert!(matches!(err, Error::Tls(_)), "{err:?}");
+    }
+
     #[tokio::test]
     async fn test_default_ns() {
         let (mock_service, _) = mock::pair::<Request<Body>, Response<Body>>();
@@ -516,4 +930,139 @@ mod tests {
         assert_eq!(pod.metadata.annotations.unwrap().get("kube-rs").unwrap(), "test");
         spawned.await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_apiserver_version_is_cached_until_refreshed() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            for minor in ["28", "29"] {
+                let (request, send) = handle.next_request().await.expect("service not called");
+                assert_eq!(request.uri().to_string(), "/version");
+                let version = serde_json::json!({
+                    "major": "1", "minor": minor, "gitVersion": format!("v1.{minor}.2"), "gitCommit": "",
+                    "gitTreeState": "", "buildDate": "", "goVersion": "", "compiler": "", "platform": ""
+                });
+                send.send_response(Response::builder().body(Body::from(version.to_string())).unwrap());
+            }
+            // no further requests are made
+            assert!(handle.next_request().await.is_none());
+        });
+
+        let client = Client::new(mock_service, "default");
+        assert_eq!(client.apiserver_version().await.unwrap().git_version, "v1.28.2");
+        let caps = client.clone().capabilities().await.unwrap();
+        assert_eq!(caps.version(), (1, 28));
+        assert_eq!(client.refresh_apiserver_version().await.unwrap().git_version, "v1.29.2");
+        assert_eq!(client.clone().capabilities().await.unwrap().version(), (1, 29));
+        drop(client);
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_max_response_bytes() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            for body in [r#"{"items":[]}"#.repeat(10), r#"{"items":[]}"#.to_string()] {
+                let (_, send) = handle.next_request().await.expect("service not called");
+                // stream the body in chunks to avoid a content-length header
+                let chunks = body.into_bytes().chunks(4).map(|c| Ok::<_, std::io::Error>(c.to_vec())).collect::<Vec<_>>();
+                send.send_response(
+                    Response::builder()
+                        .body(Body::wrap_stream(futures::stream::iter(chunks)))
+                        .unwrap(),
+                );
+            }
+        });
+
+        let client = Client::new(mock_service, "default").with_max_response_bytes(64);
+        let req = || Request::builder().uri("/api/v1/pods").body(vec![]).unwrap();
+        assert!(matches!(
+            client.request_text(req()).await,
+            Err(Error::ResponseTooLarge(64))
+        ));
+        assert_eq!(client.request_text(req()).await.unwrap(), r#"{"items":[]}"#);
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_write_limit_applies_to_all_clones() {
+        let service = tower::service_fn(|_req: Request<Body>| async {
+            Ok::<_, std::convert::Infallible>(Response::new(Body::from("{}")))
+        });
+        let client = Client::new(service, "default");
+        let clone = client.clone();
+        client.set_write_limit(Some(RateLimitLayer::new(1.0, 1)));
+
+        let req = |method| Request::builder().method(method).uri("/").body(vec![]).unwrap();
+        let start = tokio::time::Instant::now();
+        clone.request_text(req(http::Method::POST)).await.unwrap();
+        // reads are not limited
+        clone.request_text(req(http::Method::GET)).await.unwrap();
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        client.request_text(req(http::Method::PATCH)).await.unwrap();
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+
+        let limited = clone.with_rate_limit(RateLimitLayer::new(1.0, 1));
+        limited.request_text(req(http::Method::GET)).await.unwrap();
+        limited.request_text(req(http::Method::GET)).await.unwrap();
+        assert_eq!(limited.rate_limit_stats(), RateLimitStats {
+            delayed: 2,
+            rejected: 0,
+            total_delay: Duration::from_secs(2),
+        });
+        // the limits added to a new client do not count for the original
+        assert_eq!(client.rate_limit_stats().delayed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_too_many_requests() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let throttled = r#"{"kind":"Status","apiVersion":"v1","status":"Failure","message":"Too many requests, please try again later.","reason":"TooManyRequests","code":429}"#;
+            for _ in 0..3 {
+                let (_, send) = handle.next_request().await.expect("service not called");
+                send.send_response(
+                    Response::builder()
+                        .status(429)
+                        .header("Retry-After", "0")
+                        .body(Body::from(throttled))
+                        .unwrap(),
+                );
+            }
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.uri().to_string(), "/api/v1/pods");
+            send.send_response(Response::builder().body(Body::from(r#"{"items":[]}"#)).unwrap());
+        });
+
+        let client = Client::new(mock_service, "default");
+        let req = |method| {
+            Request::builder()
+                .method(method)
+                .uri("/api/v1/pods")
+                .body(vec![])
+                .unwrap()
+        };
+        // retries are opt-in
+        match client.request_text(req(http::Method::GET)).await {
+            Err(Error::TooManyRequests { response, retry_after }) => {
+                assert_eq!(response.reason, "TooManyRequests");
+                assert_eq!(retry_after, Some(std::time::Duration::ZERO));
+            }
+            res => panic!("expected TooManyRequests, got {res:?}"),
+        }
+        // and only done for safe requests
+        let client = client.with_throttle_retries(3);
+        assert!(matches!(
+            client.request_text(req(http::Method::POST)).await,
+            Err(Error::TooManyRequests { .. })
+        ));
+        assert_eq!(
+            client.request_text(req(http::Method::GET)).await.unwrap(),
+            r#"{"items":[]}"#
+        );
+        spawned.await.unwrap();
+    }
 }
@@ -1,9 +1,15 @@
 #[cfg(feature = "rustls-tls")]
 pub mod rustls_tls {
+    use std::{
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex, PoisonError},
+        time::SystemTime,
+    };
+
     use hyper_rustls::ConfigBuilderExt;
     use rustls::{
         self,
-        client::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        client::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier, WebPkiVerifier},
         Certificate, ClientConfig, DigitallySignedStruct, PrivateKey,
     };
     use thiserror::Error;
@@ -35,12 +41,21 @@ pub mod rustls_tls {
         /// Failed to add a root certificate
         #[error("failed to add a root certificate: {0}")]
         AddRootCertificate(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+        /// Failed to read the CA bundle file
+        #[error("failed to read CA bundle from '{1:?}': {0}")]
+        ReadCaBundle(#[source] std::io::Error, PathBuf),
+
+        /// The CA bundle file does not contain any certificates
+        #[error("CA bundle '{0:?}' does not contain any certificates")]
+        EmptyCaBundle(PathBuf),
     }
 
     /// Create `rustls::ClientConfig`.
     pub fn rustls_client_config(
         identity_pem: Option<&[u8]>,
         root_certs: Option<&[Vec<u8>]>,
+        ca_bundle_path: Option<&Path>,
         accept_invalid: bool,
     ) -> Result<ClientConfig, Error> {
         let config_builder = if let Some(certs) = root_certs {
@@ -62,7 +77,11 @@ pub mod rustls_tls {
         if accept_invalid {
             client_config
                 .dangerous()
-                .set_certificate_verifier(std::sync::Arc::new(NoCertificateVerification {}));
+                .set_certificate_verifier(Arc::new(NoCertificateVerification {}));
+        } else if let Some(path) = ca_bundle_path {
+            client_config
+                .dangerous()
+                .set_certificate_verifier(Arc::new(ReloadingCaVerifier::new(path)?));
         }
         Ok(client_config)
     }
@@ -102,6 +121,101 @@ pub mod rustls_tls {
         Ok((cert_chain, private_key))
     }
 
+    /// Verifies server certificates against a CA bundle file, reloading it when it changes
+    ///
+    /// The file is checked for modifications on every handshake, and re-read unconditionally
+    /// when a certificate fails to verify, in case the CA was rotated within the same mtime.
+    /// If reloading fails, the last successfully loaded bundle keeps being used.
+    struct ReloadingCaVerifier {
+        path: PathBuf,
+        bundle: Mutex<CaBundle>,
+    }
+
+    struct CaBundle {
+        modified: Option<SystemTime>,
+        verifier: Arc<WebPkiVerifier>,
+    }
+
+    impl CaBundle {
+        fn load(path: &Path) -> Result<Self, Error> {
+            let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+            let pem = std::fs::read(path).map_err(|e| Error::ReadCaBundle(e, path.to_owned()))?;
+            let certs = rustls_pemfile::certs(&mut std::io::Cursor::new(pem))
+                .map_err(|e| Error::ReadCaBundle(e, path.to_owned()))?;
+            if certs.is_empty() {
+                return Err(Error::EmptyCaBundle(path.to_owned()));
+            }
+            Ok(Self {
+                modified,
+                verifier: Arc::new(WebPkiVerifier::new(root_store(&certs)?, None)),
+            })
+        }
+    }
+
+    impl ReloadingCaVerifier {
+        fn new(path: &Path) -> Result<Self, Error> {
+            Ok(Self {
+                path: path.to_owned(),
+                bundle: Mutex::new(CaBundle::load(path)?),
+            })
+        }
+
+        /// The verifier for the current bundle, reloaded if the file changed (or if `force` is set)
+        ///
+        /// Returns whether the bundle was reloaded.
+        fn verifier(&self, force: bool) -> (Arc<WebPkiVerifier>, bool) {
+            let mut bundle = self.bundle.lock().unwrap_or_else(PoisonError::into_inner);
+            let modified = std::fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+            let mut reloaded = false;
+            if force || modified != bundle.modified {
+                match CaBundle::load(&self.path) {
+                    Ok(new) => {
+                        tracing::debug!(path = ?self.path, "reloaded CA bundle");
+                        *bundle = new;
+                        reloaded = true;
+                    }
+                    Err(err) => {
+                        tracing::warn!(error = &err as &dyn std::error::Error, "failed to reload CA bundle");
+                    }
+                }
+            }
+            (bundle.verifier.clone(), reloaded)
+        }
+    }
+
+    impl ServerCertVerifier for ReloadingCaVerifier {
+        fn verify_server_cert(
+            &self,
+            end_entity: &Certificate,
+            intermediates: &[Certificate],
+            server_name: &rustls::client::ServerName,
+            scts: &mut dyn Iterator<Item = &[u8]>,
+            ocsp_response: &[u8],
+            now: SystemTime,
+        ) -> Result<ServerCertVerified, rustls::Error> {
+            let scts = scts.collect::<Vec<_>>();
+            let verify = |verifier: &WebPkiVerifier| {
+                verifier.verify_server_cert(
+                    end_entity,
+                    intermediates,
+                    server_name,
+                    &mut scts.iter().copied(),
+                    ocsp_response,
+                    now,
+                )
+            };
+            let (verifier, _) = self.verifier(false);
+            match verify(&verifier) {
+                // The CA may have been rotated without changing the mtime, try again with a fresh bundle
+                Err(err @ rustls::Error::InvalidCertificate(_)) => match self.verifier(true) {
+                    (verifier, true) => verify(&verifier),
+                    (_, false) => Err(err),
+                },
+                res => res,
+            }
+        }
+    }
+
     struct NoCertificateVerification {}
 
     impl ServerCertVerifier for NoCertificateVerification {
@@ -136,10 +250,36 @@ pub mod rustls_tls {
             Ok(HandshakeSignatureValid::assertion())
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::{rustls_client_config, Error};
+        use std::io::Write;
+
+        #[test]
+        fn ca_bundle_path_must_contain_certificates() {
+            let mut file = tempfile::NamedTempFile::new().unwrap();
+            let res = rustls_client_config(None, None, Some(file.path()), false);
+            assert!(matches!(res, Err(Error::EmptyCaBundle(_))));
+
+            file.write_all(b"not a certificate").unwrap();
+            let res = rustls_client_config(None, None, Some(file.path()), false);
+            assert!(matches!(res, Err(Error::EmptyCaBundle(_))));
+
+            let missing = file.path().with_extension("missing");
+            let res = rustls_client_config(None, None, Some(&missing), false);
+            assert!(matches!(res, Err(Error::ReadCaBundle(..))));
+
+            // not loaded when certificates are not verified anyway
+            assert!(rustls_client_config(None, None, Some(&missing), true).is_ok());
+        }
+    }
 }
 
 #[cfg(feature = "openssl-tls")]
 pub mod openssl_tls {
+    use std::path::Path;
+
     use openssl::{
         pkey::PKey,
         ssl::{SslConnector, SslConnectorBuilder, SslMethod},
@@ -197,12 +337,17 @@ pub mod openssl_tls {
         /// Failed to add a root certificate
         #[error("failed to add a root certificate: {0}")]
         AddRootCertificate(#[source] openssl::error::ErrorStack),
+
+        /// Failed to load the CA bundle file
+        #[error("failed to load CA bundle: {0}")]
+        LoadCaBundle(#[source] openssl::error::ErrorStack),
     }
 
     /// Create `openssl::ssl::SslConnectorBuilder` required for `hyper_openssl::HttpsConnector`.
     pub fn ssl_connector_builder(
         identity_pem: Option<&Vec<u8>>,
         root_certs: Option<&Vec<Vec<u8>>>,
+        ca_bundle_path: Option<&Path>,
     ) -> Result<SslConnectorBuilder, SslConnectorError> {
         let mut builder =
             SslConnector::builder(SslMethod::tls()).map_err(SslConnectorError::CreateBuilder)?;
@@ -236,6 +381,13 @@ pub mod openssl_tls {
             }
         }
 
+        if let Some(path) = ca_bundle_path {
+            // Only read once, openssl does not support swapping the trust store of a connector
+            builder
+                .set_ca_file(path)
+                .map_err(SslConnectorError::LoadCaBundle)?;
+        }
+
         Ok(builder)
     }
 }
@@ -5,6 +5,27 @@ use tokio_tungstenite::tungstenite as ws;
 
 // Binary subprotocol v4. See `Client::connect`.
 pub const WS_PROTOCOL: &str = "v4.channel.k8s.io";
+// Binary subprotocol v5, which adds a close signal for the stdin channel.
+pub const WS_PROTOCOL_V5: &str = "v5.channel.k8s.io";
+
+/// The subprotocols offered for remote commands and port forwards, in order of preference
+pub const STREAM_PROTOCOLS: &[&str] = &[WS_PROTOCOL_V5, WS_PROTOCOL];
+
+/// The version of the streaming subprotocol negotiated with the server
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamProtocol {
+    /// `v4.channel.k8s.io`
+    V4,
+    /// `v5.channel.k8s.io`
+    V5,
+}
+
+impl StreamProtocol {
+    /// Whether the protocol can signal the end of a single channel, without closing the connection
+    pub fn supports_close(self) -> bool {
+        self == Self::V5
+    }
+}
 
 /// Possible errors from upgrading to a WebSocket connection
 #[cfg(feature = "ws")]
@@ -39,9 +60,13 @@ pub enum UpgradeConnectionError {
     GetPendingUpgrade(#[source] hyper::Error),
 }
 
-// Verify upgrade response according to RFC6455.
+// Verify upgrade response according to RFC6455, returning the subprotocol chosen by the server.
 // Based on `tungstenite` and added subprotocol verification.
-pub fn verify_response(res: &Response<Body>, key: &str) -> Result<(), UpgradeConnectionError> {
+pub fn verify_response(
+    res: &Response<Body>,
+    key: &str,
+    protocols: &[&str],
+) -> Result<StreamProtocol, UpgradeConnectionError> {
     if res.status() != StatusCode::SWITCHING_PROTOCOLS {
         return Err(UpgradeConnectionError::ProtocolSwitch(res.status()));
     }
@@ -74,16 +99,16 @@ pub fn verify_response(res: &Response<Body>, key: &str) -> Result<(), UpgradeCon
         return Err(UpgradeConnectionError::SecWebSocketAcceptKeyMismatch);
     }
 
-    // Make sure that the server returned the correct subprotocol.
-    if !headers
+    // Make sure that the server returned one of the requested subprotocols.
+    match headers
         .get(http::header::SEC_WEBSOCKET_PROTOCOL)
-        .map(|h| h == WS_PROTOCOL)
-        .unwrap_or(false)
+        .and_then(|h| h.to_str().ok())
+        .filter(|h| protocols.contains(h))
     {
-        return Err(UpgradeConnectionError::SecWebSocketProtocolMismatch);
+        Some(WS_PROTOCOL_V5) => Ok(StreamProtocol::V5),
+        Some(_) => Ok(StreamProtocol::V4),
+        None => Err(UpgradeConnectionError::SecWebSocketProtocolMismatch),
     }
-
-    Ok(())
 }
 
 /// Generate a random key for the `Sec-WebSocket-Key` header.
@@ -93,3 +118,40 @@ pub fn sec_websocket_key() -> String {
     let r: [u8; 16] = rand::random();
     base64::engine::general_purpose::STANDARD.encode(r)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(key: &str, protocol: &str) -> Response<Body> {
+        Response::builder()
+            .status(StatusCode::SWITCHING_PROTOCOLS)
+            .header(http::header::UPGRADE, "websocket")
+            .header(http::header::CONNECTION, "Upgrade")
+            .header(
+                http::header::SEC_WEBSOCKET_ACCEPT,
+                ws::handshake::derive_accept_key(key.as_bytes()),
+            )
+            .header(http::header::SEC_WEBSOCKET_PROTOCOL, protocol)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[test]
+    fn verify_response_returns_negotiated_protocol() {
+        let key = sec_websocket_key();
+        let negotiated = |protocol| verify_response(&response(&key, protocol), &key, STREAM_PROTOCOLS);
+        assert_eq!(negotiated(WS_PROTOCOL_V5).unwrap(), StreamProtocol::V5);
+        // older servers only speak v4
+        assert_eq!(negotiated(WS_PROTOCOL).unwrap(), StreamProtocol::V4);
+        assert!(matches!(
+            negotiated("channel.k8s.io"),
+            Err(UpgradeConnectionError::SecWebSocketProtocolMismatch)
+        ));
+        // v5 is only accepted when it was offered
+        assert!(matches!(
+            verify_response(&response(&key, WS_PROTOCOL_V5), &key, &[WS_PROTOCOL]),
+            Err(UpgradeConnectionError::SecWebSocketProtocolMismatch)
+        ));
+    }
+}
@@ -4,7 +4,10 @@
 //! The [`Config`] has several constructors plus logic to infer environment.
 //!
 //! Unless you have issues, prefer using [`Config::infer`], and pass it to a [`Client`][crate::Client].
-use std::{path::PathBuf, time::Duration};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use thiserror::Error;
 
@@ -132,6 +135,15 @@ pub struct Config {
     pub default_namespace: String,
     /// The configured root certificate
     pub root_cert: Option<Vec<Vec<u8>>>,
+    /// Path to a PEM-encoded CA bundle to verify the apiserver certificate with, instead of [`Config::root_cert`]
+    ///
+    /// Unlike [`Config::root_cert`], the bundle is read from disk when connecting, so that long-lived
+    /// clients keep working across CA rotations. With `rustls-tls`, the file is reloaded when it changes,
+    /// and re-read whenever the apiserver certificate fails to verify. Existing connections are not affected.
+    ///
+    /// In-cluster, this can be set to `/var/run/secrets/kubernetes.io/serviceaccount/ca.crt` to follow
+    /// cluster CA rotations. With `openssl-tls`, the file is only read once when the [`Client`](crate::Client) is created.
+    pub ca_bundle_path: Option<PathBuf>,
     /// Set the timeout for connecting to the Kubernetes API.
     ///
     /// A value of `None` means no timeout
@@ -153,8 +165,88 @@ pub struct Config {
     pub proxy_url: Option<http::Uri>,
     /// If set, apiserver certificate will be validated to contain this string
     ///
-    /// If not set, the `cluster_url` is used instead
+    /// It is also sent as the server name (SNI) when connecting.
+    /// If not set, the host of the `cluster_url` is used instead
     pub tls_server_name: Option<String>,
+    /// Set how long idle connections are kept in the connection pool.
+    ///
+    /// Defaults to 90 seconds. A value of `None` keeps idle connections open indefinitely.
+    pub pool_idle_timeout: Option<std::time::Duration>,
+    /// Set the maximum number of idle connections kept in the connection pool per host.
+    ///
+    /// Since all requests go to the same apiserver, this effectively bounds the pool.
+    /// Raise it when bursts of short requests (list/get) keep opening new connections.
+    /// A value of `None` means no limit, which is the default.
+    pub pool_max_idle_per_host: Option<usize>,
+    /// Set the interval of HTTP/2 keep-alive pings on established HTTP/2 connections.
+    ///
+    /// Pings help to detect dead connections underneath long-lived watches.
+    /// A value of `None` disables keep-alive pings, which is the default.
+    /// Only applies when HTTP/2 is negotiated, see [`HttpVersion::PreferHttp2`].
+    pub http2_keep_alive_interval: Option<std::time::Duration>,
+    /// Set how long to wait for an HTTP/2 keep-alive ping to be acknowledged before closing the connection.
+    ///
+    /// Only applies when [`Config::http2_keep_alive_interval`] is set.
+    /// A value of `None` uses the default timeout of 20 seconds.
+    pub http2_keep_alive_timeout: Option<std::time::Duration>,
+    /// The HTTP versions to use for requests to the apiserver.
+    ///
+    /// Defaults to [`HttpVersion::Http1Only`].
+    pub http_version: HttpVersion,
+    /// Set the maximum size in bytes of a buffered response body.
+    ///
+    /// Guards against unexpectedly large responses (e.g. from a misbehaving aggregated api) exhausting memory.
+    /// Requests whose responses are read into memory (get, list, create, etc) fail with
+    /// [`Error::ResponseTooLarge`](crate::Error::ResponseTooLarge) when the limit is exceeded.
+    /// Watches are checked line-by-line instead, limiting the size of each individual event.
+    /// Streamed responses such as logs are not limited.
+    ///
+    /// A value of `None` means no limit, which is the default.
+    pub max_response_bytes: Option<usize>,
+    /// Limit requests made by the [`Client`](crate::Client) to this many per second on average.
+    ///
+    /// This is the equivalent of the `QPS` setting of client-go. Requests over the limit wait for their turn,
+    /// see [`RateLimitLayer`](crate::client::middleware::RateLimitLayer) for details.
+    /// The limit is shared between all clones of the [`Client`](crate::Client), and applies to all requests,
+    /// including the start of watches. How much requests were delayed by it is reported by
+    /// [`Client::rate_limit_stats`](crate::Client::rate_limit_stats).
+    ///
+    /// A value of `None` (or a non-positive value) means no limit, which is the default.
+    pub qps: Option<f64>,
+    /// The number of requests that can be made at once before [`Config::qps`] applies.
+    ///
+    /// This is the equivalent of the `Burst` setting of client-go, and defaults to 10 like it.
+    /// Only applies when [`Config::qps`] is set.
+    pub burst: Option<u32>,
+    /// The maximum time a request waits for [`Config::qps`] before failing with
+    /// [`Error::RateLimited`](crate::Error::RateLimited).
+    ///
+    /// A value of `None` means requests wait for as long as needed, which is the default.
+    pub rate_limit_max_wait: Option<std::time::Duration>,
+}
+
+/// The HTTP versions that a [`Client`](crate::Client) uses, see [`Config::http_version`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HttpVersion {
+    /// Only use HTTP/1.1
+    ///
+    /// HTTP/1.1 cannot multiplex requests, so every concurrent request (and every open watch) takes
+    /// a connection of its own, but it works through proxies that mishandle HTTP/2.
+    #[default]
+    Http1Only,
+    /// Offer HTTP/2 to the apiserver, falling back to HTTP/1.1 when it is not supported
+    ///
+    /// HTTP/2 multiplexes concurrent requests over a single connection,
+    /// avoiding head-of-line blocking when many requests and watches share the client.
+    /// The protocol is negotiated through TLS ALPN.
+    ///
+    /// Some proxies negotiate HTTP/2 but break it, so once a request fails with an HTTP/2 specific error,
+    /// or the apiserver rejects the negotiation, the [`Client`](crate::Client) and its clones only offer
+    /// HTTP/1.1 for new connections. The request that failed is not retried.
+    ///
+    /// Note that connection upgrades (used by `exec`, `attach` and `portforward`) require HTTP/1.1,
+    /// so a separate [`Client`](crate::Client) with [`HttpVersion::Http1Only`] should be used for those.
+    PreferHttp2,
 }
 
 impl Config {
@@ -168,6 +260,7 @@ impl Config {
             cluster_url,
             default_namespace: String::from("default"),
             root_cert: None,
+            ca_bundle_path: None,
             connect_timeout: Some(DEFAULT_CONNECT_TIMEOUT),
             read_timeout: Some(DEFAULT_READ_TIMEOUT),
             write_timeout: None,
@@ -175,6 +268,15 @@ impl Config {
             auth_info: AuthInfo::default(),
             proxy_url: None,
             tls_server_name: None,
+            pool_idle_timeout: Some(DEFAULT_POOL_IDLE_TIMEOUT),
+            pool_max_idle_per_host: None,
+            http2_keep_alive_interval: None,
+            http2_keep_alive_timeout: None,
+            http_version: HttpVersion::default(),
+            max_response_bytes: None,
+            qps: None,
+            burst: None,
+            rate_limit_max_wait: None,
         }
     }
 
@@ -247,6 +349,7 @@ impl Config {
             cluster_url,
             default_namespace,
             root_cert: Some(root_cert),
+            ca_bundle_path: None,
             connect_timeout: Some(DEFAULT_CONNECT_TIMEOUT),
             read_timeout: Some(DEFAULT_READ_TIMEOUT),
             write_timeout: None,
@@ -257,6 +360,15 @@ impl Config {
             },
             proxy_url: None,
             tls_server_name: None,
+            pool_idle_timeout: Some(DEFAULT_POOL_IDLE_TIMEOUT),
+            pool_max_idle_per_host: None,
+            http2_keep_alive_interval: None,
+            http2_keep_alive_timeout: None,
+            http_version: HttpVersion::default(),
+            max_response_bytes: None,
+            qps: None,
+            burst: None,
+            rate_limit_max_wait: None,
         })
     }
 
@@ -307,6 +419,7 @@ impl Config {
             cluster_url,
             default_namespace,
             root_cert,
+            ca_bundle_path: None,
             connect_timeout: Some(DEFAULT_CONNECT_TIMEOUT),
             read_timeout: Some(DEFAULT_READ_TIMEOUT),
             write_timeout: None,
@@ -314,6 +427,15 @@ impl Config {
             proxy_url: loader.proxy_url()?,
             auth_info: loader.user,
             tls_server_name: loader.cluster.tls_server_name,
+            pool_idle_timeout: Some(DEFAULT_POOL_IDLE_TIMEOUT),
+            pool_max_idle_per_host: None,
+            http2_keep_alive_interval: None,
+            http2_keep_alive_timeout: None,
+            http_version: HttpVersion::default(),
+            max_response_bytes: None,
+            qps: None,
+            burst: None,
+            rate_limit_max_wait: None,
         })
     }
 
@@ -355,6 +477,54 @@ impl Config {
         }
     }
 
+    /// Send requests to another `cluster_url`, keeping the credentials and TLS settings of this config
+    ///
+    /// This is useful to reach the apiserver through a tunnel or port-forward, for instance
+    /// to access an in-cluster service address from a controller running outside of the cluster.
+    ///
+    /// Since the apiserver certificate was issued for the original host, [`Config::tls_server_name`]
+    /// is set to the host of the previous `cluster_url`, which is then both sent as the server name (SNI)
+    /// and expected in the certificate. A `tls_server_name` that was already set is kept as is.
+    /// To expect another name, set [`Config::tls_server_name`] after overriding the host.
+    #[must_use]
+    pub fn override_host(mut self, cluster_url: http::Uri) -> Self {
+        if self.tls_server_name.is_none() {
+            self.tls_server_name = self
+                .cluster_url
+                .host()
+                .map(|host| host.trim_start_matches('[').trim_end_matches(']').to_string());
+        }
+        self.cluster_url = cluster_url;
+        self
+    }
+
+    /// Authenticate with the bearer token in the file at `path`, keeping the rest of this config
+    ///
+    /// This replaces the whole [`Config::auth_info`], so that no other credentials (such as client certificates)
+    /// or impersonation settings of the original config are used. The file is re-read at least once a minute,
+    /// so that rotated `ServiceAccount` tokens are picked up, and must exist when the [`Client`](crate::Client) is created.
+    ///
+    /// This makes it possible to run several controllers in one process under different identities,
+    /// for instance to watch one resource with a narrowly scoped token:
+    ///
+    /// ```no_run
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// use kube::{Client, Config};
+    /// let config = Config::infer().await?;
+    /// let client = Client::try_from(config.clone())?;
+    /// let secrets_client = Client::try_from(config.with_token_file("/var/run/secrets/tokens/secrets-reader"))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_token_file(mut self, path: impl AsRef<Path>) -> Self {
+        self.auth_info = AuthInfo {
+            token_file: Some(path.as_ref().to_string_lossy().into_owned()),
+            ..AuthInfo::default()
+        };
+        self
+    }
+
     /// Client certificate and private key in PEM.
     pub(crate) fn identity_pem(&self) -> Option<Vec<u8>> {
         self.auth_info.identity_pem().ok()
@@ -377,6 +547,8 @@ fn certs(data: &[u8]) -> Result<Vec<Vec<u8>>, pem::PemError> {
 // https://github.com/kube-rs/kube/issues/146#issuecomment-590924397
 const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
 const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(295);
+// Matches hyper's default pool idle timeout
+const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
 
 // Expose raw config structs
 pub use file_config::{
@@ -386,6 +558,42 @@ pub use file_config::{
 
 #[cfg(test)]
 mod tests {
+    #[test]
+    fn override_host_keeps_the_expected_server_name() {
+        use super::Config;
+        let config = Config::new("https://kubernetes.default.svc".parse().unwrap())
+            .override_host("https://127.0.0.1:6443".parse().unwrap());
+        assert_eq!(config.cluster_url, "https://127.0.0.1:6443/");
+        assert_eq!(config.tls_server_name.as_deref(), Some("kubernetes.default.svc"));
+
+        let mut config = Config::new("https://[::1]:6443".parse().unwrap());
+        assert_eq!(
+            config.clone().override_host("https://localhost:6443".parse().unwrap()).tls_server_name.as_deref(),
+            Some("::1")
+        );
+        config.tls_server_name = Some("apiserver".into());
+        let config = config.override_host("https://localhost:6443".parse().unwrap());
+        assert_eq!(config.tls_server_name.as_deref(), Some("apiserver"));
+    }
+
+    #[test]
+    fn with_token_file_replaces_the_credentials() {
+        use super::{AuthInfo, Config};
+        let mut config = Config::new("https://kubernetes.default.svc".parse().unwrap());
+        config.auth_info = AuthInfo {
+            token: Some("token".to_string().into()),
+            client_certificate_data: Some("cert".into()),
+            impersonate: Some("admin".into()),
+            ..AuthInfo::default()
+        };
+        let config = config.with_token_file("/var/run/secrets/tokens/reader");
+        assert_eq!(config.auth_info, AuthInfo {
+            token_file: Some("/var/run/secrets/tokens/reader".into()),
+            ..AuthInfo::default()
+        });
+        assert_eq!(config.cluster_url, "https://kubernetes.default.svc/");
+    }
+
     #[cfg(not(feature = "client"))] // want to ensure this works without client features
     #[tokio::test]
     async fn config_loading_on_small_feature_set() {
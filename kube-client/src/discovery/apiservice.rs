@@ -0,0 +1,137 @@
+//! Availability of aggregated apis via `apiregistration.k8s.io` `APIService` objects
+use crate::{Api, Client, Result};
+use k8s_openapi::kube_aggregator::pkg::apis::apiregistration::v1::APIService;
+
+/// Availability of an api group version as reported by its `APIService`
+///
+/// Every served group version has an `APIService` object named `<version>.<group>`.
+/// Groups served by the kube-apiserver itself are "local", while groups backed by an
+/// extension apiserver (e.g. `metrics.k8s.io`) are "aggregated". Only aggregated groups
+/// can become unavailable independently of the main apiserver.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApiServiceAvailability {
+    /// Name of the `APIService` object
+    pub name: String,
+    /// The api group served by the `APIService`
+    pub group: String,
+    /// The api version served by the `APIService`
+    pub version: String,
+    /// Whether the group version is proxied to an extension apiserver
+    pub aggregated: bool,
+    /// Whether the `Available` condition is `True`
+    pub available: bool,
+    /// The reason of the `Available` condition, if any
+    pub reason: Option<String>,
+    /// The message of the `Available` condition, if any
+    pub message: Option<String>,
+}
+
+impl From<&APIService> for ApiServiceAvailability {
+    fn from(apiservice: &APIService) -> Self {
+        let spec = apiservice.spec.as_ref();
+        let condition = apiservice
+            .status
+            .as_ref()
+            .and_then(|s| s.conditions.as_ref())
+            .and_then(|conds| conds.iter().find(|c| c.type_ == "Available"));
+        Self {
+            name: apiservice.metadata.name.clone().unwrap_or_default(),
+            group: spec.and_then(|s| s.group.clone()).unwrap_or_default(),
+            version: spec.and_then(|s| s.version.clone()).unwrap_or_default(),
+            aggregated: spec.map_or(false, |s| s.service.is_some()),
+            available: condition.map_or(false, |c| c.status == "True"),
+            reason: condition.and_then(|c| c.reason.clone()),
+            message: condition.and_then(|c| c.message.clone()),
+        }
+    }
+}
+
+/// Check the availability of a single api group version through its `APIService`
+///
+/// ```no_run
+/// use kube::{Client, discovery};
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let client = Client::try_default().await?;
+///     let metrics = discovery::apiservice_availability(&client, "metrics.k8s.io", "v1beta1").await?;
+///     if !metrics.available {
+///         println!("metrics api unavailable: {:?}", metrics.reason);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub async fn apiservice_availability(
+    client: &Client,
+    group: &str,
+    version: &str,
+) -> Result<ApiServiceAvailability> {
+    let api: Api<APIService> = Api::all(client.clone());
+    let apiservice = api.get(&format!("{version}.{group}")).await?;
+    Ok(ApiServiceAvailability::from(&apiservice))
+}
+
+/// List the availability of all api group versions through their `APIService` objects
+pub async fn apiservices(client: &Client) -> Result<Vec<ApiServiceAvailability>> {
+    let api: Api<APIService> = Api::all(client.clone());
+    let list = api.list(&Default::default()).await?;
+    Ok(list.items.iter().map(ApiServiceAvailability::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregated_unavailable_apiservice() {
+        let apiservice: APIService = serde_json::from_value(serde_json::json!({
+            "apiVersion": "apiregistration.k8s.io/v1",
+            "kind": "APIService",
+            "metadata": { "name": "v1beta1.metrics.k8s.io" },
+            "spec": {
+                "group": "metrics.k8s.io",
+                "version": "v1beta1",
+                "groupPriorityMinimum": 100,
+                "versionPriority": 100,
+                "service": { "name": "metrics-server", "namespace": "kube-system" }
+            },
+            "status": {
+                "conditions": [{
+                    "type": "Available",
+                    "status": "False",
+                    "reason": "FailedDiscoveryCheck",
+                    "message": "failing or missing response from https://10.96.0.1:443"
+                }]
+            }
+        }))
+        .unwrap();
+        let availability = ApiServiceAvailability::from(&apiservice);
+        assert_eq!(availability.name, "v1beta1.metrics.k8s.io");
+        assert_eq!(availability.group, "metrics.k8s.io");
+        assert_eq!(availability.version, "v1beta1");
+        assert!(availability.aggregated);
+        assert!(!availability.available);
+        assert_eq!(availability.reason.as_deref(), Some("FailedDiscoveryCheck"));
+    }
+
+    #[test]
+    fn local_available_apiservice() {
+        let apiservice: APIService = serde_json::from_value(serde_json::json!({
+            "apiVersion": "apiregistration.k8s.io/v1",
+            "kind": "APIService",
+            "metadata": { "name": "v1.apps" },
+            "spec": {
+                "group": "apps",
+                "version": "v1",
+                "groupPriorityMinimum": 17800,
+                "versionPriority": 15
+            },
+            "status": {
+                "conditions": [{ "type": "Available", "status": "True", "reason": "Local" }]
+            }
+        }))
+        .unwrap();
+        let availability = ApiServiceAvailability::from(&apiservice);
+        assert!(!availability.aggregated);
+        assert!(availability.available);
+    }
+}
@@ -1,12 +1,21 @@
 //! High-level utilities for runtime API discovery.
 
-use crate::{Client, Result};
+use crate::{
+    api::{Patch, PatchParams},
+    error::DiscoveryError,
+    Api, Client, Error, Result,
+};
 pub use kube_core::discovery::{verbs, ApiCapabilities, ApiResource, Scope};
-use kube_core::gvk::GroupVersionKind;
-use std::collections::HashMap;
+use kube_core::{dynamic::DynamicObject, gvk::GroupVersionKind, ErrorResponse, ResourceExt};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 mod apigroup;
+mod apiservice;
 pub mod oneshot;
 pub use apigroup::ApiGroup;
+pub use apiservice::{apiservice_availability, apiservices, ApiServiceAvailability};
 mod parse;
 
 // re-export one-shots
@@ -53,8 +62,19 @@ pub struct Discovery {
     client: Client,
     groups: HashMap<String, ApiGroup>,
     mode: DiscoveryMode,
+    skip_unavailable_aggregated: bool,
+    aggregated_timeout: Duration,
+    apiservices: Vec<ApiServiceAvailability>,
+    unavailable: Vec<ApiServiceAvailability>,
 }
 
+/// Default for [`Discovery::aggregated_timeout`]
+const DEFAULT_AGGREGATED_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long [`Discovery::apply_manifest`] waits for the custom resources defined by a manifest to be served
+const ESTABLISH_TIMEOUT: Duration = Duration::from_secs(30);
+/// The delay before polling for a custom resource again, doubled after every poll
+const ESTABLISH_POLL_DELAY: Duration = Duration::from_millis(100);
+
 /// Caching discovery interface
 ///
 /// Builds an internal map of its cache
@@ -64,7 +84,15 @@ impl Discovery {
     pub fn new(client: Client) -> Self {
         let groups = HashMap::new();
         let mode = DiscoveryMode::Block(vec![]);
-        Self { client, groups, mode }
+        Self {
+            client,
+            groups,
+            mode,
+            skip_unavailable_aggregated: false,
+            aggregated_timeout: DEFAULT_AGGREGATED_TIMEOUT,
+            apiservices: vec![],
+            unavailable: vec![],
+        }
     }
 
     /// Configure the discovery client to only look for the listed apigroups
@@ -81,6 +109,34 @@ impl Discovery {
         self
     }
 
+    /// Configure the discovery client to skip aggregated apigroups that are unavailable
+    ///
+    /// Aggregated apis (like `metrics.k8s.io`) are served by extension apiservers, and querying
+    /// them while their backing service is down can fail or stall the entire discovery.
+    /// With this set, the `APIService` objects are checked before querying, and groups without
+    /// any available aggregated version are skipped.
+    /// The skipped `APIService`s can be inspected through [`Discovery::unavailable_apiservices`].
+    ///
+    /// The `Available` condition can be stale when an extension apiserver went down recently,
+    /// so aggregated groups are also skipped when querying them fails, or takes longer than the
+    /// [`aggregated_timeout`](Discovery::aggregated_timeout), rather than failing or stalling the discovery.
+    ///
+    /// If the `APIService` objects cannot be listed (e.g. due to missing RBAC), no groups are skipped.
+    #[must_use]
+    pub fn skip_unavailable_aggregated(mut self) -> Self {
+        self.skip_unavailable_aggregated = true;
+        self
+    }
+
+    /// Configure how long querying an aggregated apigroup may take before it is skipped
+    ///
+    /// Only used with [`Discovery::skip_unavailable_aggregated`]. Defaults to 10 seconds.
+    #[must_use]
+    pub fn aggregated_timeout(mut self, timeout: Duration) -> Self {
+        self.aggregated_timeout = timeout;
+        self
+    }
+
     /// Runs or re-runs the configured discovery algorithm and updates/populates the cache
     ///
     /// The cache is empty cleared when this is started. By default, every api group found is checked,
@@ -110,11 +166,59 @@ impl Discovery {
     /// See a bigger example in [examples/dynamic.api](https://github.com/kube-rs/kube/blob/main/examples/dynamic_api.rs)
     pub async fn run(mut self) -> Result<Self> {
         self.groups.clear();
+        self.apiservices.clear();
+        self.unavailable.clear();
+        let mut skipped = HashSet::new();
+        if self.skip_unavailable_aggregated {
+            match apiservices(&self.client).await {
+                Ok(services) => {
+                    let (available, unavailable): (Vec<_>, Vec<_>) = services
+                        .iter()
+                        .filter(|s| s.aggregated)
+                        .cloned()
+                        .partition(|s| s.available);
+                    // a group is only skipped when none of its aggregated versions are available
+                    skipped = unavailable.iter().map(|s| s.group.clone()).collect();
+                    for s in &available {
+                        skipped.remove(&s.group);
+                    }
+                    self.apiservices = services;
+                    self.unavailable = unavailable;
+                }
+                Err(err) => tracing::debug!("unable to list apiservices, not skipping any groups: {err}"),
+            }
+        }
         let api_groups = self.client.list_api_groups().await?;
         // query regular groups + crds under /apis
         for g in api_groups.groups {
             let key = g.name.clone();
-            if self.mode.is_queryable(&key) {
+            if skipped.contains(&key) {
+                tracing::warn!("skipping discovery of unavailable aggregated apigroup {key}");
+                continue;
+            }
+            if !self.mode.is_queryable(&key) {
+                continue;
+            }
+            if self.skip_unavailable_aggregated && self.is_aggregated(&key) {
+                // the availability of aggregated groups can be stale, so do not let them fail the discovery
+                let query = ApiGroup::query_apis(&self.client, g);
+                let reason = match tokio::time::timeout(self.aggregated_timeout, query).await {
+                    Ok(Ok(apigroup)) => {
+                        self.groups.insert(key, apigroup);
+                        continue;
+                    }
+                    Ok(Err(err)) => format!("failed to query apigroup: {err}"),
+                    Err(_) => format!("querying apigroup timed out after {:?}", self.aggregated_timeout),
+                };
+                tracing::warn!("skipping discovery of unavailable aggregated apigroup {key}: {reason}");
+                let failed = self.apiservices.iter().filter(|s| s.aggregated && s.group == key);
+                self.unavailable.extend(failed.map(|s| ApiServiceAvailability {
+                    available: false,
+                    reason: Some("DiscoveryFailed".into()),
+                    message: Some(reason.clone()),
+                    ..s.clone()
+                }));
+            } else {
                 let apigroup = ApiGroup::query_apis(&self.client, g).await?;
                 self.groups.insert(key, apigroup);
             }
@@ -152,6 +256,29 @@ impl Discovery {
         self.groups.get(group)
     }
 
+    /// Returns the unavailable aggregated `APIService`s found during the last run
+    ///
+    /// This is only populated when [`Discovery::skip_unavailable_aggregated`] is set,
+    /// and includes the reason for the `Available=False` condition to help diagnose the extension apiserver.
+    /// Groups that were skipped because querying them failed have the reason `DiscoveryFailed`.
+    pub fn unavailable_apiservices(&self) -> &[ApiServiceAvailability] {
+        &self.unavailable
+    }
+
+    /// Returns all the `APIService`s found during the last run
+    ///
+    /// This is only populated when [`Discovery::skip_unavailable_aggregated`] is set.
+    pub fn apiservices(&self) -> &[ApiServiceAvailability] {
+        &self.apiservices
+    }
+
+    /// Check if a group is served by an extension apiserver through aggregation
+    ///
+    /// This is only known when [`Discovery::skip_unavailable_aggregated`] is set, and `false` otherwise.
+    pub fn is_aggregated(&self, group: &str) -> bool {
+        self.apiservices.iter().any(|s| s.aggregated && s.group == group)
+    }
+
     /// Check if a group is served by the apiserver
     pub fn has_group(&self, group: &str) -> bool {
         self.groups.contains_key(group)
@@ -168,3 +295,389 @@ impl Discovery {
             .find(|res| res.0.kind == gvk.kind)
     }
 }
+
+impl Api<DynamicObject> {
+    /// Create an [`Api`] for the kind of a [`DynamicObject`], as resolved by a [`Discovery`]
+    ///
+    /// The kind is looked up from the `apiVersion` and `kind` of the object, so this works for objects
+    /// of arbitrary kinds, like the documents of a manifest file.
+    /// Namespaced resources are scoped to the namespace of the object, or the default namespace of the
+    /// `client` if the object has none, while cluster-scoped resources ignore the namespace of the object.
+    ///
+    /// ```no_run
+    /// use kube::{api::{Api, DynamicObject, Patch, PatchParams, ResourceExt}, discovery::Discovery, Client};
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: Client = todo!();
+    /// # let objects: Vec<DynamicObject> = todo!();
+    /// let discovery = Discovery::new(client.clone()).run().await?;
+    /// for obj in objects {
+    ///     let api = Api::from_object(client.clone(), &obj, &discovery)?;
+    ///     api.patch(&obj.name_any(), &PatchParams::apply("my-tool"), &Patch::Apply(&obj)).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`DiscoveryError::MissingKind`] if the object has no `apiVersion` or `kind`,
+    /// [`DiscoveryError::InvalidGroupVersion`] if its `apiVersion` is invalid, and
+    /// [`DiscoveryError::MissingResource`] if its kind was not found by `discovery`.
+    pub fn from_object(client: Client, obj: &DynamicObject, discovery: &Discovery) -> Result<Self> {
+        let gvk = object_gvk(obj)?;
+        let (ar, caps) = discovery
+            .resolve_gvk(&gvk)
+            .ok_or_else(|| missing_resource(obj))?;
+        Ok(Self::scoped_for(client, obj, &ar, &caps))
+    }
+
+    fn scoped_for(client: Client, obj: &DynamicObject, ar: &ApiResource, caps: &ApiCapabilities) -> Self {
+        match (&caps.scope, obj.metadata.namespace.as_deref()) {
+            (Scope::Cluster, _) => Self::all_with(client, ar),
+            (Scope::Namespaced, Some(ns)) => Self::namespaced_with(client, ns, ar),
+            (Scope::Namespaced, None) => Self::default_namespaced_with(client, ar),
+        }
+    }
+}
+
+fn object_gvk(obj: &DynamicObject) -> Result<GroupVersionKind> {
+    let types = obj.types.as_ref().ok_or_else(|| {
+        Error::Discovery(DiscoveryError::MissingKind(format!(
+            "object {} has no apiVersion and kind",
+            obj.metadata.name.as_deref().unwrap_or_default()
+        )))
+    })?;
+    GroupVersionKind::try_from(types)
+        .map_err(|_| Error::Discovery(DiscoveryError::InvalidGroupVersion(types.api_version.clone())))
+}
+
+fn missing_resource(obj: &DynamicObject) -> Error {
+    let types = obj.types.clone().unwrap_or_default();
+    Error::Discovery(DiscoveryError::MissingResource(format!(
+        "{}/{}",
+        types.api_version, types.kind
+    )))
+}
+
+/// The order in which kinds are applied by [`Discovery::apply_manifest`]
+///
+/// Namespaces come first since they contain other objects, followed by definitions of the custom resources
+/// that may be created by the rest of the manifest.
+fn apply_rank(obj: &DynamicObject) -> u8 {
+    match obj.types.as_ref().map(|t| (t.api_version.as_str(), t.kind.as_str())) {
+        Some(("v1", "Namespace")) => 0,
+        Some((api_version, "CustomResourceDefinition")) if api_version.starts_with("apiextensions.k8s.io/") => 1,
+        _ => 2,
+    }
+}
+
+/// The group of the custom resource defined by a `CustomResourceDefinition`
+fn defined_group(crd: &DynamicObject) -> Option<&str> {
+    crd.data.get("spec")?.get("group")?.as_str()
+}
+
+/// Resolve a kind with [`pinned_kind`] once the apiserver serves it
+///
+/// A custom resource is only served once its definition is established, so this polls with an exponential backoff
+/// while the kind is not found, for up to [`ESTABLISH_TIMEOUT`].
+async fn await_served(client: &Client, gvk: &GroupVersionKind) -> Result<(ApiResource, ApiCapabilities)> {
+    let deadline = tokio::time::Instant::now() + ESTABLISH_TIMEOUT;
+    let mut delay = ESTABLISH_POLL_DELAY;
+    loop {
+        match pinned_kind(client, gvk).await {
+            Err(Error::Api(ErrorResponse { code: 404, .. }) | Error::Discovery(DiscoveryError::MissingKind(_)))
+                if tokio::time::Instant::now() + delay < deadline =>
+            {
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            res => return res,
+        }
+    }
+}
+
+impl Discovery {
+    /// Apply the objects of a manifest with server-side apply, like `kubectl apply --server-side -f`
+    ///
+    /// Every object is sent as a [`Patch::Apply`] with the given [`PatchParams`] to the endpoint of its kind,
+    /// scoped like [`Api::from_object`]. Namespaces are applied first, and then `CustomResourceDefinition`s,
+    /// while the other objects keep the order of the manifest.
+    /// Kinds that were not found by this discovery, like those defined by the manifest itself,
+    /// are resolved with [`pinned_kind`] once per manifest. The apiserver only serves a new custom resource
+    /// once its definition is established, so the kinds of the `CustomResourceDefinition`s in the manifest
+    /// are polled with a backoff for up to 30 seconds before their objects are applied.
+    ///
+    /// Returns the applied objects in the order they were applied, and stops at the first failure.
+    /// The objects of a YAML manifest can be parsed with `kube::core::dynamic::from_yaml_multidoc`.
+    ///
+    /// ```no_run
+    /// use kube::{api::{DynamicObject, PatchParams}, discovery::Discovery, Client};
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: Client = todo!();
+    /// # let objects: Vec<DynamicObject> = todo!();
+    /// let discovery = Discovery::new(client).run().await?;
+    /// discovery.apply_manifest(&objects, &PatchParams::apply("installer").force()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn apply_manifest(&self, objects: &[DynamicObject], pp: &PatchParams) -> Result<Vec<DynamicObject>> {
+        let mut ordered = objects.iter().collect::<Vec<_>>();
+        ordered.sort_by_key(|obj| apply_rank(obj));
+        let mut applied = Vec::with_capacity(ordered.len());
+        let mut pinned = HashMap::<_, (ApiResource, ApiCapabilities)>::new();
+        let mut defined_groups = HashSet::new();
+        for obj in ordered {
+            let gvk = object_gvk(obj)?;
+            let (ar, caps) = match self.resolve_gvk(&gvk) {
+                Some(found) => found,
+                None => match pinned.get(&gvk) {
+                    Some(found) => found.clone(),
+                    None => {
+                        let found = if defined_groups.contains(gvk.group.as_str()) {
+                            await_served(&self.client, &gvk).await?
+                        } else {
+                            pinned_kind(&self.client, &gvk).await?
+                        };
+                        pinned.insert(gvk, found.clone());
+                        found
+                    }
+                },
+            };
+            let api = Api::scoped_for(self.client.clone(), obj, &ar, &caps);
+            applied.push(api.patch(&obj.name_any(), pp, &Patch::Apply(obj)).await?);
+            if apply_rank(obj) == 1 {
+                defined_groups.extend(defined_group(obj));
+            }
+        }
+        Ok(applied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Discovery;
+    use crate::{
+        api::{DynamicObject, PatchParams},
+        error::DiscoveryError,
+        Api, Client, Error,
+    };
+    use futures::pin_mut;
+    use http::{Request, Response};
+    use hyper::Body;
+    use serde_json::json;
+    use std::time::Duration;
+    use tower_test::mock;
+
+    async fn core_discovery() -> Discovery {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let responses = [
+                ("/apis", json!({ "groups": [] })),
+                ("/api", json!({ "versions": ["v1"], "serverAddressByClientCIDRs": [] })),
+                ("/api/v1", json!({
+                    "groupVersion": "v1",
+                    "resources": [
+                        { "name": "configmaps", "namespaced": true, "kind": "ConfigMap", "singularName": "", "verbs": ["get", "list"] },
+                        { "name": "namespaces", "namespaced": false, "kind": "Namespace", "singularName": "", "verbs": ["get", "list"] },
+                    ]
+                })),
+            ];
+            for (uri, body) in responses {
+                let (request, send) = handle.next_request().await.expect("service not called");
+                assert_eq!(request.uri().to_string(), uri);
+                send.send_response(Response::builder().body(Body::from(body.to_string())).unwrap());
+            }
+        });
+        let discovery = Discovery::new(Client::new(mock_service, "default"))
+            .filter(&[""])
+            .run()
+            .await
+            .unwrap();
+        spawned.await.unwrap();
+        discovery
+    }
+
+    fn apiservice(group: &str, aggregated: bool) -> serde_json::Value {
+        json!({
+            "metadata": { "name": format!("v1.{group}") },
+            "spec": {
+                "group": group,
+                "version": "v1",
+                "groupPriorityMinimum": 100,
+                "versionPriority": 100,
+                "service": if aggregated { json!({ "name": group, "namespace": "kube-system" }) } else { json!(null) }
+            },
+            "status": { "conditions": [{ "type": "Available", "status": "True" }] }
+        })
+    }
+
+    fn api_group(group: &str) -> serde_json::Value {
+        json!({
+            "name": group,
+            "versions": [{ "groupVersion": format!("{group}/v1"), "version": "v1" }]
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn discovery_skips_failing_aggregated_groups() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.uri().to_string(), "/apis/apiregistration.k8s.io/v1/apiservices?");
+            let list = json!({
+                "metadata": {},
+                "items": [apiservice("apps", false), apiservice("broken.io", true), apiservice("stalled.io", true)]
+            });
+            send.send_response(Response::builder().body(Body::from(list.to_string())).unwrap());
+
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.uri().to_string(), "/apis");
+            let groups = json!({ "groups": [api_group("apps"), api_group("broken.io"), api_group("stalled.io")] });
+            send.send_response(Response::builder().body(Body::from(groups.to_string())).unwrap());
+
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.uri().to_string(), "/apis/apps/v1");
+            let resources = json!({
+                "groupVersion": "apps/v1",
+                "resources": [{ "name": "deployments", "namespaced": true, "kind": "Deployment", "singularName": "", "verbs": ["get"] }]
+            });
+            send.send_response(Response::builder().body(Body::from(resources.to_string())).unwrap());
+
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.uri().to_string(), "/apis/broken.io/v1");
+            let status = json!({ "status": "Failure", "message": "service unavailable", "reason": "ServiceUnavailable", "code": 503 });
+            send.send_response(Response::builder().status(503).body(Body::from(status.to_string())).unwrap());
+
+            // never answered, so the query times out
+            let (request, _send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.uri().to_string(), "/apis/stalled.io/v1");
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        });
+        let discovery = Discovery::new(Client::new(mock_service, "default"))
+            .exclude(&[""])
+            .skip_unavailable_aggregated()
+            .aggregated_timeout(Duration::from_secs(5))
+            .run()
+            .await
+            .unwrap();
+
+        assert!(discovery.has_group("apps"));
+        assert!(!discovery.has_group("broken.io"));
+        assert!(!discovery.has_group("stalled.io"));
+        assert!(!discovery.is_aggregated("apps"));
+        assert!(discovery.is_aggregated("broken.io"));
+        assert_eq!(discovery.apiservices().len(), 3);
+        let unavailable = discovery
+            .unavailable_apiservices()
+            .iter()
+            .map(|s| (s.group.as_str(), s.reason.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(unavailable, [
+            ("broken.io", Some("DiscoveryFailed")),
+            ("stalled.io", Some("DiscoveryFailed"))
+        ]);
+        spawned.abort();
+    }
+
+    fn object(api_version: &str, kind: &str, namespace: Option<&str>) -> DynamicObject {
+        serde_json::from_value(json!({
+            "apiVersion": api_version,
+            "kind": kind,
+            "metadata": { "name": "obj", "namespace": namespace }
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn api_from_object_is_scoped_by_discovered_kind() {
+        let discovery = core_discovery().await;
+        let (mock_service, _handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(mock_service, "fallback");
+
+        let api = Api::from_object(client.clone(), &object("v1", "ConfigMap", Some("apps")), &discovery).unwrap();
+        assert_eq!(api.resource_url(), "/api/v1/namespaces/apps/configmaps");
+
+        let api = Api::from_object(client.clone(), &object("v1", "ConfigMap", None), &discovery).unwrap();
+        assert_eq!(api.resource_url(), "/api/v1/namespaces/fallback/configmaps");
+
+        let api = Api::from_object(client.clone(), &object("v1", "Namespace", Some("apps")), &discovery).unwrap();
+        assert_eq!(api.resource_url(), "/api/v1/namespaces");
+
+        let err = Api::from_object(client.clone(), &object("apps/v1", "Deployment", None), &discovery).unwrap_err();
+        assert!(matches!(err, Error::Discovery(DiscoveryError::MissingResource(_))));
+
+        let mut untyped = object("v1", "ConfigMap", None);
+        untyped.types = None;
+        let err = Api::from_object(client, &untyped, &discovery).unwrap_err();
+        assert!(matches!(err, Error::Discovery(DiscoveryError::MissingKind(_))));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn apply_manifest_applies_namespaces_first_and_resolves_new_kinds() {
+        let discovery = core_discovery().await;
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let resources = |group_version: &str, name: &str, namespaced: bool, kind: &str| {
+                json!({
+                    "groupVersion": group_version,
+                    "resources": [{ "name": name, "namespaced": namespaced, "kind": kind, "singularName": "", "verbs": ["patch"] }]
+                })
+            };
+            let not_found = json!({ "status": "Failure", "message": "not found", "reason": "NotFound", "code": 404 });
+            let expected = [
+                ("PATCH", "/api/v1/namespaces/obj", None),
+                (
+                    "GET",
+                    "/apis/apiextensions.k8s.io/v1",
+                    Some((200, resources("apiextensions.k8s.io/v1", "customresourcedefinitions", false, "CustomResourceDefinition"))),
+                ),
+                ("PATCH", "/apis/apiextensions.k8s.io/v1/customresourcedefinitions/obj", None),
+                // the custom resource is not served until its definition is established
+                ("GET", "/apis/example.com/v1", Some((404, not_found))),
+                ("GET", "/apis/example.com/v1", Some((200, resources("example.com/v1", "foos", true, "Foo")))),
+                ("PATCH", "/apis/example.com/v1/namespaces/fallback/foos/obj", None),
+                ("PATCH", "/api/v1/namespaces/apps/configmaps/obj", None),
+                // the resolved kind is reused
+                ("PATCH", "/apis/example.com/v1/namespaces/apps/foos/obj", None),
+            ];
+            for (method, path, response) in expected {
+                let (request, send) = handle.next_request().await.expect("service not called");
+                assert_eq!((request.method().as_str(), request.uri().path()), (method, path));
+                let response = match response {
+                    Some((status, body)) => Response::builder().status(status).body(Body::from(body.to_string())),
+                    None => {
+                        assert!(request.uri().query().unwrap().contains("fieldManager=installer"));
+                        Response::builder().body(request.into_body())
+                    }
+                };
+                send.send_response(response.unwrap());
+            }
+        });
+        let discovery = Discovery {
+            client: Client::new(mock_service, "fallback"),
+            ..discovery
+        };
+        let mut crd = object("apiextensions.k8s.io/v1", "CustomResourceDefinition", None);
+        crd.data = json!({ "spec": { "group": "example.com" } });
+        let objects = [
+            object("example.com/v1", "Foo", None),
+            object("v1", "ConfigMap", Some("apps")),
+            object("v1", "Namespace", None),
+            crd,
+            object("example.com/v1", "Foo", Some("apps")),
+        ];
+        let applied = discovery
+            .apply_manifest(&objects, &PatchParams::apply("installer"))
+            .await
+            .unwrap();
+        let kinds = applied
+            .iter()
+            .map(|obj| obj.types.as_ref().unwrap().kind.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, ["Namespace", "CustomResourceDefinition", "Foo", "ConfigMap", "Foo"]);
+        spawned.await.unwrap();
+    }
+}
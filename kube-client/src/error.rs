@@ -16,7 +16,26 @@ pub enum Error {
     #[error("ApiError: {0} ({0:?})")]
     Api(#[source] ErrorResponse),
 
+    /// The apiserver rejected the request with `429 Too Many Requests`
+    ///
+    /// This is usually [API Priority and Fairness](https://kubernetes.io/docs/concepts/cluster-administration/flow-control/)
+    /// shedding load, and the request can be retried once `retry_after` has passed.
+    /// See [`Client::with_throttle_retries`](crate::Client::with_throttle_retries) to retry safe requests automatically.
+    ///
+    /// Note that these responses used to be returned as an [`Error::Api`] with code `429`,
+    /// so code handling throttling by matching on that needs to match this variant instead.
+    #[error("TooManyRequests: {response} (retry after {retry_after:?})")]
+    TooManyRequests {
+        /// The error returned by the apiserver
+        response: ErrorResponse,
+        /// The delay requested by the `Retry-After` header, if present
+        retry_after: Option<std::time::Duration>,
+    },
+
     /// Hyper error
+    ///
+    /// Failures to connect, TLS errors and timeouts are reported as [`Error::Connect`],
+    /// [`Error::Tls`] and [`Error::Timeout`] instead.
     #[cfg(feature = "client")]
     #[error("HyperError: {0}")]
     HyperError(#[source] hyper::Error),
@@ -25,16 +44,57 @@ pub enum Error {
     #[error("ServiceError: {0}")]
     Service(#[source] tower::BoxError),
 
+    /// Failed to connect to the apiserver
+    ///
+    /// For instance because its address could not be resolved, or it refused the connection.
+    /// No request was sent, so it is always safe to retry.
+    #[cfg(feature = "client")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "client")))]
+    #[error("failed to connect to the apiserver: {0}")]
+    Connect(#[source] hyper::Error),
+
+    /// TLS error when talking to the apiserver
+    ///
+    /// Usually the handshake failed, for instance because the apiserver certificate is not trusted,
+    /// which is unlikely to be resolved by retrying.
+    #[cfg(feature = "client")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "client")))]
+    #[error("tls error: {0}")]
+    Tls(#[source] hyper::Error),
+
+    /// The apiserver did not respond in time
+    ///
+    /// See [`Config::connect_timeout`](crate::Config::connect_timeout) and
+    /// [`Config::read_timeout`](crate::Config::read_timeout).
+    #[cfg(feature = "client")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "client")))]
+    #[error("request to the apiserver timed out: {0}")]
+    Timeout(#[source] hyper::Error),
+
     /// UTF-8 Error
     #[error("UTF-8 Error: {0}")]
     FromUtf8(#[source] std::string::FromUtf8Error),
 
     /// Returned when failed to find a newline character within max length.
-    /// Only returned by `Client::request_events` and this should never happen as
-    /// the max is `usize::MAX`.
+    /// Only returned by `Client::request_events` when a watch event exceeds
+    /// [`Config::max_response_bytes`](crate::Config::max_response_bytes).
     #[error("Error finding newline character")]
     LinesCodecMaxLineLengthExceeded,
 
+    /// Returned when a response body exceeds the configured maximum size
+    ///
+    /// See [`Config::max_response_bytes`](crate::Config::max_response_bytes).
+    #[error("response body exceeded the maximum size of {0} bytes")]
+    ResponseTooLarge(usize),
+
+    /// Returned when a request would have to wait for longer than the maximum wait of a client-side rate limit
+    ///
+    /// See [`RateLimitLayer::max_wait`](crate::client::middleware::RateLimitLayer::max_wait).
+    #[cfg(feature = "client")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "client")))]
+    #[error("client-side rate limit exceeded, the request would have been delayed by {0:?}")]
+    RateLimited(std::time::Duration),
+
     /// Returned on `std::io::Error` when reading event stream.
     #[error("Error reading events stream: {0}")]
     ReadEvents(#[source] std::io::Error),
@@ -81,6 +141,12 @@ pub enum Error {
     #[error("failed to upgrade to a WebSocket connection: {0}")]
     UpgradeConnection(#[source] crate::client::UpgradeConnectionError),
 
+    /// Errors from a remote command session (exec or attach)
+    #[cfg(feature = "ws")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ws")))]
+    #[error("remote command error: {0}")]
+    RemoteCommand(#[source] Box<crate::api::RemoteCommandError>),
+
     /// Errors related to client auth
     #[cfg(feature = "client")]
     #[cfg_attr(docsrs, doc(cfg(feature = "client")))]
@@ -110,4 +176,8 @@ pub enum DiscoveryError {
     /// Empty ApiGroup
     #[error("Empty Api Group: {0}")]
     EmptyApiGroup(String),
+
+    /// Unparseable apiserver version
+    #[error("Invalid apiserver version: {0}")]
+    InvalidVersion(String),
 }
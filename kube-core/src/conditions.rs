@@ -0,0 +1,106 @@
+//! Helpers for maintaining the standard `status.conditions` array
+use chrono::Utc;
+pub use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+
+/// Insert or update a condition in a list of conditions
+///
+/// This follows the semantics of `meta.SetStatusCondition` from apimachinery:
+///
+/// - A condition with a new `type_` is appended.
+/// - An existing condition of the same `type_` has its `reason`, `message` and
+///   `observed_generation` replaced.
+///   Its `last_transition_time` is only replaced when the `status` changes, so that it reflects
+///   when the condition last flipped rather than when it was last reconciled.
+///
+/// A `last_transition_time` left at its default (the unix epoch) is set to the current time.
+/// Set `observed_generation` on `new` to the `metadata.generation` of the object being reconciled.
+///
+/// ```
+/// use k8s_openapi::{apimachinery::pkg::apis::meta::v1::Time, chrono::Utc};
+/// use kube_core::conditions::{set_condition, Condition};
+///
+/// let mut conditions = vec![];
+/// set_condition(&mut conditions, Condition {
+///     type_: "Ready".into(),
+///     status: "True".into(),
+///     reason: "Reconciled".into(),
+///     message: "all replicas are available".into(),
+///     observed_generation: Some(2),
+///     last_transition_time: Time(Utc::now()),
+/// });
+/// assert_eq!(conditions.len(), 1);
+/// ```
+pub fn set_condition(conditions: &mut Vec<Condition>, mut new: Condition) {
+    let unset = new.last_transition_time == Time(Default::default());
+    match conditions.iter_mut().find(|c| c.type_ == new.type_) {
+        Some(existing) => {
+            if existing.status != new.status {
+                existing.status = new.status;
+                existing.last_transition_time = if unset { now() } else { new.last_transition_time };
+            }
+            existing.reason = new.reason;
+            existing.message = new.message;
+            existing.observed_generation = new.observed_generation;
+        }
+        None => {
+            if unset {
+                new.last_transition_time = now();
+            }
+            conditions.push(new);
+        }
+    }
+}
+
+fn now() -> Time {
+    Time(Utc::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn condition(status: &str, reason: &str, generation: i64) -> Condition {
+        Condition {
+            type_: "Ready".into(),
+            status: status.into(),
+            reason: reason.into(),
+            message: String::new(),
+            observed_generation: Some(generation),
+            last_transition_time: Time(Default::default()),
+        }
+    }
+
+    #[test]
+    fn set_condition_only_bumps_transition_time_on_status_change() {
+        let mut conditions = vec![];
+        set_condition(&mut conditions, condition("False", "Pending", 1));
+        let first = conditions[0].last_transition_time.clone();
+        assert_ne!(first, Time(Default::default()));
+
+        set_condition(&mut conditions, condition("False", "StillPending", 2));
+        assert_eq!(conditions.len(), 1);
+        assert_eq!(conditions[0].reason, "StillPending");
+        assert_eq!(conditions[0].observed_generation, Some(2));
+        assert_eq!(conditions[0].last_transition_time, first);
+
+        let flipped = Time(first.0 + chrono::Duration::seconds(10));
+        set_condition(&mut conditions, Condition {
+            last_transition_time: flipped.clone(),
+            ..condition("True", "Ready", 3)
+        });
+        assert_eq!(conditions[0].status, "True");
+        assert_eq!(conditions[0].last_transition_time, flipped);
+    }
+
+    #[test]
+    fn set_condition_appends_new_types() {
+        let mut conditions = vec![condition("True", "Ready", 1)];
+        set_condition(&mut conditions, Condition {
+            type_: "Degraded".into(),
+            ..condition("False", "Healthy", 1)
+        });
+        let types = conditions.iter().map(|c| c.type_.as_str()).collect::<Vec<_>>();
+        assert_eq!(types, vec!["Ready", "Degraded"]);
+    }
+}
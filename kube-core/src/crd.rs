@@ -4,7 +4,9 @@ use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions as apiexts;
 
 /// Types for v1 CustomResourceDefinitions
 pub mod v1 {
-    use super::apiexts::v1::CustomResourceDefinition as Crd;
+    use super::apiexts::v1::{
+        CustomResourceDefinition as Crd, JSONSchemaProps, JSONSchemaPropsOrArray, JSONSchemaPropsOrBool,
+    };
     /// Extension trait that is implemented by kube-derive
     pub trait CustomResourceExt {
         /// Helper to generate the CRD including the JsonSchema
@@ -138,7 +140,145 @@ pub mod v1 {
         Ok(root)
     }
 
+    /// Compute which fields of an object the apiserver would prune when storing it under a structural schema
+    ///
+    /// Fields that are not specified by a structural schema are dropped silently by the apiserver,
+    /// unless they are covered by `additionalProperties` or `x-kubernetes-preserve-unknown-fields`.
+    /// This emulates that pruning locally, so that a schema can be checked against a sample object
+    /// before the [`CRD`] is applied.
+    ///
+    /// The pruned fields are returned sorted, as paths like `spec.items[0].name`.
+    /// As in the apiserver, `apiVersion`, `kind` and `metadata` are never pruned from the root object,
+    /// nor from `x-kubernetes-embedded-resource` objects. Value validations such as `allOf` are not
+    /// considered, since they cannot specify additional fields in a structural schema.
+    ///
+    /// ## Usage
+    ///
+    /// ```no_run
+    /// # use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+    /// use kube::core::crd::pruned_fields;
+    /// # let crd: CustomResourceDefinition = todo!(); // MyCrd::crd();
+    /// let schema = crd.spec.versions[0].schema.as_ref().and_then(|s| s.open_api_v3_schema.as_ref()).unwrap();
+    /// let sample = serde_json::json!({
+    ///     "apiVersion": "kube.rs/v1",
+    ///     "kind": "MyCrd",
+    ///     "metadata": { "name": "sample" },
+    ///     "spec": { "replicas": 1 },
+    /// });
+    /// for path in pruned_fields(schema, &sample) {
+    ///     println!("{path} would be pruned");
+    /// }
+    /// ```
+    ///
+    /// [`CRD`]: https://docs.rs/k8s-openapi/latest/k8s_openapi/apiextensions_apiserver/pkg/apis/apiextensions/v1/struct.CustomResourceDefinition.html
+    pub fn pruned_fields(schema: &JSONSchemaProps, obj: &serde_json::Value) -> Vec<String> {
+        let mut pruned = Vec::new();
+        collect_pruned(schema, obj, "", true, &mut pruned);
+        pruned.sort();
+        pruned
+    }
+
+    fn collect_pruned(
+        schema: &JSONSchemaProps,
+        value: &serde_json::Value,
+        path: &str,
+        is_resource: bool,
+        pruned: &mut Vec<String>,
+    ) {
+        let is_resource = is_resource || schema.x_kubernetes_embedded_resource == Some(true);
+        let preserve_unknown = schema.x_kubernetes_preserve_unknown_fields == Some(true);
+        match value {
+            serde_json::Value::Object(fields) => {
+                for (key, field) in fields {
+                    let field_path = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{path}.{key}")
+                    };
+                    let field_schema = schema.properties.as_ref().and_then(|props| props.get(key));
+                    let field_schema = field_schema.or(match &schema.additional_properties {
+                        Some(JSONSchemaPropsOrBool::Schema(additional)) => Some(additional),
+                        _ => None,
+                    });
+                    if let Some(field_schema) = field_schema {
+                        collect_pruned(field_schema, field, &field_path, false, pruned);
+                    } else if !(preserve_unknown
+                        || matches!(
+                            schema.additional_properties,
+                            Some(JSONSchemaPropsOrBool::Bool(true))
+                        )
+                        || is_resource && ["apiVersion", "kind", "metadata"].contains(&key.as_str()))
+                    {
+                        pruned.push(field_path);
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => {
+                if let Some(JSONSchemaPropsOrArray::Schema(item_schema)) = &schema.items {
+                    for (i, item) in items.iter().enumerate() {
+                        collect_pruned(item_schema, item, &format!("{path}[{i}]"), false, pruned);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
     mod tests {
+        #[test]
+        fn pruned_fields_of_sample_object() {
+            use super::{pruned_fields, JSONSchemaProps};
+            let schema = r#"
+            type: object
+            properties:
+              spec:
+                type: object
+                properties:
+                  replicas:
+                    type: integer
+                  containers:
+                    type: array
+                    items:
+                      type: object
+                      properties:
+                        name:
+                          type: string
+                  labels:
+                    type: object
+                    additionalProperties:
+                      type: string
+                  config:
+                    type: object
+                    x-kubernetes-preserve-unknown-fields: true
+                  template:
+                    type: object
+                    x-kubernetes-embedded-resource: true
+                    properties:
+                      spec:
+                        type: object"#;
+            let schema: JSONSchemaProps = serde_yaml::from_str(schema).unwrap();
+            let obj = serde_json::json!({
+                "apiVersion": "kube.rs/v1",
+                "kind": "Foo",
+                "metadata": { "name": "foo" },
+                "spec": {
+                    "replicas": 1,
+                    "replicaCount": 2,
+                    "containers": [{ "name": "a" }, { "name": "b", "image": "nginx" }],
+                    "labels": { "app": "foo" },
+                    "config": { "anything": { "goes": true } },
+                    "template": { "apiVersion": "v1", "kind": "Pod", "spec": { "nodeName": "x" } },
+                },
+                "status": {},
+            });
+            assert_eq!(pruned_fields(&schema, &obj), [
+                "spec.containers[1].image",
+                "spec.replicaCount",
+                "spec.template.spec.nodeName",
+                "status",
+            ]);
+        }
+
         #[test]
         fn crd_merge() {
             use super::{merge_crds, Crd};
@@ -235,4 +375,4 @@ pub mod v1 {
 }
 
 // re-export current latest (v1)
-pub use v1::{merge_crds, CustomResourceExt, MergeError};
+pub use v1::{merge_crds, pruned_fields, CustomResourceExt, MergeError};
@@ -0,0 +1,245 @@
+//! Migration of field ownership from client-side apply to server-side apply
+//!
+//! Objects that were managed with client-side apply (like `kubectl apply` without `--server-side`)
+//! have their fields owned by `Update` operations of the client-side manager.
+//! Applying them with server-side apply under a new field manager does not remove fields that
+//! are no longer set, and conflicts when changing fields owned by the old manager.
+//!
+//! [`upgrade_managed_fields`] transfers the ownership of those fields to the server-side apply manager,
+//! like `kubectl apply --server-side` does when it takes over an object from client-side apply.
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{FieldsV1, ManagedFieldsEntry};
+use serde_json::Value;
+
+/// Field managers used by `kubectl` for client-side apply
+///
+/// - `kubectl-client-side-apply` is used by `kubectl apply` since Kubernetes 1.18
+/// - `kubectl` was used by `kubectl apply` before 1.18
+/// - `before-first-apply` is assigned by the apiserver to fields that were set before the first apply
+pub const KUBECTL_CSA_MANAGERS: &[&str] = &["kubectl-client-side-apply", "kubectl", "before-first-apply"];
+
+const OPERATION_APPLY: &str = "Apply";
+const OPERATION_UPDATE: &str = "Update";
+
+/// Transfer ownership of fields from client-side apply managers to a server-side apply manager
+///
+/// Every `Update` entry of the `csa_managers` for the main resource is merged into the
+/// `Apply` entry of `ssa_manager` for the same `apiVersion`, since the fields of an entry are
+/// expressed in the schema of its version:
+///
+/// - if `ssa_manager` already has an `Apply` entry for that version, the field sets are combined
+///   into it and the client-side entry is removed
+/// - otherwise, the client-side entry is converted into the `Apply` entry of `ssa_manager`
+///
+/// Entries of other managers, and entries for subresources like `status`, are left untouched.
+/// Returns whether any entry was changed, in which case the `managedFields` must be written back to
+/// the object (for instance with [`Patch::Merge`](crate::params::Patch::Merge), including the
+/// `resourceVersion` to guard against concurrent updates).
+///
+/// ```
+/// use k8s_openapi::api::core::v1::ConfigMap;
+/// use kube_core::{csaupgrade::{upgrade_managed_fields, KUBECTL_CSA_MANAGERS}, ResourceExt};
+/// # let mut cm = ConfigMap::default();
+/// if upgrade_managed_fields(cm.managed_fields_mut(), KUBECTL_CSA_MANAGERS, "my-operator") {
+///     // write cm.metadata.managed_fields back to the apiserver
+/// }
+/// ```
+pub fn upgrade_managed_fields(
+    managed_fields: &mut Vec<ManagedFieldsEntry>,
+    csa_managers: &[&str],
+    ssa_manager: &str,
+) -> bool {
+    let mut changed = false;
+    // every pass merges or converts one client-side entry, so none are left over in the end
+    while let Some(csa_idx) = managed_fields
+        .iter()
+        .position(|e| csa_managers.iter().any(|m| is_entry(e, m, OPERATION_UPDATE)))
+    {
+        changed = true;
+        let api_version = managed_fields[csa_idx].api_version.clone();
+        match managed_fields
+            .iter()
+            .position(|e| is_entry(e, ssa_manager, OPERATION_APPLY) && e.api_version == api_version)
+        {
+            Some(ssa_idx) => {
+                let csa = managed_fields.remove(csa_idx);
+                let ssa = &mut managed_fields[if ssa_idx > csa_idx { ssa_idx - 1 } else { ssa_idx }];
+                if let Some(FieldsV1(csa_fields)) = csa.fields_v1 {
+                    let ssa_fields = &mut ssa.fields_v1.get_or_insert_with(|| FieldsV1(Value::Null)).0;
+                    union(ssa_fields, csa_fields);
+                }
+                if csa.time > ssa.time {
+                    ssa.time = csa.time;
+                }
+            }
+            None => {
+                let entry = &mut managed_fields[csa_idx];
+                entry.manager = Some(ssa_manager.to_string());
+                entry.operation = Some(OPERATION_APPLY.to_string());
+            }
+        }
+    }
+    changed
+}
+
+/// Whether an entry is for `manager` and `operation` on the main resource
+fn is_entry(entry: &ManagedFieldsEntry, manager: &str, operation: &str) -> bool {
+    entry.manager.as_deref() == Some(manager)
+        && entry.operation.as_deref() == Some(operation)
+        && entry.subresource.as_deref().map_or(true, str::is_empty)
+}
+
+/// Add all the fields of the `FieldsV1` set `other` to `fields`
+fn union(fields: &mut Value, other: Value) {
+    match (fields, other) {
+        (Value::Object(fields), Value::Object(other)) => {
+            for (key, value) in other {
+                match fields.get_mut(&key) {
+                    Some(existing) => union(existing, value),
+                    None => {
+                        fields.insert(key, value);
+                    }
+                }
+            }
+        }
+        (fields @ Value::Null, other) => *fields = other,
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(manager: &str, operation: &str, fields: Value) -> ManagedFieldsEntry {
+        ManagedFieldsEntry {
+            api_version: Some("v1".into()),
+            fields_type: Some("FieldsV1".into()),
+            fields_v1: Some(FieldsV1(fields)),
+            manager: Some(manager.into()),
+            operation: Some(operation.into()),
+            ..ManagedFieldsEntry::default()
+        }
+    }
+
+    fn owners(managed_fields: &[ManagedFieldsEntry]) -> Vec<(&str, &str)> {
+        managed_fields
+            .iter()
+            .map(|e| (e.manager.as_deref().unwrap(), e.operation.as_deref().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn converts_csa_entry_when_ssa_manager_is_new() {
+        let mut managed_fields = vec![
+            entry(
+                "kubectl-client-side-apply",
+                "Update",
+                json!({"f:data": {"f:a": {}}}),
+            ),
+            entry("kube-controller-manager", "Update", json!({"f:status": {}})),
+        ];
+        assert!(upgrade_managed_fields(
+            &mut managed_fields,
+            KUBECTL_CSA_MANAGERS,
+            "my-operator"
+        ));
+        assert_eq!(owners(&managed_fields), vec![
+            ("my-operator", "Apply"),
+            ("kube-controller-manager", "Update"),
+        ]);
+        assert_eq!(
+            managed_fields[0].fields_v1,
+            Some(FieldsV1(json!({"f:data": {"f:a": {}}})))
+        );
+    }
+
+    #[test]
+    fn merges_csa_entries_into_existing_ssa_entry() {
+        let mut managed_fields = vec![
+            entry(
+                "before-first-apply",
+                "Update",
+                json!({"f:metadata": {"f:labels": {"f:app": {}}}}),
+            ),
+            entry("my-operator", "Apply", json!({"f:data": {"f:b": {}}})),
+            entry(
+                "kubectl-client-side-apply",
+                "Update",
+                json!({"f:data": {"f:a": {}}}),
+            ),
+            ManagedFieldsEntry {
+                subresource: Some("status".into()),
+                ..entry("kubectl-client-side-apply", "Update", json!({"f:status": {}}))
+            },
+        ];
+        assert!(upgrade_managed_fields(
+            &mut managed_fields,
+            KUBECTL_CSA_MANAGERS,
+            "my-operator"
+        ));
+        assert_eq!(owners(&managed_fields), vec![
+            ("my-operator", "Apply"),
+            ("kubectl-client-side-apply", "Update"),
+        ]);
+        assert_eq!(
+            managed_fields[0].fields_v1,
+            Some(FieldsV1(json!({
+                "f:data": {"f:a": {}, "f:b": {}},
+                "f:metadata": {"f:labels": {"f:app": {}}}
+            })))
+        );
+        assert_eq!(managed_fields[1].subresource.as_deref(), Some("status"));
+    }
+
+    #[test]
+    fn merges_csa_entries_per_api_version() {
+        let mut managed_fields = vec![
+            entry(
+                "kubectl-client-side-apply",
+                "Update",
+                json!({"f:data": {"f:a": {}}}),
+            ),
+            ManagedFieldsEntry {
+                api_version: Some("v2".into()),
+                ..entry(
+                    "kubectl-client-side-apply",
+                    "Update",
+                    json!({"f:spec": {"f:b": {}}}),
+                )
+            },
+            entry("kubectl", "Update", json!({"f:data": {"f:c": {}}})),
+        ];
+        assert!(upgrade_managed_fields(
+            &mut managed_fields,
+            KUBECTL_CSA_MANAGERS,
+            "my-operator"
+        ));
+        assert_eq!(owners(&managed_fields), vec![
+            ("my-operator", "Apply"),
+            ("my-operator", "Apply"),
+        ]);
+        assert_eq!(managed_fields[0].api_version.as_deref(), Some("v1"));
+        assert_eq!(
+            managed_fields[0].fields_v1,
+            Some(FieldsV1(json!({"f:data": {"f:a": {}, "f:c": {}}})))
+        );
+        assert_eq!(managed_fields[1].api_version.as_deref(), Some("v2"));
+        assert_eq!(
+            managed_fields[1].fields_v1,
+            Some(FieldsV1(json!({"f:spec": {"f:b": {}}})))
+        );
+    }
+
+    #[test]
+    fn leaves_ssa_only_objects_untouched() {
+        let mut managed_fields = vec![entry("my-operator", "Apply", json!({"f:data": {}}))];
+        let before = managed_fields.clone();
+        assert!(!upgrade_managed_fields(
+            &mut managed_fields,
+            KUBECTL_CSA_MANAGERS,
+            "my-operator"
+        ));
+        assert_eq!(managed_fields, before);
+    }
+}
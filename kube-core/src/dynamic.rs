@@ -8,6 +8,7 @@ use crate::{
 };
 
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use serde_json::Value;
 use std::borrow::Cow;
 use thiserror::Error;
 
@@ -19,6 +20,91 @@ pub struct ParseDynamicObjectError {
     source: serde_json::Error,
 }
 
+/// Failed to resolve a field path of a `DynamicObject`
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FieldPathError {
+    /// The path is not valid syntax
+    #[error("malformed field path {path:?}: {reason}")]
+    Malformed {
+        /// The path that failed to parse
+        path: String,
+        /// What is wrong with the path
+        reason: &'static str,
+    },
+
+    /// The path traverses a value that is not an object or array
+    #[error("field path {path:?} expects an {expected} at {at:?}")]
+    TypeMismatch {
+        /// The full path
+        path: String,
+        /// The prefix of the path that resolved to an unexpected type
+        at: String,
+        /// The type required to continue along the path
+        expected: &'static str,
+    },
+
+    /// The path indexes past the end of an array
+    #[error("field path {path:?} indexes past the end of {at:?} (length {len})")]
+    IndexOutOfBounds {
+        /// The full path
+        path: String,
+        /// The prefix of the path that resolved to the array
+        at: String,
+        /// The length of the array
+        len: usize,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum PathSegment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Parse a path like `spec.containers[0].image` into its segments
+fn parse_path(path: &str) -> Result<Vec<PathSegment<'_>>, FieldPathError> {
+    let malformed = |reason| FieldPathError::Malformed {
+        path: path.to_string(),
+        reason,
+    };
+    let mut segments = Vec::new();
+    for part in path.split('.') {
+        let (key, mut indices) = part.split_at(part.find('[').unwrap_or(part.len()));
+        if key.is_empty() {
+            return Err(malformed("empty field name"));
+        }
+        segments.push(PathSegment::Key(key));
+        while !indices.is_empty() {
+            let end = indices.find(']').ok_or_else(|| malformed("unclosed '['"))?;
+            let index = indices[1..end]
+                .parse()
+                .map_err(|_| malformed("array index is not a non-negative integer"))?;
+            segments.push(PathSegment::Index(index));
+            indices = &indices[end + 1..];
+            if !indices.is_empty() && !indices.starts_with('[') {
+                return Err(malformed("unexpected characters after ']'"));
+            }
+        }
+    }
+    Ok(segments)
+}
+
+/// The prefix of `path` that covers `segments`, for error messages
+fn path_prefix(segments: &[PathSegment<'_>]) -> String {
+    let mut prefix = String::new();
+    for segment in segments {
+        match segment {
+            PathSegment::Key(key) if prefix.is_empty() => prefix.push_str(key),
+            PathSegment::Key(key) => {
+                prefix.push('.');
+                prefix.push_str(key);
+            }
+            PathSegment::Index(index) => prefix.push_str(&format!("[{index}]")),
+        }
+    }
+    prefix
+}
+
 /// A dynamic representation of a kubernetes object
 ///
 /// This will work with any non-list type object.
@@ -67,6 +153,102 @@ impl DynamicObject {
         self
     }
 
+    /// Get a nested field of the object data by its path
+    ///
+    /// Paths are dot separated field names with optional array indices, like `spec.replicas`
+    /// or `spec.containers[0].image`. They are resolved within [`data`](Self::data), so they cannot
+    /// address `metadata`, `apiVersion` or `kind`, which are available as typed fields instead.
+    /// Field names containing dots or brackets cannot be addressed.
+    ///
+    /// Returns `Ok(None)` if the field does not exist, including when a part of the path
+    /// resolves to a value of a different type, and an error if the path is malformed.
+    ///
+    /// ```
+    /// use kube_core::DynamicObject;
+    /// use serde_json::json;
+    /// let obj: DynamicObject = serde_json::from_value(json!({
+    ///     "metadata": { "name": "web" },
+    ///     "spec": { "containers": [{ "name": "app", "image": "nginx" }] }
+    /// })).unwrap();
+    /// assert_eq!(obj.get_path("spec.containers[0].image").unwrap(), Some(&json!("nginx")));
+    /// assert_eq!(obj.get_path("spec.replicas").unwrap(), None);
+    /// ```
+    pub fn get_path(&self, path: &str) -> Result<Option<&Value>, FieldPathError> {
+        let segments = parse_path(path)?;
+        Ok(segments
+            .iter()
+            .try_fold(&self.data, |value, segment| match segment {
+                PathSegment::Key(key) => value.get(key),
+                PathSegment::Index(index) => value.get(index),
+            }))
+    }
+
+    /// Set a nested field of the object data by its path
+    ///
+    /// Paths use the same format as [`get_path`](Self::get_path).
+    /// Missing objects along the path are created, but arrays are not: an index must refer to
+    /// an existing element of an existing array.
+    ///
+    /// Fails if the path is malformed, indexes past the end of an array, or traverses a value
+    /// that is not an object (for field names) or array (for indices).
+    ///
+    /// ```
+    /// use kube_core::DynamicObject;
+    /// use serde_json::json;
+    /// let mut obj = DynamicObject {
+    ///     types: None,
+    ///     metadata: Default::default(),
+    ///     data: json!({ "spec": { "containers": [{ "name": "app" }] } }),
+    /// };
+    /// obj.set_path("spec.replicas", json!(3)).unwrap();
+    /// obj.set_path("spec.containers[0].image", json!("nginx")).unwrap();
+    /// assert_eq!(obj.data, json!({
+    ///     "spec": { "replicas": 3, "containers": [{ "name": "app", "image": "nginx" }] }
+    /// }));
+    /// ```
+    pub fn set_path(&mut self, path: &str, value: Value) -> Result<(), FieldPathError> {
+        let segments = parse_path(path)?;
+        // check the whole path first, so that a failing path leaves the data untouched
+        let mut current = Some(&self.data);
+        for (i, segment) in segments.iter().enumerate() {
+            let type_mismatch = |expected| FieldPathError::TypeMismatch {
+                path: path.to_string(),
+                at: path_prefix(&segments[..i]),
+                expected,
+            };
+            current = match (segment, current) {
+                // missing objects are created
+                (PathSegment::Key(_), None | Some(Value::Null)) => None,
+                (PathSegment::Key(key), Some(Value::Object(fields))) => fields.get(*key),
+                (PathSegment::Key(_), Some(_)) => return Err(type_mismatch("object")),
+                (PathSegment::Index(index), Some(Value::Array(items))) => Some(
+                    items
+                        .get(*index)
+                        .ok_or_else(|| FieldPathError::IndexOutOfBounds {
+                            path: path.to_string(),
+                            at: path_prefix(&segments[..i]),
+                            len: items.len(),
+                        })?,
+                ),
+                (PathSegment::Index(_), _) => return Err(type_mismatch("array")),
+            };
+        }
+
+        let mut current = &mut self.data;
+        for segment in &segments {
+            if current.is_null() {
+                *current = Value::Object(Default::default());
+            }
+            current = match (segment, current) {
+                (PathSegment::Key(key), Value::Object(fields)) => fields.entry(*key).or_insert(Value::Null),
+                (PathSegment::Index(index), Value::Array(items)) => &mut items[*index],
+                _ => unreachable!("path {path} was checked"),
+            };
+        }
+        *current = value;
+        Ok(())
+    }
+
     /// Attempt to convert this `DynamicObject` to a `Resource`
     pub fn try_parse<K: Resource + for<'a> serde::Deserialize<'a>>(
         self,
@@ -75,6 +257,85 @@ impl DynamicObject {
     }
 }
 
+/// Parse a multi-document YAML manifest into [`DynamicObject`]s, like the files given to `kubectl apply -f`
+///
+/// Documents are separated by `---`. Empty documents, such as those left by leading or trailing separators,
+/// or those that only contain comments, are skipped. Every other document is parsed on its own, so a document
+/// that is not an object does not prevent the others from being used, while invalid YAML ends the manifest with
+/// an error. The `apiVersion` and `kind` of the documents are kept in [`DynamicObject::types`], so the objects
+/// can be sent to the apiserver as they are.
+///
+/// Like `kubectl`, documents that are lists, such as `kind: List` or `kind: ConfigMapList`, are replaced by their
+/// `items`, so every object of the manifest ends up in the returned [`Vec`] in order.
+///
+/// ```
+/// use kube_core::dynamic::from_yaml_multidoc;
+/// let manifest = "
+/// apiVersion: v1
+/// kind: ConfigMap
+/// metadata:
+///   name: config
+/// ---
+/// ## just a comment
+/// ---
+/// apiVersion: v1
+/// kind: Secret
+/// metadata:
+///   name: secret
+/// ---
+/// ";
+/// let objects = from_yaml_multidoc(manifest).into_iter().collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(objects.len(), 2);
+/// assert_eq!(objects[1].types.as_ref().unwrap().kind, "Secret");
+/// # Ok::<(), serde_yaml::Error>(())
+/// ```
+#[cfg(feature = "yaml")]
+#[cfg_attr(docsrs, doc(cfg(feature = "yaml")))]
+pub fn from_yaml_multidoc(yaml: &str) -> Vec<Result<DynamicObject, serde_yaml::Error>> {
+    use serde::Deserialize;
+    let mut objects = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(yaml) {
+        match serde_yaml::Value::deserialize(document) {
+            Ok(serde_yaml::Value::Null) => {}
+            Ok(mut value) if is_yaml_list(&value) => {
+                if let serde_yaml::Value::Sequence(items) = std::mem::take(&mut value["items"]) {
+                    objects.extend(items.into_iter().map(serde_yaml::from_value));
+                }
+            }
+            Ok(value) => objects.push(serde_yaml::from_value(value)),
+            // the parser cannot recover from invalid YAML, so this ends the manifest
+            Err(err) => {
+                objects.push(Err(err));
+                break;
+            }
+        }
+    }
+    objects
+}
+
+/// Whether a YAML document is a list of objects, which `kubectl` recognizes by a `kind` ending in `List`
+#[cfg(feature = "yaml")]
+fn is_yaml_list(value: &serde_yaml::Value) -> bool {
+    let kind = value.get("kind").and_then(serde_yaml::Value::as_str);
+    kind.map_or(false, |kind| kind.ends_with("List"))
+        && value.get("items").map_or(false, |items| items.is_sequence())
+}
+
+/// Serialize [`DynamicObject`]s into a multi-document YAML manifest
+///
+/// This is the inverse of [`from_yaml_multidoc`], with every object in its own document.
+#[cfg(feature = "yaml")]
+#[cfg_attr(docsrs, doc(cfg(feature = "yaml")))]
+pub fn to_yaml_multidoc<'a>(
+    objects: impl IntoIterator<Item = &'a DynamicObject>,
+) -> Result<String, serde_yaml::Error> {
+    let documents = objects
+        .into_iter()
+        .map(serde_yaml::to_string)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(documents.join("---\n"))
+}
+
 impl Resource for DynamicObject {
     type DynamicType = ApiResource;
     type Scope = DynamicResourceScope;
@@ -118,6 +379,82 @@ mod test {
         resource::Resource,
     };
     use k8s_openapi::api::core::v1::Pod;
+    use serde_json::json;
+
+    use super::{parse_path, FieldPathError, PathSegment};
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn yaml_multidoc_round_trips_objects() {
+        use super::{from_yaml_multidoc, to_yaml_multidoc};
+
+        let manifest = r#"---
+# leading separator and comment-only documents are skipped
+---
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: config
+  namespace: apps
+data:
+  key: value
+---
+---
+apiVersion: example.com/v1
+kind: Foo
+metadata:
+  name: foo
+spec:
+  replicas: 2
+...
+---
+apiVersion: v1
+kind: List
+items:
+- apiVersion: v1
+  kind: Secret
+  metadata:
+    name: listed
+---
+apiVersion: v1
+kind: NamespaceList
+metadata: {}
+items: []
+---
+null
+---
+- not an object
+---
+kind: [broken
+---
+apiVersion: v1
+kind: Ignored
+"#;
+        let mut parsed = from_yaml_multidoc(manifest);
+        assert_eq!(parsed.len(), 5);
+        assert!(parsed.pop().unwrap().is_err());
+        assert!(parsed.pop().unwrap().is_err());
+        let mut objects = parsed.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+        let listed = objects.pop().unwrap();
+        assert_eq!(listed.types.unwrap().kind, "Secret");
+        assert_eq!(listed.metadata.name.as_deref(), Some("listed"));
+        let types = objects[1].types.as_ref().unwrap();
+        assert_eq!(
+            (types.api_version.as_str(), types.kind.as_str()),
+            ("example.com/v1", "Foo")
+        );
+        assert_eq!(objects[0].metadata.namespace.as_deref(), Some("apps"));
+        assert_eq!(objects[0].data, json!({ "data": { "key": "value" } }));
+        assert_eq!(objects[1].data, json!({ "spec": { "replicas": 2 } }));
+
+        let yaml = to_yaml_multidoc(&objects).unwrap();
+        assert_eq!(yaml.matches("\n---\n").count(), 1);
+        let reparsed = from_yaml_multidoc(&yaml)
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(reparsed, objects);
+    }
 
     #[test]
     fn raw_custom_resource() {
@@ -168,4 +505,80 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn parses_field_paths() {
+        assert_eq!(parse_path("spec.containers[0].ports[12]").unwrap(), vec![
+            PathSegment::Key("spec"),
+            PathSegment::Key("containers"),
+            PathSegment::Index(0),
+            PathSegment::Key("ports"),
+            PathSegment::Index(12),
+        ]);
+        for path in [
+            "",
+            "spec..replicas",
+            "spec.",
+            "[0]",
+            "spec.items[",
+            "spec.items[-1]",
+            "spec.items[0]x",
+        ] {
+            assert!(
+                matches!(parse_path(path), Err(FieldPathError::Malformed { .. })),
+                "{path:?} should be malformed"
+            );
+        }
+    }
+
+    #[test]
+    fn get_and_set_paths() {
+        let mut obj = DynamicObject::new("web", &ApiResource::erase::<Pod>(&()));
+        obj.set_path("spec.containers", json!([{ "name": "app" }]))
+            .unwrap();
+        obj.set_path("spec.containers[0].image", json!("nginx")).unwrap();
+        assert_eq!(
+            obj.get_path("spec.containers[0].image").unwrap(),
+            Some(&json!("nginx"))
+        );
+        assert_eq!(obj.get_path("spec.containers[1].image").unwrap(), None);
+        assert_eq!(obj.get_path("spec.containers.image").unwrap(), None);
+
+        assert_eq!(
+            obj.set_path("spec.containers[1].image", json!("nginx")),
+            Err(FieldPathError::IndexOutOfBounds {
+                path: "spec.containers[1].image".into(),
+                at: "spec.containers".into(),
+                len: 1,
+            })
+        );
+        assert_eq!(
+            obj.set_path("spec.containers[0].image.tag", json!("1.25")),
+            Err(FieldPathError::TypeMismatch {
+                path: "spec.containers[0].image.tag".into(),
+                at: "spec.containers[0].image".into(),
+                expected: "object",
+            })
+        );
+        assert_eq!(
+            obj.set_path("spec[0]", json!(1)),
+            Err(FieldPathError::TypeMismatch {
+                path: "spec[0]".into(),
+                at: "spec".into(),
+                expected: "array",
+            })
+        );
+
+        // failing paths do not create the objects leading up to the failure
+        let before = obj.clone();
+        assert_eq!(
+            obj.set_path("spec.volumes[0].name", json!("data")),
+            Err(FieldPathError::TypeMismatch {
+                path: "spec.volumes[0].name".into(),
+                at: "spec.volumes".into(),
+                expected: "array",
+            })
+        );
+        assert_eq!(obj, before);
+    }
 }
@@ -0,0 +1,302 @@
+//! Typed field selectors for the fields that the apiserver supports selecting on
+//!
+//! Field selectors only support a few fields per resource, and the apiserver rejects a
+//! selector for any other field with a `400 Bad Request`. [`FieldSelector`] only exposes the
+//! selectable fields of a resource, so unsupported selectors are caught at compile time:
+//!
+//! ```
+//! use k8s_openapi::api::core::v1::Pod;
+//! use kube_core::{field_selector::FieldSelector, params::ListParams};
+//!
+//! let selector = FieldSelector::<Pod>::new().node_name("n1").phase_running();
+//! assert_eq!(selector.to_string(), "spec.nodeName=n1,status.phase=Running");
+//! let lp = ListParams::default().fields(&selector.to_string());
+//! ```
+//!
+//! `metadata.name` and `metadata.namespace` are selectable for every resource.
+//! Fields of other resources can still be selected with the untyped [`ListParams::fields`](crate::params::ListParams::fields).
+use std::{fmt, marker::PhantomData};
+
+use k8s_openapi::api::{
+    batch::v1::Job,
+    core::v1::{Event, Namespace, Node, Pod, Secret},
+};
+
+/// A field selector for resources of kind `K`, see the [module docs](self)
+///
+/// Requirements are combined with a logical AND, and formatted with [`Display`](fmt::Display)
+/// into the selector string for [`ListParams::fields`](crate::params::ListParams::fields),
+/// [`WatchParams::fields`](crate::params::WatchParams::fields) or the `fields` of a watcher config.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[must_use]
+pub struct FieldSelector<K> {
+    requirements: Vec<String>,
+    _phantom: PhantomData<fn() -> K>,
+}
+
+impl<K> Default for FieldSelector<K> {
+    fn default() -> Self {
+        Self {
+            requirements: Vec::new(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<K> FieldSelector<K> {
+    /// An empty selector, matching every object
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the selector has no requirements
+    pub fn is_empty(&self) -> bool {
+        self.requirements.is_empty()
+    }
+
+    fn equal(mut self, field: &str, value: &str) -> Self {
+        self.requirements.push(format!("{field}={}", escape_value(value)));
+        self
+    }
+
+    fn not_equal(mut self, field: &str, value: &str) -> Self {
+        self.requirements
+            .push(format!("{field}!={}", escape_value(value)));
+        self
+    }
+
+    /// Select objects with the name `name` (`metadata.name`)
+    pub fn name(self, name: &str) -> Self {
+        self.equal("metadata.name", name)
+    }
+
+    /// Select objects without the name `name` (`metadata.name`)
+    pub fn not_name(self, name: &str) -> Self {
+        self.not_equal("metadata.name", name)
+    }
+
+    /// Select objects in the namespace `namespace` (`metadata.namespace`)
+    pub fn namespace(self, namespace: &str) -> Self {
+        self.equal("metadata.namespace", namespace)
+    }
+
+    /// Select objects outside of the namespace `namespace` (`metadata.namespace`)
+    pub fn not_namespace(self, namespace: &str) -> Self {
+        self.not_equal("metadata.namespace", namespace)
+    }
+}
+
+impl<K> fmt::Display for FieldSelector<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.requirements.join(","))
+    }
+}
+
+/// Escape the characters that have a meaning in field selectors, like `fields.EscapeValue` in apimachinery
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | ',' | '=') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// The phase of a [`Pod`], as selectable with [`FieldSelector::phase`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PodPhase {
+    /// The pod was accepted, but not all of its containers are running yet
+    Pending,
+    /// The pod is bound to a node and at least one of its containers is running
+    Running,
+    /// All containers of the pod terminated successfully
+    Succeeded,
+    /// All containers of the pod terminated, and at least one of them failed
+    Failed,
+    /// The state of the pod could not be obtained
+    Unknown,
+}
+
+impl PodPhase {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "Pending",
+            Self::Running => "Running",
+            Self::Succeeded => "Succeeded",
+            Self::Failed => "Failed",
+            Self::Unknown => "Unknown",
+        }
+    }
+}
+
+/// A field selector for [`Pod`]s
+pub type PodFieldSelector = FieldSelector<Pod>;
+
+impl FieldSelector<Pod> {
+    /// Select pods scheduled to the node `node` (`spec.nodeName`)
+    ///
+    /// An empty `node` selects the pods that were not scheduled yet.
+    pub fn node_name(self, node: &str) -> Self {
+        self.equal("spec.nodeName", node)
+    }
+
+    /// Select pods that are not scheduled to the node `node` (`spec.nodeName`)
+    pub fn not_node_name(self, node: &str) -> Self {
+        self.not_equal("spec.nodeName", node)
+    }
+
+    /// Select pods in the phase `phase` (`status.phase`)
+    pub fn phase(self, phase: PodPhase) -> Self {
+        self.equal("status.phase", phase.as_str())
+    }
+
+    /// Select pods that are not in the phase `phase` (`status.phase`)
+    pub fn not_phase(self, phase: PodPhase) -> Self {
+        self.not_equal("status.phase", phase.as_str())
+    }
+
+    /// Select running pods, shorthand for `phase(PodPhase::Running)`
+    pub fn phase_running(self) -> Self {
+        self.phase(PodPhase::Running)
+    }
+
+    /// Select pods that have not terminated yet, excluding the `Succeeded` and `Failed` phases
+    pub fn not_terminated(self) -> Self {
+        self.not_phase(PodPhase::Succeeded).not_phase(PodPhase::Failed)
+    }
+
+    /// Select pods with the restart policy `policy` (`spec.restartPolicy`)
+    pub fn restart_policy(self, policy: &str) -> Self {
+        self.equal("spec.restartPolicy", policy)
+    }
+
+    /// Select pods scheduled by the scheduler `scheduler` (`spec.schedulerName`)
+    pub fn scheduler_name(self, scheduler: &str) -> Self {
+        self.equal("spec.schedulerName", scheduler)
+    }
+
+    /// Select pods running as the service account `service_account` (`spec.serviceAccountName`)
+    pub fn service_account_name(self, service_account: &str) -> Self {
+        self.equal("spec.serviceAccountName", service_account)
+    }
+
+    /// Select pods by whether they use the network namespace of their node (`spec.hostNetwork`)
+    pub fn host_network(self, host_network: bool) -> Self {
+        self.equal("spec.hostNetwork", if host_network { "true" } else { "false" })
+    }
+
+    /// Select pods with the IP address `ip` (`status.podIP`)
+    pub fn pod_ip(self, ip: &str) -> Self {
+        self.equal("status.podIP", ip)
+    }
+
+    /// Select pods nominated to be scheduled to the node `node` after preemption (`status.nominatedNodeName`)
+    pub fn nominated_node_name(self, node: &str) -> Self {
+        self.equal("status.nominatedNodeName", node)
+    }
+}
+
+impl FieldSelector<Node> {
+    /// Select nodes by whether they are cordoned (`spec.unschedulable`)
+    pub fn unschedulable(self, unschedulable: bool) -> Self {
+        self.equal("spec.unschedulable", if unschedulable { "true" } else { "false" })
+    }
+}
+
+impl FieldSelector<Namespace> {
+    /// Select namespaces in the phase `phase` (`status.phase`), either `Active` or `Terminating`
+    pub fn phase(self, phase: &str) -> Self {
+        self.equal("status.phase", phase)
+    }
+}
+
+impl FieldSelector<Secret> {
+    /// Select secrets of the type `type_` (`type`), like `kubernetes.io/tls`
+    pub fn type_(self, type_: &str) -> Self {
+        self.equal("type", type_)
+    }
+}
+
+impl FieldSelector<Job> {
+    /// Select jobs with `succeeded` successfully completed pods (`status.successful`)
+    pub fn successful(self, succeeded: i32) -> Self {
+        self.equal("status.successful", &succeeded.to_string())
+    }
+}
+
+impl FieldSelector<Event> {
+    /// Select events about objects of the kind `kind` (`involvedObject.kind`)
+    pub fn involved_object_kind(self, kind: &str) -> Self {
+        self.equal("involvedObject.kind", kind)
+    }
+
+    /// Select events about objects in the namespace `namespace` (`involvedObject.namespace`)
+    pub fn involved_object_namespace(self, namespace: &str) -> Self {
+        self.equal("involvedObject.namespace", namespace)
+    }
+
+    /// Select events about objects with the name `name` (`involvedObject.name`)
+    pub fn involved_object_name(self, name: &str) -> Self {
+        self.equal("involvedObject.name", name)
+    }
+
+    /// Select events about the object with the uid `uid` (`involvedObject.uid`)
+    pub fn involved_object_uid(self, uid: &str) -> Self {
+        self.equal("involvedObject.uid", uid)
+    }
+
+    /// Select events with the reason `reason` (`reason`)
+    pub fn reason(self, reason: &str) -> Self {
+        self.equal("reason", reason)
+    }
+
+    /// Select events reported by the component `source` (`source`)
+    pub fn source(self, source: &str) -> Self {
+        self.equal("source", source)
+    }
+
+    /// Select events of the type `type_` (`type`), either `Normal` or `Warning`
+    pub fn type_(self, type_: &str) -> Self {
+        self.equal("type", type_)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FieldSelector, PodFieldSelector, PodPhase};
+    use k8s_openapi::api::core::v1::{Event, Node};
+
+    #[test]
+    fn pod_selectors_combine_requirements() {
+        let selector = PodFieldSelector::new().node_name("n1").phase_running();
+        assert_eq!(selector.to_string(), "spec.nodeName=n1,status.phase=Running");
+
+        let selector = PodFieldSelector::new()
+            .namespace("apps")
+            .not_terminated()
+            .not_phase(PodPhase::Unknown);
+        assert_eq!(
+            selector.to_string(),
+            "metadata.namespace=apps,status.phase!=Succeeded,status.phase!=Failed,status.phase!=Unknown"
+        );
+    }
+
+    #[test]
+    fn selectors_escape_values() {
+        let selector = FieldSelector::<Event>::new().involved_object_name(r"a,b=c\d");
+        assert_eq!(selector.to_string(), r"involvedObject.name=a\,b\=c\\d");
+    }
+
+    #[test]
+    fn empty_selector_matches_everything() {
+        let selector = FieldSelector::<Node>::new();
+        assert!(selector.is_empty());
+        assert_eq!(selector.to_string(), "");
+        assert_eq!(
+            selector.unschedulable(false).to_string(),
+            "spec.unschedulable=false"
+        );
+    }
+}
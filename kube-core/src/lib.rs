@@ -14,8 +14,12 @@
 #[cfg(feature = "admission")]
 pub mod admission;
 
+pub mod conditions;
+
 pub mod conversion;
 
+pub mod csaupgrade;
+
 pub mod discovery;
 
 pub mod duration;
@@ -27,11 +31,17 @@ pub use dynamic::{ApiResource, DynamicObject};
 pub mod crd;
 pub use crd::CustomResourceExt;
 
+pub mod field_selector;
+
 pub mod gvk;
 pub use gvk::{GroupVersion, GroupVersionKind, GroupVersionResource};
 
+pub mod managed_fields;
+
 pub mod metadata;
-pub use metadata::{ListMeta, ObjectMeta, PartialObjectMeta, PartialObjectMetaExt, TypeMeta};
+pub use metadata::{
+    ListMeta, ObjectMeta, ObjectMetaBuilder, PartialObjectMeta, PartialObjectMetaExt, TypeMeta,
+};
 
 pub mod object;
 pub use object::{NotUsed, Object, ObjectList};
@@ -54,6 +64,8 @@ pub use response::Status;
 #[cfg(feature = "schema")]
 pub mod schema;
 
+pub mod strategic;
+
 pub mod subresource;
 
 pub mod util;
@@ -0,0 +1,540 @@
+//! Decoding of the field ownership recorded by server-side apply
+//!
+//! The apiserver tracks which field manager owns which fields of an object in `metadata.managedFields`,
+//! with the owned fields of every entry encoded in the `FieldsV1` format. [`managed_fields`] decodes
+//! those entries into [`FieldSet`]s, and [`managed_fields_for`] collects the fields owned by a single manager:
+//!
+//! ```
+//! use k8s_openapi::api::core::v1::ConfigMap;
+//! use kube_core::managed_fields::{managed_fields_for, FieldPath};
+//! # let cm = ConfigMap::default();
+//! let owned = managed_fields_for(&cm, "my-operator", "v1").unwrap();
+//! if owned.contains(&FieldPath::from_fields(&["data", "config.yaml"])) {
+//!     // my-operator still owns the config.yaml key, and can leave it out of its next apply to relinquish it
+//! }
+//! ```
+use std::{cmp::Ordering, collections::BTreeSet, fmt};
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{FieldsV1, ManagedFieldsEntry, Time};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::{Resource, ResourceExt};
+
+/// Failed to decode the `FieldsV1` of a managed fields entry
+#[derive(Debug, Error)]
+pub enum FieldsV1Error {
+    /// A field set was not a JSON object
+    #[error("expected a JSON object for the fields of {0}")]
+    NotAnObject(String),
+    /// A key of a field set did not start with one of the known prefixes
+    #[error("unknown field set key {0:?} in {1}")]
+    UnknownKey(String, String),
+    /// A key of a field set had an invalid value after its prefix
+    #[error("invalid field set key {0:?} in {1}")]
+    InvalidKey(String, String, #[source] Option<serde_json::Error>),
+    /// The entry uses a different `fieldsType` than `FieldsV1`
+    #[error("unsupported fields type {0:?}")]
+    UnsupportedType(String),
+}
+
+/// A single step of a [`FieldPath`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathElement {
+    /// A field of an object, or a key of a map (`f:` in `FieldsV1`)
+    Field(String),
+    /// An item of an associative list, identified by the values of its key fields (`k:` in `FieldsV1`)
+    Key(Map<String, Value>),
+    /// An item of a set, identified by its value (`v:` in `FieldsV1`)
+    Value(Value),
+    /// An item of an atomic list, identified by its index (`i:` in `FieldsV1`)
+    Index(usize),
+}
+
+impl PathElement {
+    fn parse(key: &str, parent: &FieldPath) -> Result<Self, FieldsV1Error> {
+        let invalid = |err| FieldsV1Error::InvalidKey(key.to_string(), parent.to_string(), err);
+        match key.split_at(key.find(':').map_or(0, |idx| idx + 1)) {
+            ("f:", name) => Ok(Self::Field(name.to_string())),
+            ("k:", keys) => match serde_json::from_str(keys).map_err(|err| invalid(Some(err)))? {
+                Value::Object(keys) => Ok(Self::Key(keys)),
+                _ => Err(invalid(None)),
+            },
+            ("v:", value) => Ok(Self::Value(
+                serde_json::from_str(value).map_err(|err| invalid(Some(err)))?,
+            )),
+            ("i:", index) => Ok(Self::Index(index.parse().map_err(|_| invalid(None))?)),
+            _ => Err(FieldsV1Error::UnknownKey(key.to_string(), parent.to_string())),
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Field(_) => 0,
+            Self::Key(_) => 1,
+            Self::Value(_) => 2,
+            Self::Index(_) => 3,
+        }
+    }
+}
+
+impl Ord for PathElement {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Field(a), Self::Field(b)) => a.cmp(b),
+            (Self::Key(a), Self::Key(b)) => cmp_maps(a, b),
+            (Self::Value(a), Self::Value(b)) => cmp_values(a, b),
+            (Self::Index(a), Self::Index(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl PartialOrd for PathElement {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A total order of JSON values that agrees with their equality, so that paths can be kept in a [`BTreeSet`]
+fn cmp_values(a: &Value, b: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Number(_) => 2,
+            Value::String(_) => 3,
+            Value::Array(_) => 4,
+            Value::Object(_) => 5,
+        }
+    }
+    match (a, b) {
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        // numbers are only equal if they have the same representation
+        (Value::Number(a), Value::Number(b)) => a.to_string().cmp(&b.to_string()),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Array(a), Value::Array(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| cmp_values(a, b))
+            .find(|ord| ord.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        (Value::Object(a), Value::Object(b)) => cmp_maps(a, b),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// Compare maps by their sorted entries, regardless of the order they were inserted in
+fn cmp_maps(a: &Map<String, Value>, b: &Map<String, Value>) -> Ordering {
+    fn sorted(map: &Map<String, Value>) -> Vec<(&String, &Value)> {
+        let mut entries = map.iter().collect::<Vec<_>>();
+        entries.sort_by(|x, y| x.0.cmp(y.0));
+        entries
+    }
+    let (a, b) = (sorted(a), sorted(b));
+    a.iter()
+        .zip(&b)
+        .map(|((ka, va), (kb, vb))| ka.cmp(kb).then_with(|| cmp_values(va, vb)))
+        .find(|ord| ord.is_ne())
+        .unwrap_or_else(|| a.len().cmp(&b.len()))
+}
+
+impl fmt::Display for PathElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Field(name) => write!(f, ".{name}"),
+            Self::Key(keys) => {
+                f.write_str("[")?;
+                for (i, (key, value)) in keys.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{key}={value}")?;
+                }
+                f.write_str("]")
+            }
+            Self::Value(value) => write!(f, "[={value}]"),
+            Self::Index(index) => write!(f, "[{index}]"),
+        }
+    }
+}
+
+/// The path of a field within an object, like `.spec.containers[name="app"].image`
+///
+/// Paths are ordered like the fields of the object, with the children of a field right after the field itself.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FieldPath(pub Vec<PathElement>);
+
+impl FieldPath {
+    /// A path made up of only object fields and map keys, like `.metadata.labels.app`
+    pub fn from_fields(fields: &[&str]) -> Self {
+        Self(fields.iter().map(|f| PathElement::Field(f.to_string())).collect())
+    }
+
+    /// Whether `self` is `other` or one of its children
+    pub fn starts_with(&self, other: &FieldPath) -> bool {
+        self.0.starts_with(&other.0)
+    }
+
+    fn child(&self, element: PathElement) -> Self {
+        let mut path = self.clone();
+        path.0.push(element);
+        path
+    }
+}
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str(".");
+        }
+        self.0.iter().try_for_each(|element| element.fmt(f))
+    }
+}
+
+/// A set of fields owned by a field manager, as decoded from `FieldsV1`
+///
+/// A path in the set means that the field itself is owned, which does not imply owning its children.
+/// For instance, owning `.metadata.labels` (the map itself) is separate from owning `.metadata.labels.app`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FieldSet {
+    paths: BTreeSet<FieldPath>,
+}
+
+impl FieldSet {
+    /// Decode a `FieldsV1` field set
+    pub fn from_fields_v1(fields: &FieldsV1) -> Result<Self, FieldsV1Error> {
+        let mut set = Self::default();
+        match &fields.0 {
+            // an entry without any owned fields
+            Value::Null => {}
+            value => decode(value, &FieldPath::default(), &mut set)?,
+        }
+        Ok(set)
+    }
+
+    /// Whether the field at `path` is owned
+    pub fn contains(&self, path: &FieldPath) -> bool {
+        self.paths.contains(path)
+    }
+
+    /// Whether the field at `path`, or any of its children, is owned
+    pub fn contains_prefix(&self, path: &FieldPath) -> bool {
+        // the children of a path are ordered right after it
+        self.paths
+            .range(path..)
+            .next()
+            .map_or(false, |owned| owned.starts_with(path))
+    }
+
+    /// The owned fields, in order
+    pub fn iter(&self) -> impl Iterator<Item = &FieldPath> {
+        self.paths.iter()
+    }
+
+    /// The number of owned fields
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Whether no fields are owned
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Add all fields of `other` to the set
+    pub fn union(&mut self, other: FieldSet) {
+        self.paths.extend(other.paths);
+    }
+
+    fn insert(&mut self, path: FieldPath) {
+        self.paths.insert(path);
+    }
+}
+
+impl<'a> IntoIterator for &'a FieldSet {
+    type IntoIter = std::collections::btree_set::Iter<'a, FieldPath>;
+    type Item = &'a FieldPath;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.paths.iter()
+    }
+}
+
+/// Add the fields of the `FieldsV1` node at `path` to `set`
+fn decode(node: &Value, path: &FieldPath, set: &mut FieldSet) -> Result<(), FieldsV1Error> {
+    let children = node
+        .as_object()
+        .ok_or_else(|| FieldsV1Error::NotAnObject(path.to_string()))?;
+    for (key, child) in children {
+        // `.` marks that the field with children is owned itself
+        if key == "." {
+            set.insert(path.clone());
+            continue;
+        }
+        let child_path = path.child(PathElement::parse(key, path)?);
+        match child.as_object() {
+            Some(grandchildren) if grandchildren.is_empty() => set.insert(child_path),
+            _ => decode(child, &child_path, set)?,
+        }
+    }
+    Ok(())
+}
+
+/// A decoded entry of `metadata.managedFields`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ManagedFields {
+    /// The name of the field manager
+    pub manager: String,
+    /// The operation that set the fields, either `Apply` or `Update`
+    pub operation: String,
+    /// The API version that the fields are expressed in
+    pub api_version: Option<String>,
+    /// The subresource that the fields were set through, or `None` for the main resource
+    pub subresource: Option<String>,
+    /// When the fields were last changed
+    pub time: Option<Time>,
+    /// The fields owned through this entry
+    pub fields: FieldSet,
+}
+
+impl ManagedFields {
+    /// Decode a managed fields entry
+    pub fn from_entry(entry: &ManagedFieldsEntry) -> Result<Self, FieldsV1Error> {
+        if let Some(fields_type) = entry.fields_type.as_deref().filter(|t| *t != "FieldsV1") {
+            return Err(FieldsV1Error::UnsupportedType(fields_type.to_string()));
+        }
+        let fields = match &entry.fields_v1 {
+            Some(fields) => FieldSet::from_fields_v1(fields)?,
+            None => FieldSet::default(),
+        };
+        Ok(Self {
+            manager: entry.manager.clone().unwrap_or_default(),
+            operation: entry.operation.clone().unwrap_or_default(),
+            api_version: entry.api_version.clone(),
+            subresource: entry.subresource.clone().filter(|s| !s.is_empty()),
+            time: entry.time.clone(),
+            fields,
+        })
+    }
+}
+
+/// Decode all `metadata.managedFields` entries of an object
+pub fn managed_fields<K: Resource>(obj: &K) -> Result<Vec<ManagedFields>, FieldsV1Error> {
+    obj.managed_fields()
+        .iter()
+        .map(ManagedFields::from_entry)
+        .collect()
+}
+
+/// The fields of the main resource owned by `manager` in `api_version`, across its `Apply` and `Update` entries
+///
+/// The fields of an entry are expressed in the schema of its `apiVersion` (like `apps/v1`), which can differ
+/// between versions, so only the entries for `api_version` are included.
+/// Fields owned through subresources (such as `status`) are not included either,
+/// use [`managed_fields`] to tell the entries apart.
+pub fn managed_fields_for<K: Resource>(
+    obj: &K,
+    manager: &str,
+    api_version: &str,
+) -> Result<FieldSet, FieldsV1Error> {
+    let mut owned = FieldSet::default();
+    for entry in obj.managed_fields() {
+        if entry.manager.as_deref() != Some(manager)
+            || entry.api_version.as_deref() != Some(api_version)
+            || entry.subresource.as_deref().map_or(false, |s| !s.is_empty())
+        {
+            continue;
+        }
+        owned.union(ManagedFields::from_entry(entry)?.fields);
+    }
+    Ok(owned)
+}
+
+/// A field whose ownership was taken over from another field manager, see [`transferred_fields`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldTransfer {
+    /// The field that changed owners
+    pub field: FieldPath,
+    /// The field manager that owned the field before
+    pub from: String,
+}
+
+/// The fields of the main resource that `manager` took over from other field managers between `before` and `after`
+///
+/// A field is transferred if another manager owned it in `before`, no longer owns it in `after`,
+/// and `manager` owns it in `after`. This is what happens to conflicting fields in a forced apply,
+/// so comparing the object before and after a forced apply reveals which fields were taken over.
+///
+/// The fields are compared in the `apiVersion` of the `Apply` entry of `manager` in `after`,
+/// which is the version that the apply was made in. If `manager` has no such entry, nothing was taken over.
+pub fn transferred_fields<K: Resource>(
+    before: &K,
+    after: &K,
+    manager: &str,
+) -> Result<Vec<FieldTransfer>, FieldsV1Error> {
+    let api_version = after.managed_fields().iter().find_map(|entry| {
+        let applied = entry.manager.as_deref() == Some(manager)
+            && entry.operation.as_deref() == Some("Apply")
+            && entry.subresource.as_deref().map_or(true, str::is_empty);
+        entry.api_version.as_deref().filter(|_| applied)
+    });
+    let api_version = match api_version {
+        Some(api_version) => api_version,
+        None => return Ok(Vec::new()),
+    };
+    let owned = managed_fields_for(after, manager, api_version)?;
+    let mut transfers = Vec::new();
+    let mut previous_owners = Vec::new();
+    for entry in managed_fields(before)? {
+        if entry.manager != manager
+            && entry.subresource.is_none()
+            && !previous_owners.contains(&entry.manager)
+        {
+            previous_owners.push(entry.manager);
+        }
+    }
+    for previous_owner in previous_owners {
+        let owned_before = managed_fields_for(before, &previous_owner, api_version)?;
+        let owned_after = managed_fields_for(after, &previous_owner, api_version)?;
+        for field in &owned_before {
+            if owned.contains(field) && !owned_after.contains(field) {
+                transfers.push(FieldTransfer {
+                    field: field.clone(),
+                    from: previous_owner.clone(),
+                });
+            }
+        }
+    }
+    Ok(transfers)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use k8s_openapi::api::core::v1::Pod;
+    use serde_json::json;
+
+    fn pod() -> Pod {
+        serde_json::from_value(json!({
+            "metadata": {
+                "name": "app",
+                "managedFields": [
+                    {
+                        "manager": "my-operator",
+                        "operation": "Apply",
+                        "apiVersion": "v1",
+                        "fieldsType": "FieldsV1",
+                        "fieldsV1": {
+                            "f:metadata": { "f:labels": { ".": {}, "f:app": {} } },
+                            "f:spec": {
+                                "f:containers": {
+                                    "k:{\"name\":\"app\"}": { ".": {}, "f:image": {}, "f:name": {} }
+                                },
+                                "f:tolerations": {}
+                            }
+                        }
+                    },
+                    {
+                        "manager": "my-operator",
+                        "operation": "Update",
+                        "apiVersion": "v1",
+                        "subresource": "status",
+                        "fieldsType": "FieldsV1",
+                        "fieldsV1": { "f:status": { "f:conditions": {} } }
+                    },
+                    {
+                        "manager": "kubectl-edit",
+                        "operation": "Update",
+                        "apiVersion": "v1",
+                        "fieldsType": "FieldsV1",
+                        "fieldsV1": { "f:metadata": { "f:finalizers": { "v:\"example.com/cleanup\"": {} } } }
+                    }
+                ]
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn decodes_fields_owned_by_manager() {
+        let owned = managed_fields_for(&pod(), "my-operator", "v1").unwrap();
+        let paths = owned.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(paths, [
+            ".metadata.labels",
+            ".metadata.labels.app",
+            ".spec.containers[name=\"app\"]",
+            ".spec.containers[name=\"app\"].image",
+            ".spec.containers[name=\"app\"].name",
+            ".spec.tolerations",
+        ]);
+        assert!(owned.contains(&FieldPath::from_fields(&["metadata", "labels", "app"])));
+        assert!(!owned.contains(&FieldPath::from_fields(&["spec"])));
+        assert!(owned.contains_prefix(&FieldPath::from_fields(&["spec", "containers"])));
+        assert!(!owned.contains_prefix(&FieldPath::from_fields(&["status"])));
+    }
+
+    #[test]
+    fn only_collects_fields_of_the_api_version() {
+        let mut pod = pod();
+        pod.metadata.managed_fields.as_mut().unwrap()[2].api_version = Some("v1beta1".into());
+        assert!(managed_fields_for(&pod, "kubectl-edit", "v1").unwrap().is_empty());
+        assert_eq!(
+            managed_fields_for(&pod, "kubectl-edit", "v1beta1").unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn decodes_entries_by_manager_and_operation() {
+        let entries = managed_fields(&pod()).unwrap();
+        let owners = entries
+            .iter()
+            .map(|e| (e.manager.as_str(), e.operation.as_str(), e.subresource.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(owners, [
+            ("my-operator", "Apply", None),
+            ("my-operator", "Update", Some("status")),
+            ("kubectl-edit", "Update", None),
+        ]);
+        let finalizer = entries[2].fields.iter().next().unwrap();
+        assert_eq!(
+            finalizer.0[2],
+            PathElement::Value(Value::String("example.com/cleanup".into()))
+        );
+        assert_eq!(
+            finalizer.to_string(),
+            ".metadata.finalizers[=\"example.com/cleanup\"]"
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_field_sets() {
+        let err = FieldSet::from_fields_v1(&FieldsV1(json!({ "f:spec": { "x:foo": {} } }))).unwrap_err();
+        assert!(matches!(err, FieldsV1Error::UnknownKey(key, path) if key == "x:foo" && path == ".spec"));
+        let err = FieldSet::from_fields_v1(&FieldsV1(json!({ "k:[1]": {} }))).unwrap_err();
+        assert!(matches!(err, FieldsV1Error::InvalidKey(..)));
+    }
+
+    #[test]
+    fn finds_fields_taken_over_from_other_managers() {
+        let mut after = pod();
+        let entries = after.metadata.managed_fields.as_mut().unwrap();
+        // my-operator took over the finalizer, and kubectl-edit is left without fields
+        entries[0].fields_v1 = Some(FieldsV1(json!({
+            "f:metadata": { "f:finalizers": { "v:\"example.com/cleanup\"": {} } }
+        })));
+        entries.remove(2);
+
+        let transfers = transferred_fields(&pod(), &after, "my-operator").unwrap();
+        assert_eq!(transfers, [FieldTransfer {
+            field: FieldPath::from_fields(&["metadata", "finalizers"])
+                .child(PathElement::Value(Value::String("example.com/cleanup".into()))),
+            from: "kubectl-edit".into(),
+        }]);
+        // fields that changed owners the other way around are not taken over by my-operator
+        assert!(transferred_fields(&after, &pod(), "my-operator")
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,6 +1,7 @@
 //! Metadata structs used in traits, lists, and dynamic objects.
-use std::{borrow::Cow, marker::PhantomData};
+use std::{borrow::Cow, collections::BTreeMap, marker::PhantomData};
 
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::OwnerReference;
 pub use k8s_openapi::apimachinery::pkg::apis::meta::v1::{ListMeta, ObjectMeta};
 use serde::{Deserialize, Serialize};
 
@@ -110,6 +111,129 @@ impl PartialObjectMetaExt for ObjectMeta {
     }
 }
 
+/// Builder for [`ObjectMeta`]
+///
+/// Avoids the `Some(..)` and `..Default::default()` boilerplate of constructing `ObjectMeta` by hand:
+///
+/// ```
+/// use k8s_openapi::api::core::v1::ConfigMap;
+/// use kube::core::ObjectMetaBuilder;
+/// let cm = ConfigMap {
+///     metadata: ObjectMetaBuilder::new("my-config")
+///         .namespace("apps")
+///         .labels([("app", "web"), ("tier", "frontend")])
+///         .annotation("example.com/owner", "team-a")
+///         .build(),
+///     ..ConfigMap::default()
+/// };
+/// assert_eq!(cm.metadata.labels.unwrap().len(), 2);
+/// ```
+///
+/// For custom resources, the `new(name, spec)` constructor generated by `#[derive(CustomResource)]`
+/// can be combined with [`ResourceExt`](crate::ResourceExt) accessors like `labels_mut`.
+#[derive(Clone, Debug, Default)]
+#[must_use]
+pub struct ObjectMetaBuilder {
+    meta: ObjectMeta,
+}
+
+impl ObjectMetaBuilder {
+    /// Start building an `ObjectMeta` with the given name
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            meta: ObjectMeta {
+                name: Some(name.into()),
+                ..ObjectMeta::default()
+            },
+        }
+    }
+
+    /// Start building an `ObjectMeta` that lets the apiserver generate a name with the given prefix
+    pub fn generate_name(prefix: impl Into<String>) -> Self {
+        Self {
+            meta: ObjectMeta {
+                generate_name: Some(prefix.into()),
+                ..ObjectMeta::default()
+            },
+        }
+    }
+
+    /// Set the namespace
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.meta.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Add a label
+    pub fn label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        insert(&mut self.meta.labels, key.into(), value.into());
+        self
+    }
+
+    /// Add several labels
+    pub fn labels<K: Into<String>, V: Into<String>>(
+        mut self,
+        labels: impl IntoIterator<Item = (K, V)>,
+    ) -> Self {
+        for (key, value) in labels {
+            insert(&mut self.meta.labels, key.into(), value.into());
+        }
+        self
+    }
+
+    /// Add an annotation
+    pub fn annotation(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        insert(&mut self.meta.annotations, key.into(), value.into());
+        self
+    }
+
+    /// Add several annotations
+    pub fn annotations<K: Into<String>, V: Into<String>>(
+        mut self,
+        annotations: impl IntoIterator<Item = (K, V)>,
+    ) -> Self {
+        for (key, value) in annotations {
+            insert(&mut self.meta.annotations, key.into(), value.into());
+        }
+        self
+    }
+
+    /// Add an owner reference
+    ///
+    /// See [`Resource::controller_owner_ref`] for how to create one.
+    pub fn owner_reference(mut self, owner: OwnerReference) -> Self {
+        self.meta
+            .owner_references
+            .get_or_insert_with(Vec::new)
+            .push(owner);
+        self
+    }
+
+    /// Add a finalizer
+    pub fn finalizer(mut self, finalizer: impl Into<String>) -> Self {
+        self.meta
+            .finalizers
+            .get_or_insert_with(Vec::new)
+            .push(finalizer.into());
+        self
+    }
+
+    /// Finish building the `ObjectMeta`
+    pub fn build(self) -> ObjectMeta {
+        self.meta
+    }
+}
+
+impl From<ObjectMetaBuilder> for ObjectMeta {
+    fn from(builder: ObjectMetaBuilder) -> Self {
+        builder.build()
+    }
+}
+
+fn insert(map: &mut Option<BTreeMap<String, String>>, key: String, value: String) {
+    map.get_or_insert_with(BTreeMap::new).insert(key, value);
+}
+
 impl<K: Resource> Resource for PartialObjectMeta<K> {
     type DynamicType = K::DynamicType;
     type Scope = K::Scope;
@@ -141,7 +265,7 @@ impl<K: Resource> Resource for PartialObjectMeta<K> {
 
 #[cfg(test)]
 mod test {
-    use super::{ObjectMeta, PartialObjectMeta, PartialObjectMetaExt};
+    use super::{ObjectMeta, ObjectMetaBuilder, PartialObjectMeta, PartialObjectMetaExt};
     use crate::Resource;
     use k8s_openapi::api::core::v1::Pod;
 
@@ -171,4 +295,27 @@ mod test {
         assert_eq!(response_pom.types.as_ref().unwrap().api_version, "meta.k8s.io/v1");
         assert_eq!(response_pom.types.as_ref().unwrap().kind, "PartialObjectMetadata");
     }
+
+    #[test]
+    fn object_meta_builder() {
+        let meta = ObjectMetaBuilder::new("web")
+            .namespace("apps")
+            .label("app", "web")
+            .labels([("tier", "frontend"), ("app", "override")])
+            .annotation("note", "hi")
+            .finalizer("example.com/cleanup")
+            .build();
+        assert_eq!(meta.name.as_deref(), Some("web"));
+        assert_eq!(meta.namespace.as_deref(), Some("apps"));
+        let labels = meta.labels.unwrap();
+        assert_eq!(labels.len(), 2);
+        assert_eq!(labels["app"], "override");
+        assert_eq!(meta.annotations.unwrap()["note"], "hi");
+        assert_eq!(meta.finalizers.unwrap(), vec!["example.com/cleanup"]);
+        assert_eq!(meta.owner_references, None);
+
+        let generated: ObjectMeta = ObjectMetaBuilder::generate_name("job-").into();
+        assert_eq!(generated.name, None);
+        assert_eq!(generated.generate_name.as_deref(), Some("job-"));
+    }
 }
@@ -149,7 +149,8 @@ impl ListParams {
     ///
     /// Defaults to everything.
     /// Supports `=`, `==`, `!=`, and can be comma separated: `key1=value1,key2=value2`.
-    /// The server only supports a limited number of field queries per type.
+    /// The server only supports a limited number of field queries per type,
+    /// see [`FieldSelector`](crate::field_selector::FieldSelector) for a typed selector that only allows the supported fields.
     #[must_use]
     pub fn fields(mut self, field_selector: &str) -> Self {
         self.field_selector = Some(field_selector.to_string());
@@ -412,7 +413,8 @@ impl WatchParams {
     ///
     /// Defaults to everything.
     /// Supports `=`, `==`, `!=`, and can be comma separated: `key1=value1,key2=value2`.
-    /// The server only supports a limited number of field queries per type.
+    /// The server only supports a limited number of field queries per type,
+    /// see [`FieldSelector`](crate::field_selector::FieldSelector) for a typed selector that only allows the supported fields.
     #[must_use]
     pub fn fields(mut self, field_selector: &str) -> Self {
         self.field_selector = Some(field_selector.to_string());
@@ -483,6 +485,9 @@ impl WatchParams {
 }
 
 /// Common query parameters for put/post calls
+///
+/// Objects created with a `metadata.generateName` instead of a name are named by the apiserver,
+/// the assigned name is part of the returned object.
 #[derive(Default, Clone, Debug, PartialEq)]
 pub struct PostParams {
     /// Whether to run this as a dry run
@@ -558,6 +563,24 @@ pub enum Patch<T: Serialize> {
     /// [Server side apply](https://kubernetes.io/docs/reference/using-api/api-concepts/#server-side-apply)
     ///
     /// Requires kubernetes >= 1.16
+    ///
+    /// The applied object does not have to be a complete `K`: an apply patch is the set of fields that the
+    /// field manager wants to own, and the apiserver merges it into the existing object (or creates the object
+    /// from it). Only the `apiVersion`, `kind` and `metadata.name` are required, along with the owned fields.
+    /// Such partial objects are usually built with `serde_json::json!`, since the typed structs require fields
+    /// that the field manager may not want to own. The patch is sent as is, without client-side validation.
+    ///
+    /// ```
+    /// use kube::api::{Patch, PatchParams};
+    /// // only take ownership of the replica count of a deployment
+    /// let patch = Patch::Apply(serde_json::json!({
+    ///     "apiVersion": "apps/v1",
+    ///     "kind": "Deployment",
+    ///     "metadata": { "name": "web" },
+    ///     "spec": { "replicas": 3 }
+    /// }));
+    /// let params = PatchParams::apply("autoscaler");
+    /// ```
     Apply(T),
 
     /// [JSON patch](https://kubernetes.io/docs/tasks/run-application/update-api-object-kubectl-patch/#use-a-json-merge-patch-to-update-a-deployment)
@@ -578,6 +601,9 @@ pub enum Patch<T: Serialize> {
     /// [JSON Merge patch](https://kubernetes.io/docs/tasks/run-application/update-api-object-kubectl-patch/#use-a-json-merge-patch-to-update-a-deployment)
     Merge(T),
     /// [Strategic JSON Merge patch](https://kubernetes.io/docs/tasks/run-application/update-api-object-kubectl-patch/#use-a-strategic-merge-patch-to-update-a-deployment)
+    ///
+    /// Only supported for built-in types, not custom resources.
+    /// See [`strategic`](crate::strategic) for builders of common patches.
     Strategic(T),
 }
 
@@ -660,6 +686,10 @@ impl PatchParams {
     }
 
     /// Construct `PatchParams` for server-side apply
+    ///
+    /// The `manager` owns the fields it applies. Applies to a subresource (such as with `Api::patch_status`)
+    /// are tracked in a separate `managedFields` entry for the subresource, so the same or different managers
+    /// can apply the main resource and its status without taking over each other's fields.
     #[must_use]
     pub fn apply(manager: &str) -> Self {
         Self {
@@ -627,6 +627,31 @@ mod test {
         assert_eq!(req.method(), "PATCH");
     }
     #[test]
+    fn patch_apply_partial_object() {
+        let url = appsv1::Deployment::url_path(&(), Some("ns"));
+        let pp = PatchParams::apply("autoscaler").force();
+        // no required fields of a Deployment besides the ones being applied
+        let partial = serde_json::json!({
+            "apiVersion": "apps/v1",
+            "kind": "Deployment",
+            "metadata": { "name": "web" },
+            "spec": { "replicas": 3 }
+        });
+        let req = Request::new(url)
+            .patch("web", &pp, &Patch::Apply(&partial))
+            .unwrap();
+        assert_eq!(
+            req.uri(),
+            "/apis/apps/v1/namespaces/ns/deployments/web?&force=true&fieldManager=autoscaler"
+        );
+        assert_eq!(
+            req.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/apply-patch+yaml"
+        );
+        let body: serde_json::Value = serde_json::from_slice(req.body()).unwrap();
+        assert_eq!(body, partial);
+    }
+    #[test]
     fn replace_status_path() {
         let url = corev1::Node::url_path(&(), None);
         let pp = PostParams::default();
@@ -657,6 +682,25 @@ mod test {
         assert_eq!(req.method(), "PATCH");
     }
 
+    #[test]
+    fn create_with_generate_name_posts_to_collection() {
+        let url = corev1::ConfigMap::url_path(&(), Some("ns"));
+        let cm = corev1::ConfigMap {
+            metadata: crate::ObjectMeta {
+                generate_name: Some("cm-".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let req = Request::new(url)
+            .create(&PostParams::default(), serde_json::to_vec(&cm).unwrap())
+            .unwrap();
+        assert_eq!(req.uri(), "/api/v1/namespaces/ns/configmaps?");
+        assert_eq!(req.method(), "POST");
+        let body: serde_json::Value = serde_json::from_slice(req.body()).unwrap();
+        assert_eq!(body["metadata"], serde_json::json!({ "generateName": "cm-" }));
+    }
+
     #[test]
     fn replace_status() {
         let url = apiextsv1::CustomResourceDefinition::url_path(&(), None);
@@ -6,11 +6,14 @@
 #[allow(unused_imports)] use schemars::gen::SchemaSettings;
 
 use schemars::{
+    gen::SchemaGenerator,
     schema::{InstanceType, Metadata, ObjectValidation, Schema, SchemaObject, SingleOrVec},
     visit::Visitor,
     MapEntry,
 };
 
+const PRESERVE_UNKNOWN_FIELDS: &str = "x-kubernetes-preserve-unknown-fields";
+
 /// schemars [`Visitor`] that rewrites a [`Schema`] to conform to Kubernetes' "structural schema" rules
 ///
 /// The following two transformations are applied
@@ -60,12 +63,56 @@ impl Visitor for StructuralSchemaRewriter {
                 object.additional_properties = None;
                 schema
                     .extensions
-                    .insert("x-kubernetes-preserve-unknown-fields".into(), true.into());
+                    .insert(PRESERVE_UNKNOWN_FIELDS.into(), true.into());
             }
         }
     }
 }
 
+/// Schema for a freeform object whose unknown fields are preserved by the apiserver
+///
+/// The apiserver prunes every field that is not specified in a structural schema. Use this
+/// for fields holding an open-ended object (e.g. a `serde_json::Map` or an embedded raw
+/// manifest) to mark the subtree with `x-kubernetes-preserve-unknown-fields: true`:
+///
+/// ```
+/// # use schemars::JsonSchema;
+/// # use serde::{Deserialize, Serialize};
+/// #[derive(Serialize, Deserialize, JsonSchema)]
+/// struct FooSpec {
+///     #[schemars(schema_with = "kube_core::schema::preserve_unknown_fields")]
+///     template: serde_json::Map<String, serde_json::Value>,
+/// }
+/// ```
+///
+/// Nothing inside the subtree is validated or pruned by the apiserver, and server-side apply
+/// treats the whole subtree as a single atomic value (no per-field ownership or merging).
+pub fn preserve_unknown_fields(_: &mut SchemaGenerator) -> Schema {
+    let mut schema = SchemaObject {
+        instance_type: Some(InstanceType::Object.into()),
+        ..SchemaObject::default()
+    };
+    schema
+        .extensions
+        .insert(PRESERVE_UNKNOWN_FIELDS.into(), true.into());
+    Schema::Object(schema)
+}
+
+/// Schema for a field holding arbitrary JSON that must not be pruned by the apiserver
+///
+/// Unlike [`preserve_unknown_fields`] this does not constrain the type, so the field can hold
+/// any JSON value (object, array, string, number or bool), as e.g. a `serde_json::Value` does.
+/// Use with `#[schemars(schema_with = "kube_core::schema::preserve_arbitrary")]`.
+///
+/// The same tradeoffs as for [`preserve_unknown_fields`] apply.
+pub fn preserve_arbitrary(_: &mut SchemaGenerator) -> Schema {
+    let mut schema = SchemaObject::default();
+    schema
+        .extensions
+        .insert(PRESERVE_UNKNOWN_FIELDS.into(), true.into());
+    Schema::Object(schema)
+}
+
 /// Bring all plain enum values up to the root schema,
 /// since Kubernetes doesn't allow subschemas to define enum options.
 ///
@@ -0,0 +1,207 @@
+//! Typed builders for common strategic merge patches
+//!
+//! Strategic merge patches merge lists of objects by a merge key (like the `name` of a container)
+//! instead of replacing them, and support directives like `$patch: delete` and `$patch: replace`.
+//! The [`PodSpecPatch`] builder produces correct patch bodies for common container, env and volume
+//! mutations, to be sent with [`Patch::Strategic`](crate::params::Patch::Strategic).
+//!
+//! Strategic merge patches are only supported for built-in types.
+//! The apiserver rejects them for custom resources, use [`Patch::Merge`](crate::params::Patch::Merge)
+//! or [`Patch::Apply`](crate::params::Patch::Apply) for those instead.
+use k8s_openapi::api::core::v1::{EnvVar, Volume};
+use serde_json::{json, Map, Value};
+
+/// Builder for strategic merge patches of a pod spec
+///
+/// ```
+/// use k8s_openapi::api::core::v1::EnvVar;
+/// use kube::core::{params::Patch, strategic::PodSpecPatch};
+/// let patch = PodSpecPatch::pod_template()
+///     .container_image("app", "nginx:1.25")
+///     .container_env("app", EnvVar {
+///         name: "MODE".into(),
+///         value: Some("fast".into()),
+///         ..EnvVar::default()
+///     })
+///     .remove_container_env("app", "DEBUG")
+///     .build();
+/// // pass to Api::patch for a Deployment
+/// let patch = Patch::Strategic(patch);
+/// ```
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct PodSpecPatch {
+    template: bool,
+    containers: Vec<Map<String, Value>>,
+    volumes: Vec<Value>,
+}
+
+impl PodSpecPatch {
+    /// Patch the spec of a `Pod`
+    pub fn pod() -> Self {
+        Self {
+            template: false,
+            containers: Vec::new(),
+            volumes: Vec::new(),
+        }
+    }
+
+    /// Patch the pod template of a workload, such as a `Deployment`, `StatefulSet`, `DaemonSet` or `Job`
+    pub fn pod_template() -> Self {
+        Self {
+            template: true,
+            ..Self::pod()
+        }
+    }
+
+    /// Set the image of the container named `container`
+    pub fn container_image(mut self, container: &str, image: impl Into<String>) -> Self {
+        self.container(container)
+            .insert("image".into(), Value::String(image.into()));
+        self
+    }
+
+    /// Add or update an environment variable of the container named `container`
+    pub fn container_env(mut self, container: &str, env: EnvVar) -> Self {
+        let env = serde_json::to_value(env).expect("EnvVar is serializable");
+        self.container_env_list(container).push(env);
+        self
+    }
+
+    /// Remove the environment variable `name` from the container named `container`
+    pub fn remove_container_env(mut self, container: &str, name: &str) -> Self {
+        self.container_env_list(container).push(delete(name));
+        self
+    }
+
+    /// Replace all environment variables of the container named `container`
+    ///
+    /// Variables that are not in `env` are removed, rather than merged with the existing ones.
+    pub fn replace_container_env(mut self, container: &str, env: impl IntoIterator<Item = EnvVar>) -> Self {
+        let list = self.container_env_list(container);
+        list.clear();
+        list.extend(
+            env.into_iter()
+                .map(|env| serde_json::to_value(env).expect("EnvVar is serializable")),
+        );
+        list.push(json!({ "$patch": "replace" }));
+        self
+    }
+
+    /// Add or update a volume of the pod
+    pub fn volume(mut self, volume: Volume) -> Self {
+        let volume = serde_json::to_value(volume).expect("Volume is serializable");
+        self.volumes.push(volume);
+        self
+    }
+
+    /// Remove the volume `name` from the pod
+    pub fn remove_volume(mut self, name: &str) -> Self {
+        self.volumes.push(delete(name));
+        self
+    }
+
+    /// Build the patch body
+    pub fn build(self) -> Value {
+        let mut spec = Map::new();
+        if !self.containers.is_empty() {
+            let containers = self.containers.into_iter().map(Value::Object).collect();
+            spec.insert("containers".into(), Value::Array(containers));
+        }
+        if !self.volumes.is_empty() {
+            spec.insert("volumes".into(), Value::Array(self.volumes));
+        }
+        if self.template {
+            json!({ "spec": { "template": { "spec": spec } } })
+        } else {
+            json!({ "spec": spec })
+        }
+    }
+
+    fn container(&mut self, name: &str) -> &mut Map<String, Value> {
+        let idx = match self.containers.iter().position(|c| c["name"] == name) {
+            Some(idx) => idx,
+            None => {
+                let mut container = Map::new();
+                container.insert("name".into(), Value::String(name.into()));
+                self.containers.push(container);
+                self.containers.len() - 1
+            }
+        };
+        &mut self.containers[idx]
+    }
+
+    fn container_env_list(&mut self, container: &str) -> &mut Vec<Value> {
+        match self
+            .container(container)
+            .entry("env")
+            .or_insert_with(|| Value::Array(Vec::new()))
+        {
+            Value::Array(list) => list,
+            _ => unreachable!("env is always a list"),
+        }
+    }
+}
+
+fn delete(name: &str) -> Value {
+    json!({ "name": name, "$patch": "delete" })
+}
+
+#[cfg(test)]
+mod test {
+    use super::PodSpecPatch;
+    use k8s_openapi::api::core::v1::{EnvVar, Volume};
+    use serde_json::json;
+
+    fn env(name: &str, value: &str) -> EnvVar {
+        EnvVar {
+            name: name.into(),
+            value: Some(value.into()),
+            ..EnvVar::default()
+        }
+    }
+
+    #[test]
+    fn pod_template_patch() {
+        let patch = PodSpecPatch::pod_template()
+            .container_image("app", "nginx:1.25")
+            .container_env("app", env("MODE", "fast"))
+            .remove_container_env("app", "DEBUG")
+            .replace_container_env("sidecar", [env("ONLY", "this")])
+            .volume(Volume {
+                name: "cache".into(),
+                ..Volume::default()
+            })
+            .remove_volume("old")
+            .build();
+        assert_eq!(
+            patch,
+            json!({ "spec": { "template": { "spec": {
+                "containers": [
+                    {
+                        "name": "app",
+                        "image": "nginx:1.25",
+                        "env": [
+                            { "name": "MODE", "value": "fast" },
+                            { "name": "DEBUG", "$patch": "delete" },
+                        ]
+                    },
+                    {
+                        "name": "sidecar",
+                        "env": [{ "name": "ONLY", "value": "this" }, { "$patch": "replace" }]
+                    }
+                ],
+                "volumes": [{ "name": "cache" }, { "name": "old", "$patch": "delete" }]
+            }}}})
+        );
+    }
+
+    #[test]
+    fn pod_patch() {
+        let patch = PodSpecPatch::pod().container_image("app", "nginx:1.25").build();
+        assert_eq!(
+            patch,
+            json!({ "spec": { "containers": [{ "name": "app", "image": "nginx:1.25" }] } })
+        );
+    }
+}
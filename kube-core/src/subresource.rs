@@ -124,7 +124,7 @@ impl Request {
 /// - `stderr` and `tty` cannot both be `true` because multiplexing is not supported with TTY.
 #[cfg(feature = "ws")]
 #[cfg_attr(docsrs, doc(cfg(feature = "ws")))]
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct AttachParams {
     /// The name of the container to attach.
     /// Defaults to the only container if there is only one container in the pod.
@@ -1,4 +1,4 @@
-use darling::{FromDeriveInput, FromMeta};
+use darling::{util::SpannedValue, FromDeriveInput, FromMeta};
 use proc_macro2::{Ident, Literal, Span, TokenStream};
 use quote::ToTokens;
 use syn::{parse_quote, Data, DeriveInput, Path, Visibility};
@@ -25,11 +25,13 @@ struct KubeAttrs {
     #[darling(multiple, rename = "category")]
     categories: Vec<String>,
     #[darling(multiple, rename = "shortname")]
-    shortnames: Vec<String>,
+    shortnames: Vec<SpannedValue<String>>,
     #[darling(multiple, rename = "printcolumn")]
     printcolums: Vec<String>,
     scale: Option<String>,
     #[darling(default)]
+    builder: bool,
+    #[darling(default)]
     crates: Crates,
 }
 
@@ -155,6 +157,7 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
         shortnames,
         printcolums,
         scale,
+        builder,
         crates:
             Crates {
                 kube_core,
@@ -166,6 +169,21 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
             },
     } = kube_attrs;
 
+    // Shortnames are used as resource names by kubectl, so they must be valid DNS labels
+    for shortname in &shortnames {
+        if !is_dns_label(shortname) {
+            return syn::Error::new(
+                shortname.span(),
+                format!(
+                    r#"#[kube(shortname = "{}")] must be a valid DNS label (lowercase alphanumerics and '-', at most 63 characters)"#,
+                    shortname.as_str()
+                ),
+            )
+            .to_compile_error();
+        }
+    }
+    let shortnames: Vec<String> = shortnames.iter().map(|s| s.to_string()).collect();
+
     let struct_name = kind_struct.unwrap_or_else(|| kind.clone());
     if derive_input.ident == struct_name {
         return syn::Error::new_spanned(
@@ -240,6 +258,11 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
     }
 
     let docstr = format!(" Auto-generated derived type for {ident} via `CustomResource`");
+    let new_docstr = if namespaced {
+        " Spec based constructor for derived custom resource\n\n Only `metadata.name` is set; the namespace is left for the caller (or the `Api` it is created through) to decide."
+    } else {
+        " Spec based constructor for derived custom resource\n\n Only `metadata.name` is set."
+    };
     let quoted_serde = Literal::string(&serde.to_token_stream().to_string());
     let root_obj = quote! {
         #[doc = #docstr]
@@ -255,7 +278,7 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
             #status_field
         }
         impl #rootident {
-            /// Spec based constructor for derived custom resource
+            #[doc = #new_docstr]
             pub fn new(name: &str, spec: #ident) -> Self {
                 Self {
                     metadata: #k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta {
@@ -467,6 +490,21 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
 
     let impl_hasspec = generate_hasspec(&ident, &rootident, &kube_core);
 
+    // 5. Optionally generate a builder that refuses to build without a name and a spec
+    let impl_builder = if builder {
+        generate_builder(BuilderInformation {
+            spec_ident: &ident,
+            root_ident: &rootident,
+            status: &status,
+            namespaced,
+            visibility: &visibility,
+            k8s_openapi: &k8s_openapi,
+            std: &std,
+        })
+    } else {
+        quote! {}
+    };
+
     // Concat output
     quote! {
         #root_obj
@@ -475,6 +513,7 @@ pub(crate) fn derive(input: proc_macro2::TokenStream) -> proc_macro2::TokenStrea
         #impl_crd
         #impl_hasspec
         #impl_hasstatus
+        #impl_builder
     }
 }
 
@@ -503,6 +542,158 @@ fn generate_hasspec(spec_ident: &Ident, root_ident: &Ident, kube_core: &Path) ->
     }
 }
 
+struct BuilderInformation<'a> {
+    /// The identity (name) of the spec struct
+    spec_ident: &'a Ident,
+    /// The identity (name) of the main CRD struct (the one we generate in this macro)
+    root_ident: &'a Ident,
+    /// The optional name of the `status` struct to use
+    status: &'a Option<String>,
+    /// Whether the builder should allow setting a namespace
+    namespaced: bool,
+    /// Desired visibility of the generated builder
+    visibility: &'a Visibility,
+    k8s_openapi: &'a Path,
+    std: &'a Path,
+}
+
+/// This generates the builder for `#[kube(builder)]`.
+///
+/// The builder tracks whether the name and the spec have been set in its type parameters
+/// (`()` while unset), so that `build` only exists once both have been provided.
+fn generate_builder(info: BuilderInformation<'_>) -> TokenStream {
+    let BuilderInformation {
+        spec_ident,
+        root_ident,
+        status,
+        namespaced,
+        visibility,
+        k8s_openapi,
+        std,
+    } = info;
+    let builder_ident = format_ident!("{}Builder", root_ident);
+    let object_meta = quote! { #k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta };
+    let builder_docstr = format!(
+        " Builder for [`{root_ident}`], created by [`{root_ident}::builder`]\n\n \
+         `build` is only available once both `name` and `spec` have been set."
+    );
+
+    let (status_field, status_move, status_init, status_setter) = if let Some(status_name) = status {
+        let status_ident = format_ident!("{}", status_name);
+        (
+            quote! { status: #std::option::Option<#status_ident>, },
+            quote! { status: self.status, },
+            quote! { status: #std::option::Option::None, },
+            quote! {
+                /// Sets the initial status
+                #[must_use]
+                pub fn status(mut self, status: #status_ident) -> Self {
+                    self.status = #std::option::Option::Some(status);
+                    self
+                }
+            },
+        )
+    } else {
+        (quote! {}, quote! {}, quote! {}, quote! {})
+    };
+    let namespace_setter = if namespaced {
+        quote! {
+            /// Sets `metadata.namespace`
+            #[must_use]
+            pub fn namespace(mut self, namespace: impl #std::convert::Into<#std::string::String>) -> Self {
+                self.metadata.namespace = #std::option::Option::Some(namespace.into());
+                self
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    quote! {
+        #[doc = #builder_docstr]
+        #[automatically_derived]
+        #[must_use]
+        #visibility struct #builder_ident<Name, Spec> {
+            metadata: #object_meta,
+            name: Name,
+            spec: Spec,
+            #status_field
+        }
+        impl #root_ident {
+            /// Creates a builder that requires a name and a spec before it can `build`
+            pub fn builder() -> #builder_ident<(), ()> {
+                #builder_ident {
+                    metadata: #std::default::Default::default(),
+                    name: (),
+                    spec: (),
+                    #status_init
+                }
+            }
+        }
+        impl<Spec> #builder_ident<(), Spec> {
+            /// Sets `metadata.name`
+            pub fn name(self, name: impl #std::convert::Into<#std::string::String>) -> #builder_ident<#std::string::String, Spec> {
+                #builder_ident {
+                    metadata: self.metadata,
+                    name: name.into(),
+                    spec: self.spec,
+                    #status_move
+                }
+            }
+        }
+        impl<Name> #builder_ident<Name, ()> {
+            /// Sets the spec
+            pub fn spec(self, spec: #spec_ident) -> #builder_ident<Name, #spec_ident> {
+                #builder_ident {
+                    metadata: self.metadata,
+                    name: self.name,
+                    spec,
+                    #status_move
+                }
+            }
+        }
+        impl<Name, Spec> #builder_ident<Name, Spec> {
+            /// Replaces the metadata, `metadata.name` is still taken from `name`
+            #[must_use]
+            pub fn metadata(mut self, metadata: #object_meta) -> Self {
+                self.metadata = metadata;
+                self
+            }
+
+            #namespace_setter
+
+            /// Adds a label to `metadata.labels`
+            #[must_use]
+            pub fn label(mut self, key: impl #std::convert::Into<#std::string::String>, value: impl #std::convert::Into<#std::string::String>) -> Self {
+                self.metadata.labels.get_or_insert_with(#std::default::Default::default).insert(key.into(), value.into());
+                self
+            }
+
+            /// Adds an annotation to `metadata.annotations`
+            #[must_use]
+            pub fn annotation(mut self, key: impl #std::convert::Into<#std::string::String>, value: impl #std::convert::Into<#std::string::String>) -> Self {
+                self.metadata.annotations.get_or_insert_with(#std::default::Default::default).insert(key.into(), value.into());
+                self
+            }
+
+            #status_setter
+        }
+        impl #builder_ident<#std::string::String, #spec_ident> {
+            /// Builds the custom resource
+            pub fn build(self) -> #root_ident {
+                #root_ident {
+                    metadata: #object_meta {
+                        name: #std::option::Option::Some(self.name),
+                        ..self.metadata
+                    },
+                    spec: self.spec,
+                    #status_move
+                }
+            }
+        }
+    }
+}
+
 struct StatusInformation {
     /// The code to be used for the field in the main struct
     field: TokenStream,
@@ -563,6 +754,16 @@ fn process_status(
     }
 }
 
+// Checks if a name is a valid RFC 1123 DNS label, as required for resource shortnames.
+fn is_dns_label(name: &str) -> bool {
+    let valid_char = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit();
+    !name.is_empty()
+        && name.len() <= 63
+        && name.chars().all(|c| valid_char(c) || c == '-')
+        && name.starts_with(valid_char)
+        && name.ends_with(valid_char)
+}
+
 // Simple pluralizer.
 // Duplicating the code from kube (without special casing) because it's simple enough.
 // Irregular plurals must be explicitly specified.
@@ -613,4 +814,15 @@ mod tests {
         assert_eq!(kube_attrs.kind, "Foo".to_string());
         assert!(kube_attrs.namespaced);
     }
+
+    #[test]
+    fn test_is_dns_label() {
+        assert!(is_dns_label("f"));
+        assert!(is_dns_label("foo-2"));
+        assert!(!is_dns_label(""));
+        assert!(!is_dns_label("Foo"));
+        assert!(!is_dns_label("-foo"));
+        assert!(!is_dns_label("foo_bar"));
+        assert!(!is_dns_label(&"a".repeat(64)));
+    }
 }
@@ -137,10 +137,35 @@ mod custom_resource;
 ///
 /// ## `#[kube(shortname = "sn")]`
 /// Add a single shortname to the generated crd.
+/// Shortnames must be valid DNS labels (lowercase alphanumerics and `-`), and are checked at compile time.
 ///
 /// ## `#[kube(category = "apps")]`
 /// Add a single category to `crd.spec.names.categories`.
 ///
+/// ## `#[kube(builder)]`
+/// Generates a `{Kind}Builder` along with a `{Kind}::builder()` constructor.
+/// The builder can set labels, annotations, the full metadata, the namespace (if `namespaced`) and the status (if `status` is set),
+/// but `build()` only compiles once both `name` and `spec` have been provided:
+///
+/// ```rust
+/// # use kube_derive::CustomResource;
+/// # use schemars::JsonSchema;
+/// # use serde::{Deserialize, Serialize};
+/// #[derive(CustomResource, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+/// #[kube(group = "clux.dev", version = "v1", kind = "Foo", namespaced, builder)]
+/// struct FooSpec {
+///     info: String,
+/// }
+///
+/// let foo = Foo::builder()
+///     .name("baz")
+///     .namespace("default")
+///     .label("app", "baz")
+///     .spec(FooSpec { info: "hello".into() })
+///     .build();
+/// assert_eq!(foo.metadata.name.as_deref(), Some("baz"));
+/// ```
+///
 /// ## Example with all properties
 ///
 /// ```rust
@@ -202,6 +227,7 @@ mod custom_resource;
 /// impl kube::Resource for FooCrd { .. }
 ///
 /// impl FooCrd {
+///     /// Sets `metadata.name`, leaving the namespace unset
 ///     pub fn new(name: &str, spec: FooSpec) -> Self { .. }
 ///     pub fn crd() -> CustomResourceDefinition { .. }
 /// }
@@ -223,6 +249,17 @@ mod custom_resource;
 ///
 /// If you have to override a lot, [you can opt-out of schema-generation entirely](#kubeschema--mode)
 ///
+/// ## Preserving Unknown Fields
+/// The apiserver prunes any field not described by the schema. Fields that hold freeform data (like raw manifests or
+/// arbitrary `serde_json::Value`s) can opt out of pruning with the helpers in `kube::core::schema`:
+/// - `#[schemars(schema_with = "kube::core::schema::preserve_unknown_fields")]` for freeform objects
+/// - `#[schemars(schema_with = "kube::core::schema::preserve_arbitrary")]` for values of any type
+///
+/// Both set `x-kubernetes-preserve-unknown-fields: true` on that subtree only.
+/// The tradeoff is that the apiserver no longer validates anything below it, and [server-side apply](https://kubernetes.io/docs/reference/using-api/server-side-apply/)
+/// cannot track field ownership inside it, so the subtree is always replaced as a whole.
+/// Prefer typed fields where the structure is known.
+///
 /// # Advanced Features
 ///
 /// - **embedding k8s-openapi types** can be done by enabling the `schemars` feature of `k8s-openapi` from [`0.13.0`](https://github.com/Arnavion/k8s-openapi/blob/master/CHANGELOG.md#v0130-2021-08-09)
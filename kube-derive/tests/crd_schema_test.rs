@@ -63,6 +63,16 @@ pub struct FlatteningSpec {
     arbitrary: HashMap<String, serde_json::Value>,
 }
 
+#[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+#[kube(group = "clux.dev", version = "v1", kind = "Preserving")]
+pub struct PreservingSpec {
+    foo: String,
+    #[schemars(schema_with = "kube::core::schema::preserve_unknown_fields")]
+    template: serde_json::Map<String, serde_json::Value>,
+    #[schemars(schema_with = "kube::core::schema::preserve_arbitrary")]
+    value: serde_json::Value,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[allow(clippy::enum_variant_names)]
@@ -116,6 +126,40 @@ fn test_crd_name() {
     assert_eq!("foos.clux.dev", Foo::crd_name());
 }
 
+#[derive(CustomResource, Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[kube(
+    group = "clux.dev",
+    version = "v1",
+    kind = "Bar",
+    namespaced,
+    status = "BarStatus",
+    builder
+)]
+struct BarSpec {
+    info: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+struct BarStatus {
+    ready: bool,
+}
+
+#[test]
+fn test_builder() {
+    let bar = Bar::builder()
+        .spec(BarSpec { info: "hi".into() })
+        .label("app", "bar")
+        .namespace("default")
+        .name("bar")
+        .status(BarStatus { ready: true })
+        .build();
+    assert_eq!(bar.metadata.name.as_deref(), Some("bar"));
+    assert_eq!(bar.metadata.namespace.as_deref(), Some("default"));
+    assert_eq!(bar.metadata.labels.unwrap()["app"], "bar");
+    assert_eq!(bar.spec.info, "hi");
+    assert!(bar.status.unwrap().ready);
+}
+
 #[test]
 fn test_shortnames() {
     use kube::core::CustomResourceExt;
@@ -338,3 +382,23 @@ fn flattening() {
     assert_eq!(spec.x_kubernetes_preserve_unknown_fields, Some(true));
     assert_eq!(spec.additional_properties, None);
 }
+
+#[test]
+fn preserve_unknown_fields() {
+    use kube::core::CustomResourceExt;
+    let spec = &Preserving::crd().spec.versions[0]
+        .schema
+        .clone()
+        .unwrap()
+        .open_api_v3_schema
+        .unwrap()
+        .properties
+        .unwrap()["spec"];
+    assert_eq!(spec.x_kubernetes_preserve_unknown_fields, None);
+    let props = spec.properties.as_ref().unwrap();
+    assert_eq!(props["foo"].x_kubernetes_preserve_unknown_fields, None);
+    assert_eq!(props["template"].x_kubernetes_preserve_unknown_fields, Some(true));
+    assert_eq!(props["template"].type_.as_deref(), Some("object"));
+    assert_eq!(props["value"].x_kubernetes_preserve_unknown_fields, Some(true));
+    assert_eq!(props["value"].type_, None);
+}
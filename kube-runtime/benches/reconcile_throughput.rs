@@ -0,0 +1,83 @@
+//! Throughput of large objects from a watch stream to the reconciler
+//!
+//! Compares [`reflector::reflector`], which clones every object into the store, with
+//! [`reflector::reflector_shared`], which stores the same allocation that it passes on:
+//!
+//! ```sh
+//! cargo bench -p kube-runtime --bench reconcile_throughput
+//! ```
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use futures::{stream, Stream, StreamExt};
+use k8s_openapi::{api::core::v1::ConfigMap, apimachinery::pkg::apis::meta::v1::ObjectMeta};
+use kube_runtime::{
+    reflector::{self, store::Writer, ObjectRef, Store},
+    watcher, WatchStreamExt,
+};
+
+const EVENTS: usize = 5_000;
+/// The number of distinct objects that the events update
+const OBJECTS: usize = 100;
+/// The size of the data of each object
+const OBJECT_BYTES: usize = 16 * 1024;
+
+/// A watch stream updating large config maps, as decoded from the apiserver
+fn watch_events() -> impl Stream<Item = watcher::Result<watcher::Event<ConfigMap>>> {
+    let events = (0..EVENTS)
+        .map(|i| {
+            let data = (0..16)
+                .map(|key| (format!("key-{key}"), "x".repeat(OBJECT_BYTES / 16)))
+                .collect::<BTreeMap<_, _>>();
+            Ok(watcher::Event::Applied(ConfigMap {
+                metadata: ObjectMeta {
+                    name: Some(format!("config-{}", i % OBJECTS)),
+                    namespace: Some("default".to_string()),
+                    resource_version: Some(i.to_string()),
+                    ..ObjectMeta::default()
+                },
+                data: Some(data),
+                ..ConfigMap::default()
+            }))
+        })
+        .collect::<Vec<_>>();
+    stream::iter(events)
+}
+
+/// Reconciles an object like the controller does, by looking it up in the store
+fn reconcile(store: &Store<ConfigMap>, obj_ref: &ObjectRef<ConfigMap>) -> usize {
+    let obj = store.get(obj_ref).expect("object should be stored");
+    obj.data.as_ref().map_or(0, BTreeMap::len)
+}
+
+fn report(name: &str, reconciles: usize, elapsed: Duration) {
+    println!(
+        "{name}: {reconciles} reconciles in {elapsed:?}, {:.0} reconciles/s",
+        reconciles as f64 / elapsed.as_secs_f64()
+    );
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let (writer, events) = (Writer::default(), watch_events());
+    let store = writer.as_reader();
+    let start = Instant::now();
+    let reconciles = reflector::reflector(writer, events)
+        .applied_objects()
+        .map(|obj| reconcile(&store, &ObjectRef::from_obj(&obj.unwrap())))
+        .count()
+        .await;
+    report("reflector", reconciles, start.elapsed());
+
+    let (writer, events) = (Writer::default(), watch_events());
+    let store = writer.as_reader();
+    let start = Instant::now();
+    let reconciles = reflector::reflector_shared(writer, events)
+        .applied_objects()
+        .map(|obj| reconcile(&store, &ObjectRef::from_obj(&*obj.unwrap())))
+        .count()
+        .await;
+    report("reflector_shared", reconciles, start.elapsed());
+}
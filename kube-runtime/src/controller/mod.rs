@@ -2,13 +2,18 @@
 
 use self::runner::Runner;
 use crate::{
+    events::{InvolvedObject, Recorder, Reporter},
     reflector::{
-        self, reflector,
+        self, reflector_shared,
         store::{Store, Writer},
         ObjectRef,
     },
-    scheduler::{debounced_scheduler, ScheduleRequest},
-    utils::{trystream_try_via, CancelableJoinHandle, KubeRuntimeStreamExt, StreamBackoff, WatchStreamExt},
+    scheduler::{debounced_scheduler, QueueMirror, ScheduleRequest, SchedulerStats},
+    utils::{
+        trystream_try_via, CancelableJoinHandle, Change, Clock, KubeRuntimeStreamExt, StreamBackoff,
+        TokioClock, WatchStreamExt,
+    },
+    wait::{self, await_condition, conditions, Condition},
     watcher::{self, metadata_watcher, watcher, DefaultBackoff},
 };
 use backoff::backoff::Backoff;
@@ -16,12 +21,20 @@ use derivative::Derivative;
 use futures::{
     channel,
     future::{self, BoxFuture},
-    ready, stream, Future, FutureExt, Stream, StreamExt, TryFuture, TryFutureExt, TryStream, TryStreamExt,
+    ready, stream, Future, FutureExt, SinkExt, Stream, StreamExt, TryFuture, TryFutureExt, TryStream,
+    TryStreamExt,
+};
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+use kube_client::{
+    api::{Api, ApiResource, DynamicObject, Resource},
+    client::middleware::RateLimitLayer,
+    Client,
 };
-use kube_client::api::{Api, DynamicObject, Resource};
+use parking_lot::Mutex;
 use pin_project::pin_project;
 use serde::de::DeserializeOwned;
 use std::{
+    collections::HashMap,
     fmt::{Debug, Display},
     hash::Hash,
     sync::Arc,
@@ -50,6 +63,20 @@ pub enum Error<ReconcilerErr: 'static, QueueErr: 'static> {
     RunnerError(#[source] RunnerError),
 }
 
+/// Errors from [`Controller::await_crd_established`]
+#[derive(Debug, Error)]
+pub enum CrdEstablishError {
+    /// Watching the CRD failed, with the name of the CRD
+    #[error("failed to wait for CustomResourceDefinition {0} to be established: {1}")]
+    ProbeFailed(String, #[source] wait::Error),
+    /// The CRD was not established in time, with the name of the CRD and the timeout
+    #[error("CustomResourceDefinition {0} was not established within {1:?}")]
+    TimedOut(String, Duration),
+    /// The names of the CRD conflict with another CRD, with the name of the CRD and the `NamesAccepted` message
+    #[error("CustomResourceDefinition {0} names were not accepted: {1}")]
+    NamesRejected(String, String),
+}
+
 /// Results of the reconciliation attempt
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Action {
@@ -74,6 +101,19 @@ impl Action {
         }
     }
 
+    /// Action to requeue the reconciliation after a random delay between `duration` and `duration + max_jitter`
+    ///
+    /// Objects that are requeued with the same fixed interval are reconciled at the same time, causing spikes of
+    /// load on the apiserver (and other systems that the reconciler talks to). Jittering the interval spreads
+    /// the reconciliations out over the band, while never requeueing earlier than `duration`.
+    ///
+    /// Use [`Action::requeue`] when the reconciliation must happen at a precise time.
+    #[must_use]
+    pub fn requeue_jittered(duration: Duration, max_jitter: Duration) -> Self {
+        let jitter = max_jitter.mul_f64(rand::random::<f64>());
+        Self::requeue(duration + jitter)
+    }
+
     /// Do nothing until a change is detected
     ///
     /// This stops the controller periodically reconciling this object until a relevant watch event
@@ -235,6 +275,9 @@ impl Display for ReconcileReason {
 
 const APPLIER_REQUEUE_BUF_SIZE: usize = 100;
 
+/// Delay before retrying a reconcile whose [fresh read](Controller::reconcile_with_fresh_read) failed
+const FRESH_READ_RETRY_DELAY: Duration = Duration::from_secs(5);
+
 /// Apply a reconciler to an input stream, with a given retry policy
 ///
 /// Takes a `store` parameter for the core objects, which should usually be updated by a [`reflector()`].
@@ -246,14 +289,63 @@ const APPLIER_REQUEUE_BUF_SIZE: usize = 100;
 ///
 /// This is the "hard-mode" version of [`Controller`], which allows you some more customization
 /// (such as triggering from arbitrary [`Stream`]s), at the cost of being a bit more verbose.
-#[allow(clippy::needless_pass_by_value)]
 pub fn applier<K, QueueStream, ReconcilerFut, Ctx>(
-    mut reconciler: impl FnMut(Arc<K>, Arc<Ctx>) -> ReconcilerFut,
+    reconciler: impl FnMut(Arc<K>, Arc<Ctx>) -> ReconcilerFut,
+    error_policy: impl Fn(Arc<K>, &ReconcilerFut::Error, Arc<Ctx>) -> Action,
+    context: Arc<Ctx>,
+    store: Store<K>,
+    queue: QueueStream,
+    config: Config,
+) -> impl Stream<Item = Result<(ObjectRef<K>, Action), Error<ReconcilerFut::Error, QueueStream::Error>>>
+where
+    K: Clone + Resource + 'static,
+    K::DynamicType: Debug + Eq + Hash + Clone + Unpin,
+    ReconcilerFut: TryFuture<Ok = Action> + Unpin,
+    ReconcilerFut::Error: std::error::Error + 'static,
+    QueueStream: TryStream,
+    QueueStream::Ok: Into<ReconcileRequest<K>>,
+    QueueStream::Error: std::error::Error + 'static,
+{
+    introspected_applier(
+        reconciler,
+        error_policy,
+        context,
+        store,
+        queue,
+        config,
+        ApplierOptions::default(),
+    )
+}
+
+/// The extensions of the [`Controller`] to the plain [`applier`], all disabled by default
+#[derive(Derivative)]
+#[derivative(Default(bound = ""))]
+struct ApplierOptions<K: Resource> {
+    /// Publishes the queue and the reconcile results
+    introspection: Option<ControllerIntrospection<K>>,
+    /// Deduplicates requests by a key, the [`Store`] must be indexed by the same key
+    reconcile_key: Option<ReconcileKey<K>>,
+    /// Reads the objects to reconcile, instead of taking them from the [`Store`]
+    fresh_read: Option<FreshRead<K>>,
+}
+
+/// The reconciler of an [`applier`], only shared with the reconciliations that have to read their object first
+enum ApplierReconciler<R, K: Resource> {
+    Cached(R),
+    FreshRead(Arc<Mutex<R>>, FreshRead<K>),
+}
+
+/// [`applier`] with the extensions of the [`Controller`], see [`ApplierOptions`]
+#[allow(clippy::needless_pass_by_value, clippy::too_many_lines)]
+#[allow(clippy::type_complexity)]
+fn introspected_applier<K, QueueStream, ReconcilerFut, Ctx>(
+    reconciler: impl FnMut(Arc<K>, Arc<Ctx>) -> ReconcilerFut,
     error_policy: impl Fn(Arc<K>, &ReconcilerFut::Error, Arc<Ctx>) -> Action,
     context: Arc<Ctx>,
     store: Store<K>,
     queue: QueueStream,
     config: Config,
+    options: ApplierOptions<K>,
 ) -> impl Stream<Item = Result<(ObjectRef<K>, Action), Error<ReconcilerFut::Error, QueueStream::Error>>>
 where
     K: Clone + Resource + 'static,
@@ -264,11 +356,25 @@ where
     QueueStream::Ok: Into<ReconcileRequest<K>>,
     QueueStream::Error: std::error::Error + 'static,
 {
+    let ApplierOptions {
+        introspection,
+        reconcile_key,
+        fresh_read,
+    } = options;
     let (scheduler_shutdown_tx, scheduler_shutdown_rx) = channel::oneshot::channel();
     let (scheduler_tx, scheduler_rx) =
         channel::mpsc::channel::<ScheduleRequest<ReconcileRequest<K>>>(APPLIER_REQUEUE_BUF_SIZE);
     let error_policy = Arc::new(error_policy);
+    let mut reconciler = match fresh_read {
+        // only locked to start a reconcile, once its object has been read
+        Some(read) => ApplierReconciler::FreshRead(Arc::new(Mutex::new(reconciler)), read),
+        None => ApplierReconciler::Cached(reconciler),
+    };
     let delay_store = store.clone();
+    let key_store = store.clone();
+    let key_of_request = reconcile_key.clone();
+    let clock: Arc<dyn Clock> = config.clock.clone().unwrap_or_else(|| Arc::new(TokioClock));
+    let request_clock = clock.clone();
     // Create a stream of ObjectRefs that need to be reconciled
     trystream_try_via(
         // input: stream combining scheduled tasks and user specified inputs event
@@ -276,9 +382,18 @@ where
             // 1. inputs from users queue stream
             queue
                 .map_err(Error::QueueError)
-                .map_ok(|request| ScheduleRequest {
-                    message: request.into(),
-                    run_at: Instant::now(),
+                .map_ok(move |request| {
+                    let mut request = request.into();
+                    if let Some(key) = &key_of_request {
+                        // requests for objects that are no longer in the store keep their own key
+                        if let Some(obj) = key_store.get(&request.obj_ref) {
+                            request.obj_ref = key(&obj);
+                        }
+                    }
+                    ScheduleRequest {
+                        message: request,
+                        run_at: request_clock.now(),
+                    }
                 })
                 .on_complete(async move {
                     // On error: scheduler has already been shut down and there is nothing for us to do
@@ -293,43 +408,121 @@ where
         )),
         // all the Oks from the select gets passed through the scheduler stream, and are then executed
         move |s| {
+            let mirror = introspection.as_ref().map(|introspection| introspection.queued.clone());
             Runner::new(
-                debounced_scheduler(s, config.debounce),
+                debounced_scheduler(s, config.debounce)
+                    .mirror_queue(mirror)
+                    .with_clock(clock.clone()),
                 config.concurrency,
                 move |request| {
                     let request = request.clone();
-                    match store.get(&request.obj_ref) {
+                    let obj = match &reconcile_key {
+                        Some(_) => store.get_indexed(&request.obj_ref).into_iter().next(),
+                        None => store.get(&request.obj_ref),
+                    };
+                    let deleted = obj.as_ref().map_or(true, |obj| {
+                        let meta = obj.meta();
+                        meta.deletion_timestamp.is_some() && meta.finalizers.as_ref().map_or(true, Vec::is_empty)
+                    });
+                    if (config.ignore_deleted && deleted) || obj.is_none() {
+                        if let Some(introspection) = &introspection {
+                            introspection.last_results.lock().remove(&request.obj_ref);
+                        }
+                    }
+                    if config.ignore_deleted && deleted {
+                        tracing::debug!(object.ref = %request.obj_ref, "skipping reconcile of deleted object");
+                        return future::ok(None).right_future();
+                    }
+                    match obj {
                         Some(obj) => {
-                            let scheduler_tx = scheduler_tx.clone();
+                            let mut scheduler_tx = scheduler_tx.clone();
                             let error_policy_ctx = context.clone();
                             let error_policy = error_policy.clone();
+                            let introspection = introspection.clone();
+                            let clock = clock.clone();
                             let reconciler_span = info_span!(
                                 "reconciling object",
                                 "object.ref" = %request.obj_ref,
                                 object.reason = %request.reason
                             );
-                            reconciler_span
-                                .in_scope(|| reconciler(Arc::clone(&obj), context.clone()))
-                                .into_future()
-                                .then(move |res| {
-                                    let error_policy = error_policy;
-                                    RescheduleReconciliation::new(
+                            let started = match &mut reconciler {
+                                ApplierReconciler::Cached(reconciler) => {
+                                    let res =
+                                        reconciler_span.in_scope(|| reconciler(Arc::clone(&obj), context.clone()));
+                                    future::ok(Some((obj, res))).left_future()
+                                }
+                                ApplierReconciler::FreshRead(reconciler, read) => {
+                                    let reconciler = reconciler.clone();
+                                    let context = context.clone();
+                                    let span = reconciler_span.clone();
+                                    read(&request.obj_ref)
+                                        .map_ok(move |obj| {
+                                            obj.map(|obj| {
+                                                let obj = Arc::new(obj);
+                                                let res = span
+                                                    .in_scope(|| (reconciler.lock())(Arc::clone(&obj), context));
+                                                (obj, res)
+                                            })
+                                        })
+                                        .right_future()
+                                }
+                            };
+                            Box::pin(
+                                async move {
+                                    let (obj, res) = match started.await {
+                                        Ok(Some(started)) => started,
+                                        Ok(None) => {
+                                            tracing::debug!("skipping reconcile of object that no longer exists");
+                                            if let Some(introspection) = &introspection {
+                                                introspection.last_results.lock().remove(&request.obj_ref);
+                                            }
+                                            return Ok(None);
+                                        }
+                                        Err(err) => {
+                                            tracing::warn!(
+                                                error = %err,
+                                                "failed to read the latest version of object, retrying"
+                                            );
+                                            let retry = ScheduleRequest {
+                                                message: request,
+                                                run_at: clock.now() + FRESH_READ_RETRY_DELAY,
+                                            };
+                                            // Can only fail when the applier is shutting down anyway
+                                            let _ = scheduler_tx.send(retry).await;
+                                            return Ok(None);
+                                        }
+                                    };
+                                    let res = res.into_future().await;
+                                    if let (Some(introspection), Ok(action)) = (&introspection, &res) {
+                                        introspection.record(&request.obj_ref, action, None);
+                                    }
+                                    let res = RescheduleReconciliation::new(
                                         res,
-                                        |err| error_policy(obj, err, error_policy_ctx),
+                                        |err| {
+                                            let action = error_policy(obj, err, error_policy_ctx);
+                                            if let Some(introspection) = &introspection {
+                                                introspection.record(&request.obj_ref, &action, Some(err));
+                                            }
+                                            action
+                                        },
                                         request.obj_ref.clone(),
                                         scheduler_tx,
+                                        clock.now(),
                                     )
+                                    .await;
                                     // Reconciler errors are OK from the applier's PoV, we need to apply the error policy
                                     // to them separately
-                                    .map(|res| Ok((request.obj_ref, res)))
-                                })
-                                .instrument(reconciler_span)
-                                .left_future()
+                                    Ok(Some((request.obj_ref, res)))
+                                }
+                                .instrument(reconciler_span),
+                            )
+                            .left_future()
                         }
                         None => future::err(Error::ObjectNotFound(request.obj_ref.erase())).right_future(),
                     }
                 },
             )
+            .with_stats(config.stats)
             .delay_tasks_until(async move {
                 tracing::debug!("applier runner held until store is ready");
                 let res = delay_store.wait_until_ready().await;
@@ -342,10 +535,12 @@ where
     )
     .on_complete(async { tracing::debug!("applier runner-merge terminated") })
     // finally, for each completed reconcile call:
-    .and_then(move |(obj_ref, reconciler_result)| async move {
-        match reconciler_result {
-            Ok(action) => Ok((obj_ref, action)),
-            Err(err) => Err(Error::ReconcilerFailed(err, obj_ref.erase())),
+    .try_filter_map(move |reconciled| async move {
+        match reconciled {
+            Some((obj_ref, Ok(action))) => Ok(Some((obj_ref, action))),
+            Some((obj_ref, Err(err))) => Err(Error::ReconcilerFailed(err, obj_ref.erase())),
+            // skipped deleted object
+            None => Ok(None),
         }
     })
     .on_complete(async { tracing::debug!("applier terminated") })
@@ -372,9 +567,8 @@ where
         error_policy: impl FnOnce(&ReconcilerErr) -> Action,
         obj_ref: ObjectRef<K>,
         reschedule_tx: channel::mpsc::Sender<ScheduleRequest<ReconcileRequest<K>>>,
+        reconciler_finished_at: Instant,
     ) -> Self {
-        let reconciler_finished_at = Instant::now();
-
         let (action, reschedule_reason) = result.as_ref().map_or_else(
             |err| (error_policy(err), ReconcileReason::ErrorPolicyRequestedRetry),
             |action| (action.clone(), ReconcileReason::ReconcilerRequestedRetry),
@@ -423,11 +617,157 @@ where
     }
 }
 
+/// Shared handle to the queue statistics of a running [`Controller`] (or [`applier`])
+///
+/// Register it with [`Config::stats`], and read it from elsewhere (such as a metrics exporter)
+/// to tell whether the controller is keeping up with its reconcile requests.
+///
+/// ```no_run
+/// # use kube::runtime::controller::{Config, ControllerStats};
+/// let stats = ControllerStats::default();
+/// let config = Config::default().concurrency(4).stats(stats.clone());
+/// // pass `config` to Controller::with_config, then periodically:
+/// if stats.max_pending_age().map_or(false, |age| age.as_secs() > 60) {
+///     eprintln!("controller is falling behind: {} pending reconciles", stats.pending());
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ControllerStats(Arc<Mutex<ControllerStatsInner>>);
+
+#[derive(Debug, Default)]
+struct ControllerStatsInner {
+    scheduler: SchedulerStats,
+    running: usize,
+}
+
+impl ControllerStats {
+    pub(crate) fn update(&self, scheduler: SchedulerStats, running: usize) {
+        *self.0.lock() = ControllerStatsInner { scheduler, running };
+    }
+
+    /// The number of reconciles that are scheduled to run at a later time (e.g. requeues)
+    #[must_use]
+    pub fn scheduled(&self) -> usize {
+        self.0.lock().scheduler.scheduled
+    }
+
+    /// The number of reconciles that are due, but waiting for a free slot
+    ///
+    /// Reconciles are held pending when the [`Config::concurrency`] limit is reached,
+    /// or when a reconcile for the same object is still running.
+    #[must_use]
+    pub fn pending(&self) -> usize {
+        self.0.lock().scheduler.pending
+    }
+
+    /// How long the oldest pending reconcile has been waiting for a free slot
+    #[must_use]
+    pub fn max_pending_age(&self) -> Option<Duration> {
+        let oldest = self.0.lock().scheduler.oldest_pending?;
+        Some(Instant::now().saturating_duration_since(oldest))
+    }
+
+    /// The number of reconciles that are currently running
+    #[must_use]
+    pub fn running(&self) -> usize {
+        self.0.lock().running
+    }
+}
+
+/// Shared handle to inspect the reconcile queue of a running [`Controller`]
+///
+/// Created with [`Controller::introspect`] before running the controller, and read from elsewhere
+/// (such as a debugging endpoint) to tell why an object has not been reconciled (yet).
+///
+/// ```no_run
+/// # use k8s_openapi::api::core::v1::ConfigMap;
+/// # use kube::runtime::Controller;
+/// # let mut controller: Controller<ConfigMap> = todo!();
+/// let introspection = controller.introspect();
+/// // run the controller, then from elsewhere:
+/// let state = introspection.snapshot();
+/// for (request, run_at) in &state.queued {
+///     println!("{} is due in {:?}", request.obj_ref, run_at.saturating_duration_since(tokio::time::Instant::now()));
+/// }
+/// ```
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Default(bound = "K::DynamicType: Eq + Hash"))]
+pub struct ControllerIntrospection<K: Resource> {
+    queued: QueueMirror<ReconcileRequest<K>>,
+    last_results: Arc<Mutex<HashMap<ObjectRef<K>, ReconcileResult>>>,
+}
+
+impl<K> ControllerIntrospection<K>
+where
+    K: Resource,
+    K::DynamicType: Eq + Hash + Clone,
+{
+    fn record(&self, obj_ref: &ObjectRef<K>, action: &Action, error: Option<&dyn std::error::Error>) {
+        self.last_results.lock().insert(obj_ref.clone(), ReconcileResult {
+            finished_at: Instant::now(),
+            action: action.clone(),
+            error: error.map(ToString::to_string),
+        });
+    }
+
+    /// A snapshot of the reconcile queue and the latest reconcile results of the controller
+    ///
+    /// The snapshot is empty until the controller is running.
+    #[must_use]
+    pub fn snapshot(&self) -> ControllerState<K> {
+        let mut queued = self
+            .queued
+            .lock()
+            .iter()
+            .map(|(request, run_at)| (request.clone(), *run_at))
+            .collect::<Vec<_>>();
+        queued.sort_by_key(|(_, run_at)| *run_at);
+        ControllerState {
+            queued,
+            last_results: self.last_results.lock().clone(),
+        }
+    }
+}
+
+/// A snapshot of the state of a [`Controller`], see [`ControllerIntrospection::snapshot`]
+#[derive(Derivative)]
+#[derivative(
+    Clone(bound = "K::DynamicType: Clone"),
+    Debug(bound = "K::DynamicType: Debug")
+)]
+pub struct ControllerState<K: Resource> {
+    /// The objects that are queued to be reconciled, and when they are due, ordered by due time
+    ///
+    /// Objects that are already due are waiting for a free slot, either because of [`Config::concurrency`],
+    /// or because a reconcile for the same object is still running.
+    pub queued: Vec<(ReconcileRequest<K>, Instant)>,
+    /// The result of the latest reconcile of each object that still exists
+    pub last_results: HashMap<ObjectRef<K>, ReconcileResult>,
+}
+
+/// The result of a finished reconcile, see [`ControllerState::last_results`]
+#[derive(Clone, Debug)]
+pub struct ReconcileResult {
+    /// When the reconcile finished
+    pub finished_at: Instant,
+    /// The [`Action`] returned by the reconciler, or by the error policy if the reconciler failed
+    pub action: Action,
+    /// The error returned by the reconciler, if it failed
+    pub error: Option<String>,
+}
+
 /// Accumulates all options that can be used on a [`Controller`] invocation.
 #[derive(Clone, Debug, Default)]
 pub struct Config {
     debounce: Duration,
     concurrency: u16,
+    field_manager: Option<String>,
+    write_limit: Option<RateLimitLayer>,
+    stats: Option<ControllerStats>,
+    ignore_deleted: bool,
+    reporter: Option<Reporter>,
+    fail_on_permanent_watch_errors: bool,
+    clock: Option<Arc<dyn Clock>>,
 }
 
 impl Config {
@@ -461,6 +801,141 @@ impl Config {
         self.concurrency = concurrency;
         self
     }
+
+    /// The field manager name that the controller's writes should be attributed to.
+    ///
+    /// When unset, [`Controller::field_manager`] derives a name from the running binary.
+    /// Using a single name for all of a controller's patches keeps `managedFields` coherent.
+    #[must_use]
+    pub fn field_manager(mut self, manager: impl Into<String>) -> Self {
+        self.field_manager = Some(manager.into());
+        self
+    }
+
+    /// Limit the writes made on behalf of the controller to `qps` per second, with bursts of up to `burst` writes.
+    ///
+    /// This is the equivalent of the `QPS` and `Burst` settings of client-go for mutating requests,
+    /// to avoid being throttled by the apiserver (with `429 Too Many Requests`) when many reconciles
+    /// write at once. The limit applies to the [`Client`] of the [`Api`] that the controller was created with,
+    /// see [`Controller::with_write_qps`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `qps` is not a positive number, or if `burst` is zero.
+    #[must_use]
+    pub fn write_qps(mut self, qps: f64, burst: u32) -> Self {
+        self.write_limit = Some(RateLimitLayer::new(qps, burst).mutating_only());
+        self
+    }
+
+    /// Publish the queue statistics of the controller to `stats` while it runs.
+    #[must_use]
+    pub fn stats(mut self, stats: ControllerStats) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+
+    /// The [`Reporter`] that events published by [`Controller::run_with_recorder`] are attributed to.
+    ///
+    /// When unset, the controller is named after [`Controller::field_manager`], and the instance
+    /// after the `HOSTNAME` environment variable (which is the pod name when running in a pod).
+    #[must_use]
+    pub fn reporter(mut self, reporter: Reporter) -> Self {
+        self.reporter = Some(reporter);
+        self
+    }
+
+    /// Skip reconciling objects that have been deleted.
+    ///
+    /// By default, a reconcile request for an object that is no longer in the store
+    /// (for example one triggered by the deletion of an owned object, or a requeue) is reported as
+    /// [`Error::ObjectNotFound`], and an object that is being deleted is passed to the reconciler.
+    ///
+    /// When enabled, requests for objects that are no longer in the store are dropped silently,
+    /// and objects that are being deleted are only reconciled if they still have finalizers
+    /// (such as those managed by [`finalizer`](crate::finalizer())), since there is nothing
+    /// left to clean up otherwise. Leave this disabled if you want to be notified of deletions.
+    #[must_use]
+    pub fn ignore_deleted(mut self, ignore_deleted: bool) -> Self {
+        self.ignore_deleted = ignore_deleted;
+        self
+    }
+
+    /// Stop the controller when one of its watches fails with a permanent error.
+    ///
+    /// Errors from the watches of a [`Controller`] are passed on as [`Error::QueueError`], and the watches
+    /// are retried with backoff, so a controller keeps running through disruptions of the apiserver.
+    /// Errors that are not resolved by retrying, like a `403 Forbidden` due to missing RBAC rules
+    /// (see [`watcher::Error::is_permanent`]), then keep the controller waiting (and logging) forever.
+    ///
+    /// When enabled, the stream returned by [`Controller::run`] ends right after such an error,
+    /// so that the misconfiguration surfaces as a crash of the controller. Disabled by default.
+    #[must_use]
+    pub fn fail_on_permanent_watch_errors(mut self, enabled: bool) -> Self {
+        self.fail_on_permanent_watch_errors = enabled;
+        self
+    }
+
+    /// The [`Clock`] that tells when requeues and debounced reconciles are due.
+    ///
+    /// Defaults to [`TokioClock`]. A custom clock lets tests move the time of the controller forward
+    /// without affecting other timers. The backoff of the watches is not affected by this clock.
+    #[must_use]
+    pub fn clock(mut self, clock: impl Clock) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+}
+
+/// Wait for the `CustomResourceDefinition` of `K` to be established
+///
+/// This is [`Controller::await_crd_established`] for callers that do not have a [`Controller`] (yet),
+/// such as operators that install their CRDs before creating any objects.
+///
+/// ```no_run
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// # let client: kube::Client = todo!();
+/// use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+/// use kube::{Api, runtime::controller::await_crd_established};
+/// use std::time::Duration;
+/// # #[derive(Clone, Debug, serde::Deserialize, kube::CustomResource, serde::Serialize, schemars::JsonSchema)]
+/// # #[kube(group = "clux.dev", version = "v1", kind = "Foo")]
+/// # struct FooSpec {}
+/// let crds = Api::<CustomResourceDefinition>::all(client);
+/// await_crd_established::<Foo>(crds, &(), Duration::from_secs(30)).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// See [`Controller::await_crd_established`].
+pub async fn await_crd_established<K: Resource>(
+    crd_api: Api<CustomResourceDefinition>,
+    dyntype: &K::DynamicType,
+    timeout: Duration,
+) -> Result<(), CrdEstablishError> {
+    let name = format!("{}.{}", K::plural(dyntype), K::group(dyntype));
+    let established = conditions::is_crd_established().and(conditions::are_crd_names_accepted());
+    let rejected = conditions::is_crd_names_rejected();
+    let done = await_condition(crd_api, &name, established.or(rejected));
+    match tokio::time::timeout(timeout, done).await {
+        Ok(Ok(crd)) if conditions::is_crd_names_rejected().matches_object(crd.as_ref()) => {
+            let message = crd
+                .and_then(|crd| {
+                    crd.status?
+                        .conditions?
+                        .into_iter()
+                        .find(|c| c.type_ == "NamesAccepted")
+                })
+                .and_then(|c| c.message)
+                .unwrap_or_default();
+            Err(CrdEstablishError::NamesRejected(name, message))
+        }
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(err)) => Err(CrdEstablishError::ProbeFailed(name, err)),
+        Err(_) => Err(CrdEstablishError::TimedOut(name, timeout)),
+    }
 }
 
 /// Controller for a Resource `K`
@@ -533,6 +1008,14 @@ impl Config {
 ///     Ok(())
 /// }
 /// ```
+///
+/// ## Testing
+///
+/// All timing in the controller (requeues, debouncing and the backoff of its watchers) follows the
+/// clock of the Tokio runtime, see [`TokioClock`].
+/// Tests can pause time with `#[tokio::test(start_paused = true)]` and move it forward with
+/// [`tokio::time::advance`] to verify requeue timing without waiting in real time.
+/// Alternatively, [`Controller::with_clock`] installs a [`Clock`] that only the controller follows.
 pub struct Controller<K>
 where
     K: Clone + Resource + Debug + 'static,
@@ -549,9 +1032,82 @@ where
     /// However, note that they *will* keep running until their next yield point (`.await`),
     /// blocking [`tokio::runtime::Runtime`] destruction (unless you follow up by calling [`std::process::exit`] after `run`).
     forceful_shutdown_selector: Vec<BoxFuture<'static, ()>>,
+    /// The watch of the main resource, turned into triggers by [`run`](crate::Controller::run).
+    /// Unset when the controller was created from a stream.
+    main_watch: Option<MainWatch<K>>,
     dyntype: K::DynamicType,
     reader: Store<K>,
     config: Config,
+    introspection: Option<ControllerIntrospection<K>>,
+    reconcile_key: Option<ReconcileKey<K>>,
+    /// The client of the main [`Api`], unset when the controller was created from a stream
+    client: Option<Client>,
+    fresh_read: Option<FreshRead<K>>,
+}
+
+/// Whether an update of an object from `old` to `new` should trigger a reconcile
+type ChangeFilter<K> = Box<dyn Fn(&K, &K) -> bool + Send>;
+
+/// The key that reconcile requests for an object are deduplicated by, see [`Controller::with_reconcile_key`]
+type ReconcileKey<K> = Arc<dyn Fn(&K) -> ObjectRef<K> + Send + Sync>;
+
+/// Reads the latest version of an object from the apiserver, see [`Controller::reconcile_with_fresh_read`]
+type FreshRead<K> =
+    Arc<dyn Fn(&ObjectRef<K>) -> BoxFuture<'static, kube_client::Result<Option<K>>> + Send + Sync>;
+
+fn fresh_reader<K>(client: Client) -> FreshRead<K>
+where
+    K: Resource + DeserializeOwned + Send + 'static,
+{
+    Arc::new(move |obj_ref| {
+        // `K` may have either scope, so the object is read through an `Api` of the erased type
+        let resource = ApiResource::erase::<K>(&obj_ref.dyntype);
+        let api = match &obj_ref.namespace {
+            Some(ns) => Api::<DynamicObject>::namespaced_with(client.clone(), ns, &resource),
+            None => Api::<DynamicObject>::all_with(client.clone(), &resource),
+        };
+        let name = obj_ref.name.clone();
+        async move {
+            api.get_opt(&name)
+                .await?
+                .map(|obj| serde_json::to_value(obj).and_then(serde_json::from_value))
+                .transpose()
+                .map_err(kube_client::Error::SerdeError)
+        }
+        .boxed()
+    })
+}
+
+struct MainWatch<K>
+where
+    K: Resource + 'static,
+    K::DynamicType: Eq + Hash,
+{
+    stream: BoxStream<'static, watcher::Result<watcher::Event<K>>>,
+    writer: Writer<K>,
+    filter: Option<ChangeFilter<K>>,
+}
+
+/// Reflect a watch into `writer`, and emit the objects whose updates pass the `filter`
+///
+/// Created objects always pass, while deleted objects never do (like with `applied_objects`).
+fn filtered_changes<K>(
+    stream: impl Stream<Item = watcher::Result<watcher::Event<K>>> + Send + 'static,
+    writer: Writer<K>,
+    filter: ChangeFilter<K>,
+) -> impl Stream<Item = watcher::Result<K>> + Send
+where
+    K: Clone + Resource + Send + Sync + 'static,
+    K::DynamicType: Eq + Hash + Clone + Send,
+{
+    stream.reflect_changes(writer).try_filter_map(move |change| {
+        let obj = match change {
+            Change::Added(obj) => Some(obj),
+            Change::Modified { old, new } => filter(&old, &new).then_some(new),
+            Change::Deleted(_) => None,
+        };
+        future::ok(obj.map(|obj| K::clone(&obj)))
+    })
 }
 
 impl<K> Controller<K>
@@ -591,15 +1147,9 @@ where
     pub fn new_with(main_api: Api<K>, wc: watcher::Config, dyntype: K::DynamicType) -> Self {
         let writer = Writer::<K>::new(dyntype.clone());
         let reader = writer.as_reader();
-        let mut trigger_selector = stream::SelectAll::new();
-        let self_watcher = trigger_self(
-            reflector(writer, watcher(main_api, wc)).applied_objects(),
-            dyntype.clone(),
-        )
-        .boxed();
-        trigger_selector.push(self_watcher);
+        let client = main_api.clone().into_client();
         Self {
-            trigger_selector,
+            trigger_selector: stream::SelectAll::new(),
             trigger_backoff: Box::<DefaultBackoff>::default(),
             graceful_shutdown_selector: vec![
                 // Fallback future, ensuring that we never terminate if no additional futures are added to the selector
@@ -609,9 +1159,18 @@ where
                 // Fallback future, ensuring that we never terminate if no additional futures are added to the selector
                 future::pending().boxed(),
             ],
+            main_watch: Some(MainWatch {
+                stream: watcher(main_api, wc).boxed(),
+                writer,
+                filter: None,
+            }),
             dyntype,
             reader,
             config: Default::default(),
+            introspection: None,
+            reconcile_key: None,
+            client: Some(client),
+            fresh_read: None,
         }
     }
 
@@ -695,9 +1254,14 @@ where
                 // Fallback future, ensuring that we never terminate if no additional futures are added to the selector
                 future::pending().boxed(),
             ],
+            main_watch: None,
             dyntype,
             reader,
             config: Default::default(),
+            introspection: None,
+            reconcile_key: None,
+            client: None,
+            fresh_read: None,
         }
     }
 
@@ -708,6 +1272,246 @@ where
         self
     }
 
+    /// Only reconcile updates of the main resource that change the value of a [`Predicate`]
+    ///
+    /// Each update of an object is compared against the previous version of the object in the [`store`](Self::store),
+    /// and only triggers a reconcile if the predicate differs between them (or cannot be computed for either).
+    /// Newly created objects always trigger a reconcile.
+    /// This is the equivalent of the predicates of controller-runtime, e.g. `GenerationChangedPredicate`:
+    ///
+    /// ```no_run
+    /// # use k8s_openapi::api::apps::v1::Deployment;
+    /// # use kube::runtime::{controller::{Action, Controller}, predicates, watcher};
+    /// # use kube::{Api, Client, Error};
+    /// # use futures::StreamExt;
+    /// # use std::sync::Arc;
+    /// # async fn reconcile(_: Arc<Deployment>, _: Arc<()>) -> Result<Action, Error> { Ok(Action::await_change()) }
+    /// # fn error_policy(_: Arc<Deployment>, _: &kube::Error, _: Arc<()>) -> Action { Action::await_change() }
+    /// # async fn doc(client: Client) {
+    /// // ignore updates that only touch the status or metadata of deployments
+    /// Controller::new(Api::<Deployment>::all(client), watcher::Config::default())
+    ///     .with_predicate(predicates::generation)
+    ///     .run(reconcile, error_policy, Arc::new(()))
+    ///     .for_each(|_| std::future::ready(()))
+    ///     .await;
+    /// # }
+    /// ```
+    ///
+    /// Stock predicates are found in [`predicates`](crate::predicates), and can be combined with [`Predicate::combine`]
+    /// and [`Predicate::fallback`]. A later call replaces the predicate of an earlier one.
+    ///
+    /// The predicate also applies to the objects listed when the watcher (re)starts, so objects that did not
+    /// change while the watch was down are not reconciled again. Use [`Action::requeue`] for periodic reconciles.
+    /// Triggers from related objects (like [`owns`](Self::owns)) are not filtered.
+    ///
+    /// This has no effect on controllers created with [`for_stream`](Self::for_stream), filter that stream with
+    /// [`predicate_filter`](crate::WatchStreamExt::predicate_filter) instead.
+    ///
+    /// **NB**: This is constructor requires an [`unstable`](https://github.com/kube-rs/kube/blob/main/kube-runtime/Cargo.toml#L17-L21) feature.
+    ///
+    /// [`Predicate`]: crate::Predicate
+    /// [`Predicate::combine`]: crate::Predicate::combine
+    /// [`Predicate::fallback`]: crate::Predicate::fallback
+    #[cfg(feature = "unstable-runtime-predicates")]
+    #[must_use]
+    pub fn with_predicate(mut self, predicate: impl crate::Predicate<K> + Send + 'static) -> Self {
+        if let Some(main_watch) = &mut self.main_watch {
+            main_watch.filter = Some(Box::new(move |old, new| {
+                match (predicate.hash_property(old), predicate.hash_property(new)) {
+                    (Some(old), Some(new)) => old != new,
+                    _ => true,
+                }
+            }));
+        }
+        self
+    }
+
+    /// Deduplicate reconciles by a key derived from each object, instead of by the object itself
+    ///
+    /// By default, every object is reconciled on its own. With a reconcile key, objects that map to the
+    /// same key share a single slot in the queue, so a burst of changes to related objects (like all the
+    /// objects labelled with the same parent) results in a single reconcile for the group, which never
+    /// runs concurrently with itself.
+    ///
+    /// The key is an [`ObjectRef`] that identifies the group, and does not have to refer to an existing object.
+    /// The [`store`](Self::store) is indexed by the key, and the reconciler is called with the first object of the
+    /// group (by namespace and name) that is still in the store. It can fetch the whole group with
+    /// [`Store::get_indexed`]:
+    ///
+    /// ```no_run
+    /// # use kube::runtime::{controller::{Action, Controller}, reflector::{ObjectRef, Store}, watcher};
+    /// # use kube::{Api, Client, Error, ResourceExt};
+    /// # use k8s_openapi::api::core::v1::ConfigMap;
+    /// # use futures::StreamExt;
+    /// # use std::sync::Arc;
+    /// fn parent_key(cm: &ConfigMap) -> ObjectRef<ConfigMap> {
+    ///     let parent = cm.labels().get("example.com/parent").cloned().unwrap_or_else(|| cm.name_any());
+    ///     ObjectRef::new(&parent).within(&cm.namespace().unwrap_or_default())
+    /// }
+    ///
+    /// async fn reconcile(cm: Arc<ConfigMap>, store: Arc<Store<ConfigMap>>) -> Result<Action, Error> {
+    ///     let siblings = store.get_indexed(&parent_key(&cm));
+    ///     // reconcile the whole group of config maps
+    ///     Ok(Action::await_change())
+    /// }
+    /// # fn error_policy(_: Arc<ConfigMap>, _: &Error, _: Arc<Store<ConfigMap>>) -> Action { Action::await_change() }
+    /// # async fn doc(client: Client) {
+    /// let controller = Controller::new(Api::<ConfigMap>::all(client), watcher::Config::default())
+    ///     .with_reconcile_key(parent_key);
+    /// let store = Arc::new(controller.store());
+    /// controller
+    ///     .run(reconcile, error_policy, store)
+    ///     .for_each(|_| std::future::ready(()))
+    ///     .await;
+    /// # }
+    /// ```
+    ///
+    /// The key is computed from the object in the store when a reconcile is requested, so it applies to
+    /// triggers from related objects and [`reconcile_on`](Self::reconcile_on) as well. Requests for objects
+    /// that are no longer in the store keep the key of the object itself. The results of the controller stream,
+    /// requeues, and [introspection](Self::introspect) all refer to the key.
+    ///
+    /// Controllers created with [`for_stream`](Self::for_stream) do not own the writer of their store,
+    /// which has to be indexed by the same key with [`Writer::with_index`].
+    #[must_use]
+    pub fn with_reconcile_key(mut self, key: impl Fn(&K) -> ObjectRef<K> + Send + Sync + 'static) -> Self {
+        let key: ReconcileKey<K> = Arc::new(key);
+        if let Some(main_watch) = &mut self.main_watch {
+            main_watch.writer.set_index(key.clone());
+        }
+        self.reconcile_key = Some(key);
+        self
+    }
+
+    /// Read each object from the apiserver right before it is reconciled, rather than using the cached version
+    ///
+    /// By default the reconciler is passed the object from the [`store`](Self::store), which lags slightly behind
+    /// the apiserver, since it is only updated as watch events arrive. With this, the object is read by `client`
+    /// with a quorum read (a `get` without `resourceVersion`) at the start of every reconcile, so that the reconciler
+    /// never acts on an outdated spec, for instance right after a change that has not been watched yet.
+    ///
+    /// This trades latency and apiserver load for consistency: every reconcile makes an extra request, which
+    /// goes through to etcd. Objects that turn out to be deleted are not reconciled, and when the read fails
+    /// a warning is logged and the reconcile is retried after a few seconds.
+    #[must_use]
+    pub fn reconcile_with_fresh_read(mut self, client: Client) -> Self {
+        self.fresh_read = Some(fresh_reader(client));
+        self
+    }
+
+    /// Specify the field manager name used for writes made on behalf of this controller
+    ///
+    /// This is a shorthand for setting [`Config::field_manager`].
+    /// The name is not applied to the reconciler's writes automatically (it only names the event
+    /// [`Reporter`] of [`Controller::run_with_recorder`] by default), so it takes effect once it is read
+    /// back via [`Controller::field_manager`],
+    /// typically by setting it as the default on the [`Client`](kube_client::Client) used inside the reconciler:
+    ///
+    /// ```no_run
+    /// # use k8s_openapi::api::core::v1::ConfigMap;
+    /// # use kube::{Api, Client};
+    /// # use kube::runtime::Controller;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: Client = todo!();
+    /// struct Context {
+    ///     client: Client,
+    /// }
+    /// let controller = Controller::new(Api::<ConfigMap>::all(client.clone()), Default::default())
+    ///     .with_field_manager("configmap-controller");
+    /// let context = Context {
+    ///     // `Api::patch` calls without an explicit field manager now use "configmap-controller"
+    ///     client: client.with_field_manager(controller.field_manager()),
+    /// };
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_field_manager(mut self, manager: &str) -> Self {
+        self.config.field_manager = Some(manager.to_string());
+        self
+    }
+
+    /// The field manager name for writes made on behalf of this controller
+    ///
+    /// Returns the name set through [`Controller::with_field_manager`] or [`Config::field_manager`].
+    /// Otherwise a name is derived from the file name of the running binary,
+    /// falling back to `<kind>-controller` when that cannot be determined.
+    #[must_use]
+    pub fn field_manager(&self) -> String {
+        if let Some(manager) = &self.config.field_manager {
+            return manager.clone();
+        }
+        std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+            .filter(|stem| !stem.is_empty())
+            .unwrap_or_else(|| format!("{}-controller", K::kind(&self.dyntype).to_lowercase()))
+    }
+
+    /// Limit the writes made on behalf of this controller
+    ///
+    /// This is a shorthand for setting [`Config::write_qps`].
+    /// When the controller runs, the limit is set on the [`Client`] of the [`Api`] that the controller was
+    /// created with (see [`Client::set_write_limit`]), and so applies to all clones of that client,
+    /// such as the one that is usually handed to the reconciler:
+    ///
+    /// ```no_run
+    /// # use k8s_openapi::api::core::v1::ConfigMap;
+    /// # use kube::{Api, Client};
+    /// # use kube::runtime::Controller;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: Client = todo!();
+    /// struct Context {
+    ///     // creates, patches and deletes wait for the rate limit, gets and lists are not limited
+    ///     client: Client,
+    /// }
+    /// let controller = Controller::new(Api::<ConfigMap>::all(client.clone()), Default::default())
+    ///     .with_write_qps(5.0, 10);
+    /// let context = Context { client };
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Other clients, and the clients of controllers created with `Controller::for_stream`,
+    /// can be limited with [`Controller::write_client`].
+    /// How much the writes were delayed is reported by [`Client::rate_limit_stats`].
+    ///
+    /// Reads are not limited, since the watches of the controller are long-lived and reconcilers mostly
+    /// read from the [`store`](Self::store). To limit all requests of the [`Client`], set
+    /// [`Config::qps`](kube_client::Config::qps) when creating it, or add a separate [`RateLimitLayer`]
+    /// through [`Client::with_rate_limit`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `qps` is not a positive number, or if `burst` is zero.
+    #[must_use]
+    pub fn with_write_qps(mut self, qps: f64, burst: u32) -> Self {
+        self.config = self.config.write_qps(qps, burst);
+        self
+    }
+
+    /// Tell when requeues are due with `clock`, instead of the clock of the Tokio runtime
+    ///
+    /// See [`Config::clock`].
+    #[must_use]
+    pub fn with_clock(mut self, clock: impl Clock) -> Self {
+        self.config = self.config.clock(clock);
+        self
+    }
+
+    /// Apply the write limit of this controller to `client`
+    ///
+    /// Returns `client` unchanged unless a limit was set through [`Controller::with_write_qps`]
+    /// or [`Config::write_qps`]. All clients returned by this method share the same limit,
+    /// which is also the limit set on the client of the controller when it runs.
+    #[must_use]
+    pub fn write_client(&self, client: Client) -> Client {
+        match &self.config.write_limit {
+            Some(limit) => client.with_rate_limit(limit.clone()),
+            None => client,
+        }
+    }
+
     /// Specify the backoff policy for "trigger" watches
     ///
     /// This includes the core watch, as well as auxilary watches introduced by [`Self::owns`] and [`Self::watches`].
@@ -725,6 +1529,53 @@ where
         self.reader.clone()
     }
 
+    /// Retrieve a handle to inspect the reconcile queue before starting the controller
+    ///
+    /// Introspection is only enabled by calling this method, so that controllers that are not inspected do not
+    /// keep a copy of their queue. All handles of a controller share the same state.
+    /// See [`ControllerIntrospection`].
+    pub fn introspect(&mut self) -> ControllerIntrospection<K> {
+        self.introspection.get_or_insert_with(Default::default).clone()
+    }
+
+    /// Wait for the `CustomResourceDefinition` of `K` to be established
+    ///
+    /// Operators that install their own CRDs on startup race the apiserver when they start
+    /// the controller right away, as the watches fail until the new api is being served.
+    /// Awaiting this before [`run`](Controller::run) removes that race.
+    ///
+    /// The CRD is looked up as `<plural>.<group>` of `K`, so this is only meaningful for custom resources.
+    ///
+    /// ```no_run
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+    /// use kube::{Api, runtime::{watcher, Controller}};
+    /// use std::time::Duration;
+    /// # #[derive(Clone, Debug, serde::Deserialize, kube::CustomResource, serde::Serialize, schemars::JsonSchema)]
+    /// # #[kube(group = "clux.dev", version = "v1", kind = "Foo")]
+    /// # struct FooSpec {}
+    /// let controller = Controller::new(Api::<Foo>::all(client.clone()), watcher::Config::default());
+    /// controller
+    ///     .await_crd_established(Api::<CustomResourceDefinition>::all(client), Duration::from_secs(30))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`CrdEstablishError::TimedOut`] if the CRD is not established within `timeout`,
+    /// with [`CrdEstablishError::NamesRejected`] if its names conflict with another CRD,
+    /// or with [`CrdEstablishError::ProbeFailed`] if the CRD could not be watched.
+    pub async fn await_crd_established(
+        &self,
+        crd_api: Api<CustomResourceDefinition>,
+        timeout: Duration,
+    ) -> Result<(), CrdEstablishError> {
+        await_crd_established::<K>(crd_api, &self.dyntype, timeout).await
+    }
+
     /// Specify `Child` objects which `K` owns and should be watched
     ///
     /// Takes an [`Api`] object that determines how the `Controller` listens for changes to the `Child`.
@@ -937,6 +1788,43 @@ where
         self
     }
 
+    /// Specify Kubernetes `Event`s that should trigger reconciliation of the objects they are about
+    ///
+    /// Events are mapped to the `K` they are about through their `involvedObject` (for `core/v1` events)
+    /// or `regarding` (for `events.k8s.io/v1` events) reference, and events about other kinds are ignored.
+    /// This allows reacting to things that are only visible as events, such as a container being `OOMKilled`.
+    ///
+    /// Use the [`watcher::Config`] to limit the events watched, for example to the `Warning` type
+    /// with a field selector of `type=Warning`.
+    ///
+    /// ```no_run
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// use k8s_openapi::api::core::v1::{Event, Pod};
+    /// use kube::{Api, runtime::{watcher, Controller}};
+    ///
+    /// let controller = Controller::new(Api::<Pod>::all(client.clone()), watcher::Config::default())
+    ///     .watches_events(
+    ///         Api::<Event>::all(client),
+    ///         watcher::Config::default().fields("type=Warning"),
+    ///     );
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn watches_events<E>(self, api: Api<E>, wc: watcher::Config) -> Self
+    where
+        E: InvolvedObject + Clone + Resource<DynamicType = ()> + DeserializeOwned + Debug + Send + 'static,
+        K::DynamicType: Send + Sync,
+    {
+        let dyntype = self.dyntype.clone();
+        self.watches(api, wc, move |event| {
+            event
+                .involved_object()
+                .and_then(|reference| ObjectRef::from_object_reference_with(reference, dyntype.clone()))
+        })
+    }
+
     /// Trigger the reconciliation process for a stream of `Other` objects related to a `K`
     ///
     /// Same as [`Controller::watches`], but instead of an `Api`, a stream of resources is used.
@@ -1254,7 +2142,34 @@ where
         ReconcilerFut: TryFuture<Ok = Action> + Send + 'static,
         ReconcilerFut::Error: std::error::Error + Send + 'static,
     {
-        applier(
+        let fail_on_permanent_watch_errors = self.config.fail_on_permanent_watch_errors;
+        if let (Some(client), Some(limit)) = (&self.client, &self.config.write_limit) {
+            client.set_write_limit(Some(limit.clone()));
+        }
+        let mut trigger_selector = self.trigger_selector;
+        if let Some(MainWatch {
+            stream,
+            writer,
+            filter,
+        }) = self.main_watch
+        {
+            let dyntype = self.dyntype.clone();
+            let requests = if let Some(filter) = filter {
+                trigger_self(filtered_changes(stream, writer, filter), dyntype).boxed()
+            } else {
+                // the objects are shared with the store rather than cloned into it, only their refs are needed here
+                let objects = reflector_shared(writer, stream).applied_objects();
+                trigger_with(objects, move |obj: Arc<K>| {
+                    Some(ReconcileRequest {
+                        obj_ref: ObjectRef::from_obj_with(&*obj, dyntype.clone()),
+                        reason: ReconcileReason::ObjectUpdated,
+                    })
+                })
+                .boxed()
+            };
+            trigger_selector.push(requests);
+        }
+        introspected_applier(
             move |obj, ctx| {
                 CancelableJoinHandle::spawn(
                     reconciler(obj, ctx).into_future().in_current_span(),
@@ -1264,11 +2179,99 @@ where
             error_policy,
             context,
             self.reader,
-            StreamBackoff::new(self.trigger_selector, self.trigger_backoff)
+            StreamBackoff::new(trigger_selector, self.trigger_backoff)
                 .take_until(future::select_all(self.graceful_shutdown_selector)),
             self.config,
+            ApplierOptions {
+                introspection: self.introspection,
+                reconcile_key: self.reconcile_key,
+                fresh_read: self.fresh_read,
+            },
         )
         .take_until(futures::future::select_all(self.forceful_shutdown_selector))
+        .scan(false, move |failed, res| {
+            if *failed {
+                return future::ready(None);
+            }
+            if let Err(Error::QueueError(err)) = &res {
+                if fail_on_permanent_watch_errors && err.is_permanent() {
+                    tracing::error!(error = %err, "stopping controller due to a permanent watch error");
+                    *failed = true;
+                }
+            }
+            future::ready(Some(res))
+        })
+    }
+
+    /// Start the applier stream, passing a [`Recorder`] for the reconciled object to the `reconciler`
+    ///
+    /// Same as [`Controller::run`], but each `reconciler` call also gets a [`Recorder`] whose events
+    /// are about the object being reconciled, so publishing an event is a single call.
+    /// Events are attributed to the [`Reporter`] set with [`Config::reporter`].
+    ///
+    /// ```no_run
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// use futures::StreamExt;
+    /// use k8s_openapi::api::core::v1::ConfigMap;
+    /// use kube::{
+    ///     runtime::{
+    ///         controller::{Action, Controller},
+    ///         events::{Event, EventType, Recorder},
+    ///         watcher,
+    ///     },
+    ///     Api,
+    /// };
+    /// use std::sync::Arc;
+    ///
+    /// async fn reconcile(cm: Arc<ConfigMap>, _ctx: Arc<()>, recorder: Recorder) -> Result<Action, kube::Error> {
+    ///     recorder
+    ///         .publish(Event {
+    ///             type_: EventType::Normal,
+    ///             reason: "Reconciled".into(),
+    ///             note: None,
+    ///             action: "Reconcile".into(),
+    ///             secondary: None,
+    ///         })
+    ///         .await?;
+    ///     Ok(Action::await_change())
+    /// }
+    ///
+    /// Controller::new(Api::<ConfigMap>::all(client.clone()), watcher::Config::default())
+    ///     .run_with_recorder(reconcile, |_, _, _| Action::await_change(), Arc::new(()), client)
+    ///     .for_each(|_| futures::future::ready(()))
+    ///     .await;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// See [`Recorder`] for the RBAC rules required to publish events.
+    pub fn run_with_recorder<ReconcilerFut, Ctx>(
+        self,
+        mut reconciler: impl FnMut(Arc<K>, Arc<Ctx>, Recorder) -> ReconcilerFut,
+        error_policy: impl Fn(Arc<K>, &ReconcilerFut::Error, Arc<Ctx>) -> Action,
+        context: Arc<Ctx>,
+        client: Client,
+    ) -> impl Stream<Item = Result<(ObjectRef<K>, Action), Error<ReconcilerFut::Error, watcher::Error>>>
+    where
+        K::DynamicType: Debug + Unpin,
+        ReconcilerFut: TryFuture<Ok = Action> + Send + 'static,
+        ReconcilerFut::Error: std::error::Error + Send + 'static,
+    {
+        let reporter = self.config.reporter.clone().unwrap_or_else(|| Reporter {
+            controller: self.field_manager(),
+            instance: std::env::var("HOSTNAME").ok(),
+        });
+        let dyntype = self.dyntype.clone();
+        let client = self.write_client(client);
+        self.run(
+            move |obj, ctx| {
+                let recorder = Recorder::new(client.clone(), reporter.clone(), obj.object_ref(&dyntype));
+                reconciler(obj, ctx, recorder)
+            },
+            error_policy,
+            context,
+        )
     }
 }
 
@@ -1276,16 +2279,22 @@ where
 mod tests {
     use std::{convert::Infallible, sync::Arc, time::Duration};
 
-    use super::{Action, APPLIER_REQUEUE_BUF_SIZE};
+    use super::{
+        filtered_changes, introspected_applier, Action, ApplierOptions, ControllerIntrospection, FreshRead,
+        ReconcileKey, APPLIER_REQUEUE_BUF_SIZE,
+    };
     use crate::{
         applier,
-        reflector::{self, ObjectRef},
+        reflector::{self, store::Writer, ObjectRef},
         watcher::{self, metadata_watcher, watcher, Event},
         Config, Controller,
     };
-    use futures::{pin_mut, Stream, StreamExt, TryStreamExt};
-    use k8s_openapi::api::core::v1::ConfigMap;
-    use kube_client::{core::ObjectMeta, Api, Resource};
+    use futures::{future, pin_mut, FutureExt, Stream, StreamExt, TryStreamExt};
+    use k8s_openapi::{api::core::v1::ConfigMap, apimachinery::pkg::apis::meta::v1::Time, chrono::Utc};
+    use kube_client::{
+        core::{ErrorResponse, ObjectMeta},
+        Api, Resource, ResourceExt,
+    };
     use serde::de::DeserializeOwned;
     use tokio::time::timeout;
 
@@ -1319,6 +2328,14 @@ mod tests {
                 Arc::new(()),
             ),
         );
+        assert_send(
+            Controller::new(mock_type::<Api<ConfigMap>>(), Default::default()).run_with_recorder(
+                |_, _, _| async { Ok(mock_type::<Action>()) },
+                |_: Arc<ConfigMap>, _: &std::io::Error, _| mock_type::<Action>(),
+                Arc::new(()),
+                mock_type(),
+            ),
+        );
     }
 
     // not #[test] because we don't want to actually run it, we just want to
@@ -1335,6 +2352,34 @@ mod tests {
         ));
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn write_limit_applies_to_the_client_of_the_controller() {
+        let (mock_service, handle) =
+            tower_test::mock::pair::<http::Request<hyper::Body>, http::Response<hyper::Body>>();
+        tokio::spawn(async move {
+            pin_mut!(handle);
+            while let Some((_, send)) = handle.next_request().await {
+                send.send_response(http::Response::new(hyper::Body::from("{}")));
+            }
+        });
+        let client = kube_client::Client::new(mock_service, "default");
+        let controller =
+            Controller::new(Api::<ConfigMap>::all(client.clone()), Default::default()).with_write_qps(1.0, 1);
+        let _applier = controller.run(
+            |_, _| async { Ok::<_, Infallible>(Action::await_change()) },
+            |_, _, _| Action::await_change(),
+            Arc::new(()),
+        );
+
+        let start = tokio::time::Instant::now();
+        for _ in 0..2 {
+            let request = http::Request::post("/api/v1/namespaces/default/configmaps").body(vec![]);
+            client.request_text(request.unwrap()).await.unwrap();
+        }
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+        assert_eq!(client.rate_limit_stats().delayed, 1);
+    }
+
     #[tokio::test]
     async fn applier_must_not_deadlock_if_reschedule_buffer_fills() {
         // This tests that `applier` handles reschedule queue backpressure correctly, by trying to flood it with no-op reconciles
@@ -1397,4 +2442,505 @@ mod tests {
         .expect("applier cleanup timeout expired, individual reconciler likely deadlocked?")
         .unwrap();
     }
+
+    #[tokio::test]
+    async fn filtered_changes_drops_updates_rejected_by_filter() {
+        let cm = |name: &str, rv: &str, generation: i64| ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: Some("default".to_string()),
+                resource_version: Some(rv.to_string()),
+                generation: Some(generation),
+                ..ObjectMeta::default()
+            },
+            ..ConfigMap::default()
+        };
+        let events = futures::stream::iter([
+            Ok(Event::Restarted(vec![cm("a", "1", 1), cm("b", "1", 1)])),
+            // status-only update
+            Ok(Event::Applied(cm("a", "2", 1))),
+            Ok(Event::Applied(cm("b", "2", 2))),
+            Ok(Event::Deleted(cm("a", "3", 1))),
+            Ok(Event::Applied(cm("a", "4", 1))),
+            // relist without changes
+            Ok(Event::Restarted(vec![cm("a", "4", 1), cm("b", "2", 2)])),
+        ]);
+        let (reader, writer) = reflector::store();
+        let triggered = filtered_changes(
+            events,
+            writer,
+            Box::new(|old: &ConfigMap, new: &ConfigMap| old.metadata.generation != new.metadata.generation),
+        )
+        .map_ok(|cm| {
+            format!(
+                "{}@{}",
+                cm.metadata.name.unwrap(),
+                cm.metadata.resource_version.unwrap()
+            )
+        })
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+        assert_eq!(triggered, ["a@1", "b@1", "b@2", "a@4"]);
+        assert_eq!(reader.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn applier_must_skip_deleted_objects_when_ignore_deleted() {
+        let cm = |name: &str, deleting: bool, finalizers: &[&str]| ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: Some("default".to_string()),
+                deletion_timestamp: deleting.then(|| Time(Utc::now())),
+                finalizers: Some(finalizers.iter().map(|&f| f.to_owned()).collect()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (store_rx, mut store_tx) = reflector::store();
+        let objs = [
+            cm("live", false, &[]),
+            cm("deleting", true, &[]),
+            cm("finalizing", true, &["example.com/cleanup"]),
+        ];
+        for obj in &objs {
+            store_tx.apply_watcher_event(&watcher::Event::Applied(obj.clone()));
+        }
+        let (queue_tx, queue_rx) = futures::channel::mpsc::unbounded::<ObjectRef<ConfigMap>>();
+        for obj_ref in objs
+            .iter()
+            .map(ObjectRef::from_obj)
+            .chain([ObjectRef::new("gone").within("default")])
+        {
+            queue_tx.unbounded_send(obj_ref).unwrap();
+        }
+
+        let applier = applier(
+            |_: Arc<ConfigMap>, _| Box::pin(async move { Ok::<_, Infallible>(Action::await_change()) }),
+            |_: Arc<ConfigMap>, _: &Infallible, _| todo!(),
+            Arc::new(()),
+            store_rx,
+            queue_rx.map(Result::<_, Infallible>::Ok),
+            Config::default().ignore_deleted(true),
+        )
+        .map_ok(|(obj_ref, _)| obj_ref.name);
+        pin_mut!(applier);
+        let mut reconciled = timeout(
+            Duration::from_secs(10),
+            applier.as_mut().take(2).try_collect::<Vec<_>>(),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        reconciled.sort();
+        drop(queue_tx);
+        // the remaining requests are skipped rather than reported as `ObjectNotFound`
+        let rest = timeout(Duration::from_secs(10), applier.try_collect::<Vec<_>>())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(reconciled, vec!["finalizing", "live"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn applier_must_deduplicate_requests_by_reconcile_key() {
+        let cm = |name: &str, parent: &str| ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: Some("default".to_string()),
+                labels: Some([("parent".to_string(), parent.to_string())].into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let parent_key: ReconcileKey<ConfigMap> =
+            Arc::new(|cm: &ConfigMap| ObjectRef::new(&cm.labels()["parent"]).within("default"));
+        let mut store_tx = Writer::default();
+        store_tx.set_index(parent_key.clone());
+        let store_rx = store_tx.as_reader();
+        let objs = [cm("a-1", "a"), cm("a-2", "a"), cm("b-1", "b")];
+        let (queue_tx, queue_rx) = futures::channel::mpsc::unbounded::<ObjectRef<ConfigMap>>();
+        for obj in &objs {
+            store_tx.apply_watcher_event(&watcher::Event::Applied(obj.clone()));
+            queue_tx.unbounded_send(ObjectRef::from_obj(obj)).unwrap();
+        }
+
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let applier = introspected_applier(
+            |obj: Arc<ConfigMap>, _| {
+                received.lock().unwrap().push(obj.name_any());
+                Box::pin(async move { Ok::<_, Infallible>(Action::await_change()) })
+            },
+            |_: Arc<ConfigMap>, _: &Infallible, _| todo!(),
+            Arc::new(()),
+            store_rx,
+            queue_rx.map(Result::<_, Infallible>::Ok),
+            Config::default().debounce(Duration::from_millis(100)),
+            ApplierOptions {
+                reconcile_key: Some(parent_key),
+                ..ApplierOptions::default()
+            },
+        );
+        pin_mut!(applier);
+        let mut reconciled = applier
+            .as_mut()
+            .take(2)
+            .map_ok(|(obj_ref, _)| obj_ref.name)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        reconciled.sort();
+        drop(queue_tx);
+        assert!(applier.try_collect::<Vec<_>>().await.unwrap().is_empty());
+        // one reconcile per key, with the first object of the group
+        assert_eq!(reconciled, ["a", "b"]);
+        let mut received = received.lock().unwrap().clone();
+        received.sort();
+        assert_eq!(received, ["a-1", "b-1"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn applier_must_reconcile_freshly_read_objects() {
+        let cm = |name: &str, value: &str| ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: Some("default".to_string()),
+                ..Default::default()
+            },
+            data: Some([("value".to_string(), value.to_string())].into()),
+            ..Default::default()
+        };
+        let (store_rx, mut store_tx) = reflector::store();
+        let (queue_tx, queue_rx) = futures::channel::mpsc::unbounded::<ObjectRef<ConfigMap>>();
+        for obj in [cm("live", "cached"), cm("deleted", "cached")] {
+            store_tx.apply_watcher_event(&watcher::Event::Applied(obj.clone()));
+            queue_tx.unbounded_send(ObjectRef::from_obj(&obj)).unwrap();
+        }
+
+        let fresh_read: FreshRead<ConfigMap> = Arc::new(move |obj_ref: &ObjectRef<ConfigMap>| {
+            let obj = (obj_ref.name == "live").then(|| cm("live", "fresh"));
+            future::ok(obj).boxed()
+        });
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let applier = introspected_applier(
+            |obj: Arc<ConfigMap>, _| {
+                received
+                    .lock()
+                    .unwrap()
+                    .push(obj.data.as_ref().unwrap()["value"].clone());
+                Box::pin(async move { Ok::<_, Infallible>(Action::await_change()) })
+            },
+            |_: Arc<ConfigMap>, _: &Infallible, _| todo!(),
+            Arc::new(()),
+            store_rx,
+            queue_rx.map(Result::<_, Infallible>::Ok),
+            Config::default(),
+            ApplierOptions {
+                fresh_read: Some(fresh_read),
+                ..ApplierOptions::default()
+            },
+        );
+        pin_mut!(applier);
+        let reconciled = applier
+            .as_mut()
+            .take(1)
+            .map_ok(|(obj_ref, _)| obj_ref.name)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        drop(queue_tx);
+        // the deleted object is skipped
+        assert!(applier.try_collect::<Vec<_>>().await.unwrap().is_empty());
+        assert_eq!(reconciled, ["live"]);
+        assert_eq!(*received.lock().unwrap(), ["fresh"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn applier_must_retry_failed_fresh_reads() {
+        let cm = ConfigMap {
+            metadata: ObjectMeta {
+                name: Some("cm".to_string()),
+                namespace: Some("default".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (store_rx, mut store_tx) = reflector::store();
+        store_tx.apply_watcher_event(&watcher::Event::Applied(cm.clone()));
+        let (queue_tx, queue_rx) = futures::channel::mpsc::unbounded::<ObjectRef<ConfigMap>>();
+        queue_tx.unbounded_send(ObjectRef::from_obj(&cm)).unwrap();
+
+        let reads = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let fresh_read: FreshRead<ConfigMap> = {
+            let reads = reads.clone();
+            Arc::new(move |_: &ObjectRef<ConfigMap>| {
+                let res = if reads.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
+                    Err(kube_client::Error::Api(ErrorResponse {
+                        status: "Failure".to_string(),
+                        message: "etcdserver: request timed out".to_string(),
+                        reason: "InternalError".to_string(),
+                        code: 500,
+                    }))
+                } else {
+                    Ok(Some(cm.clone()))
+                };
+                future::ready(res).boxed()
+            })
+        };
+        let applier = introspected_applier(
+            |_: Arc<ConfigMap>, _| Box::pin(async move { Ok::<_, Infallible>(Action::await_change()) }),
+            |_: Arc<ConfigMap>, _: &Infallible, _| todo!(),
+            Arc::new(()),
+            store_rx,
+            queue_rx.map(Result::<_, Infallible>::Ok),
+            Config::default(),
+            ApplierOptions {
+                fresh_read: Some(fresh_read),
+                ..ApplierOptions::default()
+            },
+        );
+        pin_mut!(applier);
+        let started = tokio::time::Instant::now();
+        let (obj_ref, _) = applier.try_next().await.unwrap().unwrap();
+        assert_eq!(obj_ref.name, "cm");
+        assert_eq!(reads.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert!(started.elapsed() >= super::FRESH_READ_RETRY_DELAY);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn applier_must_requeue_after_the_requested_duration() {
+        let cm = ConfigMap {
+            metadata: ObjectMeta {
+                name: Some("cm".to_string()),
+                namespace: Some("default".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (store_rx, mut store_tx) = reflector::store();
+        store_tx.apply_watcher_event(&watcher::Event::Applied(cm.clone()));
+        let (queue_tx, queue_rx) = futures::channel::mpsc::unbounded::<ObjectRef<ConfigMap>>();
+        queue_tx.unbounded_send(ObjectRef::from_obj(&cm)).unwrap();
+
+        let applier = applier(
+            |_: Arc<ConfigMap>, _| {
+                Box::pin(async { Ok::<_, Infallible>(Action::requeue(Duration::from_secs(60))) })
+            },
+            |_: Arc<ConfigMap>, _: &Infallible, _| todo!(),
+            Arc::new(()),
+            store_rx,
+            queue_rx.map(Result::<_, Infallible>::Ok),
+            Config::default(),
+        );
+        pin_mut!(applier);
+        applier.try_next().await.unwrap().unwrap();
+        let reconciled_at = tokio::time::Instant::now();
+
+        // time only moves when the runtime is idle, so the requeue fires exactly when it is due
+        applier.try_next().await.unwrap().unwrap();
+        assert_eq!(reconciled_at.elapsed(), Duration::from_secs(60));
+        drop(queue_tx);
+    }
+
+    /// A [`Clock`](crate::utils::Clock) that only moves when it is advanced
+    #[derive(Clone, Debug)]
+    struct ManualClock {
+        tx: Arc<tokio::sync::watch::Sender<tokio::time::Instant>>,
+        rx: tokio::sync::watch::Receiver<tokio::time::Instant>,
+    }
+
+    impl ManualClock {
+        fn new() -> Self {
+            let (tx, rx) = tokio::sync::watch::channel(tokio::time::Instant::now());
+            Self { tx: Arc::new(tx), rx }
+        }
+
+        fn advance(&self, duration: Duration) {
+            let now = *self.rx.borrow();
+            self.tx.send(now + duration).unwrap();
+        }
+    }
+
+    impl crate::utils::Clock for ManualClock {
+        fn now(&self) -> tokio::time::Instant {
+            *self.rx.borrow()
+        }
+
+        fn sleep_until(&self, deadline: tokio::time::Instant) -> future::BoxFuture<'static, ()> {
+            let mut rx = self.rx.clone();
+            Box::pin(async move {
+                while *rx.borrow() < deadline {
+                    if rx.changed().await.is_err() {
+                        return;
+                    }
+                }
+            })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn applier_must_requeue_by_its_clock() {
+        let cm = ConfigMap {
+            metadata: ObjectMeta {
+                name: Some("cm".to_string()),
+                namespace: Some("default".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (store_rx, mut store_tx) = reflector::store();
+        store_tx.apply_watcher_event(&watcher::Event::Applied(cm.clone()));
+        let (queue_tx, queue_rx) = futures::channel::mpsc::unbounded::<ObjectRef<ConfigMap>>();
+        queue_tx.unbounded_send(ObjectRef::from_obj(&cm)).unwrap();
+        let clock = ManualClock::new();
+
+        let applier = applier(
+            |_: Arc<ConfigMap>, _| {
+                Box::pin(async { Ok::<_, Infallible>(Action::requeue(Duration::from_secs(60))) })
+            },
+            |_: Arc<ConfigMap>, _: &Infallible, _| todo!(),
+            Arc::new(()),
+            store_rx,
+            queue_rx.map(Result::<_, Infallible>::Ok),
+            Config::default().clock(clock.clone()),
+        );
+        pin_mut!(applier);
+        applier.try_next().await.unwrap().unwrap();
+
+        // the time of the runtime does not affect the controller
+        assert!(timeout(Duration::from_secs(3600), applier.try_next())
+            .await
+            .is_err());
+        clock.advance(Duration::from_secs(59));
+        assert!(timeout(Duration::from_secs(1), applier.try_next()).await.is_err());
+        clock.advance(Duration::from_secs(1));
+        applier.try_next().await.unwrap().unwrap();
+        drop(queue_tx);
+    }
+
+    #[test]
+    fn requeue_jittered_must_stay_within_the_band() {
+        let base = Duration::from_secs(15);
+        let delays = (0..100)
+            .map(|_| {
+                Action::requeue_jittered(base, Duration::from_secs(5))
+                    .requeue_after
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert!(delays
+            .iter()
+            .all(|delay| (base..=base + Duration::from_secs(5)).contains(delay)));
+        assert!(delays.iter().any(|delay| *delay != delays[0]));
+        assert_eq!(
+            Action::requeue_jittered(base, Duration::ZERO),
+            Action::requeue(base)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn applier_must_publish_queue_and_results_to_introspection() {
+        let cm = |name: &str| ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: Some("default".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (store_rx, mut store_tx) = reflector::store();
+        let (queue_tx, queue_rx) = futures::channel::mpsc::unbounded::<ObjectRef<ConfigMap>>();
+        for name in ["ok", "failing"] {
+            store_tx.apply_watcher_event(&watcher::Event::Applied(cm(name)));
+            queue_tx.unbounded_send(ObjectRef::from_obj(&cm(name))).unwrap();
+        }
+
+        let introspection = ControllerIntrospection::default();
+        let applier = introspected_applier(
+            |obj: Arc<ConfigMap>, _| {
+                Box::pin(async move {
+                    match obj.metadata.name.as_deref() {
+                        Some("ok") => Ok(Action::requeue(Duration::from_secs(60))),
+                        _ => Err(std::io::Error::new(std::io::ErrorKind::Other, "broken")),
+                    }
+                })
+            },
+            |_, _, _| Action::requeue(Duration::from_secs(5)),
+            Arc::new(()),
+            store_rx,
+            queue_rx.map(Result::<_, Infallible>::Ok),
+            Config::default(),
+            ApplierOptions {
+                introspection: Some(introspection.clone()),
+                ..ApplierOptions::default()
+            },
+        );
+        pin_mut!(applier);
+        let started_at = tokio::time::Instant::now();
+        let results = applier.as_mut().take(2).collect::<Vec<_>>().await;
+        assert_eq!(results.len(), 2);
+        // let the requeues reach the scheduler
+        tokio::task::yield_now().await;
+        assert!(futures::poll!(applier.next()).is_pending());
+
+        let state = introspection.snapshot();
+        let queued = state
+            .queued
+            .iter()
+            .map(|(request, run_at)| (request.obj_ref.name.as_str(), *run_at - started_at))
+            .collect::<Vec<_>>();
+        assert_eq!(queued, [
+            ("failing", Duration::from_secs(5)),
+            ("ok", Duration::from_secs(60))
+        ]);
+        let ok = &state.last_results[&ObjectRef::from_obj(&cm("ok"))];
+        assert_eq!(ok.action, Action::requeue(Duration::from_secs(60)));
+        assert_eq!(ok.error, None);
+        let failing = &state.last_results[&ObjectRef::from_obj(&cm("failing"))];
+        assert_eq!(failing.action, Action::requeue(Duration::from_secs(5)));
+        assert_eq!(failing.error.as_deref(), Some("broken"));
+        drop(queue_tx);
+    }
+
+    #[tokio::test]
+    async fn controller_stops_on_permanent_watch_errors_when_enabled() {
+        let (mock_service, handle) =
+            tower_test::mock::pair::<http::Request<hyper::Body>, http::Response<hyper::Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.uri().path(), "/api/v1/namespaces/default/configmaps");
+            let status = serde_json::json!({
+                "kind": "Status",
+                "apiVersion": "v1",
+                "status": "Failure",
+                "message": "configmaps is forbidden",
+                "reason": "Forbidden",
+                "code": 403,
+            });
+            send.send_response(
+                http::Response::builder()
+                    .status(403)
+                    .body(hyper::Body::from(status.to_string()))
+                    .unwrap(),
+            );
+        });
+
+        let client = kube_client::Client::new(mock_service, "default");
+        let results = Controller::new(Api::<ConfigMap>::default_namespaced(client), Default::default())
+            .with_config(Config::default().fail_on_permanent_watch_errors(true))
+            .run(
+                |_, _| async { Ok::<_, Infallible>(Action::await_change()) },
+                |_: Arc<ConfigMap>, _: &Infallible, _| todo!(),
+                Arc::new(()),
+            )
+            .collect::<Vec<_>>();
+        let results = timeout(Duration::from_secs(10), results).await.unwrap();
+        assert!(matches!(
+            &results[..],
+            [Err(super::Error::QueueError(err))] if err.is_permanent()
+        ));
+        spawned.await.unwrap();
+    }
 }
@@ -1,5 +1,8 @@
-use super::future_hash_map::FutureHashMap;
-use crate::scheduler::{ScheduleRequest, Scheduler};
+use super::{future_hash_map::FutureHashMap, ControllerStats};
+use crate::{
+    scheduler::{ScheduleRequest, Scheduler},
+    utils::{Clock, TokioClock},
+};
 use futures::{future, Future, FutureExt, Stream, StreamExt};
 use pin_project::pin_project;
 use std::{
@@ -23,9 +26,9 @@ pub enum Error<ReadyErr> {
 /// already being processed then it will be held pending until the current item
 /// is finished.
 #[pin_project]
-pub struct Runner<T, R, F, MkF, Ready = future::Ready<Result<(), Infallible>>> {
+pub struct Runner<T, R, F, MkF, Ready = future::Ready<Result<(), Infallible>>, Clk = TokioClock> {
     #[pin]
-    scheduler: Scheduler<T, R>,
+    scheduler: Scheduler<T, R, Clk>,
     run_msg: MkF,
     slots: FutureHashMap<T, F>,
     #[pin]
@@ -33,9 +36,10 @@ pub struct Runner<T, R, F, MkF, Ready = future::Ready<Result<(), Infallible>>> {
     is_ready_to_execute: bool,
     stopped: bool,
     max_concurrent_executions: u16,
+    stats: Option<ControllerStats>,
 }
 
-impl<T, R, F, MkF> Runner<T, R, F, MkF>
+impl<T, R, F, MkF, Clk> Runner<T, R, F, MkF, future::Ready<Result<(), Infallible>>, Clk>
 where
     F: Future + Unpin,
     MkF: FnMut(&T) -> F,
@@ -43,7 +47,7 @@ where
     /// Creates a new [`Runner`]. [`max_concurrent_executions`] can be used to
     /// limit the number of items are run concurrently. It can be set to 0 to
     /// allow for unbounded concurrency.
-    pub fn new(scheduler: Scheduler<T, R>, max_concurrent_executions: u16, run_msg: MkF) -> Self {
+    pub fn new(scheduler: Scheduler<T, R, Clk>, max_concurrent_executions: u16, run_msg: MkF) -> Self {
         Self {
             scheduler,
             run_msg,
@@ -52,16 +56,23 @@ where
             is_ready_to_execute: false,
             stopped: false,
             max_concurrent_executions,
+            stats: None,
         }
     }
 
+    /// Publish the queue statistics to `stats` whenever the [`Runner`] is polled.
+    pub fn with_stats(mut self, stats: Option<ControllerStats>) -> Self {
+        self.stats = stats;
+        self
+    }
+
     /// Wait for `ready_to_execute_after` to complete before starting to run any scheduled tasks.
     ///
     /// `scheduler` will still be polled in the meantime.
     pub fn delay_tasks_until<Ready, ReadyErr>(
         self,
         ready_to_execute_after: Ready,
-    ) -> Runner<T, R, F, MkF, Ready>
+    ) -> Runner<T, R, F, MkF, Ready, Clk>
     where
         Ready: Future<Output = Result<(), ReadyErr>>,
     {
@@ -73,22 +84,46 @@ where
             is_ready_to_execute: false,
             stopped: false,
             max_concurrent_executions: self.max_concurrent_executions,
+            stats: self.stats,
         }
     }
 }
 
-#[allow(clippy::match_wildcard_for_single_variants)]
-impl<T, R, F, MkF, Ready, ReadyErr> Stream for Runner<T, R, F, MkF, Ready>
+impl<T, R, F, MkF, Ready, ReadyErr, Clk> Stream for Runner<T, R, F, MkF, Ready, Clk>
 where
     T: Eq + Hash + Clone + Unpin,
     R: Stream<Item = ScheduleRequest<T>>,
     F: Future + Unpin,
     MkF: FnMut(&T) -> F,
     Ready: Future<Output = Result<(), ReadyErr>>,
+    Clk: Clock,
 {
     type Item = Result<F::Output, Error<ReadyErr>>;
 
-    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let res = self.as_mut().poll_run(cx);
+        if let Some(stats) = &self.stats {
+            stats.update(self.scheduler.stats(), self.slots.len());
+        }
+        res
+    }
+}
+
+#[allow(clippy::match_wildcard_for_single_variants)]
+impl<T, R, F, MkF, Ready, ReadyErr, Clk> Runner<T, R, F, MkF, Ready, Clk>
+where
+    T: Eq + Hash + Clone + Unpin,
+    R: Stream<Item = ScheduleRequest<T>>,
+    F: Future + Unpin,
+    MkF: FnMut(&T) -> F,
+    Ready: Future<Output = Result<(), ReadyErr>>,
+    Clk: Clock,
+{
+    #[allow(clippy::type_complexity)]
+    fn poll_run(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<F::Output, Error<ReadyErr>>>> {
         let mut this = self.project();
         if *this.stopped {
             return Poll::Ready(None);
@@ -156,7 +191,7 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::{Error, Runner};
+    use super::{ControllerStats, Error, Runner};
     use crate::{
         scheduler::{scheduler, ScheduleRequest},
         utils::delayed_init::{self, DelayedInit},
@@ -395,6 +430,44 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn runner_should_publish_stats() {
+        pause();
+
+        let stats = ControllerStats::default();
+        let (mut sched_tx, sched_rx) = mpsc::unbounded();
+        let mut runner = Box::pin(
+            Runner::new(scheduler(sched_rx), 1, |_| {
+                DurationalFuture::new(Duration::from_secs(2))
+            })
+            .with_stats(Some(stats.clone()))
+            .for_each(|_| async {}),
+        );
+        for (message, delay) in [(1, 0), (2, 0), (3, 10)] {
+            sched_tx
+                .send(ScheduleRequest {
+                    message,
+                    run_at: Instant::now() + Duration::from_secs(delay),
+                })
+                .await
+                .unwrap();
+        }
+        assert!(poll!(runner.as_mut()).is_pending());
+        assert_eq!(stats.running(), 1);
+        assert_eq!(stats.pending(), 1);
+        assert_eq!(stats.scheduled(), 1);
+
+        advance(Duration::from_secs(1)).await;
+        assert_eq!(stats.max_pending_age(), Some(Duration::from_secs(1)));
+
+        advance(Duration::from_secs(2)).await;
+        assert!(poll!(runner.as_mut()).is_pending());
+        assert_eq!(stats.running(), 1);
+        assert_eq!(stats.pending(), 0);
+        assert_eq!(stats.max_pending_age(), None);
+        assert_eq!(stats.scheduled(), 1);
+    }
+
     #[tokio::test]
     async fn runner_should_respect_max_concurrent_executions() {
         pause();
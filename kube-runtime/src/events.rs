@@ -1,6 +1,9 @@
 //! Publishes events for objects for kubernetes >= 1.19
 use k8s_openapi::{
-    api::{core::v1::ObjectReference, events::v1::Event as K8sEvent},
+    api::{
+        core::v1::{Event as CoreEvent, ObjectReference},
+        events::v1::Event as K8sEvent,
+    },
     apimachinery::pkg::apis::meta::v1::{MicroTime, ObjectMeta},
     chrono::Utc,
 };
@@ -123,6 +126,29 @@ impl From<&str> for Reporter {
     }
 }
 
+/// Kubernetes event types that refer to the object they are about
+///
+/// This is implemented for both the `core/v1` and the `events.k8s.io/v1` `Event`,
+/// which name the object `involvedObject` and `regarding` respectively.
+/// See [`Controller::watches_events`](crate::Controller::watches_events) to reconcile objects
+/// based on the events emitted for them.
+pub trait InvolvedObject {
+    /// The object that the event is about, if any
+    fn involved_object(&self) -> Option<&ObjectReference>;
+}
+
+impl InvolvedObject for CoreEvent {
+    fn involved_object(&self) -> Option<&ObjectReference> {
+        Some(&self.involved_object)
+    }
+}
+
+impl InvolvedObject for K8sEvent {
+    fn involved_object(&self) -> Option<&ObjectReference> {
+        self.regarding.as_ref()
+    }
+}
+
 /// A publisher abstraction to emit Kubernetes' events.
 ///
 /// All events emitted by an `Recorder` are attached to the [`ObjectReference`]
@@ -4,6 +4,7 @@ use futures::{TryFuture, TryFutureExt};
 use json_patch::{AddOperation, PatchOperation, RemoveOperation, TestOperation};
 use kube_client::{
     api::{Patch, PatchParams},
+    core::ErrorResponse,
     Api, Resource, ResourceExt,
 };
 use serde::{de::DeserializeOwned, Serialize};
@@ -27,6 +28,9 @@ where
     UnnamedObject,
 }
 
+/// How many times adding or removing the finalizer is retried after a conflict
+const PATCH_CONFLICT_RETRIES: usize = 3;
+
 struct FinalizerState {
     finalizer_index: Option<usize>,
     is_deleting: bool,
@@ -97,6 +101,10 @@ impl FinalizerState {
 /// In addition, adding and removing the finalizer itself may fail. In particular, this may be because of
 /// network errors, lacking permissions, or because another `finalizer` was updated in the meantime on the same object.
 ///
+/// The latter is retried a few times: when the finalizers of `obj` turn out to be outdated, the object is fetched again,
+/// and the finalizer is added to (or removed from) the current version of the object. Only the finalizer patch is retried,
+/// the [`Event::Cleanup`] is not run again.
+///
 /// [`ObjectMeta::finalizers`]: kube_client::api::ObjectMeta#structfield.finalizers
 pub async fn finalizer<K, ReconcileFut>(
     api: &Api<K>,
@@ -118,7 +126,7 @@ where
             .await
             .map_err(Error::ApplyFailed),
         FinalizerState {
-            finalizer_index: Some(finalizer_i),
+            finalizer_index: Some(mut finalizer_i),
             is_deleting: true,
         } => {
             // Cleanup reconciliation must succeed before it's safe to remove the finalizer
@@ -129,23 +137,22 @@ where
                 // Short-circuit, so that we keep the finalizer if cleanup fails
                 .map_err(Error::CleanupFailed)?;
             // Cleanup was successful, remove the finalizer so that deletion can continue
-            let finalizer_path = format!("/metadata/finalizers/{finalizer_i}");
-            api.patch::<K>(
-                &name,
-                &PatchParams::default(),
-                &Patch::Json(json_patch::Patch(vec![
-                    // All finalizers run concurrently and we use an integer index
-                    // `Test` ensures that we fail instead of deleting someone else's finalizer
-                    // (in which case a new `Cleanup` event will be sent)
-                    PatchOperation::Test(TestOperation {
-                        path: finalizer_path.clone(),
-                        value: finalizer_name.into(),
-                    }),
-                    PatchOperation::Remove(RemoveOperation { path: finalizer_path }),
-                ])),
-            )
-            .await
-            .map_err(Error::RemoveFinalizer)?;
+            for retry in 0.. {
+                match remove_finalizer(api, &name, finalizer_name, finalizer_i).await {
+                    Ok(()) => break,
+                    Err(err) if retry < PATCH_CONFLICT_RETRIES && is_conflict(&err) => {
+                        let fresh = api.get_opt(&name).await.map_err(Error::RemoveFinalizer)?;
+                        match fresh
+                            .and_then(|obj| FinalizerState::for_object(&obj, finalizer_name).finalizer_index)
+                        {
+                            Some(i) => finalizer_i = i,
+                            // The object (or our finalizer) is already gone
+                            None => break,
+                        }
+                    }
+                    Err(err) => return Err(Error::RemoveFinalizer(err)),
+                }
+            }
             Ok(action)
         }
         FinalizerState {
@@ -153,39 +160,28 @@ where
             is_deleting: false,
         } => {
             // Finalizer must be added before it's safe to run an `Apply` reconciliation
-            let patch = json_patch::Patch(if obj.finalizers().is_empty() {
-                vec![
-                    PatchOperation::Test(TestOperation {
-                        path: "/metadata/finalizers".to_string(),
-                        value: serde_json::Value::Null,
-                    }),
-                    PatchOperation::Add(AddOperation {
-                        path: "/metadata/finalizers".to_string(),
-                        value: vec![finalizer_name].into(),
-                    }),
-                ]
-            } else {
-                vec![
-                    // Kubernetes doesn't automatically deduplicate finalizers (see
-                    // https://github.com/kube-rs/kube/issues/964#issuecomment-1197311254),
-                    // so we need to fail and retry if anyone else has added the finalizer in the meantime
-                    PatchOperation::Test(TestOperation {
-                        path: "/metadata/finalizers".to_string(),
-                        value: obj.finalizers().into(),
-                    }),
-                    PatchOperation::Add(AddOperation {
-                        path: "/metadata/finalizers/-".to_string(),
-                        value: finalizer_name.into(),
-                    }),
-                ]
-            });
-            api.patch::<K>(
-                obj.meta().name.as_deref().ok_or(Error::UnnamedObject)?,
-                &PatchParams::default(),
-                &Patch::Json(patch),
-            )
-            .await
-            .map_err(Error::AddFinalizer)?;
+            let name = obj.meta().name.as_deref().ok_or(Error::UnnamedObject)?;
+            let mut finalizers = obj.finalizers().to_vec();
+            for retry in 0.. {
+                match add_finalizer(api, name, finalizer_name, &finalizers).await {
+                    Ok(()) => break,
+                    Err(err) if retry < PATCH_CONFLICT_RETRIES && is_conflict(&err) => {
+                        match api.get_opt(name).await.map_err(Error::AddFinalizer)? {
+                            Some(fresh) => match FinalizerState::for_object(&fresh, finalizer_name) {
+                                FinalizerState {
+                                    finalizer_index: None,
+                                    is_deleting: false,
+                                } => finalizers = fresh.finalizers().to_vec(),
+                                // Already added by a concurrent reconcile, or being deleted,
+                                // in both cases the change will trigger a new reconciliation
+                                _ => break,
+                            },
+                            None => break,
+                        }
+                    }
+                    Err(err) => return Err(Error::AddFinalizer(err)),
+                }
+            }
             // No point applying here, since the patch will cause a new reconciliation
             Ok(Action::await_change())
         }
@@ -199,6 +195,83 @@ where
     }
 }
 
+/// Whether a finalizer patch failed because it was based on an outdated version of the object
+fn is_conflict(err: &kube_client::Error) -> bool {
+    // A failed `Test` operation is reported as 422 Unprocessable Entity
+    matches!(
+        err,
+        kube_client::Error::Api(ErrorResponse { code: 409 | 422, .. })
+    )
+}
+
+async fn add_finalizer<K>(
+    api: &Api<K>,
+    name: &str,
+    finalizer_name: &str,
+    finalizers: &[String],
+) -> Result<(), kube_client::Error>
+where
+    K: Resource + Clone + DeserializeOwned + Serialize + Debug,
+{
+    let patch = json_patch::Patch(if finalizers.is_empty() {
+        vec![
+            PatchOperation::Test(TestOperation {
+                path: "/metadata/finalizers".to_string(),
+                value: serde_json::Value::Null,
+            }),
+            PatchOperation::Add(AddOperation {
+                path: "/metadata/finalizers".to_string(),
+                value: vec![finalizer_name].into(),
+            }),
+        ]
+    } else {
+        vec![
+            // Kubernetes doesn't automatically deduplicate finalizers (see
+            // https://github.com/kube-rs/kube/issues/964#issuecomment-1197311254),
+            // so we need to fail and retry if anyone else has added the finalizer in the meantime
+            PatchOperation::Test(TestOperation {
+                path: "/metadata/finalizers".to_string(),
+                value: finalizers.into(),
+            }),
+            PatchOperation::Add(AddOperation {
+                path: "/metadata/finalizers/-".to_string(),
+                value: finalizer_name.into(),
+            }),
+        ]
+    });
+    api.patch::<K>(name, &PatchParams::default(), &Patch::Json(patch))
+        .await?;
+    Ok(())
+}
+
+async fn remove_finalizer<K>(
+    api: &Api<K>,
+    name: &str,
+    finalizer_name: &str,
+    finalizer_i: usize,
+) -> Result<(), kube_client::Error>
+where
+    K: Resource + Clone + DeserializeOwned + Serialize + Debug,
+{
+    let finalizer_path = format!("/metadata/finalizers/{finalizer_i}");
+    api.patch::<K>(
+        name,
+        &PatchParams::default(),
+        &Patch::Json(json_patch::Patch(vec![
+            // All finalizers run concurrently and we use an integer index
+            // `Test` ensures that we fail instead of deleting someone else's finalizer
+            // (in which case a new `Cleanup` event will be sent)
+            PatchOperation::Test(TestOperation {
+                path: finalizer_path.clone(),
+                value: finalizer_name.into(),
+            }),
+            PatchOperation::Remove(RemoveOperation { path: finalizer_path }),
+        ])),
+    )
+    .await?;
+    Ok(())
+}
+
 /// A representation of an action that should be taken by a reconciler.
 pub enum Event<K> {
     /// The reconciler should ensure that the actual state matches the state desired in the object.
@@ -220,3 +293,178 @@ pub enum Event<K> {
     /// - The grinch's heart grows a size or two
     Cleanup(Arc<K>),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{finalizer, Event};
+    use crate::controller::Action;
+    use futures::pin_mut;
+    use http::{Method, Request, Response};
+    use hyper::Body;
+    use k8s_openapi::api::core::v1::ConfigMap;
+    use kube_client::{Api, Client};
+    use serde_json::json;
+    use std::{
+        convert::Infallible,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+    use tower_test::mock;
+
+    fn configmap(finalizers: &[&str]) -> ConfigMap {
+        serde_json::from_value(json!({
+            "metadata": {
+                "name": "cm",
+                "namespace": "default",
+                "finalizers": finalizers,
+                "deletionTimestamp": "2023-01-01T00:00:00Z",
+            }
+        }))
+        .unwrap()
+    }
+
+    /// A `configmap` that is not being deleted
+    fn live_configmap(finalizers: &[&str]) -> ConfigMap {
+        let mut cm = configmap(finalizers);
+        cm.metadata.deletion_timestamp = None;
+        cm
+    }
+
+    /// The response to a patch based on an outdated version of the object
+    fn rejected(code: u16, reason: &str) -> Response<Body> {
+        let status = json!({
+            "kind": "Status",
+            "apiVersion": "v1",
+            "status": "Failure",
+            "message": "the server rejected our request",
+            "reason": reason,
+            "code": code,
+        });
+        Response::builder()
+            .status(code)
+            .body(Body::from(status.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn finalizer_removal_is_retried_on_outdated_object() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::PATCH);
+            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+            let patch: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(patch[1]["path"], "/metadata/finalizers/1");
+            send.send_response(rejected(422, "Invalid"));
+
+            // the other finalizer was removed in the meantime
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::GET);
+            let fresh = serde_json::to_string(&configmap(&["mine"])).unwrap();
+            send.send_response(Response::builder().body(Body::from(fresh)).unwrap());
+
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::PATCH);
+            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+            let patch: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(patch[1]["path"], "/metadata/finalizers/0");
+            let removed = serde_json::to_string(&configmap(&[])).unwrap();
+            send.send_response(Response::builder().body(Body::from(removed)).unwrap());
+        });
+
+        let api: Api<ConfigMap> = Api::default_namespaced(Client::new(mock_service, "default"));
+        let cleanups = AtomicUsize::new(0);
+        let obj = Arc::new(configmap(&["other", "mine"]));
+        let action = finalizer(&api, "mine", obj, |event| {
+            assert!(matches!(event, Event::Cleanup(_)));
+            cleanups.fetch_add(1, Ordering::SeqCst);
+            async { Ok::<_, Infallible>(Action::await_change()) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(action, Action::await_change());
+        assert_eq!(cleanups.load(Ordering::SeqCst), 1);
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn finalizer_addition_is_retried_with_refetched_finalizers() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::PATCH);
+            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+            let patch: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(patch[0]["value"], json!(["other"]));
+            send.send_response(rejected(409, "Conflict"));
+
+            // another finalizer was added in the meantime
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::GET);
+            let fresh = serde_json::to_string(&live_configmap(&["other", "another"])).unwrap();
+            send.send_response(Response::builder().body(Body::from(fresh)).unwrap());
+
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::PATCH);
+            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+            let patch: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(patch[0]["value"], json!(["other", "another"]));
+            assert_eq!(patch[1]["path"], "/metadata/finalizers/-");
+            assert_eq!(patch[1]["value"], "mine");
+            let added = serde_json::to_string(&live_configmap(&["other", "another", "mine"])).unwrap();
+            send.send_response(Response::builder().body(Body::from(added)).unwrap());
+        });
+
+        let api: Api<ConfigMap> = Api::default_namespaced(Client::new(mock_service, "default"));
+        let obj = Arc::new(live_configmap(&["other"]));
+        let reconciles = AtomicUsize::new(0);
+        let action = finalizer(&api, "mine", obj, |_| {
+            reconciles.fetch_add(1, Ordering::SeqCst);
+            async { Ok::<_, Infallible>(Action::await_change()) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(action, Action::await_change());
+        assert_eq!(reconciles.load(Ordering::SeqCst), 0);
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn finalizer_addition_stops_when_already_added() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::PATCH);
+            send.send_response(rejected(422, "Invalid"));
+
+            // a concurrent reconcile added the finalizer in the meantime
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::GET);
+            let fresh = serde_json::to_string(&live_configmap(&["other", "mine"])).unwrap();
+            send.send_response(Response::builder().body(Body::from(fresh)).unwrap());
+            assert!(
+                handle.next_request().await.is_none(),
+                "finalizer was patched again"
+            );
+        });
+
+        let api: Api<ConfigMap> = Api::default_namespaced(Client::new(mock_service, "default"));
+        let obj = Arc::new(live_configmap(&["other"]));
+        let reconciles = AtomicUsize::new(0);
+        let action = finalizer(&api, "mine", obj, |_| {
+            reconciles.fetch_add(1, Ordering::SeqCst);
+            async { Ok::<_, Infallible>(Action::await_change()) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(action, Action::await_change());
+        assert_eq!(reconciles.load(Ordering::SeqCst), 0);
+        drop(api);
+        spawned.await.unwrap();
+    }
+}
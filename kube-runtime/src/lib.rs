@@ -23,6 +23,7 @@ pub mod controller;
 pub mod events;
 
 pub mod finalizer;
+pub mod node;
 pub mod reflector;
 pub mod scheduler;
 pub mod utils;
@@ -34,7 +35,7 @@ pub use finalizer::finalizer;
 pub use reflector::reflector;
 pub use scheduler::scheduler;
 pub use utils::WatchStreamExt;
-pub use watcher::{metadata_watcher, watcher};
+pub use watcher::{metadata_watcher, reconfigurable_watcher, watcher};
 
 #[cfg(feature = "unstable-runtime-predicates")]
 pub use utils::{predicates, Predicate};
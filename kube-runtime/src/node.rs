@@ -0,0 +1,642 @@
+//! Draining of nodes, like `kubectl drain`
+//!
+//! See [`drain`] for the primary entry point. Nodes can be cordoned and uncordoned on their own
+//! with [`Api::cordon`] and [`Api::uncordon`].
+use std::{collections::BTreeMap, time::Duration};
+
+use backoff::{backoff::Backoff, ExponentialBackoff};
+use futures::{stream, Stream, StreamExt, TryStreamExt};
+use k8s_openapi::api::core::v1::{Node, Pod};
+use kube_client::{
+    api::{DeleteParams, EvictParams, ListParams},
+    core::field_selector::FieldSelector,
+    error::ErrorResponse,
+    Api, Client, ResourceExt,
+};
+use thiserror::Error;
+use tokio::time::Instant;
+
+use crate::reflector::ObjectRef;
+
+/// Annotation that marks the static pods mirrored into the apiserver by the kubelet
+const MIRROR_POD_ANNOTATION: &str = "kubernetes.io/config.mirror";
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("failed to cordon node: {0}")]
+    Cordon(#[source] kube_client::Error),
+    #[error("failed to list pods on node: {0}")]
+    ListPods(#[source] kube_client::Error),
+    #[error("refusing to evict {} pods without permission, see DrainOptions", .0.len())]
+    Refused(Vec<(ObjectRef<Pod>, Refusal)>),
+    #[error("failed to evict pod {0}: {1}")]
+    Evict(ObjectRef<Pod>, #[source] kube_client::Error),
+    #[error("failed to wait for pod {0} to be deleted: {1}")]
+    WaitForDeletion(ObjectRef<Pod>, #[source] kube_client::Error),
+    #[error("pod {0} was not deleted before the drain timeout")]
+    Timeout(ObjectRef<Pod>),
+}
+
+/// Why [`drain`] refused to evict a pod
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Refusal {
+    /// The pod is managed by a `DaemonSet`, which would recreate it on the node right away.
+    /// Allowed by [`DrainOptions::ignore_daemonsets`], which skips these pods.
+    DaemonSet,
+    /// The pod is not managed by a controller, so it would not be recreated elsewhere.
+    /// Allowed by [`DrainOptions::force`].
+    Unmanaged,
+    /// The pod has `emptyDir` volumes, whose data is lost when it is evicted.
+    /// Allowed by [`DrainOptions::delete_emptydir_data`].
+    LocalStorage,
+}
+
+/// Why [`drain`] left a pod on the node
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The pod is a mirror of a static pod, which is managed by the kubelet
+    Mirror,
+    /// The pod is managed by a `DaemonSet`, see [`DrainOptions::ignore_daemonsets`]
+    DaemonSet,
+}
+
+/// The progress of a [`drain`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrainEvent {
+    /// The node was cordoned, so no new pods are scheduled to it
+    Cordoned,
+    /// A pod is left on the node
+    Skipped(ObjectRef<Pod>, SkipReason),
+    /// The eviction of a pod was accepted, and its deletion is pending
+    Evicting(ObjectRef<Pod>),
+    /// The eviction of a pod was refused by a `PodDisruptionBudget`, and is retried after the delay
+    Blocked(ObjectRef<Pod>, Duration),
+    /// A pod was evicted, and is gone from the node
+    Evicted(ObjectRef<Pod>),
+    /// The drain completed, this is always the last event
+    Drained(DrainSummary),
+}
+
+/// The outcome of a completed [`drain`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DrainSummary {
+    /// The pods that were evicted, in the order that their evictions were started
+    ///
+    /// Pods with the same priority are evicted concurrently, so their order is arbitrary.
+    pub evicted: Vec<ObjectRef<Pod>>,
+    /// The pods that were left on the node
+    pub skipped: Vec<(ObjectRef<Pod>, SkipReason)>,
+}
+
+/// Options for [`drain`], mirroring the flags of `kubectl drain`
+#[derive(Clone, Debug)]
+#[must_use]
+#[allow(clippy::struct_excessive_bools)] // mirrors the flags of `kubectl drain`
+pub struct DrainOptions {
+    ignore_daemonsets: bool,
+    delete_emptydir_data: bool,
+    force: bool,
+    grace_period: Option<u32>,
+    evict_by_priority: bool,
+    timeout: Option<Duration>,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    poll_interval: Duration,
+}
+
+impl Default for DrainOptions {
+    fn default() -> Self {
+        Self {
+            ignore_daemonsets: false,
+            delete_emptydir_data: false,
+            force: false,
+            grace_period: None,
+            evict_by_priority: false,
+            timeout: None,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            // same interval that kubectl polls for deleted pods with
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl DrainOptions {
+    /// Leave the pods managed by `DaemonSet`s on the node, instead of refusing to drain it
+    pub fn ignore_daemonsets(mut self, ignore: bool) -> Self {
+        self.ignore_daemonsets = ignore;
+        self
+    }
+
+    /// Evict pods with `emptyDir` volumes, losing the data in them
+    pub fn delete_emptydir_data(mut self, delete: bool) -> Self {
+        self.delete_emptydir_data = delete;
+        self
+    }
+
+    /// Evict pods that are not managed by a controller, which are not recreated on another node
+    pub fn force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// The grace period of the evicted pods in seconds, instead of their own `terminationGracePeriodSeconds`
+    pub fn grace_period(mut self, seconds: u32) -> Self {
+        self.grace_period = Some(seconds);
+        self
+    }
+
+    /// Evict pods in the order of their priority, lowest first
+    ///
+    /// The priority is the one resolved from the `PriorityClass` of the pod when it was created (`spec.priority`),
+    /// and defaults to zero. All pods of one priority are evicted before any pod of a higher priority,
+    /// and must be gone from the node before the next evictions start. This keeps critical pods
+    /// (such as those of the `system-node-critical` class) running for as long as possible.
+    ///
+    /// All pods are evicted concurrently by default, like `kubectl drain` does.
+    pub fn evict_by_priority(mut self, enabled: bool) -> Self {
+        self.evict_by_priority = enabled;
+        self
+    }
+
+    /// Give up on pods that are not gone after `timeout`
+    ///
+    /// The drain waits for as long as it takes by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// The bounds of the exponential backoff between evictions blocked by a `PodDisruptionBudget`
+    ///
+    /// Defaults to starting at one second, and backing off to at most 30 seconds.
+    /// A delay requested by the apiserver with a `Retry-After` header takes precedence.
+    pub fn eviction_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// How often to check whether an evicted pod is gone, defaults to one second
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    fn backoff(&self) -> ExponentialBackoff {
+        ExponentialBackoff {
+            current_interval: self.initial_backoff,
+            initial_interval: self.initial_backoff,
+            max_interval: self.max_backoff,
+            max_elapsed_time: None,
+            ..ExponentialBackoff::default()
+        }
+    }
+}
+
+/// Drain a node: cordon it, and evict its pods through the eviction API
+///
+/// This is the equivalent of `kubectl drain`. Evictions respect `PodDisruptionBudget`s: an eviction that is refused
+/// because it would violate a budget (`429 Too Many Requests`) is retried with backoff until the budget allows it.
+/// Pods are evicted concurrently, and the drain completes once all evicted pods are gone from the node.
+///
+/// Returns a stream of the progress of the drain, which ends with a [`DrainEvent::Drained`] summary when the drain
+/// completed, or right after the first error. See [`DrainOptions::evict_by_priority`] to evict pods in the order of
+/// their `PriorityClass`.
+///
+/// Mirror pods are always left on the node. Pods managed by `DaemonSet`s, pods without a controller, and pods with
+/// `emptyDir` volumes make the drain fail with [`Error::Refused`] (after cordoning the node, but before evicting
+/// anything), unless they are allowed by the [`DrainOptions`]. Pods that have already finished are always evicted.
+///
+/// ```no_run
+/// use futures::TryStreamExt;
+/// use kube::runtime::node::{drain, DrainEvent, DrainOptions};
+/// use std::time::Duration;
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// # let client: kube::Client = todo!();
+/// let options = DrainOptions::default()
+///     .ignore_daemonsets(true)
+///     .timeout(Duration::from_secs(600));
+/// let progress = drain(client, "worker-1", options);
+/// futures::pin_mut!(progress);
+/// while let Some(event) = progress.try_next().await? {
+///     if let DrainEvent::Blocked(pod, retry_in) = event {
+///         println!("eviction of {pod} blocked by a disruption budget, retrying in {retry_in:?}");
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The node stays cordoned once drained, or when the drain fails. Use [`Api::uncordon`] to make it schedulable again.
+pub fn drain(
+    client: Client,
+    node: &str,
+    options: DrainOptions,
+) -> impl Stream<Item = Result<DrainEvent, Error>> + Send {
+    let node = node.to_string();
+    let deadline = options.timeout.map(|timeout| Instant::now() + timeout);
+    stream::once(async move {
+        Api::<Node>::all(client.clone())
+            .cordon(&node)
+            .await
+            .map_err(Error::Cordon)?;
+        let selector = FieldSelector::<Pod>::new().node_name(&node).to_string();
+        let pods = Api::<Pod>::all(client.clone())
+            .list(&ListParams::default().fields(&selector))
+            .await
+            .map_err(Error::ListPods)?;
+
+        let mut summary = DrainSummary::default();
+        let mut skipped = Vec::new();
+        let mut refused = Vec::new();
+        // pods to evict, grouped by the priority they are evicted in
+        let mut evict = BTreeMap::<i32, Vec<Pod>>::new();
+        for pod in pods {
+            match classify(&pod, &options) {
+                Ok(None) => {
+                    let priority = if options.evict_by_priority {
+                        pod.spec.as_ref().and_then(|s| s.priority).unwrap_or_default()
+                    } else {
+                        0
+                    };
+                    evict.entry(priority).or_default().push(pod);
+                }
+                Ok(Some(reason)) => {
+                    skipped.push(Ok(DrainEvent::Skipped(ObjectRef::from_obj(&pod), reason)));
+                    summary.skipped.push((ObjectRef::from_obj(&pod), reason));
+                }
+                Err(refusal) => refused.push((ObjectRef::from_obj(&pod), refusal)),
+            }
+        }
+        if !refused.is_empty() {
+            return Ok(stream::iter([Ok(DrainEvent::Cordoned), Err(Error::Refused(refused))]).boxed());
+        }
+        summary.evicted = evict.values().flatten().map(ObjectRef::from_obj).collect();
+        // each priority is only evicted once all pods of the previous one are gone
+        let evictions = evict
+            .into_values()
+            .map(|pods| {
+                stream::select_all(
+                    pods.iter()
+                        .map(|pod| evict_pod(client.clone(), pod, &options, deadline).boxed()),
+                )
+            })
+            .collect::<Vec<_>>();
+        Ok(stream::iter([Ok(DrainEvent::Cordoned)])
+            .chain(stream::iter(skipped))
+            .chain(stream::iter(evictions).flatten())
+            .chain(stream::iter([Ok(DrainEvent::Drained(summary))]))
+            .boxed())
+    })
+    .try_flatten()
+    .scan(false, |failed, res| {
+        // stop after the first error
+        if *failed {
+            return futures::future::ready(None);
+        }
+        *failed = res.is_err();
+        futures::future::ready(Some(res))
+    })
+}
+
+/// Whether to evict a pod (`Ok(None)`), skip it, or refuse to drain the node because of it
+fn classify(pod: &Pod, options: &DrainOptions) -> Result<Option<SkipReason>, Refusal> {
+    if pod.annotations().contains_key(MIRROR_POD_ANNOTATION) {
+        return Ok(Some(SkipReason::Mirror));
+    }
+    let phase = pod.status.as_ref().and_then(|s| s.phase.as_deref());
+    if matches!(phase, Some("Succeeded" | "Failed")) {
+        return Ok(None);
+    }
+    let controller = pod.owner_references().iter().find(|o| o.controller == Some(true));
+    match controller {
+        Some(owner) if owner.kind == "DaemonSet" => {
+            return if options.ignore_daemonsets {
+                Ok(Some(SkipReason::DaemonSet))
+            } else {
+                Err(Refusal::DaemonSet)
+            };
+        }
+        None if !options.force => return Err(Refusal::Unmanaged),
+        _ => {}
+    }
+    let volumes = pod.spec.as_ref().and_then(|s| s.volumes.as_ref());
+    let local_storage = volumes.map_or(false, |vols| vols.iter().any(|v| v.empty_dir.is_some()));
+    if local_storage && !options.delete_emptydir_data {
+        return Err(Refusal::LocalStorage);
+    }
+    Ok(None)
+}
+
+enum EvictionState {
+    Evict(Option<Duration>),
+    Wait,
+    Done,
+}
+
+/// Evict a pod, retrying while disruption budgets block it, and wait for it to be gone
+fn evict_pod(
+    client: Client,
+    pod: &Pod,
+    options: &DrainOptions,
+    deadline: Option<Instant>,
+) -> impl Stream<Item = Result<DrainEvent, Error>> + Send {
+    let api = Api::<Pod>::namespaced(client, &pod.namespace().unwrap_or_default());
+    let name = pod.name_any();
+    let uid = pod.uid();
+    let pod_ref = ObjectRef::from_obj(pod);
+    let params = EvictParams {
+        delete_options: options
+            .grace_period
+            .map(|seconds| DeleteParams::default().grace_period(seconds)),
+        ..EvictParams::default()
+    };
+    let poll_interval = options.poll_interval;
+    let timed_out =
+        move |delay: Duration| deadline.map_or(false, |deadline| Instant::now() + delay > deadline);
+    stream::unfold(
+        (EvictionState::Evict(None), options.backoff()),
+        move |(state, mut backoff)| {
+            let (api, name, uid, pod_ref, params) = (
+                api.clone(),
+                name.clone(),
+                uid.clone(),
+                pod_ref.clone(),
+                params.clone(),
+            );
+            async move {
+                match state {
+                    EvictionState::Evict(delay) => {
+                        if let Some(delay) = delay {
+                            if timed_out(delay) {
+                                return Some((Err(Error::Timeout(pod_ref)), (EvictionState::Done, backoff)));
+                            }
+                            tokio::time::sleep(delay).await;
+                        }
+                        let (event, next) = match api.evict(&name, &params).await {
+                            Ok(_) => (Ok(DrainEvent::Evicting(pod_ref)), EvictionState::Wait),
+                            // already gone
+                            Err(kube_client::Error::Api(ErrorResponse { code: 404, .. })) => {
+                                (Ok(DrainEvent::Evicted(pod_ref)), EvictionState::Done)
+                            }
+                            // refused by a disruption budget, the apiserver may suggest when to retry
+                            Err(kube_client::Error::TooManyRequests { retry_after, .. }) => {
+                                let retry_in = retry_after
+                                    .or_else(|| backoff.next_backoff())
+                                    .unwrap_or(poll_interval);
+                                (
+                                    Ok(DrainEvent::Blocked(pod_ref, retry_in)),
+                                    EvictionState::Evict(Some(retry_in)),
+                                )
+                            }
+                            Err(err) => (Err(Error::Evict(pod_ref, err)), EvictionState::Done),
+                        };
+                        Some((event, (next, backoff)))
+                    }
+                    EvictionState::Wait => loop {
+                        let event = match api.get_opt(&name).await {
+                            // still terminating, unless it is a new pod with the same name
+                            Ok(Some(current)) if current.uid() == uid => {
+                                if timed_out(poll_interval) {
+                                    Err(Error::Timeout(pod_ref))
+                                } else {
+                                    tokio::time::sleep(poll_interval).await;
+                                    continue;
+                                }
+                            }
+                            Ok(_) => Ok(DrainEvent::Evicted(pod_ref)),
+                            Err(err) => Err(Error::WaitForDeletion(pod_ref, err)),
+                        };
+                        break Some((event, (EvictionState::Done, backoff)));
+                    },
+                    EvictionState::Done => None,
+                }
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{drain, DrainEvent, DrainOptions, DrainSummary, Error, Refusal, SkipReason};
+    use crate::reflector::ObjectRef;
+    use futures::{pin_mut, StreamExt, TryStreamExt};
+    use http::{Method, Request, Response};
+    use hyper::Body;
+    use k8s_openapi::api::core::v1::Pod;
+    use kube_client::Client;
+    use serde_json::json;
+    use std::time::Duration;
+    use tower_test::mock;
+
+    fn pod(name: &str, owner_kind: Option<&str>, annotations: &serde_json::Value) -> serde_json::Value {
+        let owners = owner_kind.map_or(json!([]), |kind| {
+            json!([{ "apiVersion": "apps/v1", "kind": kind, "name": "owner", "uid": "owner-uid", "controller": true }])
+        });
+        json!({
+            "metadata": {
+                "name": name,
+                "namespace": "apps",
+                "uid": format!("uid-{name}"),
+                "annotations": annotations,
+                "ownerReferences": owners
+            },
+            "spec": { "nodeName": "node-1", "containers": [] },
+            "status": { "phase": "Running" }
+        })
+    }
+
+    fn respond(send: mock::SendResponse<Response<Body>>, status: u16, body: &serde_json::Value) {
+        send.send_response(
+            Response::builder()
+                .status(status)
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        );
+    }
+
+    fn too_many_requests() -> serde_json::Value {
+        json!({ "status": "Failure", "message": "Cannot evict pod as it would violate the pod's disruption budget.", "reason": "TooManyRequests", "code": 429 })
+    }
+
+    fn pod_with_priority(name: &str, priority: i32) -> serde_json::Value {
+        let mut pod = pod(name, Some("ReplicaSet"), &json!({}));
+        pod["spec"]["priority"] = json!(priority);
+        pod
+    }
+
+    fn node_pods() -> Vec<serde_json::Value> {
+        vec![
+            pod("web", Some("ReplicaSet"), &json!({})),
+            pod("agent", Some("DaemonSet"), &json!({})),
+            pod("static", None, &json!({ "kubernetes.io/config.mirror": "hash" })),
+        ]
+    }
+
+    async fn cordon_and_list(
+        handle: &mut mock::Handle<Request<Body>, Response<Body>>,
+        pods: Vec<serde_json::Value>,
+    ) {
+        {
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::PATCH);
+            assert_eq!(request.uri().path(), "/api/v1/nodes/node-1");
+            respond(
+                send,
+                200,
+                &json!({ "metadata": { "name": "node-1" }, "spec": { "unschedulable": true } }),
+            );
+
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(
+                request.uri().to_string(),
+                "/api/v1/pods?&fieldSelector=spec.nodeName%3Dnode-1"
+            );
+            respond(
+                send,
+                200,
+                &json!({
+                    "metadata": { "resourceVersion": "1" },
+                    "items": pods
+                }),
+            );
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn drain_evicts_pods_and_retries_blocked_evictions() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            cordon_and_list(&mut handle, node_pods()).await;
+
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::POST);
+            assert_eq!(request.uri().path(), "/api/v1/namespaces/apps/pods/web/eviction");
+            respond(send, 429, &too_many_requests());
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.uri().path(), "/api/v1/namespaces/apps/pods/web/eviction");
+            respond(send, 201, &json!({ "status": "Success" }));
+
+            // still terminating, then gone
+            let (request, send) = handle.next_request().await.expect("service not called");
+            assert_eq!(request.method(), Method::GET);
+            assert_eq!(request.uri().path(), "/api/v1/namespaces/apps/pods/web");
+            respond(send, 200, &pod("web", Some("ReplicaSet"), &json!({})));
+            let (_, send) = handle.next_request().await.expect("service not called");
+            respond(
+                send,
+                404,
+                &json!({ "status": "Failure", "reason": "NotFound", "code": 404 }),
+            );
+        });
+
+        let options = DrainOptions::default()
+            .ignore_daemonsets(true)
+            .eviction_backoff(Duration::from_secs(5), Duration::from_secs(5));
+        let events = drain(Client::new(mock_service, "default"), "node-1", options)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let web = ObjectRef::<Pod>::new("web").within("apps");
+        // the retry delay is jittered around the backoff interval
+        assert!(matches!(&events[3], DrainEvent::Blocked(pod, retry_in)
+            if pod == &web && *retry_in >= Duration::from_millis(2500) && *retry_in <= Duration::from_millis(7500)));
+        assert_eq!(events[..3], [
+            DrainEvent::Cordoned,
+            DrainEvent::Skipped(ObjectRef::new("agent").within("apps"), SkipReason::DaemonSet),
+            DrainEvent::Skipped(ObjectRef::new("static").within("apps"), SkipReason::Mirror),
+        ]);
+        assert_eq!(events[4..], [
+            DrainEvent::Evicting(web.clone()),
+            DrainEvent::Evicted(web.clone()),
+            DrainEvent::Drained(DrainSummary {
+                evicted: vec![web],
+                skipped: vec![
+                    (ObjectRef::new("agent").within("apps"), SkipReason::DaemonSet),
+                    (ObjectRef::new("static").within("apps"), SkipReason::Mirror),
+                ],
+            }),
+        ]);
+        spawned.await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn drain_evicts_pods_by_priority() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            cordon_and_list(&mut handle, vec![
+                pod_with_priority("critical", 2_000_000_000),
+                pod_with_priority("batch", -10),
+                pod("web", Some("ReplicaSet"), &json!({})),
+            ])
+            .await;
+
+            // each priority is evicted and gone before the next one is evicted
+            for name in ["batch", "web", "critical"] {
+                let (request, send) = handle.next_request().await.expect("service not called");
+                assert_eq!(
+                    request.uri().path(),
+                    format!("/api/v1/namespaces/apps/pods/{name}/eviction")
+                );
+                respond(send, 201, &json!({ "status": "Success" }));
+                let (request, send) = handle.next_request().await.expect("service not called");
+                assert_eq!(
+                    request.uri().path(),
+                    format!("/api/v1/namespaces/apps/pods/{name}")
+                );
+                respond(
+                    send,
+                    404,
+                    &json!({ "status": "Failure", "reason": "NotFound", "code": 404 }),
+                );
+            }
+        });
+
+        let options = DrainOptions::default().evict_by_priority(true);
+        let events = drain(Client::new(mock_service, "default"), "node-1", options)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        spawned.await.unwrap();
+        let [batch, web, critical] =
+            ["batch", "web", "critical"].map(|name| ObjectRef::new(name).within("apps"));
+        assert_eq!(events, [
+            DrainEvent::Cordoned,
+            DrainEvent::Evicting(batch.clone()),
+            DrainEvent::Evicted(batch.clone()),
+            DrainEvent::Evicting(web.clone()),
+            DrainEvent::Evicted(web.clone()),
+            DrainEvent::Evicting(critical.clone()),
+            DrainEvent::Evicted(critical.clone()),
+            DrainEvent::Drained(DrainSummary {
+                evicted: vec![batch, web, critical],
+                skipped: vec![],
+            }),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn drain_refuses_daemonset_pods_by_default() {
+        let (mock_service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let spawned = tokio::spawn(async move {
+            pin_mut!(handle);
+            cordon_and_list(&mut handle, node_pods()).await;
+        });
+
+        let events = drain(
+            Client::new(mock_service, "default"),
+            "node-1",
+            DrainOptions::default(),
+        )
+        .collect::<Vec<_>>()
+        .await;
+        spawned.await.unwrap();
+        assert!(
+            matches!(&events[..], [Ok(DrainEvent::Cordoned), Err(Error::Refused(refused))]
+            if refused == &[(ObjectRef::new("agent").within("apps"), Refusal::DaemonSet)])
+        );
+    }
+}
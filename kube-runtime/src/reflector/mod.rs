@@ -4,11 +4,12 @@ mod object_ref;
 pub mod store;
 
 pub use self::object_ref::{Extra as ObjectRefExtra, ObjectRef};
-use crate::watcher;
-use futures::{Stream, TryStreamExt};
+use crate::{utils::CancelableJoinHandle, watcher};
+use futures::{channel::mpsc, pin_mut, SinkExt, Stream, StreamExt, TryStreamExt};
 use kube_client::Resource;
-use std::hash::Hash;
+use std::{hash::Hash, sync::Arc};
 pub use store::{store, Store};
+use tokio::runtime::Handle;
 
 /// Cache objects from a [`watcher()`] stream into a local [`Store`]
 ///
@@ -23,6 +24,9 @@ pub use store::{store, Store};
 /// The cache contains the last-seen state of objects,
 /// which may lag slightly behind the actual state.
 ///
+/// The stream must carry the batched (re-)lists of the [`watcher()`] ([`watcher::Event::Restarted`]), which replace
+/// the contents of the store in a single step. It must not be [`unbatched`](crate::WatchStreamExt::unbatched) first.
+///
 /// ## Example
 ///
 /// Infinite watch of [`Node`](k8s_openapi::api::core::v1::Node) resources with a certain label.
@@ -89,6 +93,11 @@ pub use store::{store, Store};
 /// The `stream` can then be passed to `reflector` causing smaller objects to be written to its store.
 /// Note that you **cannot drop everything**; you minimally need the spec properties your app relies on.
 /// Additionally, only `labels`, `annotations` and `managed_fields` are safe to drop from `ObjectMeta`.
+///
+/// 3. Transform the objects before they are stored with [`Writer::with_transform`](store::Writer::with_transform)
+///
+/// This only trims the objects in the store, while the events are passed through the reflector unmodified.
+/// [`trim_managed_fields`](store::trim_managed_fields) is provided for the common case of dropping managed fields.
 pub fn reflector<K, W>(mut writer: store::Writer<K>, stream: W) -> impl Stream<Item = W::Item>
 where
     K: Resource + Clone,
@@ -98,17 +107,117 @@ where
     stream.inspect_ok(move |event| writer.apply_watcher_event(event))
 }
 
+/// Cache objects from a [`watcher()`] stream into a local [`Store`], sharing them with the consumer of the stream
+///
+/// Like [`reflector()`], but the objects of the events are moved into an [`Arc`] once, and that same allocation
+/// is both stored and passed on. A plain [`reflector()`] clones every object into the store instead, which adds
+/// up for large objects or busy watches.
+///
+/// The stream passes the events through with their objects behind an [`Arc`], see [`watcher::Event::into_shared`].
+/// When the `writer` has a [transform](store::Writer::with_transform), the stored objects are transformed copies.
+///
+/// ```no_run
+/// use k8s_openapi::api::core::v1::Node;
+/// use kube::runtime::{reflector, watcher, WatchStreamExt};
+/// use futures::{StreamExt, future::ready};
+/// # use kube::api::Api;
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// # let client: kube::Client = todo!();
+/// let nodes: Api<Node> = Api::all(client);
+/// let (reader, writer) = reflector::store();
+/// let rf = reflector::reflector_shared(writer, watcher(nodes, watcher::Config::default()));
+/// rf.applied_objects().for_each(|node| {
+///     // `node` is the same `Arc<Node>` as in `reader`
+///     ready(())
+/// }).await;
+/// # Ok(())
+/// # }
+/// ```
+pub fn reflector_shared<K, W>(
+    mut writer: store::Writer<K>,
+    stream: W,
+) -> impl Stream<Item = watcher::Result<watcher::Event<Arc<K>>>>
+where
+    K: Resource + Clone,
+    K::DynamicType: Eq + Hash + Clone,
+    W: Stream<Item = watcher::Result<watcher::Event<K>>>,
+{
+    stream
+        .map_ok(watcher::Event::into_shared)
+        .inspect_ok(move |event| writer.apply_shared_watcher_event(event))
+}
+
+/// Cache objects from a [`watcher()`] stream into a local [`Store`] in the background, buffering up to `buffer` events
+///
+/// Like [`reflector()`], but the `stream` is driven by a spawned task, which keeps the store up to date
+/// and queues the events for the consumer of the returned stream in a bounded buffer.
+/// This smooths over bursts of events while the consumer is busy, at the cost of the memory of the buffer.
+///
+/// A plain [`reflector()`] only reads from the watch when it is polled, so a slow consumer holds up the watch
+/// (and its store) directly. With a buffer, the watch is read ahead of the consumer until `buffer` events are queued,
+/// after which the task stops reading from the watch until the consumer catches up. Memory use is bounded either way,
+/// while the connection to the apiserver is left waiting.
+///
+/// ## Falling behind
+///
+/// The apiserver only keeps a limited history of changes (around 5 minutes by default).
+/// If the consumer falls so far behind that the watch is held up for longer than that, the watch is expired
+/// by the time it is resumed, and the [`watcher()`] has to relist all the objects.
+/// Prefer a larger `buffer` (or a faster consumer) over a watch that is held up for minutes at a time.
+///
+/// The task is stopped when the returned stream is dropped.
+///
+/// # Panics
+///
+/// Panics if called outside of a Tokio runtime.
+pub fn reflector_buffered<K, W>(
+    writer: store::Writer<K>,
+    stream: W,
+    buffer: usize,
+) -> impl Stream<Item = W::Item> + Send
+where
+    K: Resource + Clone + Send + Sync + 'static,
+    K::DynamicType: Eq + Hash + Clone + Send + Sync,
+    W: Stream<Item = watcher::Result<watcher::Event<K>>> + Send + 'static,
+{
+    let (mut tx, rx) = mpsc::channel(buffer);
+    let driver = CancelableJoinHandle::spawn(
+        async move {
+            let events = reflector(writer, stream);
+            pin_mut!(events);
+            while let Some(event) = events.next().await {
+                if tx.send(event).await.is_err() {
+                    break;
+                }
+            }
+        },
+        &Handle::current(),
+    );
+    // holding on to the driver aborts it when the stream is dropped
+    rx.map(move |event| {
+        let _ = &driver;
+        event
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{reflector, store, ObjectRef};
-    use crate::watcher;
-    use futures::{stream, StreamExt, TryStreamExt};
+    use super::{reflector, reflector_buffered, reflector_shared, store, ObjectRef};
+    use crate::{watcher, WatchStreamExt};
+    use futures::{pin_mut, stream, StreamExt, TryStreamExt};
     use k8s_openapi::{api::core::v1::ConfigMap, apimachinery::pkg::apis::meta::v1::ObjectMeta};
     use rand::{
         distributions::{Bernoulli, Uniform},
         Rng,
     };
-    use std::collections::{BTreeMap, HashMap};
+    use std::{
+        collections::{BTreeMap, HashMap},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
 
     #[tokio::test]
     async fn reflector_applied_should_add_object() {
@@ -131,6 +240,30 @@ mod tests {
         assert_eq!(store.get(&ObjectRef::from_obj(&cm)).as_deref(), Some(&cm));
     }
 
+    #[tokio::test]
+    async fn reflector_shared_should_store_the_passed_on_objects() {
+        let store_w = store::Writer::default();
+        let store = store_w.as_reader();
+        let cm = ConfigMap {
+            metadata: ObjectMeta {
+                name: Some("a".to_string()),
+                ..ObjectMeta::default()
+            },
+            ..ConfigMap::default()
+        };
+        let restarted = watcher::Event::Restarted(vec![cm.clone()]);
+        let applied = watcher::Event::Applied(cm.clone());
+        let objects = reflector_shared(store_w, stream::iter(vec![Ok(restarted), Ok(applied)]))
+            .applied_objects()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(objects.len(), 2);
+        let stored = store.get(&ObjectRef::from_obj(&cm)).unwrap();
+        assert!(Arc::ptr_eq(&objects[1], &stored));
+        assert_eq!(*stored, cm);
+    }
+
     #[tokio::test]
     async fn reflector_applied_should_update_object() {
         let store_w = store::Writer::default();
@@ -257,4 +390,38 @@ mod tests {
             seen_objects.insert(obj.metadata.name.clone().unwrap(), obj);
         }
     }
+
+    #[tokio::test]
+    async fn reflector_buffered_reads_ahead_until_the_buffer_is_full() {
+        let cm = |name: String| ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name),
+                ..ObjectMeta::default()
+            },
+            ..ConfigMap::default()
+        };
+        let (store, writer) = store::store();
+        let read = Arc::new(AtomicUsize::new(0));
+        let events = stream::iter((0..100).map(move |i| Ok(watcher::Event::Applied(cm(i.to_string())))))
+            .inspect({
+                let read = read.clone();
+                move |_| {
+                    read.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        let buffered = reflector_buffered(writer, events, 4);
+        pin_mut!(buffered);
+        assert!(buffered.next().await.is_some());
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        // the watch is only read ahead of the consumer by the size of the buffer
+        let read_ahead = read.load(Ordering::SeqCst);
+        assert!(
+            read_ahead > 1 && read_ahead <= 1 + 4 + 2,
+            "read {read_ahead} events"
+        );
+        assert_eq!(store.len(), read_ahead);
+
+        assert_eq!(buffered.count().await, 99);
+        assert_eq!(store.len(), 100);
+    }
 }
@@ -85,6 +85,52 @@ where
     {
         Self::from_obj_with(obj, Default::default())
     }
+
+    /// Create an `ObjectRef` from an `OwnerReference`
+    ///
+    /// This is [`ObjectRef::from_owner_ref`] for types with a default dynamic type.
+    /// Returns `None` if the owner's `apiVersion` and `kind` do not match `K`.
+    ///
+    /// An `OwnerReference` does not carry a namespace, since owners must either live in the
+    /// same namespace as the owned object, or be cluster-scoped. Hence `namespace` should be:
+    ///
+    /// - the owned object's namespace when `K` is namespaced
+    /// - `None` when `K` is cluster-scoped
+    ///
+    /// ```
+    /// use k8s_openapi::{
+    ///     api::{apps::v1::ReplicaSet, core::v1::Pod},
+    ///     apimachinery::pkg::apis::meta::v1::OwnerReference,
+    /// };
+    /// use kube::ResourceExt;
+    /// use kube_runtime::reflector::ObjectRef;
+    /// # let pod = Pod::default();
+    /// let owner_rs = pod
+    ///     .owner_references()
+    ///     .iter()
+    ///     .find_map(|owner| ObjectRef::<ReplicaSet>::from_owner_reference(pod.namespace().as_deref(), owner));
+    /// ```
+    #[must_use]
+    pub fn from_owner_reference(namespace: Option<&str>, owner: &OwnerReference) -> Option<Self> {
+        Self::from_owner_ref(namespace, owner, Default::default())
+    }
+
+    /// Create an `ObjectRef` from an `ObjectReference`
+    ///
+    /// Returns `None` if the reference has no name, or if its `apiVersion` and `kind` do not match `K`.
+    /// This is the inverse of converting an `ObjectRef` into an `ObjectReference`, and can be used
+    /// to find the object that an `Event` is about.
+    ///
+    /// ```
+    /// use k8s_openapi::api::core::v1::{Event, Pod};
+    /// use kube_runtime::reflector::ObjectRef;
+    /// # let event = Event::default();
+    /// let pod = ObjectRef::<Pod>::from_object_reference(&event.involved_object);
+    /// ```
+    #[must_use]
+    pub fn from_object_reference(reference: &ObjectReference) -> Option<Self> {
+        Self::from_object_reference_with(reference, Default::default())
+    }
 }
 
 impl<K: Resource> ObjectRef<K> {
@@ -121,6 +167,8 @@ impl<K: Resource> ObjectRef<K> {
 
     /// Create an `ObjectRef` from an `OwnerReference`
     ///
+    /// See [`ObjectRef::from_owner_reference`] for how `namespace` should be passed.
+    ///
     /// Returns `None` if the types do not match.
     #[must_use]
     pub fn from_owner_ref(
@@ -143,6 +191,27 @@ impl<K: Resource> ObjectRef<K> {
         }
     }
 
+    /// Create an `ObjectRef` from an `ObjectReference` using the given dynamic type
+    ///
+    /// See [`ObjectRef::from_object_reference`].
+    #[must_use]
+    pub fn from_object_reference_with(reference: &ObjectReference, dyntype: K::DynamicType) -> Option<Self> {
+        let matches = reference.api_version.as_deref() == Some(K::api_version(&dyntype).as_ref())
+            && reference.kind.as_deref() == Some(K::kind(&dyntype).as_ref());
+        if !matches {
+            return None;
+        }
+        Some(Self {
+            name: reference.name.clone()?,
+            namespace: reference.namespace.clone().filter(|ns| !ns.is_empty()),
+            extra: Extra {
+                resource_version: reference.resource_version.clone(),
+                uid: reference.uid.clone(),
+            },
+            dyntype,
+        })
+    }
+
     /// Convert into a reference to `K2`
     ///
     /// Note that no checking is done on whether this conversion makes sense. For example, every `Service`
@@ -224,9 +293,12 @@ mod tests {
     };
 
     use super::{Extra, ObjectRef};
-    use k8s_openapi::api::{
-        apps::v1::Deployment,
-        core::v1::{Node, Pod},
+    use k8s_openapi::{
+        api::{
+            apps::v1::{Deployment, ReplicaSet},
+            core::v1::{Node, ObjectReference, Pod},
+        },
+        apimachinery::pkg::apis::meta::v1::OwnerReference,
     };
 
     #[test]
@@ -280,4 +352,48 @@ mod tests {
         };
         assert_eq!(hash_value(&minimal), hash_value(&with_extra));
     }
+
+    #[test]
+    fn from_owner_ref_should_match_kind() {
+        let owner = OwnerReference {
+            api_version: "apps/v1".to_string(),
+            kind: "ReplicaSet".to_string(),
+            name: "my-rs".to_string(),
+            uid: "abc".to_string(),
+            ..OwnerReference::default()
+        };
+        let rs_ref = ObjectRef::<ReplicaSet>::from_owner_reference(Some("ns"), &owner).unwrap();
+        assert_eq!(rs_ref, ObjectRef::new("my-rs").within("ns"));
+        assert_eq!(rs_ref.extra.uid.as_deref(), Some("abc"));
+        assert_eq!(
+            ObjectRef::<Deployment>::from_owner_reference(Some("ns"), &owner),
+            None
+        );
+
+        let node_owner = OwnerReference {
+            api_version: "v1".to_string(),
+            kind: "Node".to_string(),
+            name: "my-node".to_string(),
+            ..OwnerReference::default()
+        };
+        assert_eq!(
+            ObjectRef::<Node>::from_owner_reference(None, &node_owner),
+            Some(ObjectRef::new("my-node"))
+        );
+    }
+
+    #[test]
+    fn from_object_reference_should_round_trip() {
+        let pod_ref = ObjectRef::<Pod>::new("my-pod").within("ns");
+        let reference = ObjectReference::from(pod_ref.clone());
+        assert_eq!(ObjectRef::<Pod>::from_object_reference(&reference), Some(pod_ref));
+        assert_eq!(ObjectRef::<Node>::from_object_reference(&reference), None);
+        assert_eq!(
+            ObjectRef::<Pod>::from_object_reference(&ObjectReference {
+                name: None,
+                ..reference
+            }),
+            None
+        );
+    }
 }
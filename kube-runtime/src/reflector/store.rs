@@ -3,28 +3,47 @@ use crate::{
     utils::delayed_init::{self, DelayedInit},
     watcher,
 };
-use ahash::AHashMap;
+use ahash::{AHashMap, AHashSet};
 use derivative::Derivative;
-use kube_client::Resource;
-use parking_lot::RwLock;
-use std::{fmt::Debug, hash::Hash, sync::Arc};
+use futures::{channel::mpsc, FutureExt, Stream};
+use kube_client::{Resource, ResourceExt};
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::{borrow::Borrow, fmt::Debug, hash::Hash, sync::Arc};
 use thiserror::Error;
 
 type Cache<K> = Arc<RwLock<AHashMap<ObjectRef<K>, Arc<K>>>>;
+type LastVersion = Arc<RwLock<Option<String>>>;
+type Transform<K> = Arc<dyn Fn(K) -> K + Send + Sync>;
+/// The key of the index of a [`Writer`], see [`Writer::with_index`]
+pub(crate) type IndexKey<K> = Arc<dyn Fn(&K) -> ObjectRef<K> + Send + Sync>;
+/// The refs of the objects in the store by their index key
+type Index<K> = Arc<RwLock<AHashMap<ObjectRef<K>, AHashSet<ObjectRef<K>>>>>;
+/// Subscribers of [`Store::watch_changes`], `None` once the [`Writer`] is dropped
+type ChangeSubscribers<K> = Arc<Mutex<Option<Vec<mpsc::UnboundedSender<ObjectRef<K>>>>>>;
 
 /// A writable Store handle
 ///
 /// This is exclusive since it's not safe to share a single `Store` between multiple reflectors.
 /// In particular, `Restarted` events will clobber the state of other connected reflectors.
-#[derive(Debug)]
+#[derive(Derivative)]
+#[derivative(Debug(bound = "K: Debug, K::DynamicType: Debug"))]
 pub struct Writer<K: 'static + Resource>
 where
     K::DynamicType: Eq + Hash,
 {
     store: Cache<K>,
+    resource_version: LastVersion,
     dyntype: K::DynamicType,
     ready_tx: Option<delayed_init::Initializer<()>>,
     ready_rx: Arc<DelayedInit<()>>,
+    #[derivative(Debug = "ignore")]
+    transform: Option<Transform<K>>,
+    #[derivative(Debug = "ignore")]
+    index_key: Option<IndexKey<K>>,
+    index: Index<K>,
+    #[derivative(Debug = "ignore")]
+    changes: ChangeSubscribers<K>,
 }
 
 impl<K: 'static + Resource + Clone> Writer<K>
@@ -39,12 +58,67 @@ where
         let (ready_tx, ready_rx) = DelayedInit::new();
         Writer {
             store: Default::default(),
+            resource_version: Default::default(),
             dyntype,
             ready_tx: Some(ready_tx),
             ready_rx: Arc::new(ready_rx),
+            transform: None,
+            index_key: None,
+            index: Index::default(),
+            changes: Arc::new(Mutex::new(Some(Vec::new()))),
         }
     }
 
+    /// Transform every object before it is stored
+    ///
+    /// The transform is applied to objects from both watch events and (re-)lists, and can be used to
+    /// drop the parts of objects that are not needed from the cache, like client-go's `TransformFunc`.
+    /// For instance, [`trim_managed_fields`] drops the `managedFields`, which are often around
+    /// half the size of the metadata:
+    ///
+    /// ```
+    /// # use k8s_openapi::api::core::v1::Pod;
+    /// use kube::runtime::reflector::store::{trim_managed_fields, Writer};
+    /// let writer = Writer::<Pod>::default().with_transform(|pod| {
+    ///     let mut pod = trim_managed_fields(pod);
+    ///     pod.status = None;
+    ///     pod
+    /// });
+    /// ```
+    ///
+    /// The transform only affects the objects in the store, the events that are passed through the
+    /// [`reflector`](crate::reflector()) are not modified. To modify both, see
+    /// [`WatchStreamExt::modify`](crate::WatchStreamExt::modify).
+    /// Note that the transformed object must keep its name and namespace, and whatever your app relies on.
+    #[must_use]
+    pub fn with_transform(mut self, transform: impl Fn(K) -> K + Send + Sync + 'static) -> Self {
+        self.transform = Some(Arc::new(transform));
+        self
+    }
+
+    /// Index the objects by a key derived from each object
+    ///
+    /// The key identifies a group of objects (such as all the objects labelled with the same parent), and does
+    /// not have to refer to an existing object. The objects of a group can then be retrieved with
+    /// [`Store::get_indexed`], without scanning the whole store. The key is derived from the stored objects,
+    /// so it must not depend on anything that the [transform](Self::with_transform) drops.
+    #[must_use]
+    pub fn with_index(mut self, key: impl Fn(&K) -> ObjectRef<K> + Send + Sync + 'static) -> Self {
+        self.set_index(Arc::new(key));
+        self
+    }
+
+    /// Index the objects by `key`, like [`Writer::with_index`]
+    pub(crate) fn set_index(&mut self, key: IndexKey<K>) {
+        let mut index = self.index.write();
+        index.clear();
+        for (obj_ref, obj) in self.store.read().iter() {
+            index.entry(key(obj)).or_default().insert(obj_ref.clone());
+        }
+        drop(index);
+        self.index_key = Some(key);
+    }
+
     /// Return a read handle to the store
     ///
     /// Multiple read handles may be obtained, by either calling `as_reader` multiple times,
@@ -53,33 +127,103 @@ where
     pub fn as_reader(&self) -> Store<K> {
         Store {
             store: self.store.clone(),
+            resource_version: self.resource_version.clone(),
             ready_rx: self.ready_rx.clone(),
+            index: self.index.clone(),
+            changes: self.changes.clone(),
+        }
+    }
+
+    /// The dynamic type of the objects in the store
+    pub(crate) fn dyntype(&self) -> &K::DynamicType {
+        &self.dyntype
+    }
+
+    /// The object as it should be stored, after the transform
+    fn stored(&self, obj: &K) -> Arc<K> {
+        let obj = obj.clone();
+        Arc::new(match &self.transform {
+            Some(transform) => transform(obj),
+            None => obj,
+        })
+    }
+
+    /// The shared object as it should be stored, only cloned when it has to be transformed
+    fn stored_shared(&self, obj: &Arc<K>) -> Arc<K> {
+        match &self.transform {
+            Some(transform) => Arc::new(transform(K::clone(obj))),
+            None => obj.clone(),
         }
     }
 
     /// Applies a single watcher event to the store
     pub fn apply_watcher_event(&mut self, event: &watcher::Event<K>) {
+        self.apply_event(event, Self::stored);
+    }
+
+    /// Applies a single watcher event with shared objects to the store
+    ///
+    /// The objects are stored without being cloned, unless the writer has a [transform](Self::with_transform).
+    /// See [`reflector_shared`](crate::reflector::reflector_shared).
+    pub fn apply_shared_watcher_event(&mut self, event: &watcher::Event<Arc<K>>) {
+        self.apply_event(event, Self::stored_shared);
+    }
+
+    fn apply_event<O: Borrow<K>>(&mut self, event: &watcher::Event<O>, stored: impl Fn(&Self, &O) -> Arc<K>) {
+        let latest = match event {
+            watcher::Event::Applied(obj) | watcher::Event::Deleted(obj) => obj.borrow().resource_version(),
+            watcher::Event::Restarted(objs) | watcher::Event::Desynced { objects: objs, .. } => objs
+                .iter()
+                .filter_map(|obj| obj.borrow().resource_version())
+                .max_by_key(|rv| rv.parse::<u64>().ok()),
+        };
+        if latest.is_some() {
+            *self.resource_version.write() = latest;
+        }
+
         match event {
             watcher::Event::Applied(obj) => {
-                let key = ObjectRef::from_obj_with(obj, self.dyntype.clone());
-                let obj = Arc::new(obj.clone());
-                self.store.write().insert(key, obj);
+                let key = ObjectRef::from_obj_with(obj.borrow(), self.dyntype.clone());
+                let obj = stored(self, obj);
+                let old = self.store.write().insert(key.clone(), obj.clone());
+                self.update_index(&key, old.as_deref(), Some(&obj));
+                self.notify_changes([key]);
             }
             watcher::Event::Deleted(obj) => {
-                let key = ObjectRef::from_obj_with(obj, self.dyntype.clone());
-                self.store.write().remove(&key);
+                let key = ObjectRef::from_obj_with(obj.borrow(), self.dyntype.clone());
+                let old = self.store.write().remove(&key);
+                self.update_index(&key, old.as_deref(), None);
+                self.notify_changes([key]);
             }
-            watcher::Event::Restarted(new_objs) => {
+            watcher::Event::Restarted(new_objs)
+            | watcher::Event::Desynced {
+                objects: new_objs, ..
+            } => {
                 let new_objs = new_objs
                     .iter()
                     .map(|obj| {
                         (
-                            ObjectRef::from_obj_with(obj, self.dyntype.clone()),
-                            Arc::new(obj.clone()),
+                            ObjectRef::from_obj_with(obj.borrow(), self.dyntype.clone()),
+                            stored(self, obj),
                         )
                     })
                     .collect::<AHashMap<_, _>>();
-                *self.store.write() = new_objs;
+                if let Some(index_key) = &self.index_key {
+                    let mut index = self.index.write();
+                    index.clear();
+                    for (obj_ref, obj) in &new_objs {
+                        index.entry(index_key(obj)).or_default().insert(obj_ref.clone());
+                    }
+                }
+                if self.has_change_subscribers() {
+                    // everything that was or is in the store may have changed
+                    let mut changed = new_objs.keys().cloned().collect::<AHashSet<_>>();
+                    let old_objs = std::mem::replace(&mut *self.store.write(), new_objs);
+                    changed.extend(old_objs.into_keys());
+                    self.notify_changes(changed);
+                } else {
+                    *self.store.write() = new_objs;
+                }
             }
         }
 
@@ -88,6 +232,58 @@ where
             ready_tx.init(())
         }
     }
+
+    /// Move `obj_ref` from the index key of the `old` object to the index key of the `new` object
+    fn update_index(&self, obj_ref: &ObjectRef<K>, old: Option<&K>, new: Option<&K>) {
+        let index_key = match &self.index_key {
+            Some(index_key) => index_key,
+            None => return,
+        };
+        let old_key = old.map(|obj| index_key(obj));
+        let new_key = new.map(|obj| index_key(obj));
+        if old_key == new_key {
+            return;
+        }
+        let mut index = self.index.write();
+        if let Some(old_key) = old_key {
+            if let Some(refs) = index.get_mut(&old_key) {
+                refs.remove(obj_ref);
+                if refs.is_empty() {
+                    index.remove(&old_key);
+                }
+            }
+        }
+        if let Some(new_key) = new_key {
+            index.entry(new_key).or_default().insert(obj_ref.clone());
+        }
+    }
+
+    fn has_change_subscribers(&self) -> bool {
+        self.changes
+            .lock()
+            .as_ref()
+            .map_or(false, |subscribers| !subscribers.is_empty())
+    }
+
+    /// Send the `changed` keys to the subscribers of [`Store::watch_changes`], dropping those that are gone
+    fn notify_changes(&self, changed: impl IntoIterator<Item = ObjectRef<K>>) {
+        let mut subscribers = self.changes.lock();
+        if let Some(subscribers) = subscribers.as_mut().filter(|subscribers| !subscribers.is_empty()) {
+            for key in changed {
+                subscribers.retain(|tx| tx.unbounded_send(key.clone()).is_ok());
+            }
+        }
+    }
+}
+
+impl<K: 'static + Resource> Drop for Writer<K>
+where
+    K::DynamicType: Eq + Hash,
+{
+    fn drop(&mut self) {
+        // ends the streams of `Store::watch_changes`
+        *self.changes.lock() = None;
+    }
 }
 impl<K> Default for Writer<K>
 where
@@ -107,12 +303,17 @@ where
 /// use `Writer::as_reader()` instead.
 #[derive(Derivative)]
 #[derivative(Debug(bound = "K: Debug, K::DynamicType: Debug"), Clone)]
+#[allow(clippy::struct_field_names)]
 pub struct Store<K: 'static + Resource>
 where
     K::DynamicType: Hash + Eq,
 {
     store: Cache<K>,
+    resource_version: LastVersion,
     ready_rx: Arc<DelayedInit<()>>,
+    index: Index<K>,
+    #[derivative(Debug = "ignore")]
+    changes: ChangeSubscribers<K>,
 }
 
 #[derive(Debug, Error)]
@@ -181,6 +382,26 @@ where
             .cloned()
     }
 
+    /// Retrieve the objects whose index key is `key`, see [`Writer::with_index`]
+    ///
+    /// The objects are ordered by namespace and name. Returns no objects if the [`Writer`] has no index.
+    #[must_use]
+    pub fn get_indexed(&self, key: &ObjectRef<K>) -> Vec<Arc<K>> {
+        let refs = self.index.read().get(key).cloned().unwrap_or_default();
+        let store = self.store.read();
+        let mut objs = refs
+            .iter()
+            .filter_map(|obj_ref| store.get(obj_ref))
+            .cloned()
+            .collect::<Vec<_>>();
+        drop(store);
+        objs.sort_by(|a, b| {
+            let (a, b) = (a.meta(), b.meta());
+            (&a.namespace, &a.name).cmp(&(&b.namespace, &b.name))
+        });
+        objs
+    }
+
     /// Return the number of elements in the store
     #[must_use]
     pub fn len(&self) -> usize {
@@ -192,6 +413,181 @@ where
     pub fn is_empty(&self) -> bool {
         self.store.read().is_empty()
     }
+
+    /// Return whether the store has been populated, see [`Store::wait_until_ready`]
+    #[must_use]
+    pub fn is_ready(&self) -> bool {
+        matches!(self.ready_rx.get().now_or_never(), Some(Ok(())))
+    }
+
+    /// The `resourceVersion` of the most recent object seen by the [`Writer`]
+    #[must_use]
+    pub fn resource_version(&self) -> Option<String> {
+        self.resource_version.read().clone()
+    }
+
+    /// Serialize the contents of the store for debugging and introspection
+    ///
+    /// The snapshot contains the `count` of cached objects, the last seen `resourceVersion`,
+    /// whether the store is `ready`, and the cached `objects` ordered by namespace and name.
+    /// This is intended to be served from a debug endpoint, to show what a controller
+    /// believes exists in the cluster.
+    ///
+    /// # Errors
+    /// Returns an error if any of the objects fail to serialize.
+    pub fn snapshot_json(&self) -> Result<serde_json::Value, serde_json::Error>
+    where
+        K: Serialize,
+    {
+        let mut objects = self.state();
+        objects.sort_by_key(|obj| (obj.namespace(), obj.name_any()));
+        Ok(serde_json::json!({
+            "count": objects.len(),
+            "resourceVersion": self.resource_version(),
+            "ready": self.is_ready(),
+            "objects": objects.iter().map(|obj| serde_json::to_value(obj.as_ref())).collect::<Result<Vec<_>, _>>()?,
+        }))
+    }
+
+    /// A stream of the keys of the objects that changed in the store, as they are changed by the [`Writer`]
+    ///
+    /// Every applied or deleted object is sent once the store has been updated, so the store can be read
+    /// for the new state of the object (which is gone if it was deleted). When the store is replaced
+    /// by a relist, the keys of all objects that were or are now in the store are sent, since any of them may
+    /// have changed. This is independent of any [`Controller`](crate::Controller), and is meant to keep state
+    /// derived from the store up to date.
+    ///
+    /// Only changes made after the call are sent, and the stream ends once the [`Writer`] is dropped.
+    /// The stream is unbounded, so it should be consumed promptly.
+    ///
+    /// ```no_run
+    /// # use k8s_openapi::api::core::v1::Pod;
+    /// use futures::StreamExt;
+    /// # async fn wrapper(store: kube::runtime::reflector::Store<Pod>) {
+    /// let mut changes = store.watch_changes();
+    /// while let Some(key) = changes.next().await {
+    ///     match store.get(&key) {
+    ///         Some(pod) => println!("{key} is now {:?}", pod.status),
+    ///         None => println!("{key} was deleted"),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn watch_changes(&self) -> impl Stream<Item = ObjectRef<K>> + Send + Unpin
+    where
+        K::DynamicType: Send,
+    {
+        let (tx, rx) = mpsc::unbounded();
+        if let Some(subscribers) = self.changes.lock().as_mut() {
+            subscribers.push(tx);
+        }
+        rx
+    }
+
+    /// Take a lightweight snapshot of the contents of the store, to [`diff`](Self::diff) against later
+    ///
+    /// The snapshot only holds the reference and `resourceVersion` of every object, not the objects themselves.
+    #[must_use]
+    pub fn snapshot(&self) -> StoreSnapshot<K> {
+        let versions = self
+            .store
+            .read()
+            .iter()
+            .map(|(key, obj)| (key.clone(), obj.resource_version()))
+            .collect();
+        StoreSnapshot { versions }
+    }
+
+    /// Compute the changes to the store since the `previous` snapshot was taken
+    ///
+    /// Objects are matched by their [`ObjectRef`], and are considered modified if their `resourceVersion` changed.
+    /// An object that was deleted and recreated with the same name in the meantime is considered modified.
+    ///
+    /// The changes are ordered by namespace and name.
+    #[must_use]
+    pub fn diff(&self, previous: &StoreSnapshot<K>) -> StoreDiff<K> {
+        let mut diff = StoreDiff::default();
+        {
+            let store = self.store.read();
+            for (key, obj) in store.iter() {
+                match previous.versions.get(key) {
+                    None => diff.added.push(obj.clone()),
+                    Some(version) if *version != obj.resource_version() => diff.modified.push(obj.clone()),
+                    Some(_) => {}
+                }
+            }
+            diff.removed = previous
+                .versions
+                .keys()
+                .filter(|key| !store.contains_key(*key))
+                .cloned()
+                .collect();
+        }
+        diff.added.sort_by_key(|obj| (obj.namespace(), obj.name_any()));
+        diff.modified.sort_by_key(|obj| (obj.namespace(), obj.name_any()));
+        diff.removed
+            .sort_by(|a, b| (&a.namespace, &a.name).cmp(&(&b.namespace, &b.name)));
+        diff
+    }
+}
+
+/// The references and `resourceVersion`s of the objects in a [`Store`], see [`Store::snapshot`]
+#[derive(Derivative)]
+#[derivative(
+    Debug(bound = "K::DynamicType: Debug"),
+    Clone(bound = "K::DynamicType: Clone")
+)]
+pub struct StoreSnapshot<K: 'static + Resource>
+where
+    K::DynamicType: Hash + Eq,
+{
+    versions: AHashMap<ObjectRef<K>, Option<String>>,
+}
+
+impl<K: 'static + Resource> StoreSnapshot<K>
+where
+    K::DynamicType: Hash + Eq,
+{
+    /// Return the number of objects in the snapshot
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.versions.len()
+    }
+
+    /// Return whether the snapshot is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.versions.is_empty()
+    }
+}
+
+/// The changes to a [`Store`] since a [`StoreSnapshot`] was taken, see [`Store::diff`]
+#[derive(Derivative)]
+#[derivative(Debug(bound = "K: Debug, K::DynamicType: Debug"), Default(bound = ""))]
+pub struct StoreDiff<K: 'static + Resource> {
+    /// Objects that were not in the snapshot
+    pub added: Vec<Arc<K>>,
+    /// Objects that are no longer in the store
+    pub removed: Vec<ObjectRef<K>>,
+    /// Objects whose `resourceVersion` changed since the snapshot
+    pub modified: Vec<Arc<K>>,
+}
+
+impl<K: 'static + Resource> StoreDiff<K> {
+    /// Return whether nothing changed
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Drops the `managedFields` of an object
+///
+/// A transform for [`Writer::with_transform`], for stores of objects whose field ownership is not needed.
+#[must_use]
+pub fn trim_managed_fields<K: Resource>(mut obj: K) -> K {
+    obj.managed_fields_mut().clear();
+    obj
 }
 
 /// Create a (Reader, Writer) for a `Store<K>` for a typed resource `K`
@@ -211,7 +607,7 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::{store, Writer};
+    use super::{store, trim_managed_fields, Writer};
     use crate::{reflector::ObjectRef, watcher};
     use k8s_openapi::api::core::v1::ConfigMap;
     use kube_client::api::ObjectMeta;
@@ -311,4 +707,206 @@ mod tests {
         let found = reader.find(|k| k.metadata.generation == Some(1234));
         assert_eq!(found.as_deref(), Some(&target_cm));
     }
+
+    #[test]
+    fn get_indexed_follows_the_index_key_of_objects() {
+        let cm = |name: &str, parent: &str| ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: Some("ns".to_string()),
+                labels: Some([("parent".to_string(), parent.to_string())].into()),
+                ..ObjectMeta::default()
+            },
+            ..ConfigMap::default()
+        };
+        let parent = |name: &str| ObjectRef::<ConfigMap>::new(name).within("ns");
+        let mut writer = Writer::default().with_index(|cm: &ConfigMap| {
+            ObjectRef::new(&cm.metadata.labels.as_ref().unwrap()["parent"]).within("ns")
+        });
+        let reader = writer.as_reader();
+        let names = |key| {
+            reader
+                .get_indexed(&key)
+                .iter()
+                .map(|cm| cm.metadata.name.clone().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        writer.apply_watcher_event(&watcher::Event::Restarted(vec![
+            cm("b", "x"),
+            cm("a", "x"),
+            cm("c", "y"),
+        ]));
+        assert_eq!(names(parent("x")), ["a", "b"]);
+        assert_eq!(names(parent("y")), ["c"]);
+
+        writer.apply_watcher_event(&watcher::Event::Applied(cm("a", "y")));
+        assert_eq!(names(parent("x")), ["b"]);
+        assert_eq!(names(parent("y")), ["a", "c"]);
+
+        writer.apply_watcher_event(&watcher::Event::Deleted(cm("c", "y")));
+        assert_eq!(names(parent("y")), ["a"]);
+
+        writer.apply_watcher_event(&watcher::Event::Restarted(vec![cm("d", "z")]));
+        assert!(names(parent("x")).is_empty());
+        assert!(names(parent("y")).is_empty());
+        assert_eq!(names(parent("z")), ["d"]);
+    }
+
+    #[test]
+    fn desynced_event_should_replace_store() {
+        let cm = |name: &str| ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: Some("ns".to_string()),
+                ..ObjectMeta::default()
+            },
+            ..ConfigMap::default()
+        };
+        let (reader, mut writer) = store::<ConfigMap>();
+        writer.apply_watcher_event(&watcher::Event::Restarted(vec![cm("a"), cm("b")]));
+        assert_eq!(reader.len(), 2);
+
+        writer.apply_watcher_event(&watcher::Event::Desynced {
+            reason: watcher::DesyncReason::Expired,
+            objects: vec![cm("c")],
+        });
+        assert_eq!(reader.len(), 1);
+        assert_eq!(
+            reader.get(&ObjectRef::from_obj(&cm("c"))).as_deref(),
+            Some(&cm("c"))
+        );
+    }
+
+    #[test]
+    fn snapshot_json_includes_objects_and_metadata() {
+        let cm = |name: &str, rv: &str| ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: Some("ns".to_string()),
+                resource_version: Some(rv.to_string()),
+                ..ObjectMeta::default()
+            },
+            ..ConfigMap::default()
+        };
+        let (reader, mut writer) = store::<ConfigMap>();
+        assert!(!reader.is_ready());
+        assert_eq!(reader.snapshot_json().unwrap()["count"], 0);
+
+        writer.apply_watcher_event(&watcher::Event::Restarted(vec![cm("b", "12"), cm("a", "9")]));
+        writer.apply_watcher_event(&watcher::Event::Applied(cm("c", "15")));
+        let snapshot = reader.snapshot_json().unwrap();
+        assert_eq!(snapshot["count"], 3);
+        assert_eq!(snapshot["resourceVersion"], "15");
+        assert_eq!(snapshot["ready"], true);
+        let names = snapshot["objects"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|obj| obj["metadata"]["name"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn diff_reports_changes_since_snapshot() {
+        let cm = |name: &str, rv: &str| ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: Some("ns".to_string()),
+                resource_version: Some(rv.to_string()),
+                ..ObjectMeta::default()
+            },
+            ..ConfigMap::default()
+        };
+        let (reader, mut writer) = store::<ConfigMap>();
+        writer.apply_watcher_event(&watcher::Event::Restarted(vec![
+            cm("a", "1"),
+            cm("b", "2"),
+            cm("c", "3"),
+        ]));
+        let snapshot = reader.snapshot();
+        assert_eq!(snapshot.len(), 3);
+        assert!(reader.diff(&snapshot).is_empty());
+
+        writer.apply_watcher_event(&watcher::Event::Applied(cm("b", "4")));
+        writer.apply_watcher_event(&watcher::Event::Deleted(cm("c", "5")));
+        writer.apply_watcher_event(&watcher::Event::Applied(cm("e", "6")));
+        writer.apply_watcher_event(&watcher::Event::Applied(cm("d", "7")));
+        // unchanged objects are not reported, even when they are applied again
+        writer.apply_watcher_event(&watcher::Event::Applied(cm("a", "1")));
+
+        let diff = reader.diff(&snapshot);
+        let names = |objs: &[std::sync::Arc<ConfigMap>]| {
+            objs.iter()
+                .map(|obj| obj.metadata.name.clone().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&diff.added), ["d", "e"]);
+        assert_eq!(names(&diff.modified), ["b"]);
+        assert_eq!(diff.removed, [ObjectRef::new("c").within("ns")]);
+    }
+
+    #[test]
+    fn transform_applies_to_applied_and_restarted_objects() {
+        let cm = |name: &str| ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: Some("ns".to_string()),
+                managed_fields: Some(vec![Default::default()]),
+                ..ObjectMeta::default()
+            },
+            ..ConfigMap::default()
+        };
+        let mut writer = Writer::<ConfigMap>::default().with_transform(trim_managed_fields);
+        let reader = writer.as_reader();
+        let trimmed = |name: &str| {
+            let obj = reader.get(&ObjectRef::from_obj(&cm(name))).unwrap();
+            obj.metadata.managed_fields.as_ref().map_or(true, Vec::is_empty)
+        };
+
+        writer.apply_watcher_event(&watcher::Event::Restarted(vec![cm("a")]));
+        assert!(trimmed("a"));
+        writer.apply_watcher_event(&watcher::Event::Applied(cm("b")));
+        assert!(trimmed("b"));
+        writer.apply_watcher_event(&watcher::Event::Desynced {
+            reason: watcher::DesyncReason::Expired,
+            objects: vec![cm("c")],
+        });
+        assert!(trimmed("c"));
+    }
+
+    #[test]
+    fn watch_changes_reports_changed_keys() {
+        use futures::{FutureExt, StreamExt};
+
+        let cm = |name: &str| ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: Some("ns".to_string()),
+                ..ObjectMeta::default()
+            },
+            ..ConfigMap::default()
+        };
+        let (reader, mut writer) = store::<ConfigMap>();
+        writer.apply_watcher_event(&watcher::Event::Applied(cm("before")));
+        let mut changes = reader.watch_changes();
+        let mut next = || changes.next().now_or_never().flatten().map(|key| key.name);
+
+        writer.apply_watcher_event(&watcher::Event::Applied(cm("a")));
+        writer.apply_watcher_event(&watcher::Event::Deleted(cm("a")));
+        assert_eq!(next().as_deref(), Some("a"));
+        assert_eq!(next().as_deref(), Some("a"));
+        assert!(next().is_none());
+
+        // a relist reports both the previous and the current objects
+        writer.apply_watcher_event(&watcher::Event::Restarted(vec![cm("b")]));
+        let mut relisted = [next(), next()].map(Option::unwrap);
+        relisted.sort();
+        assert_eq!(relisted, ["b", "before"]);
+        assert!(next().is_none());
+
+        drop(writer);
+        assert_eq!(changes.next().now_or_never(), Some(None));
+    }
 }
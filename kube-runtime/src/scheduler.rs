@@ -1,47 +1,122 @@
 //! Delays and deduplicates [`Stream`] items
-
-use futures::{stream::Fuse, Stream, StreamExt};
+//!
+//! The [`Scheduler`] is the work queue behind the [`Controller`](crate::Controller), but works for any message
+//! that can be hashed, such as the [`ObjectRef`](crate::reflector::ObjectRef)s of a custom reconcile loop.
+//! Messages are pushed as [`ScheduleRequest`]s through a stream (like a channel), and emitted by the
+//! [`Scheduler`] once they are due, at most once for every time they were requested.
+//!
+//! ```
+//! use futures::{channel::mpsc, StreamExt};
+//! use kube_runtime::scheduler::{scheduler, ScheduleRequest};
+//! use std::time::Duration;
+//! # async fn wrapper() {
+//! let (tx, rx) = mpsc::unbounded();
+//! let mut queue = Box::pin(scheduler(rx));
+//! tx.unbounded_send(ScheduleRequest::after("slow", Duration::from_secs(5))).unwrap();
+//! tx.unbounded_send(ScheduleRequest::now("fast")).unwrap();
+//! tx.unbounded_send(ScheduleRequest::now("fast")).unwrap();
+//! assert_eq!(queue.next().await, Some("fast"));
+//! assert_eq!(queue.next().await, Some("slow"));
+//! # }
+//! ```
+
+use crate::utils::{Clock, TokioClock};
+use futures::{future::BoxFuture, stream::Fuse, Stream, StreamExt};
 use hashbrown::{hash_map::Entry, HashMap};
+use parking_lot::Mutex;
 use pin_project::pin_project;
 use std::{
-    collections::HashSet,
+    collections::BTreeMap,
     hash::Hash,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
 use tokio::time::Instant;
-use tokio_util::time::delay_queue::{self, DelayQueue};
 
 /// A request to re-emit `message` at a given `Instant` (`run_at`).
 #[derive(Debug)]
 pub struct ScheduleRequest<T> {
+    /// The message to emit
     pub message: T,
+    /// When to emit the message
     pub run_at: Instant,
 }
 
+impl<T> ScheduleRequest<T> {
+    /// Request `message` to be emitted as soon as possible
+    pub fn now(message: T) -> Self {
+        Self {
+            message,
+            run_at: Instant::now(),
+        }
+    }
+
+    /// Request `message` to be emitted once `delay` has passed
+    pub fn after(message: T, delay: Duration) -> Self {
+        Self {
+            message,
+            run_at: Instant::now() + delay,
+        }
+    }
+}
+
+/// Point-in-time statistics of a [`Scheduler`]'s queue.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SchedulerStats {
+    /// Number of messages waiting for their scheduled time
+    pub scheduled: usize,
+    /// Number of messages whose scheduled time has passed, but that are held until the consumer can take them
+    pub pending: usize,
+    /// The time at which the oldest pending message became due
+    pub oldest_pending: Option<Instant>,
+}
+
+/// Shared copy of the messages held by a [`Scheduler`] (scheduled or pending), and the time they are due
+///
+/// Kept up to date by the [`Scheduler`] as messages are scheduled and emitted, see [`Scheduler::mirror_queue`].
+pub(crate) type QueueMirror<T> = Arc<Mutex<HashMap<T, Instant>>>;
+
+/// Position of a message in the queue: when it is due, and a sequence number to keep equal times apart
+type QueueKey = (Instant, u64);
+
 /// Internal metadata for a scheduled message.
 struct ScheduledEntry {
     run_at: Instant,
-    queue_key: delay_queue::Key,
+    queue_key: QueueKey,
 }
 
+/// A queue that delays and deduplicates messages, see [`scheduler()`]
+///
+/// Every message is held once: requesting a message that is already scheduled keeps whichever of the
+/// requests is due first, and requesting a message that is already due (but held pending) has no effect.
+/// Scheduled messages can be taken out of the queue again with [`Scheduler::cancel`].
+///
+/// Time is told by a [`Clock`], which is the clock of the Tokio runtime unless set with [`Scheduler::with_clock`].
 #[pin_project(project = SchedulerProj)]
-pub struct Scheduler<T, R> {
-    /// Queue of already-scheduled messages.
+pub struct Scheduler<T, R, C = TokioClock> {
+    /// Queue of already-scheduled messages, ordered by when they are due.
     ///
     /// To ensure that the metadata is kept up-to-date, use `schedule_message` and
     /// `poll_pop_queue_message` rather than manipulating this directly.
     ///
     /// NOTE: `scheduled` should be considered to hold the "canonical" representation of the message.
     /// Always pull the message out of `scheduled` once it has been retrieved from `queue`.
-    queue: DelayQueue<T>,
+    queue: BTreeMap<QueueKey, T>,
+    /// Sequence number of the next message inserted into `queue`
+    next_seq: u64,
+    /// The clock telling when messages are due
+    clock: C,
+    /// Wakes the scheduler when the message at the head of `queue` becomes due, along with its due time
+    sleep: Option<(Instant, BoxFuture<'static, ()>)>,
     /// Metadata for all currently scheduled messages. Used to detect duplicate messages.
     ///
     /// `scheduled` is considered to hold the "canonical" representation of the message.
     scheduled: HashMap<T, ScheduledEntry>,
-    /// Messages that are scheduled to have happened, but have been held using `hold_unless`.
-    pending: HashSet<T>,
+    /// Messages that are scheduled to have happened, but have been held using `hold_unless`,
+    /// along with the time they became due.
+    pending: HashMap<T, Instant>,
     /// Incoming queue of scheduling requests.
     #[pin]
     requests: Fuse<R>,
@@ -51,52 +126,139 @@ pub struct Scheduler<T, R> {
     /// for a request to be emitted, if the scheduler is "uninterrupted" for the configured
     /// debounce period. Its primary purpose to deduplicate requests that expire instantly.
     debounce: Duration,
+    /// Shared copy of `scheduled` and `pending`, for introspection from outside of the stream
+    mirror: Option<QueueMirror<T>>,
 }
 
 impl<T, R: Stream> Scheduler<T, R> {
     fn new(requests: R, debounce: Duration) -> Self {
         Self {
-            queue: DelayQueue::new(),
+            queue: BTreeMap::new(),
+            next_seq: 0,
+            clock: TokioClock,
+            sleep: None,
             scheduled: HashMap::new(),
-            pending: HashSet::new(),
+            pending: HashMap::new(),
             requests: requests.fuse(),
             debounce,
+            mirror: None,
         }
     }
 }
 
-impl<'a, T: Hash + Eq + Clone, R> SchedulerProj<'a, T, R> {
+impl<T, R, C> Scheduler<T, R, C> {
+    /// Keep `mirror` up to date with the messages held by the scheduler, and the time they are due
+    pub(crate) fn mirror_queue(mut self, mirror: Option<QueueMirror<T>>) -> Self {
+        self.mirror = mirror;
+        self
+    }
+
+    /// Tell when messages are due with `clock`, instead of the clock of the Tokio runtime
+    ///
+    /// The `run_at` of [`ScheduleRequest`]s is compared against [`Clock::now`], so requests should be created
+    /// with the time of the same clock, rather than with [`ScheduleRequest::now`] or [`ScheduleRequest::after`].
+    #[must_use]
+    pub fn with_clock<C2: Clock>(self, clock: C2) -> Scheduler<T, R, C2> {
+        Scheduler {
+            queue: self.queue,
+            next_seq: self.next_seq,
+            clock,
+            sleep: None,
+            scheduled: self.scheduled,
+            pending: self.pending,
+            requests: self.requests,
+            debounce: self.debounce,
+            mirror: self.mirror,
+        }
+    }
+}
+
+/// Insert `message` into the `queue` of a [`Scheduler`], to become due at `run_at`
+fn enqueue<T>(
+    queue: &mut BTreeMap<QueueKey, T>,
+    next_seq: &mut u64,
+    message: T,
+    run_at: Instant,
+) -> QueueKey {
+    let key = (run_at, *next_seq);
+    *next_seq += 1;
+    queue.insert(key, message);
+    key
+}
+
+impl<'a, T: Hash + Eq + Clone, R, C: Clock> SchedulerProj<'a, T, R, C> {
+    /// Poll for the next message in the queue that is due
+    ///
+    /// Returns `Poll::Ready(None)` when the queue is empty.
+    fn poll_expired(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let head = if let Some(key) = self.queue.keys().next() {
+            *key
+        } else {
+            *self.sleep = None;
+            return Poll::Ready(None);
+        };
+        if head.0 > self.clock.now() {
+            let sleep = match &mut *self.sleep {
+                Some((deadline, sleep)) if *deadline == head.0 => sleep,
+                sleep => &mut sleep.insert((head.0, self.clock.sleep_until(head.0))).1,
+            };
+            if sleep.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+        }
+        *self.sleep = None;
+        Poll::Ready(self.queue.remove(&head))
+    }
+
     /// Attempt to schedule a message into the queue.
     ///
     /// If the message is already in the queue then the earlier `request.run_at` takes precedence.
     fn schedule_message(&mut self, request: ScheduleRequest<T>) {
-        if self.pending.contains(&request.message) {
+        if self.pending.contains_key(&request.message) {
             // Message is already pending, so we can't even expedite it
             return;
         }
-        match self.scheduled.entry(request.message) {
+        let run_at = request.run_at + *self.debounce;
+        // only clone the message when the queue is mirrored
+        let mirrored = self
+            .mirror
+            .as_ref()
+            .map(|mirror| (mirror, request.message.clone()));
+        let scheduled = match self.scheduled.entry(request.message) {
             // If new request is supposed to be earlier than the current entry's scheduled
             // time (for eg: the new request is user triggered and the current entry is the
             // reconciler's usual retry), then give priority to the new request.
             Entry::Occupied(mut old_entry) if old_entry.get().run_at >= request.run_at => {
                 // Old entry will run after the new request, so replace it..
                 let entry = old_entry.get_mut();
-                self.queue
-                    .reset_at(&entry.queue_key, request.run_at + *self.debounce);
-                entry.run_at = request.run_at + *self.debounce;
+                let message = self
+                    .queue
+                    .remove(&entry.queue_key)
+                    .expect("Scheduled message was in the metadata map, but not in the Scheduler queue");
+                entry.queue_key = enqueue(self.queue, self.next_seq, message, run_at);
+                entry.run_at = run_at;
                 old_entry.replace_key();
+                true
             }
             Entry::Occupied(_old_entry) => {
                 // Old entry will run before the new request, so ignore the new request..
+                false
             }
             Entry::Vacant(entry) => {
                 // No old entry, we're free to go!
                 let message = entry.key().clone();
                 entry.insert(ScheduledEntry {
-                    run_at: request.run_at + *self.debounce,
-                    queue_key: self.queue.insert_at(message, request.run_at + *self.debounce),
+                    run_at,
+                    queue_key: enqueue(self.queue, self.next_seq, message, run_at),
                 });
+                true
             }
+        };
+        if let (true, Some((mirror, message))) = (scheduled, mirrored) {
+            let mut mirror = mirror.lock();
+            // remove first to replace the key as well, like `replace_key`
+            mirror.remove(&message);
+            mirror.insert(message, run_at);
         }
     }
 
@@ -106,45 +268,56 @@ impl<'a, T: Hash + Eq + Clone, R> SchedulerProj<'a, T, R> {
         cx: &mut Context<'_>,
         can_take_message: impl Fn(&T) -> bool,
     ) -> Poll<T> {
-        if let Some(msg) = self.pending.iter().find(|msg| can_take_message(*msg)).cloned() {
-            return Poll::Ready(self.pending.take(&msg).unwrap());
+        if let Some(msg) = self.pending.keys().find(|msg| can_take_message(*msg)).cloned() {
+            self.unmirror(&msg);
+            return Poll::Ready(self.pending.remove_entry(&msg).unwrap().0);
         }
 
         loop {
-            match self.queue.poll_expired(cx) {
+            match self.poll_expired(cx) {
                 Poll::Ready(Some(msg)) => {
-                    let msg = msg.into_inner();
-                    let (msg, _) = self.scheduled.remove_entry(&msg).expect(
+                    let (msg, entry) = self.scheduled.remove_entry(&msg).expect(
                         "Expired message was popped from the Scheduler queue, but was not in the metadata map",
                     );
                     if can_take_message(&msg) {
+                        self.unmirror(&msg);
                         break Poll::Ready(msg);
                     }
-                    self.pending.insert(msg);
+                    self.pending.insert(msg, entry.run_at);
                 }
                 Poll::Ready(None) | Poll::Pending => break Poll::Pending,
             }
         }
     }
 
+    /// Remove an emitted or cancelled message from the mirror
+    fn unmirror(&self, msg: &T) {
+        if let Some(mirror) = self.mirror.as_ref() {
+            mirror.lock().remove(msg);
+        }
+    }
+
     /// Attempt to retrieve a message from queue and mark it as pending.
     pub fn pop_queue_message_into_pending(&mut self, cx: &mut Context<'_>) {
-        while let Poll::Ready(Some(msg)) = self.queue.poll_expired(cx) {
-            let msg = msg.into_inner();
-            self.pending.insert(msg);
+        while let Poll::Ready(Some(msg)) = self.poll_expired(cx) {
+            let (msg, entry) = self.scheduled.remove_entry(&msg).expect(
+                "Expired message was popped from the Scheduler queue, but was not in the metadata map",
+            );
+            self.pending.insert(msg, entry.run_at);
         }
     }
 }
 
 /// See [`Scheduler::hold`]
-pub struct Hold<'a, T, R> {
-    scheduler: Pin<&'a mut Scheduler<T, R>>,
+pub struct Hold<'a, T, R, Clk = TokioClock> {
+    scheduler: Pin<&'a mut Scheduler<T, R, Clk>>,
 }
 
-impl<'a, T, R> Stream for Hold<'a, T, R>
+impl<'a, T, R, Clk> Stream for Hold<'a, T, R, Clk>
 where
     T: Eq + Hash + Clone,
     R: Stream<Item = ScheduleRequest<T>>,
+    Clk: Clock,
 {
     type Item = T;
 
@@ -166,16 +339,17 @@ where
 }
 
 /// See [`Scheduler::hold_unless`]
-pub struct HoldUnless<'a, T, R, C> {
-    scheduler: Pin<&'a mut Scheduler<T, R>>,
+pub struct HoldUnless<'a, T, R, C, Clk = TokioClock> {
+    scheduler: Pin<&'a mut Scheduler<T, R, Clk>>,
     can_take_message: C,
 }
 
-impl<'a, T, R, C> Stream for HoldUnless<'a, T, R, C>
+impl<'a, T, R, C, Clk> Stream for HoldUnless<'a, T, R, C, Clk>
 where
     T: Eq + Hash + Clone,
     R: Stream<Item = ScheduleRequest<T>>,
     C: Fn(&T) -> bool + Unpin,
+    Clk: Clock,
 {
     type Item = T;
 
@@ -199,10 +373,11 @@ where
     }
 }
 
-impl<T, R> Scheduler<T, R>
+impl<T, R, Clk> Scheduler<T, R, Clk>
 where
     T: Eq + Hash + Clone,
     R: Stream<Item = ScheduleRequest<T>>,
+    Clk: Clock,
 {
     /// A filtered view of the [`Scheduler`], which will keep items "pending" if
     /// `can_take_message` returns `false`, allowing them to be handled as soon as
@@ -214,7 +389,10 @@ where
     ///
     /// NOTE: `can_take_message` should be considered to be fairly performance-sensitive, since
     /// it will generally be executed for each pending message, for each [`poll_next`](Self::poll_next).
-    pub fn hold_unless<C: Fn(&T) -> bool>(self: Pin<&mut Self>, can_take_message: C) -> HoldUnless<T, R, C> {
+    pub fn hold_unless<C: Fn(&T) -> bool>(
+        self: Pin<&mut Self>,
+        can_take_message: C,
+    ) -> HoldUnless<T, R, C, Clk> {
         HoldUnless {
             scheduler: self,
             can_take_message,
@@ -225,21 +403,53 @@ where
     /// Its equivalent to doing `self.hold_unless(|_| false)` and is useful when the
     /// consumer is not ready to consume the expired messages that the [`Scheduler`] emits.
     #[must_use]
-    pub fn hold(self: Pin<&mut Self>) -> Hold<T, R> {
+    pub fn hold(self: Pin<&mut Self>) -> Hold<T, R, Clk> {
         Hold { scheduler: self }
     }
 
+    /// Statistics about the messages currently scheduled or held pending by the [`Scheduler`]
+    ///
+    /// Pending messages are due, but have not been taken by the consumer yet. A growing number of pending
+    /// messages (or an increasing age of the oldest one) means that the consumer is falling behind.
+    #[must_use]
+    pub fn stats(&self) -> SchedulerStats {
+        SchedulerStats {
+            scheduled: self.scheduled.len(),
+            pending: self.pending.len(),
+            oldest_pending: self.pending.values().min().copied(),
+        }
+    }
+
+    /// Cancel a scheduled or pending `msg`, so that it is not emitted
+    ///
+    /// Returns whether `msg` was held by the [`Scheduler`]. Only requests that have already been received from
+    /// the `requests` stream are cancelled, so `msg` is still emitted if it is requested again later.
+    pub fn cancel(self: Pin<&mut Self>, msg: &T) -> bool {
+        let this = self.project();
+        let held = if let Some(entry) = this.scheduled.remove(msg) {
+            this.queue.remove(&entry.queue_key);
+            true
+        } else {
+            this.pending.remove(msg).is_some()
+        };
+        if held {
+            this.unmirror(msg);
+        }
+        held
+    }
+
     /// Checks whether `msg` is currently a pending message (held by `hold_unless`)
     #[cfg(test)]
     pub fn contains_pending(&self, msg: &T) -> bool {
-        self.pending.contains(msg)
+        self.pending.contains_key(msg)
     }
 }
 
-impl<T, R> Stream for Scheduler<T, R>
+impl<T, R, Clk> Stream for Scheduler<T, R, Clk>
 where
     T: Eq + Hash + Clone,
     R: Stream<Item = ScheduleRequest<T>>,
+    Clk: Clock,
 {
     type Item = T;
 
@@ -281,7 +491,7 @@ pub fn debounced_scheduler<T: Eq + Hash + Clone, S: Stream<Item = ScheduleReques
 mod tests {
     use crate::utils::KubeRuntimeStreamExt;
 
-    use super::{debounced_scheduler, scheduler, ScheduleRequest};
+    use super::{debounced_scheduler, scheduler, ScheduleRequest, SchedulerStats};
     use derivative::Derivative;
     use futures::{channel::mpsc, future, pin_mut, poll, stream, FutureExt, SinkExt, StreamExt};
     use std::task::Poll;
@@ -321,6 +531,75 @@ mod tests {
         assert!(scheduler.as_mut().hold_unless(|_| true).next().await.is_none());
     }
 
+    #[tokio::test]
+    async fn scheduler_should_report_pending_stats() {
+        pause();
+        let (mut tx, rx) = mpsc::unbounded::<ScheduleRequest<u8>>();
+        let mut scheduler = Box::pin(scheduler(rx));
+        let due = Instant::now();
+        for (message, run_at) in [(1, due), (2, due + Duration::from_secs(5))] {
+            tx.send(ScheduleRequest { message, run_at }).await.unwrap();
+        }
+        assert!(poll!(scheduler.as_mut().hold().next()).is_pending());
+        assert_eq!(scheduler.stats(), SchedulerStats {
+            scheduled: 1,
+            pending: 1,
+            oldest_pending: Some(due),
+        });
+        assert_eq!(scheduler.as_mut().next().await.unwrap(), 1);
+        assert_eq!(scheduler.stats(), SchedulerStats {
+            scheduled: 1,
+            pending: 0,
+            oldest_pending: None,
+        });
+    }
+
+    #[tokio::test]
+    async fn scheduler_should_reschedule_items_taken_after_being_held() {
+        pause();
+        let (mut tx, rx) = mpsc::unbounded::<ScheduleRequest<u8>>();
+        let mut scheduler = Box::pin(scheduler(rx));
+        tx.send(ScheduleRequest::now(1)).await.unwrap();
+        assert!(poll!(scheduler.as_mut().hold().next()).is_pending());
+        assert!(scheduler.contains_pending(&1));
+        assert_eq!(scheduler.as_mut().next().await.unwrap(), 1);
+        // a held message must not be left behind as scheduled, or it would swallow this request
+        tx.send(ScheduleRequest::after(1, Duration::from_secs(1)))
+            .await
+            .unwrap();
+        assert!(poll!(scheduler.as_mut().next()).is_pending());
+        assert_eq!(scheduler.stats().scheduled, 1);
+        advance(Duration::from_secs(2)).await;
+        assert_eq!(unwrap_poll(poll!(scheduler.as_mut().next())), Some(1));
+        assert_eq!(scheduler.stats(), SchedulerStats::default());
+    }
+
+    #[tokio::test]
+    async fn scheduler_should_not_emit_cancelled_items() {
+        pause();
+        let (mut tx, rx) = mpsc::unbounded::<ScheduleRequest<u8>>();
+        let mut scheduler = Box::pin(scheduler(rx));
+        for request in [
+            ScheduleRequest::now(1),
+            ScheduleRequest::now(2),
+            ScheduleRequest::after(3, Duration::from_secs(5)),
+        ] {
+            tx.send(request).await.unwrap();
+        }
+        assert!(poll!(scheduler.as_mut().hold_unless(|msg| *msg == 3).next()).is_pending());
+        assert!(scheduler.as_mut().cancel(&1));
+        assert!(scheduler.as_mut().cancel(&3));
+        assert!(!scheduler.as_mut().cancel(&3));
+        assert_eq!(scheduler.stats(), SchedulerStats {
+            scheduled: 0,
+            pending: 1,
+            oldest_pending: Some(Instant::now()),
+        });
+        assert_eq!(scheduler.as_mut().next().await, Some(2));
+        drop(tx);
+        assert_eq!(scheduler.as_mut().next().await, None);
+    }
+
     #[tokio::test]
     async fn scheduler_should_not_reschedule_pending_items() {
         pause();
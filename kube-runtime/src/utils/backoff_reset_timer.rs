@@ -1,9 +1,11 @@
 use std::time::{Duration, Instant};
 
-use backoff::{backoff::Backoff, Clock, SystemClock};
+use backoff::{backoff::Backoff, Clock};
+
+use super::TokioClock;
 
 /// A [`Backoff`] wrapper that resets after a fixed duration has elapsed.
-pub struct ResetTimerBackoff<B, C = SystemClock> {
+pub struct ResetTimerBackoff<B, C = TokioClock> {
     backoff: B,
     clock: C,
     last_backoff: Option<Instant>,
@@ -12,12 +14,13 @@ pub struct ResetTimerBackoff<B, C = SystemClock> {
 
 impl<B: Backoff> ResetTimerBackoff<B> {
     pub fn new(backoff: B, reset_duration: Duration) -> Self {
-        Self::new_with_custom_clock(backoff, reset_duration, SystemClock {})
+        Self::new_with_custom_clock(backoff, reset_duration, TokioClock)
     }
 }
 
 impl<B: Backoff, C: Clock> ResetTimerBackoff<B, C> {
-    fn new_with_custom_clock(backoff: B, reset_duration: Duration, clock: C) -> Self {
+    /// Create a `ResetTimerBackoff` that measures the reset duration with `clock`
+    pub fn new_with_custom_clock(backoff: B, reset_duration: Duration, clock: C) -> Self {
         Self {
             backoff,
             clock,
@@ -50,20 +53,19 @@ impl<B: Backoff, C: Clock> Backoff for ResetTimerBackoff<B, C> {
 
 #[cfg(test)]
 mod tests {
-    use backoff::{backoff::Backoff, Clock};
+    use backoff::backoff::Backoff;
     use tokio::time::advance;
 
     use super::ResetTimerBackoff;
     use crate::utils::stream_backoff::tests::LinearBackoff;
-    use std::time::{Duration, Instant};
+    use std::time::Duration;
 
     #[tokio::test]
     async fn should_reset_when_timer_expires() {
         tokio::time::pause();
-        let mut backoff = ResetTimerBackoff::new_with_custom_clock(
+        let mut backoff = ResetTimerBackoff::new(
             LinearBackoff::new(Duration::from_secs(2)),
             Duration::from_secs(60),
-            TokioClock,
         );
         assert_eq!(backoff.next_backoff(), Some(Duration::from_secs(2)));
         advance(Duration::from_secs(40)).await;
@@ -75,12 +77,4 @@ mod tests {
         advance(Duration::from_secs(80)).await;
         assert_eq!(backoff.next_backoff(), Some(Duration::from_secs(2)));
     }
-
-    struct TokioClock;
-
-    impl Clock for TokioClock {
-        fn now(&self) -> Instant {
-            tokio::time::Instant::now().into_std()
-        }
-    }
 }
@@ -0,0 +1,162 @@
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::time::Duration;
+
+use futures::{Future, Stream, TryStream};
+use pin_project::pin_project;
+use tokio::time::{sleep, Sleep};
+
+/// Stream returned by the [`batched`](super::WatchStreamExt::batched) method.
+///
+/// Groups the items of the inner stream into batches of up to `max_items`,
+/// flushing a partial batch once `max_delay` has passed since its first item.
+#[pin_project]
+#[must_use = "streams do nothing unless polled"]
+pub struct Batched<St: TryStream> {
+    #[pin]
+    stream: St,
+    max_items: usize,
+    max_delay: Duration,
+    buffer: Vec<St::Ok>,
+    #[pin]
+    deadline: Option<Sleep>,
+    pending_error: Option<St::Error>,
+    done: bool,
+}
+
+impl<St: TryStream> Batched<St> {
+    pub(super) fn new(stream: St, max_items: usize, max_delay: Duration) -> Self {
+        assert!(max_items > 0, "max_items must be greater than zero");
+        Self {
+            stream,
+            max_items,
+            max_delay,
+            buffer: Vec::with_capacity(max_items),
+            deadline: None,
+            pending_error: None,
+            done: false,
+        }
+    }
+}
+
+impl<St: TryStream> Stream for Batched<St> {
+    type Item = Result<Vec<St::Ok>, St::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut me = self.project();
+        loop {
+            if let Some(err) = me.pending_error.take() {
+                return Poll::Ready(Some(Err(err)));
+            }
+            if *me.done {
+                if me.buffer.is_empty() {
+                    return Poll::Ready(None);
+                }
+                break;
+            }
+            match me.stream.as_mut().try_poll_next(cx) {
+                Poll::Ready(Some(Ok(item))) => {
+                    if me.buffer.is_empty() {
+                        me.deadline.set(Some(sleep(*me.max_delay)));
+                    }
+                    me.buffer.push(item);
+                    if me.buffer.len() >= *me.max_items {
+                        break;
+                    }
+                }
+                Poll::Ready(Some(Err(err))) => {
+                    if me.buffer.is_empty() {
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                    // Flush what we have first, so that items are not reordered around the error
+                    *me.pending_error = Some(err);
+                    break;
+                }
+                Poll::Ready(None) => *me.done = true,
+                Poll::Pending => {
+                    let expired = me
+                        .deadline
+                        .as_mut()
+                        .as_pin_mut()
+                        .map_or(false, |d| d.poll(cx).is_ready());
+                    if expired {
+                        break;
+                    }
+                    return Poll::Pending;
+                }
+            }
+        }
+        me.deadline.set(None);
+        let batch = std::mem::replace(me.buffer, Vec::with_capacity(*me.max_items));
+        Poll::Ready(Some(Ok(batch)))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::{task::Poll, time::Duration};
+
+    use super::Batched;
+    use crate::watcher::{Error, Event};
+    use futures::{channel::mpsc, pin_mut, poll, stream, StreamExt};
+
+    #[tokio::test]
+    async fn batched_flushes_full_batches_and_remainder() {
+        let st = stream::iter([
+            Ok(Event::Applied(0)),
+            Ok(Event::Applied(1)),
+            Ok(Event::Applied(2)),
+            Ok(Event::Applied(3)),
+            Ok(Event::Applied(4)),
+        ]);
+        let batches = Batched::new(st, 2, Duration::from_secs(10))
+            .map(|batch: Result<_, Error>| batch.unwrap().len())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(batches, vec![2, 2, 1]);
+    }
+
+    #[tokio::test]
+    async fn batched_flushes_before_errors() {
+        let st = stream::iter([
+            Ok(Event::Applied(0)),
+            Err(Error::TooManyObjects),
+            Ok(Event::Applied(1)),
+        ]);
+        let batched = Batched::new(st, 10, Duration::from_secs(10));
+        pin_mut!(batched);
+        assert!(matches!(poll!(batched.next()), Poll::Ready(Some(Ok(b))) if b.len() == 1));
+        assert!(matches!(
+            poll!(batched.next()),
+            Poll::Ready(Some(Err(Error::TooManyObjects)))
+        ));
+        assert!(matches!(poll!(batched.next()), Poll::Ready(Some(Ok(b))) if b.len() == 1));
+        assert!(matches!(poll!(batched.next()), Poll::Ready(None)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn batched_flushes_partial_batch_after_max_delay() {
+        let (tx, rx) = mpsc::unbounded::<Result<Event<u32>, Error>>();
+        let batched = Batched::new(rx, 10, Duration::from_secs(1));
+        pin_mut!(batched);
+        assert!(poll!(batched.next()).is_pending());
+
+        tx.unbounded_send(Ok(Event::Applied(0))).unwrap();
+        tx.unbounded_send(Ok(Event::Applied(1))).unwrap();
+        assert!(poll!(batched.next()).is_pending());
+        tokio::time::advance(Duration::from_millis(500)).await;
+        assert!(poll!(batched.next()).is_pending());
+        tokio::time::advance(Duration::from_millis(501)).await;
+        assert!(matches!(poll!(batched.next()), Poll::Ready(Some(Ok(b))) if b.len() == 2));
+
+        // The timer only starts with the first item of the next batch
+        tokio::time::advance(Duration::from_secs(5)).await;
+        tx.unbounded_send(Ok(Event::Applied(2))).unwrap();
+        assert!(poll!(batched.next()).is_pending());
+        drop(tx);
+        assert!(matches!(poll!(batched.next()), Poll::Ready(Some(Ok(b))) if b.len() == 1));
+        assert!(matches!(poll!(batched.next()), Poll::Ready(None)));
+    }
+}
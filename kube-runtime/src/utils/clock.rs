@@ -0,0 +1,55 @@
+use futures::future::BoxFuture;
+use std::{fmt::Debug, sync::Arc};
+use tokio::time::Instant;
+
+/// A source of time for the [`scheduler`](crate::scheduler()) and the [`Controller`](crate::Controller)
+///
+/// Tells when scheduled messages are due, and waits for them to become due.
+/// Defaults to [`TokioClock`], a custom clock can be installed with
+/// [`Controller::with_clock`](crate::Controller::with_clock) or [`Scheduler::with_clock`](crate::scheduler::Scheduler::with_clock),
+/// for instance to control time in tests independently of other timers.
+pub trait Clock: Debug + Send + Sync + 'static {
+    /// The current time
+    fn now(&self) -> Instant;
+
+    /// Wait until the clock has reached `deadline`
+    fn sleep_until(&self, deadline: Instant) -> BoxFuture<'static, ()>;
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> BoxFuture<'static, ()> {
+        (**self).sleep_until(deadline)
+    }
+}
+
+/// A [`Clock`] that follows the time of the Tokio runtime
+///
+/// This is the same as the system clock, except when Tokio's time is paused
+/// (with [`tokio::time::pause`] or `#[tokio::test(start_paused = true)]`),
+/// in which case it only moves forward when time is advanced.
+/// This keeps backoffs consistent with the timers of the [`scheduler`](crate::scheduler()),
+/// so that tests can control both deterministically.
+///
+/// It is also a [`backoff::Clock`], for the [`ResetTimerBackoff`](super::ResetTimerBackoff).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TokioClock;
+
+impl Clock for TokioClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep_until(deadline))
+    }
+}
+
+impl backoff::Clock for TokioClock {
+    fn now(&self) -> std::time::Instant {
+        Instant::now().into_std()
+    }
+}
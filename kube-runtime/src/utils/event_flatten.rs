@@ -45,7 +45,7 @@ where
                         continue;
                     }
                 }
-                Some(Ok(Event::Restarted(objs))) => {
+                Some(Ok(Event::Restarted(objs) | Event::Desynced { objects: objs, .. })) => {
                     *me.queue = objs.into_iter();
                     continue;
                 }
@@ -0,0 +1,99 @@
+use crate::watcher::{Error, Event, UnbatchedEvent};
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures::{ready, Stream};
+use pin_project::pin_project;
+
+#[pin_project]
+/// Stream returned by the [`unbatched`](super::WatchStreamExt::unbatched) method.
+#[must_use = "streams do nothing unless polled"]
+pub struct EventUnbatch<St, K> {
+    #[pin]
+    stream: St,
+    queue: std::vec::IntoIter<K>,
+    in_init: bool,
+}
+impl<St, K> EventUnbatch<St, K> {
+    pub(super) fn new(stream: St) -> Self {
+        Self {
+            stream,
+            queue: vec![].into_iter(),
+            in_init: false,
+        }
+    }
+}
+impl<St, K> Stream for EventUnbatch<St, K>
+where
+    St: Stream<Item = Result<Event<K>, Error>>,
+{
+    type Item = Result<UnbatchedEvent<K>, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut me = self.project();
+        if *me.in_init {
+            if let Some(obj) = me.queue.next() {
+                return Poll::Ready(Some(Ok(UnbatchedEvent::InitApply(obj))));
+            }
+            *me.in_init = false;
+            return Poll::Ready(Some(Ok(UnbatchedEvent::InitDone)));
+        }
+        Poll::Ready(match ready!(me.stream.as_mut().poll_next(cx)) {
+            Some(Ok(Event::Applied(obj))) => Some(Ok(UnbatchedEvent::Applied(obj))),
+            Some(Ok(Event::Deleted(obj))) => Some(Ok(UnbatchedEvent::Deleted(obj))),
+            Some(Ok(Event::Restarted(objs))) => {
+                *me.queue = objs.into_iter();
+                *me.in_init = true;
+                Some(Ok(UnbatchedEvent::Init { desync: None }))
+            }
+            Some(Ok(Event::Desynced { reason, objects })) => {
+                *me.queue = objects.into_iter();
+                *me.in_init = true;
+                Some(Ok(UnbatchedEvent::Init { desync: Some(reason) }))
+            }
+            Some(Err(err)) => Some(Err(err)),
+            None => None,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{Error, Event, EventUnbatch, UnbatchedEvent};
+    use crate::watcher::DesyncReason;
+    use futures::{stream, StreamExt};
+
+    #[tokio::test]
+    async fn unbatches_relists_between_init_markers() {
+        let data = stream::iter([
+            Ok(Event::Restarted(vec![1, 2])),
+            Ok(Event::Applied(3)),
+            Ok(Event::Deleted(1)),
+            Err(Error::TooManyObjects),
+            Ok(Event::Desynced {
+                reason: DesyncReason::Expired,
+                objects: vec![],
+            }),
+            Ok(Event::Applied(4)),
+        ]);
+        let events = EventUnbatch::new(data)
+            .map(|event| event.map_err(|err| err.to_string()))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(events, [
+            Ok(UnbatchedEvent::Init { desync: None }),
+            Ok(UnbatchedEvent::InitApply(1)),
+            Ok(UnbatchedEvent::InitApply(2)),
+            Ok(UnbatchedEvent::InitDone),
+            Ok(UnbatchedEvent::Applied(3)),
+            Ok(UnbatchedEvent::Deleted(1)),
+            Err(Error::TooManyObjects.to_string()),
+            Ok(UnbatchedEvent::Init {
+                desync: Some(DesyncReason::Expired)
+            }),
+            Ok(UnbatchedEvent::InitDone),
+            Ok(UnbatchedEvent::Applied(4)),
+        ]);
+    }
+}
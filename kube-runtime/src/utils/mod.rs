@@ -1,21 +1,31 @@
 //! Helpers for manipulating built-in streams
 
 mod backoff_reset_timer;
+mod batched;
+mod clock;
 pub(crate) mod delayed_init;
 mod event_flatten;
 mod event_modify;
+mod event_unbatch;
+mod pausable;
 #[cfg(feature = "unstable-runtime-predicates")] mod predicate;
 mod reflect;
+mod reflect_changes;
 mod stream_backoff;
 #[cfg(feature = "unstable-runtime-subscribe")] pub mod stream_subscribe;
 mod watch_ext;
 
 pub use backoff_reset_timer::ResetTimerBackoff;
+pub use batched::Batched;
+pub use clock::{Clock, TokioClock};
 pub use event_flatten::EventFlatten;
 pub use event_modify::EventModify;
+pub use event_unbatch::EventUnbatch;
+pub use pausable::{Pausable, PauseHandle};
 #[cfg(feature = "unstable-runtime-predicates")]
 pub use predicate::{predicates, Predicate, PredicateFilter};
 pub use reflect::Reflect;
+pub use reflect_changes::{Change, ReflectChanges};
 pub use stream_backoff::StreamBackoff;
 #[cfg(feature = "unstable-runtime-subscribe")]
 pub use stream_subscribe::StreamSubscribe;
@@ -0,0 +1,130 @@
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use futures::{task::AtomicWaker, Stream};
+use pin_project::pin_project;
+
+#[derive(Debug, Default)]
+struct PauseState {
+    paused: AtomicBool,
+    waker: AtomicWaker,
+}
+
+/// Handle to pause and resume a [`Pausable`] stream
+///
+/// Returned by [`pausable`](super::WatchStreamExt::pausable). Can be cloned to control the stream from multiple places.
+#[derive(Clone, Debug)]
+pub struct PauseHandle {
+    state: Arc<PauseState>,
+}
+
+impl PauseHandle {
+    /// Stop emitting items from the stream until [`resume`](Self::resume) is called
+    ///
+    /// An item that is already being delivered is not interrupted.
+    pub fn pause(&self) {
+        self.state.paused.store(true, Ordering::SeqCst);
+    }
+
+    /// Resume emitting items from the stream
+    pub fn resume(&self) {
+        self.state.paused.store(false, Ordering::SeqCst);
+        self.state.waker.wake();
+    }
+
+    /// Whether the stream is currently paused
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        self.state.paused.load(Ordering::SeqCst)
+    }
+}
+
+/// Stream returned by the [`pausable`](super::WatchStreamExt::pausable) method.
+///
+/// Stops polling the inner stream while paused through its [`PauseHandle`].
+#[pin_project]
+#[must_use = "streams do nothing unless polled"]
+pub struct Pausable<St> {
+    #[pin]
+    stream: St,
+    state: Arc<PauseState>,
+}
+
+impl<St> Pausable<St> {
+    pub(super) fn new(stream: St) -> (Self, PauseHandle) {
+        let state = Arc::new(PauseState::default());
+        let handle = PauseHandle { state: state.clone() };
+        (Self { stream, state }, handle)
+    }
+}
+
+impl<St: Stream> Stream for Pausable<St> {
+    type Item = St::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let me = self.project();
+        if me.state.paused.load(Ordering::SeqCst) {
+            me.state.waker.register(cx.waker());
+            // Check again, in case we were resumed before the waker was registered
+            if me.state.paused.load(Ordering::SeqCst) {
+                return Poll::Pending;
+            }
+        }
+        me.stream.poll_next(cx)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::{task::Poll, time::Duration};
+
+    use super::Pausable;
+    use crate::watcher::{Error, Event};
+    use futures::{pin_mut, poll, stream, StreamExt};
+
+    #[tokio::test]
+    async fn pausable_holds_items_while_paused() {
+        let st = stream::iter([Ok::<_, Error>(Event::Applied(0)), Ok(Event::Applied(1))]);
+        let (st, handle) = Pausable::new(st);
+        pin_mut!(st);
+        assert!(matches!(
+            poll!(st.next()),
+            Poll::Ready(Some(Ok(Event::Applied(0))))
+        ));
+
+        handle.pause();
+        assert!(handle.is_paused());
+        assert!(poll!(st.next()).is_pending());
+        assert!(poll!(st.next()).is_pending());
+
+        handle.resume();
+        assert!(matches!(
+            poll!(st.next()),
+            Poll::Ready(Some(Ok(Event::Applied(1))))
+        ));
+        assert!(matches!(poll!(st.next()), Poll::Ready(None)));
+    }
+
+    #[tokio::test]
+    async fn pausable_wakes_consumer_on_resume() {
+        let (st, handle) = Pausable::new(stream::iter([Ok::<_, Error>(Event::Applied(0))]));
+        handle.pause();
+        let mut consumer = tokio::spawn(st.collect::<Vec<_>>());
+        assert!(tokio::time::timeout(Duration::from_millis(10), &mut consumer)
+            .await
+            .is_err());
+
+        handle.resume();
+        let events = tokio::time::timeout(Duration::from_secs(1), consumer)
+            .await
+            .expect("consumer should be woken by resume")
+            .unwrap();
+        assert_eq!(events.len(), 1);
+    }
+}
@@ -191,6 +191,32 @@ pub mod predicates {
     pub fn annotations<K: Resource>(obj: &K) -> Option<u64> {
         Some(hash(obj.annotations()))
     }
+
+    /// Hash the generation of a Resource K once it was observed by `observed_generation`
+    ///
+    /// `observed_generation` is usually `.status.observedGeneration`, which the reconciler sets once it has
+    /// reconciled a generation successfully. From then on only spec changes pass, while every update passes
+    /// as long as the current generation is pending. Objects without a generation, and objects that are being
+    /// deleted, always pass.
+    ///
+    /// ```
+    /// # use k8s_openapi::api::apps::v1::Deployment;
+    /// use kube::runtime::{predicates, Predicate};
+    /// # fn blah<K>(a: impl Predicate<K>) {}
+    /// let pred = predicates::observed_generation(|deploy: &Deployment| {
+    ///     deploy.status.as_ref()?.observed_generation
+    /// });
+    /// blah::<Deployment>(pred);
+    /// ```
+    pub fn observed_generation<K: Resource>(
+        observed_generation: impl Fn(&K) -> Option<i64>,
+    ) -> impl Fn(&K) -> Option<u64> {
+        move |obj| {
+            let meta = obj.meta();
+            let generation = meta.generation.filter(|_| meta.deletion_timestamp.is_none())?;
+            (observed_generation(obj) == Some(generation)).then(|| hash(&generation))
+        }
+    }
 }
 
 #[cfg(test)]
@@ -247,4 +273,47 @@ pub(crate) mod tests {
         assert_eq!(second.meta().generation, Some(2));
         assert!(matches!(poll!(rx.next()), Poll::Ready(None)));
     }
+
+    #[tokio::test]
+    async fn observed_generation_hides_updates_of_observed_generations() {
+        use k8s_openapi::api::core::v1::ConfigMap;
+        // the observed generation is kept in an annotation to avoid a custom resource
+        let mkobj = |gen: i64, observed: i64, deleting: bool| {
+            let cm: ConfigMap = serde_json::from_value(json!({
+                "apiVersion": "v1",
+                "kind": "ConfigMap",
+                "metadata": {
+                    "name": "blog",
+                    "generation": gen,
+                    "annotations": { "observed": observed.to_string() },
+                    "deletionTimestamp": deleting.then_some("2023-01-01T00:00:00Z"),
+                },
+            }))
+            .unwrap();
+            cm
+        };
+        let data = stream::iter([
+            Ok(mkobj(1, 0, false)),
+            Ok(mkobj(1, 0, false)),
+            // the reconciler observed the generation
+            Ok(mkobj(1, 1, false)),
+            Ok(mkobj(1, 1, false)),
+            // spec update
+            Ok(mkobj(2, 1, false)),
+            Ok(mkobj(2, 2, false)),
+            Ok(mkobj(2, 2, true)),
+        ]);
+        let pred = predicates::observed_generation(|cm: &ConfigMap| {
+            cm.metadata.annotations.as_ref()?.get("observed")?.parse().ok()
+        });
+        let passed = PredicateFilter::new(data, pred)
+            .map(|cm| {
+                let cm = cm.unwrap();
+                let observed = &cm.metadata.annotations.as_ref().unwrap()["observed"];
+                format!("{}/{}", cm.metadata.generation.unwrap(), observed)
+            })
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(passed, ["1/0", "1/0", "1/1", "2/1", "2/2", "2/2"]);
+    }
 }
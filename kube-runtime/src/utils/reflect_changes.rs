@@ -0,0 +1,198 @@
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::{collections::VecDeque, sync::Arc};
+
+use ahash::AHashSet;
+use futures::{ready, Stream, TryStream};
+use pin_project::pin_project;
+
+use crate::{
+    reflector::{store::Writer, ObjectRef, Store},
+    watcher::{Error, Event},
+};
+use kube_client::{Resource, ResourceExt};
+
+/// A change to an object, as emitted by [`reflect_changes`](super::WatchStreamExt::reflect_changes)
+#[derive(Debug, Clone)]
+pub enum Change<K> {
+    /// An object that was not previously in the store
+    Added(Arc<K>),
+    /// An object that replaced a prior version in the store
+    Modified {
+        /// The version of the object in the store before the update
+        old: Arc<K>,
+        /// The updated version of the object
+        new: Arc<K>,
+    },
+    /// An object that was removed from the store
+    Deleted(Arc<K>),
+}
+
+/// Stream returned by the [`reflect_changes`](super::WatchStreamExt::reflect_changes) method
+#[pin_project]
+pub struct ReflectChanges<St, K>
+where
+    K: Resource + Clone + 'static,
+    K::DynamicType: Eq + std::hash::Hash + Clone,
+{
+    #[pin]
+    stream: St,
+    writer: Writer<K>,
+    reader: Store<K>,
+    buffer: VecDeque<Change<K>>,
+}
+
+impl<St, K> ReflectChanges<St, K>
+where
+    St: TryStream<Ok = Event<K>>,
+    K: Resource + Clone,
+    K::DynamicType: Eq + std::hash::Hash + Clone,
+{
+    pub(super) fn new(stream: St, writer: Writer<K>) -> ReflectChanges<St, K> {
+        let reader = writer.as_reader();
+        Self {
+            stream,
+            writer,
+            reader,
+            buffer: VecDeque::new(),
+        }
+    }
+}
+
+/// Compute the changes an event makes to the store, before it is applied
+fn changes<K>(reader: &Store<K>, dyntype: &K::DynamicType, event: &Event<K>) -> Vec<Change<K>>
+where
+    K: Resource + Clone,
+    K::DynamicType: Eq + std::hash::Hash + Clone,
+{
+    let key = |obj: &K| ObjectRef::from_obj_with(obj, dyntype.clone());
+    match event {
+        Event::Applied(obj) => {
+            let new = Arc::new(obj.clone());
+            vec![match reader.get(&key(obj)) {
+                Some(old) => Change::Modified { old, new },
+                None => Change::Added(new),
+            }]
+        }
+        Event::Deleted(obj) => vec![Change::Deleted(Arc::new(obj.clone()))],
+        Event::Restarted(objs) | Event::Desynced { objects: objs, .. } => {
+            let mut seen = AHashSet::with_capacity(objs.len());
+            let mut changes = Vec::new();
+            for obj in objs {
+                seen.insert(key(obj));
+                let new = Arc::new(obj.clone());
+                match reader.get(&key(obj)) {
+                    // relists repeat every object, only report the ones that actually changed
+                    Some(old) if old.resource_version() == new.resource_version() => {}
+                    Some(old) => changes.push(Change::Modified { old, new }),
+                    None => changes.push(Change::Added(new)),
+                }
+            }
+            for old in reader.state() {
+                if !seen.contains(&key(&old)) {
+                    changes.push(Change::Deleted(old));
+                }
+            }
+            changes
+        }
+    }
+}
+
+impl<St, K> Stream for ReflectChanges<St, K>
+where
+    K: Resource + Clone,
+    K::DynamicType: Eq + std::hash::Hash + Clone,
+    St: Stream<Item = Result<Event<K>, Error>>,
+{
+    type Item = Result<Change<K>, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut me = self.project();
+        loop {
+            if let Some(change) = me.buffer.pop_front() {
+                return Poll::Ready(Some(Ok(change)));
+            }
+            match ready!(me.stream.as_mut().poll_next(cx)) {
+                Some(Ok(event)) => {
+                    me.buffer.extend(changes(me.reader, me.writer.dyntype(), &event));
+                    me.writer.apply_watcher_event(&event);
+                }
+                Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::{Change, Error, Event, ReflectChanges};
+    use crate::reflector;
+    use futures::{stream, StreamExt};
+    use k8s_openapi::api::core::v1::Pod;
+
+    fn testpod(name: &str, resource_version: &str) -> Pod {
+        let mut pod = Pod::default();
+        pod.metadata.name = Some(name.to_string());
+        pod.metadata.resource_version = Some(resource_version.to_string());
+        pod
+    }
+
+    fn summary(change: &Change<Pod>) -> String {
+        let rv = |p: &Arc<Pod>| p.metadata.resource_version.clone().unwrap();
+        let name = |p: &Arc<Pod>| p.metadata.name.clone().unwrap();
+        match change {
+            Change::Added(p) => format!("added {}@{}", name(p), rv(p)),
+            Change::Modified { old, new } => format!("modified {} {}->{}", name(new), rv(old), rv(new)),
+            Change::Deleted(p) => format!("deleted {}@{}", name(p), rv(p)),
+        }
+    }
+
+    #[tokio::test]
+    async fn reflect_changes_pairs_old_and_new() {
+        let st = stream::iter([
+            Ok(Event::Applied(testpod("foo", "1"))),
+            Ok(Event::Applied(testpod("foo", "2"))),
+            Err(Error::TooManyObjects),
+            Ok(Event::Deleted(testpod("foo", "3"))),
+        ]);
+        let (reader, writer) = reflector::store();
+        let changes = ReflectChanges::new(st, writer)
+            .map(|res| res.map(|c| summary(&c)).map_err(|e| e.to_string()))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(changes, vec![
+            Ok("added foo@1".to_string()),
+            Ok("modified foo 1->2".to_string()),
+            Err(Error::TooManyObjects.to_string()),
+            Ok("deleted foo@3".to_string()),
+        ]);
+        assert!(reader.is_empty());
+    }
+
+    #[tokio::test]
+    async fn reflect_changes_diffs_restarts() {
+        let st = stream::iter([
+            Ok(Event::Restarted(vec![testpod("foo", "1"), testpod("bar", "1")])),
+            Ok(Event::Restarted(vec![testpod("foo", "2"), testpod("baz", "1")])),
+            Ok(Event::Restarted(vec![testpod("foo", "2"), testpod("baz", "1")])),
+        ]);
+        let (reader, writer) = reflector::store();
+        let changes = ReflectChanges::new(st, writer)
+            .map(|res| summary(&res.unwrap()))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(changes, vec![
+            "added foo@1",
+            "added bar@1",
+            "modified foo 1->2",
+            "added baz@1",
+            "deleted bar@1",
+        ]);
+        assert_eq!(reader.len(), 2);
+    }
+}
@@ -3,16 +3,23 @@ use crate::utils::predicate::{Predicate, PredicateFilter};
 #[cfg(feature = "unstable-runtime-subscribe")]
 use crate::utils::stream_subscribe::StreamSubscribe;
 use crate::{
-    utils::{event_flatten::EventFlatten, event_modify::EventModify, stream_backoff::StreamBackoff},
+    utils::{
+        event_flatten::EventFlatten, event_modify::EventModify, event_unbatch::EventUnbatch,
+        stream_backoff::StreamBackoff,
+    },
     watcher,
 };
 use kube_client::Resource;
 
-use crate::{reflector::store::Writer, utils::Reflect};
+use crate::{
+    reflector::store::Writer,
+    utils::{Batched, Pausable, PauseHandle, Reflect, ReflectChanges},
+};
 
 use crate::watcher::DefaultBackoff;
 use backoff::backoff::Backoff;
 use futures::{Stream, TryStream};
+use std::time::Duration;
 
 /// Extension trait for streams returned by [`watcher`](watcher()) or [`reflector`](crate::reflector::reflector)
 pub trait WatchStreamExt: Stream {
@@ -55,6 +62,44 @@ pub trait WatchStreamExt: Stream {
         EventFlatten::new(self, true)
     }
 
+    /// Split the (re-)lists of a [`watcher()`] stream into individual events
+    ///
+    /// Every [`Restarted`](watcher::Event::Restarted) and [`Desynced`](watcher::Event::Desynced) event is replaced by
+    /// an [`Init`](watcher::UnbatchedEvent::Init) marker, an [`InitApply`](watcher::UnbatchedEvent::InitApply) for each
+    /// listed object, and an [`InitDone`](watcher::UnbatchedEvent::InitDone) marker. Other events are passed through.
+    ///
+    /// This suits consumers that process objects one at a time, while the `Init` and `InitDone` markers still allow
+    /// them to replace their state when the list is complete. The [`reflector`](crate::reflector()) consumes the
+    /// batched events instead, to replace the contents of its store in a single step.
+    ///
+    /// ```no_run
+    /// # use futures::{pin_mut, TryStreamExt};
+    /// # use k8s_openapi::api::core::v1::Pod;
+    /// # use kube::{api::Api, runtime::{watcher, watcher::UnbatchedEvent, WatchStreamExt}, ResourceExt};
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::default_namespaced(client);
+    /// let events = watcher(pods, watcher::Config::default()).unbatched();
+    /// pin_mut!(events);
+    /// while let Some(event) = events.try_next().await? {
+    ///     match event {
+    ///         UnbatchedEvent::Init { .. } => println!("listing pods"),
+    ///         UnbatchedEvent::InitApply(pod) => println!("listed {}", pod.name_any()),
+    ///         UnbatchedEvent::InitDone => println!("listed all pods"),
+    ///         UnbatchedEvent::Applied(pod) => println!("applied {}", pod.name_any()),
+    ///         UnbatchedEvent::Deleted(pod) => println!("deleted {}", pod.name_any()),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn unbatched<K>(self) -> EventUnbatch<Self, K>
+    where
+        Self: Stream<Item = Result<watcher::Event<K>, watcher::Error>> + Sized,
+    {
+        EventUnbatch::new(self)
+    }
+
     /// Modify elements of a [`watcher()`] stream.
     ///
     /// Calls [`watcher::Event::modify()`] on every element.
@@ -247,6 +292,141 @@ pub trait WatchStreamExt: Stream {
     {
         Reflect::new(self, writer)
     }
+
+    /// Reflect a [`watcher()`] stream into a [`Store`] through a [`Writer`], emitting the resulting changes
+    ///
+    /// This works like [`WatchStreamExt::reflect`], but instead of passing events through, it consults the
+    /// store for the prior version of each object before applying an event, and emits a [`Change`]:
+    ///
+    /// - [`Change::Added`] for objects not previously in the store
+    /// - [`Change::Modified`] with both the `old` and `new` object for updates
+    /// - [`Change::Deleted`] for removed objects
+    ///
+    /// `Restarted` and `Desynced` events are diffed against the store, so a relist only emits changes for objects
+    /// that were created, updated (by `resourceVersion`), or removed while the watch was down.
+    ///
+    /// This lets reconcilers act on specific field transitions without maintaining their own cache.
+    ///
+    /// ```no_run
+    /// # use futures::{pin_mut, Stream, StreamExt, TryStreamExt};
+    /// # use k8s_openapi::api::apps::v1::Deployment;
+    /// # use kube::{Api, Client, ResourceExt};
+    /// use kube_runtime::{reflector, utils::Change, watcher, WatchStreamExt};
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let deploys: Api<Deployment> = Api::default_namespaced(client);
+    /// let (reader, writer) = reflector::store::<Deployment>();
+    /// let changes = watcher(deploys, watcher::Config::default()).reflect_changes(writer);
+    /// pin_mut!(changes);
+    /// while let Some(change) = changes.try_next().await? {
+    ///     if let Change::Modified { old, new } = change {
+    ///         let replicas = |d: &Deployment| d.spec.as_ref().and_then(|s| s.replicas);
+    ///         if replicas(&old) != replicas(&new) {
+    ///             println!("{} scaled to {:?}", new.name_any(), replicas(&new));
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Store`]: crate::reflector::Store
+    /// [`Change`]: crate::utils::Change
+    /// [`Change::Added`]: crate::utils::Change::Added
+    /// [`Change::Modified`]: crate::utils::Change::Modified
+    /// [`Change::Deleted`]: crate::utils::Change::Deleted
+    fn reflect_changes<K>(self, writer: Writer<K>) -> ReflectChanges<Self, K>
+    where
+        Self: Stream<Item = watcher::Result<watcher::Event<K>>> + Sized,
+        K: Resource + Clone + 'static,
+        K::DynamicType: Eq + std::hash::Hash + Clone,
+    {
+        ReflectChanges::new(self, writer)
+    }
+
+    /// Group the items of the stream into batches, to process bursts of events together
+    ///
+    /// A batch is emitted once it holds `max_items` items, or once `max_delay` has passed since
+    /// its first item arrived, whichever comes first. Any partial batch is flushed when the stream ends.
+    /// Errors are passed through as-is, after flushing the batch of items that preceded them.
+    ///
+    /// This is useful for amortizing expensive work (like recomputing a shared aggregate) over
+    /// bursts of changes, such as the relist after a watcher restart.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_items` is zero.
+    ///
+    /// ## Usage
+    /// ```no_run
+    /// # use futures::{pin_mut, TryStreamExt};
+    /// use kube::{Api, Client, ResourceExt};
+    /// use kube_runtime::{watcher, WatchStreamExt};
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// use std::time::Duration;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::default_namespaced(client);
+    /// let batches = watcher(pods, watcher::Config::default())
+    ///     .applied_objects()
+    ///     .batched(100, Duration::from_millis(500));
+    /// pin_mut!(batches);
+    ///
+    /// while let Some(batch) = batches.try_next().await? {
+    ///     println!("{} pods changed", batch.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn batched(self, max_items: usize, max_delay: Duration) -> Batched<Self>
+    where
+        Self: TryStream + Sized,
+    {
+        Batched::new(self, max_items, max_delay)
+    }
+
+    /// Make the stream pausable, returning a [`PauseHandle`] to control it
+    ///
+    /// While paused, the inner stream is not polled, so no events are emitted.
+    /// Nothing is buffered by the combinator itself, and the state of the stream is kept:
+    /// for a [`watcher()`] stream the watch connection stays open and its pending events are
+    /// delivered in order once resumed.
+    ///
+    /// Long pauses are coalesced by the [`watcher()`] instead: if the apiserver closes the
+    /// watch in the meantime (e.g. on its timeout), the watch is restarted from the last seen
+    /// `resourceVersion` on resume, and if that version has since been compacted away, the watcher
+    /// relists and emits a single [`Event::Restarted`](watcher::Event::Restarted) with the current state.
+    ///
+    /// ## Usage
+    /// ```no_run
+    /// # use futures::{pin_mut, TryStreamExt};
+    /// use kube::{Api, Client, ResourceExt};
+    /// use kube_runtime::{watcher, WatchStreamExt};
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let pods: Api<Pod> = Api::default_namespaced(client);
+    /// let (stream, handle) = watcher(pods, watcher::Config::default()).pausable();
+    /// let applied = stream.applied_objects();
+    /// pin_mut!(applied);
+    ///
+    /// // e.g. from a task that quiesces the controller during a migration
+    /// handle.pause();
+    /// // ...
+    /// handle.resume();
+    ///
+    /// while let Some(pod) = applied.try_next().await? {
+    ///     println!("saw {}", pod.name_any());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn pausable(self) -> (Pausable<Self>, PauseHandle)
+    where
+        Self: Sized,
+    {
+        Pausable::new(self)
+    }
 }
 
 impl<St: ?Sized> WatchStreamExt for St where St: Stream {}
@@ -69,7 +69,20 @@ where
 
 /// A trait for condition functions to be used by [`await_condition`]
 ///
-/// Note that this is auto-implemented for functions of type `fn(Option<&K>) -> bool`.
+/// Note that this is auto-implemented for functions of type `fn(Option<&K>) -> bool`,
+/// and [`conditions::from_fn`] adapts closures that only look at existing objects.
+///
+/// Conditions can be combined with [`not`](Condition::not), [`and`](Condition::and) and [`or`](Condition::or):
+///
+/// ```
+/// use k8s_openapi::api::apps::v1::Deployment;
+/// use kube::runtime::wait::{conditions, Condition};
+/// let available = conditions::from_fn(|deploy: &Deployment| {
+///     let conds = deploy.status.as_ref().and_then(|s| s.conditions.as_ref());
+///     conds.map_or(false, |conds| conds.iter().any(|c| c.type_ == "Available" && c.status == "True"))
+/// });
+/// let settled = available.and(conditions::is_being_deleted().not());
+/// ```
 ///
 /// # Usage
 ///
@@ -113,6 +126,8 @@ pub trait Condition<K> {
 
     /// Returns a `Condition` that holds if `self` and `other` both do
     ///
+    /// Evaluation short-circuits: `other` is only evaluated if `self` holds.
+    ///
     /// # Usage
     ///
     /// ```
@@ -133,6 +148,8 @@ pub trait Condition<K> {
 
     /// Returns a `Condition` that holds if either `self` or `other` does
     ///
+    /// Evaluation short-circuits: `other` is only evaluated if `self` does not hold.
+    ///
     /// # Usage
     ///
     /// ```
@@ -167,6 +184,31 @@ pub mod conditions {
     };
     use kube_client::Resource;
 
+    /// An await condition built from a closure over an existing object
+    ///
+    /// The condition does not hold while the object does not exist, so `f` only has to deal with existing objects.
+    /// Use a closure over `Option<&K>` directly to also match missing objects.
+    ///
+    /// ```
+    /// use k8s_openapi::api::core::v1::ConfigMap;
+    /// use kube::runtime::wait::{conditions, Condition};
+    /// let has_data = conditions::from_fn(|cm: &ConfigMap| cm.data.is_some());
+    /// assert!(!has_data.matches_object(None));
+    /// ```
+    #[must_use]
+    pub fn from_fn<K>(f: impl Fn(&K) -> bool) -> impl Condition<K> {
+        move |obj: Option<&K>| obj.map_or(false, &f)
+    }
+
+    /// An await condition that returns `true` while the object is being deleted
+    ///
+    /// An object is being deleted once it has a `deletionTimestamp`, until its finalizers are removed.
+    /// Combine it with [`Condition::not`] to wait for objects that are not being deleted.
+    #[must_use]
+    pub fn is_being_deleted<K: Resource>() -> impl Condition<K> {
+        |obj: Option<&K>| obj.map_or(false, |obj| obj.meta().deletion_timestamp.is_some())
+    }
+
     /// An await condition that returns `true` once the object has been deleted.
     ///
     /// An object is considered to be deleted if the object can no longer be found, or if its
@@ -204,6 +246,54 @@ pub mod conditions {
         }
     }
 
+    /// An await condition for `CustomResourceDefinition` that returns `true` once its names have been accepted
+    ///
+    /// The apiserver can briefly report `NamesAccepted` for the names of a previous revision of the CRD,
+    /// so this also requires the accepted `plural` and `kind` to match the ones in the spec.
+    ///
+    /// A CRD whose names conflict with another CRD will never have its names accepted,
+    /// use [`is_crd_names_rejected`] to stop waiting for it.
+    #[must_use]
+    pub fn are_crd_names_accepted() -> impl Condition<CustomResourceDefinition> {
+        |obj: Option<&CustomResourceDefinition>| {
+            if let Some(o) = obj {
+                if let Some(s) = &o.status {
+                    let accepted = s
+                        .conditions
+                        .iter()
+                        .flatten()
+                        .any(|c| c.type_ == "NamesAccepted" && c.status == "True");
+                    let current = s.accepted_names.as_ref().map_or(false, |names| {
+                        names.plural == o.spec.names.plural && names.kind == o.spec.names.kind
+                    });
+                    return accepted && current;
+                }
+            }
+            false
+        }
+    }
+
+    /// An await condition for `CustomResourceDefinition` that returns `true` if its names were rejected
+    ///
+    /// This happens when the names conflict with another CRD (the `NameConflict` reason),
+    /// in which case the CRD will not become established until the conflict is resolved.
+    /// The condition `message` explains the conflict.
+    #[must_use]
+    pub fn is_crd_names_rejected() -> impl Condition<CustomResourceDefinition> {
+        |obj: Option<&CustomResourceDefinition>| {
+            if let Some(o) = obj {
+                if let Some(s) = &o.status {
+                    if let Some(conds) = &s.conditions {
+                        if let Some(pcond) = conds.iter().find(|c| c.type_ == "NamesAccepted") {
+                            return pcond.status == "False";
+                        }
+                    }
+                }
+            }
+            false
+        }
+    }
+
     /// An await condition for `Pod` that returns `true` once it is running
     #[must_use]
     pub fn is_pod_running() -> impl Condition<Pod> {
@@ -316,3 +406,811 @@ pub mod delete {
         Ok(())
     }
 }
+
+/// Utilities for waiting for batch workloads
+pub mod job {
+    use crate::watcher::{self, watch_object};
+    use futures::TryStreamExt;
+    use k8s_openapi::api::batch::v1::{Job, JobCondition};
+    use kube_client::Api;
+    use std::time::Duration;
+    use thiserror::Error;
+
+    #[derive(Debug, Error)]
+    pub enum Error {
+        #[error("job failed: {reason}: {message}")]
+        Failed {
+            /// The failed job
+            job: Box<Job>,
+            /// The reason of the `Failed` condition, like `BackoffLimitExceeded` or `DeadlineExceeded`
+            reason: String,
+            /// The message of the `Failed` condition
+            message: String,
+        },
+        #[error("job was deleted before it completed")]
+        Deleted,
+        #[error("job did not start any pods before the timeout")]
+        NotStarted(Box<Job>),
+        #[error("job did not complete before the timeout")]
+        Timeout(Option<Box<Job>>),
+        #[error("failed to watch job: {0}")]
+        ProbeFailed(#[source] watcher::Error),
+    }
+
+    /// Wait for a [`Job`] to finish, returning the completed job
+    ///
+    /// Resolves with the final job once it has the `Complete` condition, so that its `status` can be inspected
+    /// (for instance the number of `succeeded` pods). A job that does not exist yet is waited for,
+    /// as is a suspended job until it is resumed.
+    ///
+    /// # Errors
+    ///
+    /// Fails with:
+    ///
+    /// - [`Error::Failed`] once the job has the `Failed` condition,
+    /// - [`Error::Deleted`] if the job is deleted while waiting,
+    /// - [`Error::NotStarted`] if `timeout` expires before any pod of the job became ready or finished,
+    ///   which usually means that its pods could not be scheduled or their images could not be pulled,
+    ///   or that the job is still suspended,
+    /// - [`Error::Timeout`] if `timeout` expires otherwise, with the last seen state of the job,
+    /// - [`Error::ProbeFailed`] if the job cannot be watched.
+    ///
+    /// # Usage
+    ///
+    /// ```no_run
+    /// use k8s_openapi::api::batch::v1::Job;
+    /// use kube::{Api, runtime::wait::job::{await_job_completion, Error}};
+    /// use std::time::Duration;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let jobs: Api<Job> = Api::namespaced(client, "batch");
+    /// // .. create the job here ..
+    /// match await_job_completion(jobs, "migrate-db", Duration::from_secs(600)).await {
+    ///     Ok(job) => println!("succeeded pods: {:?}", job.status.and_then(|s| s.succeeded)),
+    ///     Err(Error::Failed { reason, .. }) => println!("migration failed: {reason}"),
+    ///     Err(err) => return Err(err.into()),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[allow(clippy::missing_panics_doc)] // watch never actually terminates, expect cannot fail
+    pub async fn await_job_completion(api: Api<Job>, name: &str, timeout: Duration) -> Result<Job, Error> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let stream = watch_object(api, name);
+        futures::pin_mut!(stream);
+        let mut last: Option<Job> = None;
+        loop {
+            let next = match tokio::time::timeout_at(deadline, stream.try_next()).await {
+                Ok(next) => next
+                    .map_err(Error::ProbeFailed)?
+                    .expect("stream must not terminate"),
+                Err(_) => {
+                    return Err(match last {
+                        Some(job) if !has_started(&job) => Error::NotStarted(Box::new(job)),
+                        last => Error::Timeout(last.map(Box::new)),
+                    })
+                }
+            };
+            let job = match next {
+                Some(job) => job,
+                None if last.is_some() => return Err(Error::Deleted),
+                // not created yet
+                None => continue,
+            };
+            if condition(&job, "Complete").is_some() {
+                return Ok(job);
+            }
+            if let Some(failed) = condition(&job, "Failed") {
+                return Err(Error::Failed {
+                    reason: failed.reason.clone().unwrap_or_default(),
+                    message: failed.message.clone().unwrap_or_default(),
+                    job: Box::new(job),
+                });
+            }
+            last = Some(job);
+        }
+    }
+
+    /// The condition of type `type_` of `job`, if it is true
+    fn condition<'a>(job: &'a Job, type_: &str) -> Option<&'a JobCondition> {
+        job.status
+            .as_ref()
+            .and_then(|s| s.conditions.as_ref())
+            .and_then(|conds| conds.iter().find(|c| c.type_ == type_ && c.status == "True"))
+    }
+
+    /// Whether any pod of `job` became ready or finished
+    ///
+    /// Falls back to the active pods on clusters that do not report ready pods.
+    fn has_started(job: &Job) -> bool {
+        job.status.as_ref().map_or(false, |s| {
+            s.ready.or(s.active).unwrap_or(0) > 0 || s.succeeded.unwrap_or(0) > 0 || s.failed.unwrap_or(0) > 0
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::{await_job_completion, Error};
+        use crate::wait::tests::watched_api;
+        use std::time::Duration;
+
+        fn job_json(resource_version: &str, status: &serde_json::Value) -> serde_json::Value {
+            serde_json::json!({
+                "apiVersion": "batch/v1",
+                "kind": "Job",
+                "metadata": { "name": "pi", "namespace": "default", "resourceVersion": resource_version },
+                "status": status,
+            })
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn await_job_completion_waits_for_suspended_jobs() {
+            let mut suspended = job_json("1", &serde_json::json!({}));
+            suspended["spec"] = serde_json::json!({ "suspend": true, "template": {} });
+            let mut resumed = job_json(
+                "2",
+                &serde_json::json!({ "succeeded": 1, "conditions": [{ "type": "Complete", "status": "True" }] }),
+            );
+            resumed["spec"] = serde_json::json!({ "suspend": false, "template": {} });
+            let api = watched_api(suspended.clone(), vec![
+                serde_json::json!({ "type": "MODIFIED", "object": resumed }),
+            ]);
+            let job = await_job_completion(api, "pi", Duration::from_secs(60))
+                .await
+                .unwrap();
+            assert_eq!(job.status.unwrap().succeeded, Some(1));
+
+            // a job that stays suspended never starts
+            let api = watched_api(suspended, vec![]);
+            let err = await_job_completion(api, "pi", Duration::from_secs(60))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::NotStarted(_)));
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn await_job_completion_returns_finished_jobs() {
+            let running = job_json("1", &serde_json::json!({ "active": 1, "ready": 1 }));
+            let complete = job_json(
+                "2",
+                &serde_json::json!({
+                    "succeeded": 1,
+                    "conditions": [{ "type": "Complete", "status": "True" }],
+                }),
+            );
+            let api = watched_api(running.clone(), vec![
+                serde_json::json!({ "type": "MODIFIED", "object": complete }),
+            ]);
+            let job = await_job_completion(api, "pi", Duration::from_secs(60))
+                .await
+                .unwrap();
+            assert_eq!(job.status.unwrap().succeeded, Some(1));
+
+            let failed = job_json(
+                "2",
+                &serde_json::json!({
+                    "failed": 3,
+                    "conditions": [
+                        { "type": "Complete", "status": "False" },
+                        { "type": "Failed", "status": "True", "reason": "BackoffLimitExceeded", "message": "too many failures" },
+                    ],
+                }),
+            );
+            let api = watched_api(running, vec![
+                serde_json::json!({ "type": "MODIFIED", "object": failed }),
+            ]);
+            let err = await_job_completion(api, "pi", Duration::from_secs(60))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::Failed { reason, .. } if reason == "BackoffLimitExceeded"));
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn await_job_completion_reports_jobs_that_do_not_run() {
+            // a pod was created, but never became ready
+            let api = watched_api(
+                job_json("1", &serde_json::json!({ "active": 1, "ready": 0 })),
+                vec![],
+            );
+            let err = await_job_completion(api, "pi", Duration::from_secs(60))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::NotStarted(_)));
+
+            let api = watched_api(
+                job_json("1", &serde_json::json!({ "active": 1, "ready": 1 })),
+                vec![],
+            );
+            let err = await_job_completion(api, "pi", Duration::from_secs(60))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::Timeout(Some(_))));
+        }
+    }
+}
+
+/// Utilities for following the rollouts of workloads, like `kubectl rollout status`
+pub mod rollout {
+    use crate::watcher::{self, watch_object};
+    use futures::{future, stream, Stream, StreamExt, TryStreamExt};
+    use k8s_openapi::api::apps::v1::{DaemonSet, Deployment, StatefulSet};
+    use kube_client::{Api, Resource};
+    use serde::de::DeserializeOwned;
+    use std::{fmt, fmt::Debug, time::Duration};
+    use thiserror::Error;
+
+    #[derive(Debug, Error)]
+    pub enum Error {
+        #[error("rollout exceeded its progress deadline: {0}")]
+        ProgressDeadlineExceeded(String),
+        #[error("rollout status is only available for the RollingUpdate strategy, not {0}")]
+        UnsupportedStrategy(String),
+        #[error("workload was deleted before the rollout completed")]
+        Deleted,
+        #[error("rollout did not complete before the timeout")]
+        Timeout(Option<RolloutProgress>),
+        #[error("failed to watch workload: {0}")]
+        ProbeFailed(#[source] watcher::Error),
+    }
+
+    /// The progress of a rollout, formatted like the messages of `kubectl rollout status`
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub enum RolloutProgress {
+        /// The controller has not observed the latest generation of the workload yet
+        ObservingSpec,
+        /// Not all replicas (or pods, for a `DaemonSet`) have been updated yet
+        Updating {
+            /// The number of updated replicas
+            updated: i32,
+            /// The desired number of replicas
+            desired: i32,
+        },
+        /// All replicas have been updated, but replicas of the previous revision are still terminating
+        TerminatingOld {
+            /// The number of replicas of the previous revision
+            old: i32,
+        },
+        /// All replicas have been updated, but not all of them are available yet
+        Available {
+            /// The number of available replicas
+            available: i32,
+            /// The desired number of replicas
+            desired: i32,
+        },
+        /// The rollout is complete
+        Complete,
+    }
+
+    impl fmt::Display for RolloutProgress {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::ObservingSpec => f.write_str("waiting for the spec update to be observed"),
+                Self::Updating { updated, desired } => {
+                    write!(f, "{updated} out of {desired} new replicas have been updated")
+                }
+                Self::TerminatingOld { old } => write!(f, "{old} old replicas are pending termination"),
+                Self::Available { available, desired } => {
+                    write!(f, "{available} of {desired} updated replicas are available")
+                }
+                Self::Complete => f.write_str("successfully rolled out"),
+            }
+        }
+    }
+
+    /// A workload whose rollout can be followed with [`await_rollout`]
+    ///
+    /// Implemented for [`Deployment`], [`StatefulSet`] and [`DaemonSet`], following the same rules as
+    /// `kubectl rollout status`.
+    pub trait Rollout: Resource + Clone + DeserializeOwned + Debug + Send + 'static {
+        /// The progress of the current rollout of the workload
+        ///
+        /// # Errors
+        ///
+        /// Fails if the rollout cannot make progress, or cannot be followed.
+        fn rollout_progress(&self) -> Result<RolloutProgress, Error>;
+    }
+
+    /// Whether the controller has observed the latest generation of `obj`
+    fn is_observed<K: Resource>(obj: &K, observed_generation: Option<i64>) -> bool {
+        obj.meta().generation.unwrap_or(0) <= observed_generation.unwrap_or(0)
+    }
+
+    impl Rollout for Deployment {
+        fn rollout_progress(&self) -> Result<RolloutProgress, Error> {
+            let status = self.status.clone().unwrap_or_default();
+            if !is_observed(self, status.observed_generation) {
+                return Ok(RolloutProgress::ObservingSpec);
+            }
+            let stuck = status.conditions.iter().flatten().find(|c| {
+                c.type_ == "Progressing" && c.reason.as_deref() == Some("ProgressDeadlineExceeded")
+            });
+            if let Some(stuck) = stuck {
+                return Err(Error::ProgressDeadlineExceeded(
+                    stuck.message.clone().unwrap_or_default(),
+                ));
+            }
+            let updated = status.updated_replicas.unwrap_or(0);
+            if let Some(desired) = self.spec.as_ref().and_then(|s| s.replicas) {
+                if updated < desired {
+                    return Ok(RolloutProgress::Updating { updated, desired });
+                }
+            }
+            let replicas = status.replicas.unwrap_or(0);
+            if replicas > updated {
+                return Ok(RolloutProgress::TerminatingOld {
+                    old: replicas - updated,
+                });
+            }
+            let available = status.available_replicas.unwrap_or(0);
+            if available < updated {
+                return Ok(RolloutProgress::Available {
+                    available,
+                    desired: updated,
+                });
+            }
+            Ok(RolloutProgress::Complete)
+        }
+    }
+
+    impl Rollout for StatefulSet {
+        fn rollout_progress(&self) -> Result<RolloutProgress, Error> {
+            let strategy = self.spec.as_ref().and_then(|s| s.update_strategy.as_ref());
+            let strategy_type = strategy
+                .and_then(|s| s.type_.as_deref())
+                .unwrap_or("RollingUpdate");
+            if strategy_type != "RollingUpdate" {
+                return Err(Error::UnsupportedStrategy(strategy_type.to_string()));
+            }
+            let status = self.status.clone().unwrap_or_default();
+            if status.observed_generation.unwrap_or(0) == 0 || !is_observed(self, status.observed_generation)
+            {
+                return Ok(RolloutProgress::ObservingSpec);
+            }
+            let desired = self.spec.as_ref().and_then(|s| s.replicas);
+            let ready = status.ready_replicas.unwrap_or(0);
+            if let Some(desired) = desired {
+                if ready < desired {
+                    return Ok(RolloutProgress::Available {
+                        available: ready,
+                        desired,
+                    });
+                }
+            }
+            let updated = status.updated_replicas.unwrap_or(0);
+            let partition = strategy
+                .and_then(|s| s.rolling_update.as_ref())
+                .and_then(|r| r.partition);
+            if let (Some(desired), Some(partition)) = (desired, partition) {
+                // only the replicas with an ordinal of at least the partition are updated
+                let desired = desired - partition;
+                if updated < desired {
+                    return Ok(RolloutProgress::Updating { updated, desired });
+                }
+                return Ok(RolloutProgress::Complete);
+            }
+            if status.update_revision != status.current_revision {
+                return Ok(RolloutProgress::Updating {
+                    updated,
+                    desired: desired.unwrap_or(status.replicas),
+                });
+            }
+            Ok(RolloutProgress::Complete)
+        }
+    }
+
+    impl Rollout for DaemonSet {
+        fn rollout_progress(&self) -> Result<RolloutProgress, Error> {
+            let strategy_type = self
+                .spec
+                .as_ref()
+                .and_then(|s| s.update_strategy.as_ref())
+                .and_then(|s| s.type_.as_deref())
+                .unwrap_or("RollingUpdate");
+            if strategy_type != "RollingUpdate" {
+                return Err(Error::UnsupportedStrategy(strategy_type.to_string()));
+            }
+            let status = self.status.clone().unwrap_or_default();
+            if !is_observed(self, status.observed_generation) {
+                return Ok(RolloutProgress::ObservingSpec);
+            }
+            let desired = status.desired_number_scheduled;
+            let updated = status.updated_number_scheduled.unwrap_or(0);
+            if updated < desired {
+                return Ok(RolloutProgress::Updating { updated, desired });
+            }
+            let available = status.number_available.unwrap_or(0);
+            if available < desired {
+                return Ok(RolloutProgress::Available { available, desired });
+            }
+            Ok(RolloutProgress::Complete)
+        }
+    }
+
+    /// Follow the rollout of a workload, returning a stream of its progress
+    ///
+    /// A new item is emitted whenever the progress changes, and the stream ends after
+    /// [`RolloutProgress::Complete`], or right after the first error. A workload that does not exist yet is
+    /// waited for. See [`await_rollout`] for the errors.
+    ///
+    /// # Usage
+    ///
+    /// ```no_run
+    /// use futures::TryStreamExt;
+    /// use k8s_openapi::api::apps::v1::Deployment;
+    /// use kube::{Api, runtime::wait::rollout::rollout_progress};
+    /// use std::time::Duration;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let deploys: Api<Deployment> = Api::namespaced(client, "apps");
+    /// let progress = rollout_progress(deploys, "web", Duration::from_secs(300));
+    /// futures::pin_mut!(progress);
+    /// while let Some(progress) = progress.try_next().await? {
+    ///     println!("deployment \"web\": {progress}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn rollout_progress<K: Rollout>(
+        api: Api<K>,
+        name: &str,
+        timeout: Duration,
+    ) -> impl Stream<Item = Result<RolloutProgress, Error>> + Send {
+        rollout(api, name, timeout).map_ok(|(_, progress)| progress)
+    }
+
+    /// Wait for the rollout of a workload to complete, returning the rolled out workload
+    ///
+    /// This is the equivalent of `kubectl rollout status`. Use [`rollout_progress`] to report the progress
+    /// while waiting.
+    ///
+    /// # Errors
+    ///
+    /// Fails with:
+    ///
+    /// - [`Error::ProgressDeadlineExceeded`] if a `Deployment` exceeded its `progressDeadlineSeconds`,
+    /// - [`Error::UnsupportedStrategy`] if a `StatefulSet` or `DaemonSet` does not use rolling updates,
+    /// - [`Error::Deleted`] if the workload is deleted while waiting,
+    /// - [`Error::Timeout`] if `timeout` expires first, with the last seen progress of the rollout,
+    /// - [`Error::ProbeFailed`] if the workload cannot be watched.
+    ///
+    /// # Usage
+    ///
+    /// ```no_run
+    /// use k8s_openapi::api::apps::v1::StatefulSet;
+    /// use kube::{Api, runtime::wait::rollout::await_rollout};
+    /// use std::time::Duration;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client: kube::Client = todo!();
+    /// let sts: Api<StatefulSet> = Api::namespaced(client, "db");
+    /// await_rollout(sts, "postgres", Duration::from_secs(600)).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[allow(clippy::missing_panics_doc)] // the stream only ends after it completed or failed
+    pub async fn await_rollout<K: Rollout>(api: Api<K>, name: &str, timeout: Duration) -> Result<K, Error> {
+        let stream = rollout(api, name, timeout).try_filter_map(|(obj, progress)| {
+            future::ready(Ok((progress == RolloutProgress::Complete).then_some(obj)))
+        });
+        futures::pin_mut!(stream);
+        Ok(stream
+            .try_next()
+            .await?
+            .expect("rollout stream must not end before completing"))
+    }
+
+    /// The workload and its progress whenever the progress changes, until the rollout completed or failed
+    fn rollout<K: Rollout>(
+        api: Api<K>,
+        name: &str,
+        timeout: Duration,
+    ) -> impl Stream<Item = Result<(K, RolloutProgress), Error>> + Send {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let watch = watch_object(api, name).boxed();
+        stream::unfold(Some((watch, None, false)), move |state| async move {
+            let (mut watch, mut last, mut seen) = state?;
+            loop {
+                let next = match tokio::time::timeout_at(deadline, watch.try_next()).await {
+                    Ok(Ok(Some(next))) => next,
+                    // watch never actually terminates
+                    Ok(Ok(None)) => return None,
+                    Ok(Err(err)) => return Some((Err(Error::ProbeFailed(err)), None)),
+                    Err(_) => return Some((Err(Error::Timeout(last)), None)),
+                };
+                let obj = match next {
+                    Some(obj) => obj,
+                    None if seen => return Some((Err(Error::Deleted), None)),
+                    // not created yet
+                    None => continue,
+                };
+                seen = true;
+                let progress = match obj.rollout_progress() {
+                    Ok(progress) => progress,
+                    Err(err) => return Some((Err(err), None)),
+                };
+                if progress == RolloutProgress::Complete {
+                    return Some((Ok((obj, progress)), None));
+                }
+                if last.as_ref() != Some(&progress) {
+                    last = Some(progress.clone());
+                    return Some((Ok((obj, progress)), Some((watch, last, seen))));
+                }
+            }
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::{await_rollout, rollout_progress, Error, Rollout, RolloutProgress};
+        use crate::wait::tests::watched_api;
+        use futures::TryStreamExt;
+        use k8s_openapi::api::apps::v1::{DaemonSet, Deployment, StatefulSet};
+        use std::time::Duration;
+
+        fn deployment_json(
+            resource_version: &str,
+            generation: i64,
+            replicas: i32,
+            status: &serde_json::Value,
+        ) -> serde_json::Value {
+            serde_json::json!({
+                "apiVersion": "apps/v1",
+                "kind": "Deployment",
+                "metadata": {
+                    "name": "web",
+                    "namespace": "default",
+                    "resourceVersion": resource_version,
+                    "generation": generation,
+                },
+                "spec": { "replicas": replicas, "selector": {}, "template": {} },
+                "status": status,
+            })
+        }
+
+        #[test]
+        fn rollout_progress_follows_the_status_of_each_workload_kind() {
+            let deploy =
+                |status| serde_json::from_value::<Deployment>(deployment_json("1", 2, 3, &status)).unwrap();
+            let progress = |status| deploy(status).rollout_progress().unwrap();
+            assert_eq!(
+                progress(serde_json::json!({ "observedGeneration": 1, "replicas": 3, "updatedReplicas": 3 })),
+                RolloutProgress::ObservingSpec
+            );
+            assert_eq!(
+                progress(serde_json::json!({ "observedGeneration": 2, "replicas": 4, "updatedReplicas": 1 })),
+                RolloutProgress::Updating {
+                    updated: 1,
+                    desired: 3
+                }
+            );
+            assert_eq!(
+                progress(serde_json::json!({ "observedGeneration": 2, "replicas": 4, "updatedReplicas": 3 })),
+                RolloutProgress::TerminatingOld { old: 1 }
+            );
+            assert_eq!(
+                progress(serde_json::json!({
+                    "observedGeneration": 2, "replicas": 3, "updatedReplicas": 3, "availableReplicas": 2
+                })),
+                RolloutProgress::Available {
+                    available: 2,
+                    desired: 3
+                }
+            );
+            let stuck = deploy(serde_json::json!({
+                "observedGeneration": 2,
+                "conditions": [{ "type": "Progressing", "status": "False", "reason": "ProgressDeadlineExceeded" }],
+            }));
+            assert!(matches!(
+                stuck.rollout_progress(),
+                Err(Error::ProgressDeadlineExceeded(_))
+            ));
+
+            let sts = |partition: Option<i32>, status| {
+                serde_json::from_value::<StatefulSet>(serde_json::json!({
+                    "metadata": { "name": "db", "generation": 1 },
+                    "spec": {
+                        "replicas": 3,
+                        "selector": {},
+                        "serviceName": "db",
+                        "template": {},
+                        "updateStrategy": { "type": "RollingUpdate", "rollingUpdate": { "partition": partition } },
+                    },
+                    "status": status,
+                }))
+                .unwrap()
+            };
+            let rolling = serde_json::json!({
+                "observedGeneration": 1, "replicas": 3, "readyReplicas": 3, "updatedReplicas": 1,
+                "currentRevision": "db-1", "updateRevision": "db-2",
+            });
+            assert_eq!(
+                sts(None, rolling.clone()).rollout_progress().unwrap(),
+                RolloutProgress::Updating {
+                    updated: 1,
+                    desired: 3
+                }
+            );
+            // only the replicas from the partition up are updated
+            assert_eq!(
+                sts(Some(2), rolling).rollout_progress().unwrap(),
+                RolloutProgress::Complete
+            );
+
+            let ds = serde_json::from_value::<DaemonSet>(serde_json::json!({
+                "metadata": { "name": "agent", "generation": 1 },
+                "spec": { "selector": {}, "template": {}, "updateStrategy": { "type": "OnDelete" } },
+            }))
+            .unwrap();
+            assert!(matches!(
+                ds.rollout_progress(),
+                Err(Error::UnsupportedStrategy(strategy)) if strategy == "OnDelete"
+            ));
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn rollout_progress_reports_changes_until_complete() {
+            let updating = deployment_json(
+                "1",
+                2,
+                2,
+                &serde_json::json!({ "observedGeneration": 2, "replicas": 3, "updatedReplicas": 1 }),
+            );
+            let available = deployment_json(
+                "3",
+                2,
+                2,
+                &serde_json::json!({
+                    "observedGeneration": 2, "replicas": 2, "updatedReplicas": 2, "availableReplicas": 1
+                }),
+            );
+            let complete = deployment_json(
+                "4",
+                2,
+                2,
+                &serde_json::json!({
+                    "observedGeneration": 2, "replicas": 2, "updatedReplicas": 2, "availableReplicas": 2
+                }),
+            );
+            let events = vec![
+                // unchanged progress is not reported again
+                serde_json::json!({ "type": "MODIFIED", "object": deployment_json("2", 2, 2, &updating["status"]) }),
+                serde_json::json!({ "type": "MODIFIED", "object": available }),
+                serde_json::json!({ "type": "MODIFIED", "object": complete }),
+            ];
+            let api = watched_api::<Deployment>(updating.clone(), events);
+            let progress = rollout_progress(api, "web", Duration::from_secs(60))
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+            assert_eq!(progress, [
+                RolloutProgress::Updating {
+                    updated: 1,
+                    desired: 2
+                },
+                RolloutProgress::Available {
+                    available: 1,
+                    desired: 2
+                },
+                RolloutProgress::Complete,
+            ]);
+
+            let api = watched_api::<Deployment>(updating, vec![]);
+            let err = await_rollout(api, "web", Duration::from_secs(60))
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err,
+                Error::Timeout(Some(RolloutProgress::Updating {
+                    updated: 1,
+                    desired: 2
+                }))
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::conditions::{
+        are_crd_names_accepted, from_fn, is_being_deleted, is_crd_names_rejected, Condition,
+    };
+    use futures::pin_mut;
+    use http::{Request, Response};
+    use hyper::Body;
+    use k8s_openapi::{
+        api::core::v1::ConfigMap,
+        apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition,
+        apimachinery::pkg::apis::meta::v1::Time, chrono::Utc,
+    };
+    use std::cell::Cell;
+
+    fn crd(accepted_plural: &str, names_accepted: &str) -> CustomResourceDefinition {
+        serde_json::from_value(serde_json::json!({
+            "apiVersion": "apiextensions.k8s.io/v1",
+            "kind": "CustomResourceDefinition",
+            "metadata": { "name": "foos.clux.dev" },
+            "spec": {
+                "group": "clux.dev",
+                "names": { "kind": "Foo", "plural": "foos" },
+                "scope": "Namespaced",
+                "versions": []
+            },
+            "status": {
+                "acceptedNames": { "kind": "Foo", "plural": accepted_plural },
+                "conditions": [{ "type": "NamesAccepted", "status": names_accepted }]
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn crd_names_must_match_the_spec_to_be_accepted() {
+        assert!(are_crd_names_accepted().matches_object(Some(&crd("foos", "True"))));
+        // stale names from a previous revision of the crd
+        assert!(!are_crd_names_accepted().matches_object(Some(&crd("oldfoos", "True"))));
+        assert!(!are_crd_names_accepted().matches_object(None));
+
+        assert!(is_crd_names_rejected().matches_object(Some(&crd("", "False"))));
+        assert!(!is_crd_names_rejected().matches_object(Some(&crd("foos", "True"))));
+    }
+
+    #[test]
+    fn combined_conditions_short_circuit() {
+        let has_data = from_fn(|cm: &ConfigMap| cm.data.is_some());
+        let live = has_data.and(is_being_deleted().not());
+
+        let mut cm = ConfigMap {
+            data: Some(Default::default()),
+            ..ConfigMap::default()
+        };
+        assert!(live.matches_object(Some(&cm)));
+        assert!(!live.matches_object(None));
+        cm.metadata.deletion_timestamp = Some(Time(Utc::now()));
+        assert!(!live.matches_object(Some(&cm)));
+
+        let evaluated = Cell::new(0);
+        let counted = |_: Option<&ConfigMap>| {
+            evaluated.set(evaluated.get() + 1);
+            true
+        };
+        assert!(!from_fn(|_: &ConfigMap| false)
+            .and(&counted)
+            .matches_object(Some(&cm)));
+        assert!(from_fn(|_: &ConfigMap| true)
+            .or(&counted)
+            .matches_object(Some(&cm)));
+        assert_eq!(evaluated.get(), 0);
+        assert!(from_fn(|_: &ConfigMap| false)
+            .or(&counted)
+            .matches_object(Some(&cm)));
+        assert_eq!(evaluated.get(), 1);
+    }
+
+    /// An api whose list returns `listed`, and whose watches emit `events` and then stay open
+    pub(super) fn watched_api<
+        K: kube_client::Resource<DynamicType = (), Scope = k8s_openapi::NamespaceResourceScope>,
+    >(
+        listed: serde_json::Value,
+        events: Vec<serde_json::Value>,
+    ) -> kube_client::Api<K> {
+        let (mock_service, handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+        tokio::spawn(async move {
+            pin_mut!(handle);
+            let mut watches = Vec::new();
+            while let Some((request, send)) = handle.next_request().await {
+                if request.uri().query().map_or(false, |q| q.contains("watch=true")) {
+                    let (mut tx, body) = Body::channel();
+                    send.send_response(Response::new(body));
+                    for event in &events {
+                        tx.send_data(format!("{event}\n").into()).await.unwrap();
+                    }
+                    watches.push(tx);
+                } else {
+                    let list =
+                        serde_json::json!({ "metadata": { "resourceVersion": "1" }, "items": [listed] });
+                    send.send_response(Response::new(Body::from(list.to_string())));
+                }
+            }
+        });
+        kube_client::Api::default_namespaced(kube_client::Client::new(mock_service, "default"))
+    }
+}
@@ -3,21 +3,36 @@
 //! See [`watcher`] for the primary entry point.
 
 use crate::utils::ResetTimerBackoff;
+use ahash::AHashMap;
 use async_trait::async_trait;
 use backoff::{backoff::Backoff, ExponentialBackoff};
 use derivative::Derivative;
-use futures::{stream::BoxStream, Stream, StreamExt};
+use futures::{
+    channel::mpsc,
+    future::{self, Either},
+    stream::BoxStream,
+    Stream, StreamExt, TryStreamExt,
+};
 use kube_client::{
     api::{ListParams, Resource, ResourceExt, VersionMatch, WatchEvent, WatchParams},
-    core::{metadata::PartialObjectMeta, ObjectList},
+    core::{metadata::PartialObjectMeta, ObjectList, Request},
     error::ErrorResponse,
-    Api, Error as ClientErr,
+    Api, Client, Error as ClientErr,
 };
 use serde::de::DeserializeOwned;
 use smallvec::SmallVec;
-use std::{clone::Clone, fmt::Debug, time::Duration};
+use std::{
+    clone::Clone,
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicU64, AtomicU8, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::Duration,
+};
 use thiserror::Error;
-use tracing::{debug, error, warn};
+use tokio::time::Instant;
+use tracing::{debug, error, trace, warn};
 
 #[derive(Debug, Error)]
 pub enum Error {
@@ -33,9 +48,34 @@ pub enum Error {
     NoResourceVersion,
     #[error("too many objects matched search criteria")]
     TooManyObjects,
+    #[error("failed to decode object: {source}")]
+    Decode {
+        /// The object as received from the apiserver
+        raw: serde_json::Value,
+        source: serde_json::Error,
+    },
 }
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
+impl Error {
+    /// Whether the error is unlikely to go away by retrying, such as a misconfiguration
+    ///
+    /// This is the case when the apiserver refuses a list or watch with `401 Unauthorized`
+    /// or `403 Forbidden` (usually missing RBAC rules), or `404 Not Found` (the resource is not installed).
+    /// All other errors are transient, and the [`watcher`] recovers from them when polled again.
+    #[must_use]
+    pub fn is_permanent(&self) -> bool {
+        let code = match self {
+            Self::InitialListFailed(ClientErr::Api(err))
+            | Self::WatchStartFailed(ClientErr::Api(err))
+            | Self::WatchFailed(ClientErr::Api(err))
+            | Self::WatchError(err) => err.code,
+            _ => return false,
+        };
+        matches!(code, 401 | 403 | 404)
+    }
+}
+
 #[derive(Debug, Clone)]
 /// Watch events returned from the [`watcher`]
 pub enum Event<K> {
@@ -52,7 +92,69 @@ pub enum Event<K> {
     ///
     /// Any objects that were previously [`Applied`](Event::Applied) but are not listed in this event
     /// should be assumed to have been [`Deleted`](Event::Deleted).
+    ///
+    /// The [`reflector`](crate::reflector()) relies on receiving the whole list in this single event, to replace its
+    /// store atomically. Use [`WatchStreamExt::unbatched`](crate::WatchStreamExt::unbatched) to receive the listed
+    /// objects as individual events instead.
+    ///
+    /// Consumers that maintain state derived from incremental events should use this as a signal
+    /// to invalidate that state, since any number of events may have been missed.
     Restarted(Vec<K>),
+    /// The watch stream desynced and had to be rebuilt from a full re-list
+    ///
+    /// This is emitted in place of [`Restarted`](Event::Restarted) when the watcher re-lists after having
+    /// fallen out of sync with the apiserver (see [`DesyncReason`]), and should otherwise be handled exactly
+    /// like `Restarted`. The initial list of a watcher is always a `Restarted` event.
+    Desynced {
+        /// Why the watcher had to re-list
+        reason: DesyncReason,
+        /// The complete set of objects after the re-list
+        objects: Vec<K>,
+    },
+}
+
+/// Watch events with the (re-)lists split into individual objects, see [`WatchStreamExt::unbatched`]
+///
+/// [`WatchStreamExt::unbatched`]: crate::WatchStreamExt::unbatched
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnbatchedEvent<K> {
+    /// A (re-)list started, replacing [`Event::Restarted`] and [`Event::Desynced`]
+    ///
+    /// It is followed by an [`InitApply`](Self::InitApply) for every listed object, and then [`InitDone`](Self::InitDone).
+    Init {
+        /// Why the watcher had to re-list, or `None` for a [`Event::Restarted`]
+        desync: Option<DesyncReason>,
+    },
+    /// An object that is part of the current (re-)list
+    InitApply(K),
+    /// The (re-)list is complete
+    ///
+    /// Any objects that were previously applied, but not listed since the last `Init`, should be assumed to be deleted.
+    InitDone,
+    /// An object was added or modified
+    Applied(K),
+    /// An object was deleted
+    Deleted(K),
+}
+
+/// The reason for a [`Event::Desynced`] re-list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DesyncReason {
+    /// The resource version of the watch was too old (HTTP 410 Gone)
+    Expired = 1,
+    /// A watch event did not carry a resource version to resume from
+    NoResourceVersion = 2,
+}
+
+impl DesyncReason {
+    fn from_u8(reason: u8) -> Option<Self> {
+        match reason {
+            1 => Some(Self::Expired),
+            2 => Some(Self::NoResourceVersion),
+            _ => None,
+        }
+    }
 }
 
 impl<K> Event<K> {
@@ -64,7 +166,7 @@ impl<K> Event<K> {
         match self {
             Event::Applied(obj) => SmallVec::from_buf([obj]),
             Event::Deleted(_) => SmallVec::new(),
-            Event::Restarted(objs) => SmallVec::from_vec(objs),
+            Event::Restarted(objs) | Event::Desynced { objects: objs, .. } => SmallVec::from_vec(objs),
         }
         .into_iter()
     }
@@ -77,7 +179,7 @@ impl<K> Event<K> {
     pub fn into_iter_touched(self) -> impl Iterator<Item = K> {
         match self {
             Event::Applied(obj) | Event::Deleted(obj) => SmallVec::from_buf([obj]),
-            Event::Restarted(objs) => SmallVec::from_vec(objs),
+            Event::Restarted(objs) | Event::Desynced { objects: objs, .. } => SmallVec::from_vec(objs),
         }
         .into_iter()
     }
@@ -103,7 +205,7 @@ impl<K> Event<K> {
     pub fn modify(mut self, mut f: impl FnMut(&mut K)) -> Self {
         match &mut self {
             Event::Applied(obj) | Event::Deleted(obj) => (f)(obj),
-            Event::Restarted(objs) => {
+            Event::Restarted(objs) | Event::Desynced { objects: objs, .. } => {
                 for k in objs {
                     (f)(k)
                 }
@@ -111,6 +213,22 @@ impl<K> Event<K> {
         }
         self
     }
+
+    /// Move each object in the event into an [`Arc`], so that it can be shared without being cloned
+    ///
+    /// This is used by [`reflector_shared`](crate::reflector::reflector_shared) to store the same allocation
+    /// that is passed on to the consumers of the stream.
+    pub fn into_shared(self) -> Event<Arc<K>> {
+        match self {
+            Event::Applied(obj) => Event::Applied(Arc::new(obj)),
+            Event::Deleted(obj) => Event::Deleted(Arc::new(obj)),
+            Event::Restarted(objs) => Event::Restarted(objs.into_iter().map(Arc::new).collect()),
+            Event::Desynced { reason, objects } => Event::Desynced {
+                reason,
+                objects: objects.into_iter().map(Arc::new).collect(),
+            },
+        }
+    }
 }
 
 #[derive(Derivative)]
@@ -121,16 +239,34 @@ enum State<K> {
     Empty {
         continue_token: Option<String>,
         objects: Vec<K>,
+        /// Errors for the listed objects that failed to decode
+        undecodable: Vec<Error>,
+        /// Set when re-listing after the watcher desynced
+        desync: Option<DesyncReason>,
     },
     /// Kubernetes 1.27 Streaming Lists
     /// The initial watch is in progress
     IntialWatch {
         objects: Vec<K>,
+        undecodable: Vec<Error>,
+        desync: Option<DesyncReason>,
+        #[derivative(Debug = "ignore")]
+        stream: BoxStream<'static, Result<WatchEvent<K>>>,
+    },
+    /// A (re-)list that could not decode every object, which is emitted one event at a time
+    ///
+    /// A `Restarted` would make consumers treat the undecodable objects as deleted, so the decode errors
+    /// are emitted followed by an `Applied` event for every listed object, before moving on to `next`.
+    InitListedPartially {
         #[derivative(Debug = "ignore")]
-        stream: BoxStream<'static, kube_client::Result<WatchEvent<K>>>,
+        events: std::vec::IntoIter<Result<Event<K>>>,
+        next: Box<State<K>>,
     },
     /// The initial LIST was successful, so we should move on to starting the actual watch.
-    InitListed { resource_version: String },
+    InitListed {
+        resource_version: String,
+        listed: ListedVersions,
+    },
     /// The watch is in progress, from this point we just return events from the server.
     ///
     /// If the connection is disrupted then we propagate the error but try to restart the watch stream by
@@ -139,17 +275,119 @@ enum State<K> {
     /// with `Empty`.
     Watching {
         resource_version: String,
+        listed: ListedVersions,
         #[derivative(Debug = "ignore")]
-        stream: BoxStream<'static, kube_client::Result<WatchEvent<K>>>,
+        stream: BoxStream<'static, Result<WatchEvent<K>>>,
     },
 }
 
+/// The `resourceVersion`s of the objects included in the last (re-)list
+///
+/// Used to drop watch events that repeat an object state which the list already provided,
+/// so that consumers do not process the same state twice across the relist boundary.
+/// Repeats can only happen at the start of the first watch after the list, so the versions are dropped
+/// at the first bookmark, or when that watch ends if bookmarks are disabled.
+#[derive(Debug, Default)]
+struct ListedVersions(AHashMap<(Option<String>, String), String>);
+
+impl ListedVersions {
+    fn new<K: Resource>(objects: &[K]) -> Self {
+        Self(
+            objects
+                .iter()
+                .filter_map(|obj| Some(((obj.namespace(), obj.name_any()), obj.resource_version()?)))
+                .collect(),
+        )
+    }
+
+    /// Whether `obj` has the same version that was listed
+    ///
+    /// Only the first event for each object is checked, later events are always newer.
+    /// Resource versions are opaque, so they are only compared for equality.
+    fn is_stale<K: Resource>(&mut self, obj: &K) -> bool {
+        if self.0.is_empty() {
+            return false;
+        }
+        match (
+            self.0.remove(&(obj.namespace(), obj.name_any())),
+            obj.resource_version(),
+        ) {
+            (Some(listed), Some(current)) => current == listed,
+            _ => false,
+        }
+    }
+}
+
 impl<K: Resource + Clone> Default for State<K> {
     fn default() -> Self {
         Self::Empty {
             continue_token: None,
             objects: vec![],
+            undecodable: vec![],
+            desync: None,
+        }
+    }
+}
+
+impl<K: Resource + Clone> State<K> {
+    /// The state that a watcher with the given config starts from
+    fn initial(wc: &Config) -> Self {
+        match &wc.initial_resource_version {
+            Some(resource_version) => Self::InitListed {
+                resource_version: resource_version.clone(),
+                listed: ListedVersions::default(),
+            },
+            None => Self::default(),
+        }
+    }
+
+    /// Start over with a re-list after the watcher desynced
+    fn desynced(reason: DesyncReason) -> Self {
+        Self::Empty {
+            continue_token: None,
+            objects: vec![],
+            undecodable: vec![],
+            desync: Some(reason),
+        }
+    }
+
+    /// Start over with a re-list, preserving whether the previous attempt was a desync
+    fn relist(desync: Option<DesyncReason>) -> Self {
+        Self::Empty {
+            continue_token: None,
+            objects: vec![],
+            undecodable: vec![],
+            desync,
+        }
+    }
+
+    /// Emit a completed (re-)list, and continue with `next`
+    fn listed(
+        objects: Vec<K>,
+        undecodable: Vec<Error>,
+        desync: Option<DesyncReason>,
+        next: Self,
+    ) -> (Option<Result<Event<K>>>, Self) {
+        if undecodable.is_empty() {
+            let event = match desync {
+                Some(reason) => Event::Desynced { reason, objects },
+                None => Event::Restarted(objects),
+            };
+            return (Some(Ok(event)), next);
         }
+        warn!(
+            "{} listed objects failed to decode, emitting the list as individual events",
+            undecodable.len()
+        );
+        let events = undecodable
+            .into_iter()
+            .map(Err)
+            .chain(objects.into_iter().map(|obj| Ok(Event::Applied(obj))))
+            .collect::<Vec<_>>();
+        (None, Self::InitListedPartially {
+            events: events.into_iter(),
+            next: Box::new(next),
+        })
     }
 }
 
@@ -159,18 +397,27 @@ impl<K: Resource + Clone> Default for State<K> {
 trait ApiMode {
     type Value: Clone;
 
-    async fn list(&self, lp: &ListParams) -> kube_client::Result<ObjectList<Self::Value>>;
+    /// The kind of the watched objects, for logging
+    fn kind(&self) -> &str;
+
+    /// Whether the apiserver may serve streaming lists
+    async fn supports_streaming_lists(&self) -> bool;
+
+    /// List a page of objects, along with the errors of the objects that failed to decode
+    async fn list(&self, lp: &ListParams) -> kube_client::Result<(ObjectList<Self::Value>, Vec<Error>)>;
     async fn watch(
         &self,
         wp: &WatchParams,
         version: &str,
-    ) -> kube_client::Result<BoxStream<'static, kube_client::Result<WatchEvent<Self::Value>>>>;
+    ) -> kube_client::Result<BoxStream<'static, Result<WatchEvent<Self::Value>>>>;
 }
 
 /// A wrapper around the `Api` of a `Resource` type that when used by the
 /// watcher will return the entire (full) object
 struct FullObject<'a, K> {
     api: &'a Api<K>,
+    lenient: bool,
+    kind: &'static str,
 }
 
 /// Configurable list semantics for `watcher` relists
@@ -250,6 +497,8 @@ pub struct Config {
     ///
     /// StreamingList is more efficient than ListWatch, but it requires the server to support
     /// streaming list bookmarks (opt-in feature gate in Kubernetes 1.27).
+    /// The watcher falls back to `ListWatch` when the [capabilities](kube_client::Client::capabilities)
+    /// of the apiserver show that it is too old to serve streaming lists.
     ///
     /// See [upstream documentation on streaming lists](https://kubernetes.io/docs/reference/using-api/api-concepts/#streaming-lists),
     /// and the [KEP](https://github.com/kubernetes/enhancements/tree/master/keps/sig-api-machinery/3157-watch-list#design-details).
@@ -260,9 +509,13 @@ pub struct Config {
     /// This can reduce the memory consumption during resyncs, at the cost of requiring more
     /// API roundtrips to complete.
     ///
-    /// Defaults to 500. Note that `None` represents unbounded.
+    /// The watcher follows the `continue` token of each page until the list is complete, emits all the objects
+    /// in a single [`Event::Restarted`], and then watches from the `resourceVersion` of the last page.
+    ///
+    /// Defaults to 500, like the reflectors of client-go. Note that `None` represents unbounded.
     ///
     /// NB: This option only has an effect for [`InitialListStrategy::ListWatch`].
+    /// Lists with [`ListSemantic::Any`] are served from the watch cache of the apiserver, which ignores the page size.
     pub page_size: Option<u32>,
 
     /// Enables watch events with type "BOOKMARK".
@@ -270,6 +523,30 @@ pub struct Config {
     /// Requests watch bookmarks from the apiserver when enabled for improved watch precision and reduced list calls.
     /// This is default enabled and should generally not be turned off.
     pub bookmarks: bool,
+
+    /// Keep watching when an object fails to decode, instead of failing the watch stream.
+    ///
+    /// When enabled, an object in a watch event that does not deserialize into `K` is emitted as an
+    /// [`Error::Decode`] carrying the raw JSON of the object, and the watch continues past it.
+    /// When objects of a list fail to decode, their errors are emitted first, and the list is then emitted as an
+    /// [`Event::Applied`] for every decoded object instead of an [`Event::Restarted`], so that stores keep the
+    /// previous state of the undecodable objects. Deletions that happened since the previous list are then only
+    /// noticed by the next complete list.
+    /// This keeps a single malformed object (such as a custom resource with a schema mismatch)
+    /// from failing every relist of the watcher.
+    ///
+    /// Defaults to `false`. Has no effect on the [`metadata_watcher`], as metadata always decodes.
+    pub lenient_decoding: bool,
+
+    /// A resource version to start watching from, instead of starting with a list.
+    ///
+    /// See [`Config::initial_resource_version`](Config::initial_resource_version()) for details.
+    pub initial_resource_version: Option<String>,
+
+    /// A handle to report the connection state of the watcher to.
+    ///
+    /// See [`Config::track_connection`](Config::track_connection()) for details.
+    pub connection: Option<ConnectionHandle>,
 }
 
 impl Default for Config {
@@ -284,6 +561,9 @@ impl Default for Config {
             // https://github.com/kubernetes/client-go/blob/aed71fa5cf054e1c196d67b2e21f66fd967b8ab1/tools/pager/pager.go#L31
             page_size: Some(500),
             initial_list_strategy: InitialListStrategy::ListWatch,
+            lenient_decoding: false,
+            initial_resource_version: None,
+            connection: None,
         }
     }
 }
@@ -312,7 +592,8 @@ impl Config {
     ///
     /// Defaults to everything.
     /// Supports `=`, `==`, `!=`, and can be comma separated: `key1=value1,key2=value2`.
-    /// The server only supports a limited number of field queries per type.
+    /// The server only supports a limited number of field queries per type,
+    /// see [`FieldSelector`](kube_client::core::field_selector::FieldSelector) for a typed selector that only allows the supported fields.
     #[must_use]
     pub fn fields(mut self, field_selector: &str) -> Self {
         self.field_selector = Some(field_selector.to_string());
@@ -359,7 +640,7 @@ impl Config {
     /// Limits the number of objects retrieved in each list operation during resync.
     ///
     /// This can reduce the memory consumption during resyncs, at the cost of requiring more
-    /// API roundtrips to complete.
+    /// API roundtrips to complete. Defaults to 500, see [`Config::page_size`](#structfield.page_size) for details.
     ///
     /// NB: This option only has an effect for [`InitialListStrategy::ListWatch`].
     #[must_use]
@@ -376,6 +657,68 @@ impl Config {
         self
     }
 
+    /// Emit objects that fail to decode as errors, and continue watching past them
+    ///
+    /// See [`Config::lenient_decoding`](#structfield.lenient_decoding) for details.
+    #[must_use]
+    pub fn lenient_decoding(mut self) -> Self {
+        self.lenient_decoding = true;
+        self
+    }
+
+    /// Resume watching from a previously seen resource version, skipping the initial list
+    ///
+    /// This is meant for consumers that checkpoint their progress (for instance the
+    /// [`Store::resource_version`](crate::reflector::Store::resource_version) of a store),
+    /// and only want the changes since that version after a restart, rather than a fresh list of every object.
+    ///
+    /// The watcher starts with a watch from `resource_version`, so it does **not** begin with an
+    /// [`Event::Restarted`], and a [`reflector`](crate::reflector()) store starts out empty (and is only marked
+    /// as ready after the first event). Consumers must be able to work with incremental changes only.
+    ///
+    /// If the version is too old for the apiserver (`410 Gone`, after around 5 minutes by default), the watcher
+    /// falls back to a full list, emitted as an [`Event::Desynced`] with [`DesyncReason::Expired`].
+    /// Any relist later on starts from a list as usual. Changing the config of a
+    /// [`reconfigurable_watcher`] also starts over with a list.
+    #[must_use]
+    pub fn initial_resource_version(mut self, resource_version: impl Into<String>) -> Self {
+        self.initial_resource_version = Some(resource_version.into());
+        self
+    }
+
+    /// Report the [`ConnectionState`] of the watcher to `connection`
+    ///
+    /// The handle reports whether the watcher is currently connected, and when it last heard from the apiserver.
+    /// Since errors are retried by the watcher (and only logged by most consumers), this lets health checks
+    /// notice a watch that has been failing for too long. This works with every watcher, including the
+    /// [`metadata_watcher`] and the [`reconfigurable_watcher`].
+    ///
+    /// ```no_run
+    /// use kube::{api::Api, Client, runtime::{watcher::{self, ConnectionHandle, ConnectionState}, WatchStreamExt}};
+    /// use k8s_openapi::api::core::v1::Pod;
+    /// use futures::StreamExt;
+    /// use std::time::Duration;
+    /// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::try_default().await?;
+    /// let pods: Api<Pod> = Api::namespaced(client, "apps");
+    /// let connection = ConnectionHandle::new();
+    /// let stream = watcher::watcher(pods, watcher::Config::default().track_connection(&connection));
+    /// tokio::spawn(stream.default_backoff().applied_objects().for_each(|_| futures::future::ready(())));
+    ///
+    /// // in the liveness probe
+    /// let stale = connection.last_success().map_or(true, |t| t.elapsed() > Duration::from_secs(300));
+    /// let healthy = connection.state() == ConnectionState::Connected || !stale;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// The state is updated as the stream is polled, so it is only meaningful for a stream that is being consumed.
+    #[must_use]
+    pub fn track_connection(mut self, connection: &ConnectionHandle) -> Self {
+        self.connection = Some(connection.clone());
+        self
+    }
+
     /// Converts generic `watcher::Config` structure to the instance of `ListParams` used for list requests.
     fn to_list_params(&self) -> ListParams {
         let (resource_version, version_match) = match self.list_semantic {
@@ -409,20 +752,57 @@ impl Config {
 #[async_trait]
 impl<K> ApiMode for FullObject<'_, K>
 where
-    K: Clone + Debug + DeserializeOwned + Send + 'static,
+    K: Resource + Clone + Debug + DeserializeOwned + Send + 'static,
 {
     type Value = K;
 
-    async fn list(&self, lp: &ListParams) -> kube_client::Result<ObjectList<Self::Value>> {
-        self.api.list(lp).await
+    fn kind(&self) -> &str {
+        self.kind
+    }
+
+    async fn supports_streaming_lists(&self) -> bool {
+        supports_streaming_lists(Client::from(self.api.clone())).await
+    }
+
+    async fn list(&self, lp: &ListParams) -> kube_client::Result<(ObjectList<Self::Value>, Vec<Error>)> {
+        if !self.lenient {
+            return Ok((self.api.list(lp).await?, vec![]));
+        }
+        let mut req = Request::new(self.api.resource_url())
+            .list(lp)
+            .map_err(ClientErr::BuildRequest)?;
+        req.extensions_mut().insert("list");
+        let client = self.api.clone().into_client();
+        let list = client.request::<ObjectList<serde_json::Value>>(req).await?;
+        let mut undecodable = Vec::new();
+        let items = list
+            .items
+            .into_iter()
+            .filter_map(|item| decode(item).map_err(|err| undecodable.push(err)).ok())
+            .collect();
+        let list = ObjectList {
+            metadata: list.metadata,
+            items,
+        };
+        Ok((list, undecodable))
     }
 
     async fn watch(
         &self,
         wp: &WatchParams,
         version: &str,
-    ) -> kube_client::Result<BoxStream<'static, kube_client::Result<WatchEvent<Self::Value>>>> {
-        self.api.watch(wp, version).await.map(StreamExt::boxed)
+    ) -> kube_client::Result<BoxStream<'static, Result<WatchEvent<Self::Value>>>> {
+        if !self.lenient {
+            let stream = self.api.watch(wp, version).await?;
+            return Ok(stream.map_err(Error::WatchFailed).boxed());
+        }
+        let mut req = Request::new(self.api.resource_url())
+            .watch(wp, version)
+            .map_err(ClientErr::BuildRequest)?;
+        req.extensions_mut().insert("watch");
+        let client = self.api.clone().into_client();
+        let stream = client.request_events::<serde_json::Value>(req).await?;
+        Ok(stream.map(decode_event).boxed())
     }
 }
 
@@ -430,6 +810,7 @@ where
 /// watcher will return only the metadata associated with an object
 struct MetaOnly<'a, K> {
     api: &'a Api<K>,
+    kind: &'static str,
 }
 
 #[async_trait]
@@ -439,19 +820,99 @@ where
 {
     type Value = PartialObjectMeta<K>;
 
-    async fn list(&self, lp: &ListParams) -> kube_client::Result<ObjectList<Self::Value>> {
-        self.api.list_metadata(lp).await
+    fn kind(&self) -> &str {
+        self.kind
+    }
+
+    async fn supports_streaming_lists(&self) -> bool {
+        supports_streaming_lists(Client::from(self.api.clone())).await
+    }
+
+    async fn list(&self, lp: &ListParams) -> kube_client::Result<(ObjectList<Self::Value>, Vec<Error>)> {
+        Ok((self.api.list_metadata(lp).await?, vec![]))
     }
 
     async fn watch(
         &self,
         wp: &WatchParams,
         version: &str,
-    ) -> kube_client::Result<BoxStream<'static, kube_client::Result<WatchEvent<Self::Value>>>> {
-        self.api.watch_metadata(wp, version).await.map(StreamExt::boxed)
+    ) -> kube_client::Result<BoxStream<'static, Result<WatchEvent<Self::Value>>>> {
+        let stream = self.api.watch_metadata(wp, version).await?;
+        Ok(stream.map_err(Error::WatchFailed).boxed())
+    }
+}
+
+/// Whether the apiserver may serve streaming lists, assuming it does when its version is unknown
+async fn supports_streaming_lists(client: Client) -> bool {
+    match client.capabilities().await {
+        Ok(capabilities) => capabilities.streaming_lists(),
+        Err(err) => {
+            debug!("failed to get apiserver capabilities: {err:?}");
+            true
+        }
+    }
+}
+
+/// The strategy for the initial list, falling back to a list when the apiserver cannot serve streaming lists
+async fn initial_list_strategy<A: ApiMode>(api: &A, wc: &Config) -> InitialListStrategy {
+    match wc.initial_list_strategy {
+        InitialListStrategy::StreamingList if !api.supports_streaming_lists().await => {
+            debug!("apiserver is too old for streaming lists, falling back to a list");
+            InitialListStrategy::ListWatch
+        }
+        ref strategy => strategy.clone(),
+    }
+}
+
+/// Decodes the object of a watch event, including the raw object in the error if it does not match `K`
+fn decode_event<K: DeserializeOwned>(
+    event: kube_client::Result<WatchEvent<serde_json::Value>>,
+) -> Result<WatchEvent<K>> {
+    match event.map_err(Error::WatchFailed)? {
+        WatchEvent::Added(obj) => decode(obj).map(WatchEvent::Added),
+        WatchEvent::Modified(obj) => decode(obj).map(WatchEvent::Modified),
+        WatchEvent::Deleted(obj) => decode(obj).map(WatchEvent::Deleted),
+        WatchEvent::Bookmark(bm) => Ok(WatchEvent::Bookmark(bm)),
+        WatchEvent::Error(err) => Ok(WatchEvent::Error(err)),
     }
 }
 
+/// Decodes an object, keeping the raw object in the error if it does not deserialize into `K`
+fn decode<K: DeserializeOwned>(raw: serde_json::Value) -> Result<K> {
+    K::deserialize(&raw).map_err(|source| Error::Decode { raw, source })
+}
+
+/// The kind of `K` for logging, taken from its type name since the watcher has no dynamic type to resolve it with
+///
+/// This is the kind of `k8s-openapi` types and derived `CustomResource`s, but not of a `DynamicObject`.
+fn kind_of<K>() -> &'static str {
+    let name = std::any::type_name::<K>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Logs a watch event received from `stream.next()` at trace level, and passes it on
+///
+/// The fields are named consistently, so that the logs of a watcher can be queried for an object.
+fn traced<K: Resource>(kind: &str, next: Option<Result<WatchEvent<K>>>) -> Option<Result<WatchEvent<K>>> {
+    let (event, obj) = match &next {
+        Some(Ok(WatchEvent::Added(obj))) => ("Added", obj),
+        Some(Ok(WatchEvent::Modified(obj))) => ("Modified", obj),
+        Some(Ok(WatchEvent::Deleted(obj))) => ("Deleted", obj),
+        _ => return next,
+    };
+    let meta = obj.meta();
+    trace!(
+        kind,
+        namespace = meta.namespace.as_deref(),
+        name = meta.name.as_deref(),
+        rv = meta.resource_version.as_deref(),
+        event,
+        "watch event"
+    );
+    next
+}
+
 /// Progresses the watcher a single step, returning (event, state)
 ///
 /// This function should be trampolined: if event == `None`
@@ -470,26 +931,33 @@ where
         State::Empty {
             continue_token,
             mut objects,
-        } => match wc.initial_list_strategy {
+            mut undecodable,
+            desync,
+        } => match initial_list_strategy(api, wc).await {
             InitialListStrategy::ListWatch => {
                 let mut lp = wc.to_list_params();
                 lp.continue_token = continue_token;
                 match api.list(&lp).await {
-                    Ok(list) => {
+                    Ok((list, errors)) => {
                         objects.extend(list.items);
+                        undecodable.extend(errors);
                         if let Some(continue_token) = list.metadata.continue_.filter(|s| !s.is_empty()) {
                             (None, State::Empty {
                                 continue_token: Some(continue_token),
                                 objects,
+                                undecodable,
+                                desync,
                             })
                         } else if let Some(resource_version) =
                             list.metadata.resource_version.filter(|s| !s.is_empty())
                         {
-                            (Some(Ok(Event::Restarted(objects))), State::InitListed {
+                            let listed = ListedVersions::new(&objects);
+                            State::listed(objects, undecodable, desync, State::InitListed {
                                 resource_version,
+                                listed,
                             })
                         } else {
-                            (Some(Err(Error::NoResourceVersion)), State::default())
+                            (Some(Err(Error::NoResourceVersion)), State::relist(desync))
                         }
                     }
                     Err(err) => {
@@ -498,45 +966,65 @@ where
                         } else {
                             debug!("watch list error: {err:?}");
                         }
-                        (Some(Err(Error::InitialListFailed(err))), State::default())
+                        (Some(Err(Error::InitialListFailed(err))), State::relist(desync))
                     }
                 }
             }
             InitialListStrategy::StreamingList => match api.watch(&wc.to_watch_params(), "0").await {
-                Ok(stream) => (None, State::IntialWatch { stream, objects }),
+                Ok(stream) => (None, State::IntialWatch {
+                    stream,
+                    objects,
+                    undecodable,
+                    desync,
+                }),
                 Err(err) => {
                     if std::matches!(err, ClientErr::Api(ErrorResponse { code: 403, .. })) {
                         warn!("watch initlist error with 403: {err:?}");
                     } else {
                         debug!("watch initlist error: {err:?}");
                     }
-                    (Some(Err(Error::WatchStartFailed(err))), State::default())
+                    (Some(Err(Error::WatchStartFailed(err))), State::relist(desync))
                 }
             },
         },
         State::IntialWatch {
             mut objects,
+            mut undecodable,
             mut stream,
+            desync,
         } => {
-            match stream.next().await {
+            match traced(api.kind(), stream.next().await) {
                 Some(Ok(WatchEvent::Added(obj) | WatchEvent::Modified(obj))) => {
                     objects.push(obj);
-                    (None, State::IntialWatch { objects, stream })
+                    (None, State::IntialWatch {
+                        objects,
+                        undecodable,
+                        stream,
+                        desync,
+                    })
                 }
                 Some(Ok(WatchEvent::Deleted(obj))) => {
                     objects.retain(|o| o.name_any() != obj.name_any() && o.namespace() != obj.namespace());
-                    (None, State::IntialWatch { objects, stream })
+                    (None, State::IntialWatch {
+                        objects,
+                        undecodable,
+                        stream,
+                        desync,
+                    })
                 }
                 Some(Ok(WatchEvent::Bookmark(bm))) => {
                     let marks_initial_end = bm.metadata.annotations.contains_key("k8s.io/initial-events-end");
                     if marks_initial_end {
-                        (Some(Ok(Event::Restarted(objects))), State::Watching {
+                        let listed = ListedVersions::new(&objects);
+                        State::listed(objects, undecodable, desync, State::Watching {
                             resource_version: bm.metadata.resource_version,
+                            listed,
                             stream,
                         })
                     } else {
                         (None, State::Watching {
                             resource_version: bm.metadata.resource_version,
+                            listed: ListedVersions::default(),
                             stream,
                         })
                     }
@@ -544,9 +1032,14 @@ where
                 Some(Ok(WatchEvent::Error(err))) => {
                     // HTTP GONE, means we have desynced and need to start over and re-list :(
                     let new_state = if err.code == 410 {
-                        State::default()
+                        State::desynced(DesyncReason::Expired)
                     } else {
-                        State::IntialWatch { objects, stream }
+                        State::IntialWatch {
+                            objects,
+                            undecodable,
+                            stream,
+                            desync,
+                        }
                     };
                     if err.code == 403 {
                         warn!("watcher watchevent error 403: {err:?}");
@@ -556,48 +1049,93 @@ where
                     (Some(Err(Error::WatchError(err))), new_state)
                 }
                 Some(Err(err)) => {
-                    if std::matches!(err, ClientErr::Api(ErrorResponse { code: 403, .. })) {
+                    if std::matches!(
+                        err,
+                        Error::WatchFailed(ClientErr::Api(ErrorResponse { code: 403, .. }))
+                    ) {
                         warn!("watcher error 403: {err:?}");
                     } else {
                         debug!("watcher error: {err:?}");
                     }
-                    (Some(Err(Error::WatchFailed(err))), State::IntialWatch {
+                    // objects that failed to decode are emitted along with the list once it is complete
+                    if std::matches!(err, Error::Decode { .. }) {
+                        undecodable.push(err);
+                        return (None, State::IntialWatch {
+                            objects,
+                            undecodable,
+                            stream,
+                            desync,
+                        });
+                    }
+                    (Some(Err(err)), State::IntialWatch {
                         objects,
+                        undecodable,
                         stream,
+                        desync,
                     })
                 }
-                None => (None, State::default()),
+                None => (None, State::relist(desync)),
             }
         }
-        State::InitListed { resource_version } => {
-            match api.watch(&wc.to_watch_params(), &resource_version).await {
-                Ok(stream) => (None, State::Watching {
-                    resource_version,
-                    stream,
-                }),
-                Err(err) => {
-                    if std::matches!(err, ClientErr::Api(ErrorResponse { code: 403, .. })) {
-                        warn!("watch initlist error with 403: {err:?}");
-                    } else {
-                        debug!("watch initlist error: {err:?}");
-                    }
-                    (Some(Err(Error::WatchStartFailed(err))), State::InitListed {
-                        resource_version,
-                    })
+        State::InitListed {
+            resource_version,
+            listed,
+        } => match api.watch(&wc.to_watch_params(), &resource_version).await {
+            Ok(stream) => (None, State::Watching {
+                resource_version,
+                listed,
+                stream,
+            }),
+            Err(err) => {
+                if std::matches!(err, ClientErr::Api(ErrorResponse { code: 403, .. })) {
+                    warn!("watch initlist error with 403: {err:?}");
+                } else {
+                    debug!("watch initlist error: {err:?}");
+                }
+                // HTTP NOT FOUND, the namespace (or resource) is gone along with all of its objects,
+                // so clear out the previous state before trying to re-list
+                if std::matches!(err, ClientErr::Api(ErrorResponse { code: 404, .. })) {
+                    warn!("watch target no longer exists, restarting with no objects: {err:?}");
+                    return (Some(Ok(Event::Restarted(Vec::new()))), State::relist(None));
                 }
+                // HTTP GONE, the version to watch from is too old and we need to re-list
+                let new_state = if std::matches!(err, ClientErr::Api(ErrorResponse { code: 410, .. })) {
+                    State::desynced(DesyncReason::Expired)
+                } else {
+                    State::InitListed {
+                        resource_version,
+                        listed,
+                    }
+                };
+                (Some(Err(Error::WatchStartFailed(err))), new_state)
             }
-        }
+        },
         State::Watching {
             resource_version,
+            mut listed,
             mut stream,
-        } => match stream.next().await {
+        } => match traced(api.kind(), stream.next().await) {
             Some(Ok(WatchEvent::Added(obj) | WatchEvent::Modified(obj))) => {
                 let resource_version = obj.resource_version().unwrap_or_default();
                 if resource_version.is_empty() {
-                    (Some(Err(Error::NoResourceVersion)), State::default())
+                    (
+                        Some(Err(Error::NoResourceVersion)),
+                        State::desynced(DesyncReason::NoResourceVersion),
+                    )
+                } else if listed.is_stale(&obj) {
+                    debug!(
+                        "skipping watch event for {} already included in the last list",
+                        obj.name_any()
+                    );
+                    (None, State::Watching {
+                        resource_version,
+                        listed,
+                        stream,
+                    })
                 } else {
                     (Some(Ok(Event::Applied(obj))), State::Watching {
                         resource_version,
+                        listed,
                         stream,
                     })
                 }
@@ -605,25 +1143,35 @@ where
             Some(Ok(WatchEvent::Deleted(obj))) => {
                 let resource_version = obj.resource_version().unwrap_or_default();
                 if resource_version.is_empty() {
-                    (Some(Err(Error::NoResourceVersion)), State::default())
+                    (
+                        Some(Err(Error::NoResourceVersion)),
+                        State::desynced(DesyncReason::NoResourceVersion),
+                    )
                 } else {
+                    // deletions are always passed on, but the listed version is no longer relevant
+                    listed.is_stale(&obj);
                     (Some(Ok(Event::Deleted(obj))), State::Watching {
                         resource_version,
+                        listed,
                         stream,
                     })
                 }
             }
+            // A bookmark marks that every event up to its version has been sent,
+            // so the listed versions can no longer be repeated
             Some(Ok(WatchEvent::Bookmark(bm))) => (None, State::Watching {
                 resource_version: bm.metadata.resource_version,
+                listed: ListedVersions::default(),
                 stream,
             }),
             Some(Ok(WatchEvent::Error(err))) => {
                 // HTTP GONE, means we have desynced and need to start over and re-list :(
                 let new_state = if err.code == 410 {
-                    State::default()
+                    State::desynced(DesyncReason::Expired)
                 } else {
                     State::Watching {
                         resource_version,
+                        listed,
                         stream,
                     }
                 };
@@ -635,22 +1183,34 @@ where
                 (Some(Err(Error::WatchError(err))), new_state)
             }
             Some(Err(err)) => {
-                if std::matches!(err, ClientErr::Api(ErrorResponse { code: 403, .. })) {
+                if std::matches!(
+                    err,
+                    Error::WatchFailed(ClientErr::Api(ErrorResponse { code: 403, .. }))
+                ) {
                     warn!("watcher error 403: {err:?}");
                 } else {
                     debug!("watcher error: {err:?}");
                 }
-                (Some(Err(Error::WatchFailed(err))), State::Watching {
+                (Some(Err(err)), State::Watching {
                     resource_version,
+                    listed,
                     stream,
                 })
             }
-            None => (None, State::InitListed { resource_version }),
+            // the listed versions are only kept for the first watch, so they do not pile up without bookmarks
+            None => (None, State::InitListed {
+                resource_version,
+                listed: ListedVersions::default(),
+            }),
+        },
+        State::InitListedPartially { mut events, next } => match events.next() {
+            Some(event) => (Some(event), State::InitListedPartially { events, next }),
+            None => (None, *next),
         },
     }
 }
 
-/// Trampoline helper for `step_trampolined`
+/// Trampoline helper for `step_trampolined`, reporting every step to the [`Config::connection`]
 async fn step<A>(
     api: &A,
     config: &Config,
@@ -661,7 +1221,11 @@ where
     A::Value: Resource + 'static,
 {
     loop {
-        match step_trampolined(api, config, state).await {
+        let (result, new_state) = step_trampolined(api, config, state).await;
+        if let Some(connection) = &config.connection {
+            connection.observe(result.as_ref(), &new_state);
+        }
+        match (result, new_state) {
             (Some(result), new_state) => return (result, new_state),
             (None, new_state) => state = new_state,
         }
@@ -717,19 +1281,349 @@ where
 /// that we have seen on the stream. If this is successful then the stream is simply resumed from where it left off.
 /// If this fails because the resource version is no longer valid then we start over with a new stream, starting with
 /// an [`Event::Restarted`]. The internals mechanics of recovery should be considered an implementation detail.
+///
+/// If restarting the watch fails because the watched namespace (or the resource type itself) no longer exists,
+/// an empty [`Event::Restarted`] is emitted, so that a [`reflector`] store does not keep the objects of a deleted
+/// namespace around. The watcher then keeps trying to re-list, in case the namespace is recreated.
 pub fn watcher<K: Resource + Clone + DeserializeOwned + Debug + Send + 'static>(
     api: Api<K>,
     watcher_config: Config,
 ) -> impl Stream<Item = Result<Event<K>>> + Send {
+    let state = State::initial(&watcher_config);
     futures::stream::unfold(
-        (api, watcher_config, State::default()),
+        (api, watcher_config, state),
         |(api, watcher_config, state)| async {
-            let (event, state) = step(&FullObject { api: &api }, &watcher_config, state).await;
+            let (event, state) = step(
+                &FullObject {
+                    api: &api,
+                    lenient: watcher_config.lenient_decoding,
+                    kind: kind_of::<K>(),
+                },
+                &watcher_config,
+                state,
+            )
+            .await;
             Some((event, (api, watcher_config, state)))
         },
     )
 }
 
+/// Handle to change the [`Config`] of a running [`reconfigurable_watcher`]
+///
+/// Can be cloned to reconfigure the watcher from multiple places.
+/// Changes made after the watcher stream has been dropped are ignored.
+#[derive(Clone, Debug)]
+pub struct ConfigHandle {
+    config: Arc<Mutex<Config>>,
+    updates: mpsc::UnboundedSender<Config>,
+}
+
+impl ConfigHandle {
+    /// Modify the watcher [`Config`], restarting the watcher with the new configuration
+    ///
+    /// Changes that leave the configuration as it was do not restart the watcher.
+    pub fn update(&self, f: impl FnOnce(&mut Config)) {
+        let mut config = self.config.lock().unwrap_or_else(PoisonError::into_inner);
+        let before = config.clone();
+        f(&mut config);
+        if *config != before {
+            // the receiver is gone once the watcher is dropped, there is nothing left to reconfigure
+            let _ = self.updates.unbounded_send(config.clone());
+        }
+    }
+
+    /// Replace the label selector of the watcher
+    ///
+    /// Restarts the watcher if the selector changed. `None` watches everything.
+    pub fn set_labels(&self, label_selector: Option<&str>) {
+        self.update(|c| c.label_selector = label_selector.map(String::from));
+    }
+
+    /// Replace the field selector of the watcher
+    ///
+    /// Restarts the watcher if the selector changed. `None` watches everything.
+    pub fn set_fields(&self, field_selector: Option<&str>) {
+        self.update(|c| c.field_selector = field_selector.map(String::from));
+    }
+
+    /// The current [`Config`] of the watcher
+    #[must_use]
+    pub fn config(&self) -> Config {
+        self.config.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+}
+
+/// Progresses the watcher a single step like [`step`], while switching to new configurations
+///
+/// A new configuration aborts the step in progress and starts over with a fresh list.
+async fn step_reconfigurable<A>(
+    api: &A,
+    config: &mut Config,
+    mut state: State<A::Value>,
+    updates: &mut Option<mpsc::UnboundedReceiver<Config>>,
+) -> (Result<Event<A::Value>>, State<A::Value>)
+where
+    A: ApiMode,
+    A::Value: Resource + 'static,
+{
+    loop {
+        let mut closed = false;
+        let next = {
+            let step = Box::pin(step(api, config, state));
+            match updates.as_mut() {
+                Some(rx) => match future::select(step, rx.next()).await {
+                    Either::Left((next, _)) => Either::Left(next),
+                    Either::Right((Some(mut new_config), _)) => {
+                        // only the latest configuration matters
+                        while let Ok(newer) = rx.try_recv() {
+                            new_config = newer;
+                        }
+                        Either::Right(new_config)
+                    }
+                    Either::Right((None, step)) => {
+                        closed = true;
+                        Either::Left(step.await)
+                    }
+                },
+                None => Either::Left(step.await),
+            }
+        };
+        if closed {
+            *updates = None;
+        }
+        match next {
+            Either::Left(next) => return next,
+            Either::Right(new_config) => {
+                debug!(?new_config, "watcher reconfigured, restarting");
+                *config = new_config;
+                state = State::default();
+            }
+        }
+    }
+}
+
+/// Watches a Kubernetes Resource for changes continuously, with a handle to change its [`Config`]
+///
+/// This behaves like [`watcher`], but also returns a [`ConfigHandle`] that can change the configuration
+/// of the running watcher, such as its label selector, without recreating the stream
+/// (and any [`reflector`] or controller consuming it).
+///
+/// ```no_run
+/// use kube::{api::Api, Client, runtime::{reflector, watcher, WatchStreamExt}};
+/// use k8s_openapi::api::core::v1::Pod;
+/// use futures::StreamExt;
+/// # async fn wrapper() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = Client::try_default().await?;
+/// let pods: Api<Pod> = Api::namespaced(client, "apps");
+/// let (reader, writer) = reflector::store();
+/// let (stream, handle) = watcher::reconfigurable_watcher(pods, watcher::Config::default().labels("tier=web"));
+/// tokio::spawn(stream.reflect(writer).applied_objects().for_each(|_| futures::future::ready(())));
+///
+/// // later, when the configuration changes
+/// handle.set_labels(Some("tier in (web,api)"));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Switchover semantics
+///
+/// A configuration change takes effect on the next poll of the stream, aborting any list or watch in progress.
+/// The watcher then starts over with a new list using the new configuration, which is emitted as a single
+/// [`Event::Restarted`] containing all objects matching the new configuration.
+///
+/// Until that event, the stream (and any [`reflector`] store fed by it) still reflects the old configuration.
+/// Events that happened between the last event of the old watch and the new list are not emitted individually,
+/// but their effects are included in the [`Event::Restarted`]. Objects that only matched the old selector are
+/// removed from stores when they apply the [`Event::Restarted`], without a separate [`Event::Deleted`].
+///
+/// [`reflector`]: super::reflector::reflector
+pub fn reconfigurable_watcher<K: Resource + Clone + DeserializeOwned + Debug + Send + 'static>(
+    api: Api<K>,
+    watcher_config: Config,
+) -> (impl Stream<Item = Result<Event<K>>> + Send, ConfigHandle) {
+    let (tx, rx) = mpsc::unbounded();
+    let handle = ConfigHandle {
+        config: Arc::new(Mutex::new(watcher_config.clone())),
+        updates: tx,
+    };
+    let state = State::initial(&watcher_config);
+    let stream = futures::stream::unfold(
+        (api, watcher_config, state, Some(rx)),
+        |(api, mut watcher_config, state, mut updates)| async {
+            let (event, state) = step_reconfigurable(
+                &FullObject {
+                    api: &api,
+                    lenient: watcher_config.lenient_decoding,
+                    kind: kind_of::<K>(),
+                },
+                &mut watcher_config,
+                state,
+                &mut updates,
+            )
+            .await;
+            Some((event, (api, watcher_config, state, updates)))
+        },
+    );
+    (stream, handle)
+}
+
+/// The state of the connection of a watcher to the apiserver, see [`ConnectionHandle`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// The initial list has not completed yet
+    Connecting,
+    /// The watcher is listing or watching, and its last request succeeded
+    Connected,
+    /// The last request of the watcher failed, and it is retrying
+    Reconnecting,
+    /// The watcher fell out of sync with the apiserver, and is re-listing
+    Desynced,
+}
+
+impl ConnectionState {
+    fn from_u8(state: u8) -> Self {
+        match state {
+            1 => Self::Connected,
+            2 => Self::Reconnecting,
+            3 => Self::Desynced,
+            _ => Self::Connecting,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ConnectionShared {
+    state: AtomicU8,
+    /// The last [`DesyncReason`], plus its discriminant (zero if there was none yet)
+    last_desync: AtomicU8,
+    /// Nanoseconds between `origin` and the last successful response, plus one (zero if there was none yet)
+    last_success: AtomicU64,
+    origin: Instant,
+    transitions: Mutex<Vec<mpsc::UnboundedSender<ConnectionState>>>,
+}
+
+/// Handle to observe the [`ConnectionState`] of a watcher, see [`Config::track_connection`]
+///
+/// Reading the state is a cheap atomic load, which makes it suitable for liveness and readiness probes.
+/// Can be cloned to observe the watcher from multiple places.
+#[derive(Clone, Debug)]
+pub struct ConnectionHandle {
+    shared: Arc<ConnectionShared>,
+}
+
+impl Default for ConnectionHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Handles are equal when they observe the same watcher
+impl PartialEq for ConnectionHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.shared, &other.shared)
+    }
+}
+
+impl ConnectionHandle {
+    /// Create a handle for a watcher that has not connected yet
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            shared: Arc::new(ConnectionShared {
+                state: AtomicU8::new(ConnectionState::Connecting as u8),
+                last_desync: AtomicU8::new(0),
+                last_success: AtomicU64::new(0),
+                origin: Instant::now(),
+                transitions: Mutex::default(),
+            }),
+        }
+    }
+
+    /// The current state of the connection
+    #[must_use]
+    pub fn state(&self) -> ConnectionState {
+        ConnectionState::from_u8(self.shared.state.load(Ordering::Acquire))
+    }
+
+    /// When the watcher last received a successful response from the apiserver
+    ///
+    /// This covers completed lists, established watches, and every watch event, so a probe can consider
+    /// the watcher stale when this is too long ago. [Bookmarks](Config::bookmarks) keep it fresh
+    /// for resources that rarely change. Returns `None` until the first successful response.
+    #[must_use]
+    pub fn last_success(&self) -> Option<Instant> {
+        match self.shared.last_success.load(Ordering::Acquire) {
+            0 => None,
+            nanos => Some(self.shared.origin + Duration::from_nanos(nanos - 1)),
+        }
+    }
+
+    /// Why the watcher last fell out of sync with the apiserver, or `None` if it never did
+    ///
+    /// Every desync is followed by a re-list, which is emitted as an [`Event::Desynced`] with the same reason.
+    /// The [`transitions`](Self::transitions) through [`ConnectionState::Desynced`] mark when that happens.
+    #[must_use]
+    pub fn last_desync(&self) -> Option<DesyncReason> {
+        DesyncReason::from_u8(self.shared.last_desync.load(Ordering::Acquire))
+    }
+
+    /// A stream of the changes of the [`ConnectionState`], starting after the current state
+    ///
+    /// The stream ends once every clone of the handle is dropped, including those in the [`Config`] of the watcher.
+    pub fn transitions(&self) -> impl Stream<Item = ConnectionState> + Send + Unpin {
+        let (tx, rx) = mpsc::unbounded();
+        self.shared
+            .transitions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(tx);
+        rx
+    }
+
+    /// Update the state after a step of the watcher, which resulted in `result` and `state`
+    fn observe<K>(&self, result: Option<&Result<Event<K>>>, state: &State<K>) {
+        let succeeded = match (result, state) {
+            (Some(result), _) => result.is_ok(),
+            (
+                None,
+                State::IntialWatch { .. } | State::InitListedPartially { .. } | State::Watching { .. },
+            ) => true,
+            // a closed watch (which is restarted regularly) or a list page
+            (None, _) => false,
+        };
+        if succeeded {
+            let nanos = Instant::now()
+                .saturating_duration_since(self.shared.origin)
+                .as_nanos();
+            let nanos = u64::try_from(nanos).unwrap_or(u64::MAX - 1) + 1;
+            self.shared.last_success.store(nanos, Ordering::Release);
+        }
+        let next = match (result, state) {
+            (
+                _,
+                State::Empty {
+                    desync: Some(reason), ..
+                },
+            ) => {
+                self.shared.last_desync.store(*reason as u8, Ordering::Release);
+                ConnectionState::Desynced
+            }
+            (Some(Err(_)), State::Empty { .. } | State::InitListed { .. }) => ConnectionState::Reconnecting,
+            (Some(Ok(_)), _) | (_, State::Watching { .. }) => ConnectionState::Connected,
+            _ => return,
+        };
+        let previous = self.shared.state.swap(next as u8, Ordering::AcqRel);
+        if previous != next as u8 {
+            debug!(state = ?next, "watcher connection state changed");
+            self.shared
+                .transitions
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .retain(|tx| tx.unbounded_send(next).is_ok());
+        }
+    }
+}
+
 /// Watches a Kubernetes Resource for changes continuously and receives only the
 /// metadata
 ///
@@ -780,15 +1674,28 @@ pub fn watcher<K: Resource + Clone + DeserializeOwned + Debug + Send + 'static>(
 /// that we have seen on the stream. If this is successful then the stream is simply resumed from where it left off.
 /// If this fails because the resource version is no longer valid then we start over with a new stream, starting with
 /// an [`Event::Restarted`]. The internals mechanics of recovery should be considered an implementation detail.
+///
+/// If restarting the watch fails because the watched namespace (or the resource type itself) no longer exists,
+/// an empty [`Event::Restarted`] is emitted, so that a [`reflector`] store does not keep the objects of a deleted
+/// namespace around. The watcher then keeps trying to re-list, in case the namespace is recreated.
 #[allow(clippy::module_name_repetitions)]
 pub fn metadata_watcher<K: Resource + Clone + DeserializeOwned + Debug + Send + 'static>(
     api: Api<K>,
     watcher_config: Config,
 ) -> impl Stream<Item = Result<Event<PartialObjectMeta<K>>>> + Send {
+    let state = State::initial(&watcher_config);
     futures::stream::unfold(
-        (api, watcher_config, State::default()),
+        (api, watcher_config, state),
         |(api, watcher_config, state)| async {
-            let (event, state) = step(&MetaOnly { api: &api }, &watcher_config, state).await;
+            let (event, state) = step(
+                &MetaOnly {
+                    api: &api,
+                    kind: kind_of::<K>(),
+                },
+                &watcher_config,
+                state,
+            )
+            .await;
             Some((event, (api, watcher_config, state)))
         },
     )
@@ -811,8 +1718,13 @@ pub fn watch_object<K: Resource + Clone + DeserializeOwned + Debug + Send + 'sta
         // 2. The apiserver is ignoring our query
         // In either case, the K8s apiserver is broken and our API will return invalid data, so
         // we had better bail out ASAP.
-        Event::Restarted(objs) if objs.len() > 1 => Err(Error::TooManyObjects),
-        Event::Restarted(mut objs) => Ok(objs.pop()),
+        Event::Restarted(objs) | Event::Desynced { objects: objs, .. } if objs.len() > 1 => {
+            Err(Error::TooManyObjects)
+        }
+        Event::Restarted(mut objs)
+        | Event::Desynced {
+            objects: mut objs, ..
+        } => Ok(objs.pop()),
         Event::Applied(obj) => Ok(Some(obj)),
     })
 }
@@ -867,3 +1779,485 @@ impl Backoff for DefaultBackoff {
         self.0.reset()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decode_event, kind_of, step, step_reconfigurable, ApiMode, Config, ConfigHandle, ConnectionHandle,
+        ConnectionState, DesyncReason, Error, Event, State,
+    };
+    use crate::reflector;
+    use async_trait::async_trait;
+    use futures::{channel::mpsc, stream::BoxStream, StreamExt};
+    use k8s_openapi::api::core::v1::Pod;
+    use kube_client::{
+        api::{ListParams, WatchEvent, WatchParams},
+        core::{ErrorResponse, ObjectList},
+        ResourceExt,
+    };
+    use serde::Deserialize;
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    };
+
+    fn testpod(name: &str, resource_version: &str) -> Pod {
+        let mut pod = Pod::default();
+        pod.metadata.name = Some(name.to_string());
+        pod.metadata.resource_version = Some(resource_version.to_string());
+        pod
+    }
+
+    fn expired() -> WatchEvent<Pod> {
+        WatchEvent::Error(ErrorResponse {
+            status: "Failure".into(),
+            message: "too old resource version".into(),
+            reason: "Expired".into(),
+            code: 410,
+        })
+    }
+
+    /// An api serving a fixed set of pods, and the same watch events for every watch
+    ///
+    /// Lists are paginated when a limit is given, and filtered when the label selector is the name of a pod.
+    /// The pods and watch events are raw JSON, which is decoded like a lenient watcher does, and the watch
+    /// stays open once the events have been sent. Every request is recorded in `calls`.
+    #[derive(Default)]
+    struct FakeApi {
+        pods: Vec<serde_json::Value>,
+        events: Vec<serde_json::Value>,
+        calls: Mutex<Vec<String>>,
+        /// Pretend to be an apiserver that is too old to serve streaming lists
+        outdated: bool,
+    }
+
+    impl FakeApi {
+        fn new(pods: impl IntoIterator<Item = Pod>) -> Self {
+            Self::default().raw_pods(pods.into_iter().map(|pod| serde_json::to_value(pod).unwrap()))
+        }
+
+        fn raw_pods(mut self, pods: impl IntoIterator<Item = serde_json::Value>) -> Self {
+            self.pods.extend(pods);
+            self
+        }
+
+        fn events(self, events: impl IntoIterator<Item = WatchEvent<Pod>>) -> Self {
+            self.raw_events(
+                events
+                    .into_iter()
+                    .map(|event| serde_json::to_value(event).unwrap()),
+            )
+        }
+
+        fn raw_events(mut self, events: impl IntoIterator<Item = serde_json::Value>) -> Self {
+            self.events.extend(events);
+            self
+        }
+
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl ApiMode for FakeApi {
+        type Value = Pod;
+
+        fn kind(&self) -> &str {
+            kind_of::<Pod>()
+        }
+
+        async fn supports_streaming_lists(&self) -> bool {
+            !self.outdated
+        }
+
+        async fn list(&self, lp: &ListParams) -> kube_client::Result<(ObjectList<Pod>, Vec<Error>)> {
+            let pods = self
+                .pods
+                .iter()
+                .filter(|p| {
+                    lp.label_selector
+                        .as_deref()
+                        .map_or(true, |s| s == p["metadata"]["name"])
+                })
+                .collect::<Vec<_>>();
+            let start = lp
+                .continue_token
+                .as_deref()
+                .map_or(0, |token| token.parse().unwrap());
+            let end = lp
+                .limit
+                .map_or(pods.len(), |limit| (start + limit as usize).min(pods.len()));
+            self.calls.lock().unwrap().push(format!("list {start}..{end}"));
+            let continue_ = if end < pods.len() {
+                end.to_string()
+            } else {
+                String::new()
+            };
+            let mut list: ObjectList<Pod> = serde_json::from_value(serde_json::json!({
+                "metadata": { "resourceVersion": format!("{}", 10 + end), "continue": continue_ },
+                "items": [],
+            }))
+            .unwrap();
+            let mut undecodable = vec![];
+            for pod in &pods[start..end] {
+                match Pod::deserialize(*pod) {
+                    Ok(pod) => list.items.push(pod),
+                    Err(source) => undecodable.push(Error::Decode {
+                        raw: (*pod).clone(),
+                        source,
+                    }),
+                }
+            }
+            Ok((list, undecodable))
+        }
+
+        async fn watch(
+            &self,
+            _wp: &WatchParams,
+            version: &str,
+        ) -> kube_client::Result<BoxStream<'static, super::Result<WatchEvent<Pod>>>> {
+            self.calls.lock().unwrap().push(format!("watch {version}"));
+            let events = self.events.clone().into_iter().map(|event| {
+                decode_event(serde_json::from_value(event).map_err(kube_client::Error::SerdeError))
+            });
+            Ok(futures::stream::iter(events)
+                .chain(futures::stream::pending())
+                .boxed())
+        }
+    }
+
+    #[tokio::test]
+    async fn watcher_skips_applied_events_repeating_the_relist() {
+        let api = FakeApi::new([testpod("a", "5"), testpod("b", "7")]).events([
+            WatchEvent::Modified(testpod("a", "5")),
+            WatchEvent::Modified(testpod("b", "11")),
+            WatchEvent::Added(testpod("c", "12")),
+        ]);
+        let config = Config::default();
+        let mut state = State::default();
+        let mut events = Vec::new();
+        for _ in 0..3 {
+            let (event, next) = step(&api, &config, state).await;
+            state = next;
+            events.push(match event.unwrap() {
+                Event::Restarted(objs) => format!("restarted {}", objs.len()),
+                Event::Applied(obj) => {
+                    format!("applied {}@{}", obj.name_any(), obj.resource_version().unwrap())
+                }
+                Event::Deleted(obj) => panic!("unexpected deletion of {}", obj.name_any()),
+                Event::Desynced { reason, .. } => panic!("unexpected desync: {reason:?}"),
+            });
+        }
+        assert_eq!(events, ["restarted 2", "applied b@11", "applied c@12"]);
+    }
+
+    /// Collects the output of a `tracing_subscriber::fmt` subscriber
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn watcher_traces_every_received_event() {
+        use tracing_subscriber::util::SubscriberInitExt;
+        let logs = LogBuffer::default();
+        let writer = logs.clone();
+        let _tracing = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::TRACE)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish()
+            .set_default();
+
+        let mut deleted = testpod("b", "12");
+        deleted.metadata.namespace = Some("apps".to_string());
+        let api = FakeApi::new([testpod("a", "5")]).events([
+            WatchEvent::Modified(testpod("a", "5")),
+            WatchEvent::Deleted(deleted),
+        ]);
+        let config = Config::default();
+        let mut state = State::default();
+        for _ in 0..2 {
+            state = step(&api, &config, state).await.1;
+        }
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let traced = logs
+            .lines()
+            .filter(|line| line.contains("TRACE"))
+            .collect::<Vec<_>>();
+        // events are traced even when they are skipped as repeating the list
+        assert_eq!(traced.len(), 2, "{logs}");
+        assert!(traced[0].contains(r#"kind="Pod" name="a" rv="5" event="Modified""#));
+        assert!(traced[1].contains(r#"kind="Pod" namespace="apps" name="b" rv="12" event="Deleted""#));
+    }
+
+    #[tokio::test]
+    async fn reconfigured_watcher_relists_with_new_selector() {
+        let api = FakeApi::new([testpod("a", "1"), testpod("b", "2")]);
+        let mut config = Config::default().labels("a");
+        let (tx, rx) = mpsc::unbounded();
+        let handle = ConfigHandle {
+            config: Arc::new(Mutex::new(config.clone())),
+            updates: tx,
+        };
+        let mut updates = Some(rx);
+        let names = |event: Event<Pod>| match event {
+            Event::Restarted(objs) => objs.iter().map(ResourceExt::name_any).collect::<Vec<_>>(),
+            other => panic!("unexpected event {other:?}"),
+        };
+
+        let (event, state) = step_reconfigurable(&api, &mut config, State::default(), &mut updates).await;
+        assert_eq!(names(event.unwrap()), ["a"]);
+
+        // unchanged selectors do not restart the watcher
+        handle.set_labels(Some("a"));
+        // the watch never yields, so only the reconfiguration can complete this step
+        handle.set_labels(Some("b"));
+        let (event, _) = step_reconfigurable(&api, &mut config, state, &mut updates).await;
+        assert_eq!(names(event.unwrap()), ["b"]);
+        assert_eq!(config.label_selector.as_deref(), Some("b"));
+        assert_eq!(handle.config(), config);
+    }
+
+    #[tokio::test]
+    async fn watcher_paginates_initial_list() {
+        let api = FakeApi::new((0..5).map(|i| testpod(&format!("pod-{i}"), "1")));
+        let config = Config::default().page_size(2);
+        let (event, state) = step(&api, &config, State::default()).await;
+        assert!(matches!(event, Ok(Event::Restarted(objs)) if objs.len() == 5));
+        // starts the watch, which never yields
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(10), step(&api, &config, state))
+                .await
+                .is_err()
+        );
+        assert_eq!(api.calls(), ["list 0..2", "list 2..4", "list 4..5", "watch 15"]);
+    }
+
+    #[tokio::test]
+    async fn lenient_watcher_continues_past_undecodable_objects() {
+        let pod =
+            |name: &str, rv: &str| serde_json::json!({ "metadata": { "name": name, "resourceVersion": rv } });
+        let mut bad = pod("bad", "12");
+        bad["spec"] = serde_json::json!({ "containers": "not-a-list" });
+        let api = FakeApi::default().raw_events([
+            serde_json::json!({ "type": "ADDED", "object": bad }),
+            serde_json::json!({ "type": "ADDED", "object": pod("good", "13") }),
+        ]);
+        let config = Config::default().lenient_decoding();
+        let (event, state) = step(&api, &config, State::default()).await;
+        assert!(matches!(event, Ok(Event::Restarted(objs)) if objs.is_empty()));
+
+        let (event, state) = step(&api, &config, state).await;
+        match event {
+            Err(Error::Decode { raw, .. }) => assert_eq!(raw, bad),
+            other => panic!("expected decode error, got {other:?}"),
+        }
+        let (event, _) = step(&api, &config, state).await;
+        assert!(matches!(event, Ok(Event::Applied(pod)) if pod.name_any() == "good"));
+    }
+
+    #[tokio::test]
+    async fn lenient_watcher_keeps_undecodable_objects_of_a_relist() {
+        use crate::reflector::store::Writer;
+        let mut bad = serde_json::to_value(testpod("bad", "12")).unwrap();
+        bad["spec"] = serde_json::json!({ "containers": "not-a-list" });
+        let api = FakeApi::new([testpod("a", "11")]).raw_pods([bad]);
+        let config = Config::default().lenient_decoding();
+        let mut writer = Writer::<Pod>::default();
+        writer.apply_watcher_event(&Event::Restarted(vec![testpod("a", "1"), testpod("bad", "2")]));
+
+        let mut state = State::default();
+        let mut events = Vec::new();
+        for _ in 0..2 {
+            let (event, next) = step(&api, &config, state).await;
+            state = next;
+            if let Ok(event) = &event {
+                writer.apply_watcher_event(event);
+            }
+            events.push(event);
+        }
+        assert!(matches!(&events[0], Err(Error::Decode { .. })));
+        assert!(matches!(&events[1], Ok(Event::Applied(pod)) if pod.name_any() == "a"));
+        let store = writer.as_reader();
+        let mut versions = store
+            .state()
+            .iter()
+            .map(|pod| format!("{}@{}", pod.name_any(), pod.resource_version().unwrap()))
+            .collect::<Vec<_>>();
+        versions.sort();
+        assert_eq!(versions, ["a@11", "bad@2"]);
+    }
+
+    #[tokio::test]
+    async fn watcher_falls_back_to_list_when_streaming_lists_are_unsupported() {
+        let api = FakeApi {
+            outdated: true,
+            ..FakeApi::new([testpod("a", "1")])
+        };
+        let config = Config::default().streaming_lists();
+        let (event, _) = step(&api, &config, State::default()).await;
+        assert!(matches!(event, Ok(Event::Restarted(objs)) if objs.len() == 1));
+        assert_eq!(api.calls(), ["list 0..1"]);
+    }
+
+    #[tokio::test]
+    async fn watcher_resumes_from_initial_resource_version() {
+        let api = FakeApi::new([testpod("a", "1")]);
+        let config = Config::default().page_size(10).initial_resource_version("42");
+        // starts the watch without a list, which never yields
+        assert!(tokio::time::timeout(
+            std::time::Duration::from_millis(10),
+            step(&api, &config, State::initial(&config))
+        )
+        .await
+        .is_err());
+        assert_eq!(api.calls(), ["watch 42"]);
+    }
+
+    #[tokio::test]
+    async fn watcher_relists_when_initial_resource_version_expired() {
+        let api = FakeApi::new([testpod("a", "5")]).events([expired()]);
+        let config = Config::default().initial_resource_version("1");
+        let (event, state) = step(&api, &config, State::initial(&config)).await;
+        assert!(matches!(
+            event,
+            Err(Error::WatchError(ErrorResponse { code: 410, .. }))
+        ));
+        let (event, _) = step(&api, &config, state).await;
+        assert!(matches!(
+            event,
+            Ok(Event::Desynced { reason: DesyncReason::Expired, objects }) if objects.len() == 1
+        ));
+    }
+
+    /// An api for pods in a namespace, which can be deleted
+    struct NamespacedApi {
+        pods: Vec<Pod>,
+        deleted: AtomicBool,
+    }
+
+    #[async_trait]
+    impl ApiMode for NamespacedApi {
+        type Value = Pod;
+
+        fn kind(&self) -> &str {
+            kind_of::<Pod>()
+        }
+
+        async fn supports_streaming_lists(&self) -> bool {
+            false
+        }
+
+        async fn list(&self, _lp: &ListParams) -> kube_client::Result<(ObjectList<Pod>, Vec<Error>)> {
+            if self.deleted.load(Ordering::SeqCst) {
+                return Err(namespace_not_found());
+            }
+            let list = serde_json::from_value(serde_json::json!({
+                "metadata": { "resourceVersion": "10" },
+                "items": self.pods,
+            }))
+            .unwrap();
+            Ok((list, Vec::new()))
+        }
+
+        async fn watch(
+            &self,
+            _wp: &WatchParams,
+            _version: &str,
+        ) -> kube_client::Result<BoxStream<'static, super::Result<WatchEvent<Pod>>>> {
+            if self.deleted.load(Ordering::SeqCst) {
+                return Err(namespace_not_found());
+            }
+            // the watch is closed right away
+            Ok(futures::stream::empty().boxed())
+        }
+    }
+
+    fn namespace_not_found() -> kube_client::Error {
+        kube_client::Error::Api(ErrorResponse {
+            status: "Failure".into(),
+            message: "namespaces \"apps\" not found".into(),
+            reason: "NotFound".into(),
+            code: 404,
+        })
+    }
+
+    #[tokio::test]
+    async fn watcher_clears_store_when_namespace_is_deleted() {
+        let api = NamespacedApi {
+            pods: vec![testpod("a", "5"), testpod("b", "6")],
+            deleted: AtomicBool::new(false),
+        };
+        let config = Config::default();
+        let (reader, mut writer) = reflector::store::<Pod>();
+
+        let (event, state) = step(&api, &config, State::default()).await;
+        writer.apply_watcher_event(&event.unwrap());
+        assert_eq!(reader.len(), 2);
+
+        // the watch is closed, and the namespace is deleted before it is restarted
+        api.deleted.store(true, Ordering::SeqCst);
+        let (event, state) = step(&api, &config, state).await;
+        writer.apply_watcher_event(&event.unwrap());
+        assert!(reader.is_empty());
+
+        // and keeps trying to re-list, in case the namespace is recreated
+        let (event, state) = step(&api, &config, state).await;
+        assert!(matches!(
+            event,
+            Err(Error::InitialListFailed(kube_client::Error::Api(ErrorResponse {
+                code: 404,
+                ..
+            })))
+        ));
+        api.deleted.store(false, Ordering::SeqCst);
+        let (event, _) = step(&api, &config, state).await;
+        assert!(matches!(event, Ok(Event::Restarted(pods)) if pods.len() == 2));
+    }
+
+    #[tokio::test]
+    async fn watcher_reports_connection_state() {
+        let api =
+            FakeApi::new([testpod("a", "5")]).events([WatchEvent::Modified(testpod("a", "11")), expired()]);
+        let connection = ConnectionHandle::new();
+        let config = Config::default().track_connection(&connection);
+        let mut transitions = connection.transitions();
+        assert_eq!(connection.state(), ConnectionState::Connecting);
+        assert_eq!(connection.last_success(), None);
+        assert_eq!(connection.last_desync(), None);
+
+        let (event, state) = step(&api, &config, State::default()).await;
+        assert!(matches!(event, Ok(Event::Restarted(_))));
+        assert_eq!(connection.state(), ConnectionState::Connected);
+        let listed_at = connection.last_success().unwrap();
+
+        let (event, state) = step(&api, &config, state).await;
+        assert!(matches!(event, Ok(Event::Applied(_))));
+        assert!(connection.last_success().unwrap() >= listed_at);
+
+        let (event, state) = step(&api, &config, state).await;
+        assert!(matches!(event, Err(Error::WatchError(_))));
+        assert_eq!(connection.state(), ConnectionState::Desynced);
+        assert_eq!(connection.last_desync(), Some(DesyncReason::Expired));
+
+        let (event, _) = step(&api, &config, state).await;
+        assert!(matches!(event, Ok(Event::Desynced { .. })));
+        assert_eq!(connection.state(), ConnectionState::Connected);
+
+        drop((connection, config));
+        assert_eq!(transitions.next().await, Some(ConnectionState::Connected));
+        assert_eq!(transitions.next().await, Some(ConnectionState::Desynced));
+        assert_eq!(transitions.next().await, Some(ConnectionState::Connected));
+        assert_eq!(transitions.next().await, None);
+    }
+}
@@ -290,6 +290,41 @@ mod test {
                 .await?;
             assert!(o.status.is_some(), "status set after patch_status");
         }
+        // server-side apply status with a separate field manager
+        {
+            let status_apply = PatchParams::apply("kube-status").force();
+            let fs = json!({
+                "apiVersion": "clux.dev/v1",
+                "kind": "Foo",
+                "status": FooStatus { is_bad: true, replicas: 1 }
+            });
+            let o = foos
+                .patch_status("baz", &status_apply, &Patch::Apply(&fs))
+                .await?;
+            assert!(o.status.as_ref().unwrap().is_bad, "patch_status applied");
+            // spec and status are owned by independent apply entries
+            let appliers = o
+                .managed_fields()
+                .iter()
+                .filter(|e| e.operation.as_deref() == Some("Apply"))
+                .map(|e| (e.manager.as_deref(), e.subresource.as_deref().unwrap_or_default()))
+                .collect::<Vec<_>>();
+            assert!(appliers.contains(&(Some("kube"), "")));
+            assert!(appliers.contains(&(Some("kube-status"), "status")));
+            // re-applying the spec does not touch the applied status
+            let o = foos
+                .patch(
+                    "baz",
+                    &ssapply,
+                    &Patch::Apply(json!({
+                        "apiVersion": "clux.dev/v1",
+                        "kind": "Foo",
+                        "spec": { "name": "foo", "replicas": 2 }
+                    })),
+                )
+                .await?;
+            assert!(o.status.unwrap().is_bad, "status kept after applying spec");
+        }
         // set scale subresource
         {
             let fs = serde_json::json!({"spec": { "replicas": 3 }});